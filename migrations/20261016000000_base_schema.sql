@@ -0,0 +1,75 @@
+-- The tables the app started with, so a new database (or organization schema) can be migrated from scratch
+-- Existing databases already have them and skip this
+CREATE TABLE IF NOT EXISTS events (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name TEXT NOT NULL,
+    active_event BOOLEAN NOT NULL DEFAULT FALSE
+);
+
+CREATE TABLE IF NOT EXISTS college (
+    college_id TEXT PRIMARY KEY,
+    college_logo_path TEXT NOT NULL,
+    college_name TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS categories (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name TEXT NOT NULL,
+    weight REAL NOT NULL,
+    is_active BOOLEAN NOT NULL DEFAULT FALSE,
+    -- Relationships
+    event_id UUID NOT NULL REFERENCES events(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS criterias (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name TEXT NOT NULL,
+    max_score INTEGER NOT NULL,
+    -- Relationships
+    category_id UUID NOT NULL REFERENCES categories(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS candidates (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    first_name TEXT NOT NULL,
+    middle_name TEXT NOT NULL,
+    last_name TEXT NOT NULL,
+    gender INTEGER NOT NULL,
+    college_id TEXT NOT NULL,
+    candidate_number INTEGER NOT NULL,
+    final_score REAL NOT NULL DEFAULT 0,
+    -- Relationships
+    category_id UUID
+);
+
+CREATE TABLE IF NOT EXISTS judges (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name TEXT NOT NULL,
+    username TEXT NOT NULL,
+    password TEXT NOT NULL,
+    is_active BOOLEAN NOT NULL DEFAULT FALSE,
+    score_exclusion BOOLEAN NOT NULL DEFAULT FALSE,
+    -- Relationships
+    event_id UUID NOT NULL REFERENCES events(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS scores (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    score INTEGER NOT NULL,
+    max INTEGER NOT NULL,
+    time_of_scoring TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- Relationships
+    candidate_id UUID NOT NULL REFERENCES candidates(id),
+    criteria_id UUID NOT NULL REFERENCES criterias(id),
+    category_id UUID NOT NULL REFERENCES categories(id),
+    judge_id UUID NOT NULL REFERENCES judges(id)
+);
+
+CREATE TABLE IF NOT EXISTS notes (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    note TEXT NOT NULL,
+    last_change TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- Relationships
+    candidate_id UUID NOT NULL,
+    judge_id UUID NOT NULL
+);
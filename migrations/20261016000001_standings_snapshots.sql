@@ -0,0 +1,26 @@
+-- Categories can be closed once judging for it is done
+ALTER TABLE categories ADD COLUMN IF NOT EXISTS is_locked BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Standings taken every time a category is closed
+CREATE TABLE IF NOT EXISTS standings_snapshots (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    version INTEGER NOT NULL,
+    standings JSONB NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- Relationships
+    event_id UUID NOT NULL REFERENCES events(id),
+    category_id UUID REFERENCES categories(id),
+    UNIQUE (event_id, version)
+);
+
+-- Snapshots must never change after they are written
+CREATE OR REPLACE FUNCTION reject_snapshot_changes() RETURNS TRIGGER AS $$
+BEGIN
+    RAISE EXCEPTION 'standings snapshots are immutable';
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS standings_snapshots_immutable ON standings_snapshots;
+CREATE TRIGGER standings_snapshots_immutable
+    BEFORE UPDATE OR DELETE ON standings_snapshots
+    FOR EACH ROW EXECUTE FUNCTION reject_snapshot_changes();
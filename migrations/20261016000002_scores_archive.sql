@@ -0,0 +1,19 @@
+ALTER TABLE events ADD COLUMN IF NOT EXISTS is_archived BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Scores of archived events are moved here to keep the live table small
+CREATE TABLE IF NOT EXISTS scores_archive (
+    LIKE scores INCLUDING DEFAULTS,
+    archived_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (id)
+);
+
+CREATE INDEX IF NOT EXISTS scores_archive_category_id_idx ON scores_archive (category_id);
+CREATE INDEX IF NOT EXISTS scores_archive_candidate_id_idx ON scores_archive (candidate_id);
+
+-- Live and archived scores, used by exports and historical endpoints
+CREATE OR REPLACE VIEW scores_history AS
+    SELECT id, score, max, time_of_scoring, candidate_id, criteria_id, category_id, judge_id
+    FROM scores
+    UNION ALL
+    SELECT id, score, max, time_of_scoring, candidate_id, criteria_id, category_id, judge_id
+    FROM scores_archive;
@@ -0,0 +1,2 @@
+-- Replace judge names with "Judge 1..N" in exports meant for distribution
+ALTER TABLE events ADD COLUMN IF NOT EXISTS anonymize_judges BOOLEAN NOT NULL DEFAULT FALSE;
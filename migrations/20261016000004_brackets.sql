@@ -0,0 +1,34 @@
+-- Head-to-head segments (debates, battles)
+CREATE TABLE IF NOT EXISTS brackets (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name TEXT NOT NULL,
+    -- 'round_robin' or 'elimination'
+    format TEXT NOT NULL DEFAULT 'elimination' CHECK (format IN ('round_robin', 'elimination')),
+    current_round INTEGER NOT NULL DEFAULT 1,
+    -- Relationships
+    category_id UUID NOT NULL REFERENCES categories(id)
+);
+
+CREATE TABLE IF NOT EXISTS bracket_matches (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    round_number INTEGER NOT NULL,
+    -- Position within the round, keeps the seed order
+    match_number INTEGER NOT NULL,
+    -- Relationships
+    bracket_id UUID NOT NULL REFERENCES brackets(id) ON DELETE CASCADE,
+    candidate_a_id UUID NOT NULL REFERENCES candidates(id),
+    -- NULL when candidate A has a bye
+    candidate_b_id UUID REFERENCES candidates(id),
+    winner_id UUID REFERENCES candidates(id)
+);
+
+-- One ballot per judge per match
+CREATE TABLE IF NOT EXISTS match_ballots (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    time_of_scoring TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- Relationships
+    match_id UUID NOT NULL REFERENCES bracket_matches(id) ON DELETE CASCADE,
+    judge_id UUID NOT NULL REFERENCES judges(id),
+    winner_id UUID NOT NULL REFERENCES candidates(id),
+    UNIQUE (match_id, judge_id)
+);
@@ -0,0 +1,14 @@
+ALTER TABLE scores ADD COLUMN IF NOT EXISTS remark TEXT;
+ALTER TABLE scores_archive ADD COLUMN IF NOT EXISTS remark TEXT;
+
+-- Scores below this fraction of the max require a remark, NULL disables the rule
+ALTER TABLE events ADD COLUMN IF NOT EXISTS remark_low_threshold REAL;
+-- Perfect scores require a remark
+ALTER TABLE events ADD COLUMN IF NOT EXISTS remark_on_perfect BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE OR REPLACE VIEW scores_history AS
+    SELECT id, score, max, time_of_scoring, candidate_id, criteria_id, category_id, judge_id, remark
+    FROM scores
+    UNION ALL
+    SELECT id, score, max, time_of_scoring, candidate_id, criteria_id, category_id, judge_id, remark
+    FROM scores_archive;
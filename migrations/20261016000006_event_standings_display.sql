@@ -0,0 +1,3 @@
+-- What the display board shows: weighted 'percentage' or raw 'points'
+ALTER TABLE events ADD COLUMN IF NOT EXISTS standings_display TEXT NOT NULL DEFAULT 'percentage'
+    CHECK (standings_display IN ('percentage', 'points'));
@@ -0,0 +1,2 @@
+-- Locked judges see a holding screen until the next category is activated
+ALTER TABLE judges ADD COLUMN IF NOT EXISTS is_locked BOOLEAN NOT NULL DEFAULT FALSE;
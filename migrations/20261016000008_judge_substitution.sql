@@ -0,0 +1,4 @@
+-- A substitute takes over the panel seat of the judge it replaces
+-- Always points at the judge who originally held the seat
+ALTER TABLE judges ADD COLUMN IF NOT EXISTS substitute_for UUID REFERENCES judges(id);
+ALTER TABLE judges ADD COLUMN IF NOT EXISTS substituted_at TIMESTAMPTZ;
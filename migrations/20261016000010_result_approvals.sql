@@ -0,0 +1,21 @@
+-- Committee members acknowledge finalized results before they are published
+CREATE TABLE IF NOT EXISTS committee_members (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name TEXT NOT NULL,
+    username TEXT NOT NULL UNIQUE,
+    password TEXT NOT NULL,
+    -- Relationships
+    event_id UUID NOT NULL REFERENCES events(id)
+);
+
+ALTER TABLE events ADD COLUMN IF NOT EXISTS required_approvals INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE events ADD COLUMN IF NOT EXISTS finalized_snapshot_id UUID REFERENCES standings_snapshots(id);
+
+CREATE TABLE IF NOT EXISTS result_approvals (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    approved_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- Relationships
+    snapshot_id UUID NOT NULL REFERENCES standings_snapshots(id),
+    committee_member_id UUID NOT NULL REFERENCES committee_members(id),
+    UNIQUE (snapshot_id, committee_member_id)
+);
@@ -0,0 +1,12 @@
+-- Server errors and panics kept for debugging after the show
+CREATE TABLE IF NOT EXISTS error_reports (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    -- 'error' or 'panic'
+    kind TEXT NOT NULL,
+    status INTEGER,
+    message TEXT NOT NULL,
+    method TEXT,
+    uri TEXT,
+    judge_id TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,12 @@
+-- Forensic trail of every mutating request, independent of the audit log
+CREATE TABLE IF NOT EXISTS request_log (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    method TEXT NOT NULL,
+    uri TEXT NOT NULL,
+    -- NULL when the body isn't JSON
+    body JSONB,
+    status INTEGER NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS request_log_created_at_idx ON request_log (created_at);
@@ -0,0 +1,8 @@
+-- Messages from the tabulator to every judge of the event
+CREATE TABLE IF NOT EXISTS announcements (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    message TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- Relationships
+    event_id UUID NOT NULL REFERENCES events(id)
+);
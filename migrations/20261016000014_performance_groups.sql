@@ -0,0 +1,26 @@
+-- Candidates performing together in pair/group segments
+CREATE TABLE IF NOT EXISTS performance_groups (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name TEXT NOT NULL,
+    -- 'fan_out' gives every member the group's score, 'group' keeps it at group level only
+    score_mode TEXT NOT NULL DEFAULT 'fan_out' CHECK (score_mode IN ('fan_out', 'group')),
+    -- Relationships
+    event_id UUID NOT NULL REFERENCES events(id)
+);
+
+CREATE TABLE IF NOT EXISTS performance_group_members (
+    group_id UUID NOT NULL REFERENCES performance_groups(id) ON DELETE CASCADE,
+    candidate_id UUID NOT NULL REFERENCES candidates(id),
+    PRIMARY KEY (group_id, candidate_id)
+);
+
+-- Group scores are stored once per member, tagged with the group
+ALTER TABLE scores ADD COLUMN IF NOT EXISTS group_id UUID REFERENCES performance_groups(id);
+ALTER TABLE scores_archive ADD COLUMN IF NOT EXISTS group_id UUID;
+
+CREATE OR REPLACE VIEW scores_history AS
+    SELECT id, score, max, time_of_scoring, candidate_id, criteria_id, category_id, judge_id, remark, group_id
+    FROM scores
+    UNION ALL
+    SELECT id, score, max, time_of_scoring, candidate_id, criteria_id, category_id, judge_id, remark, group_id
+    FROM scores_archive;
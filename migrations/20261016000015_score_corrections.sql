@@ -0,0 +1,18 @@
+-- Judges ask the tabulator to fix a score after they have confirmed
+CREATE TABLE IF NOT EXISTS score_correction_requests (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    requested_score INTEGER NOT NULL,
+    reason TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'approved', 'rejected')),
+    resolution_note TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    resolved_at TIMESTAMPTZ,
+    -- Relationships
+    -- No foreign key, archived scores move out of `scores` but keep their requests
+    score_id UUID NOT NULL,
+    judge_id UUID NOT NULL REFERENCES judges(id)
+);
+
+-- Only one open request per score at a time
+CREATE UNIQUE INDEX IF NOT EXISTS score_correction_requests_pending_idx
+    ON score_correction_requests (score_id) WHERE status = 'pending';
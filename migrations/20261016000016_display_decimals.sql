@@ -0,0 +1,3 @@
+-- Decimals shown in standings, 4 tells apart close contests that look tied at 2
+ALTER TABLE events ADD COLUMN IF NOT EXISTS display_decimals INTEGER NOT NULL DEFAULT 2
+    CHECK (display_decimals IN (2, 4));
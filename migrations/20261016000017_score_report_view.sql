@@ -0,0 +1,32 @@
+-- One row per score with everything needed to report on it, live and archived
+-- Rounds are not modeled yet, so there is no round column
+CREATE OR REPLACE VIEW score_report AS
+    SELECT
+        s.id AS score_id,
+        s.score,
+        s.max,
+        s.time_of_scoring,
+        s.remark,
+        s.group_id,
+        j.id AS judge_id,
+        j.name AS judge_name,
+        j.score_exclusion AS judge_score_exclusion,
+        can.id AS candidate_id,
+        can.candidate_number,
+        can.first_name AS candidate_first_name,
+        can.middle_name AS candidate_middle_name,
+        can.last_name AS candidate_last_name,
+        can.gender AS candidate_gender,
+        cri.id AS criteria_id,
+        cri.name AS criteria_name,
+        cat.id AS category_id,
+        cat.name AS category_name,
+        cat.weight AS category_weight,
+        e.id AS event_id,
+        e.name AS event_name
+    FROM scores_history s
+    JOIN judges j ON j.id = s.judge_id
+    JOIN candidates can ON can.id = s.candidate_id
+    JOIN criterias cri ON cri.id = s.criteria_id
+    JOIN categories cat ON cat.id = s.category_id
+    JOIN events e ON e.id = cat.event_id;
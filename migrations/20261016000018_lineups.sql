@@ -0,0 +1,9 @@
+-- Performance order of candidates in a category, from the draw or arranged by hand
+CREATE TABLE IF NOT EXISTS lineups (
+    position INTEGER NOT NULL,
+    -- Relationships
+    category_id UUID NOT NULL REFERENCES categories(id) ON DELETE CASCADE,
+    candidate_id UUID NOT NULL REFERENCES candidates(id) ON DELETE CASCADE,
+    PRIMARY KEY (category_id, candidate_id),
+    UNIQUE (category_id, position)
+);
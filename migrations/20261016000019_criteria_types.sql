@@ -0,0 +1,6 @@
+-- Non-numeric criteria, scores still store the points they are worth
+-- 'boolean' gives max_score for yes and 0 for no, 'selection' uses the points of the chosen option
+ALTER TABLE criterias ADD COLUMN IF NOT EXISTS criteria_type TEXT NOT NULL DEFAULT 'numeric'
+    CHECK (criteria_type IN ('numeric', 'boolean', 'selection'));
+-- [{ "label": "Gold", "points": 10 }, ...] for selection criteria
+ALTER TABLE criterias ADD COLUMN IF NOT EXISTS options JSONB;
@@ -0,0 +1,2 @@
+-- Minimum number of judges that must score every candidate before the category is certified
+ALTER TABLE categories ADD COLUMN IF NOT EXISTS quorum INTEGER CHECK (quorum > 0);
@@ -0,0 +1,26 @@
+-- Points taken off a candidate's score in a category, by hand or from overtime
+CREATE TABLE IF NOT EXISTS deductions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    points REAL NOT NULL CHECK (points > 0),
+    reason TEXT NOT NULL,
+    -- 'manual' or 'overtime'
+    source TEXT NOT NULL DEFAULT 'manual' CHECK (source IN ('manual', 'overtime')),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- Relationships
+    candidate_id UUID NOT NULL REFERENCES candidates(id) ON DELETE CASCADE,
+    category_id UUID NOT NULL REFERENCES categories(id) ON DELETE CASCADE
+);
+
+-- Time limits for segments like talent, overtime deduction is per started minute over
+ALTER TABLE categories ADD COLUMN IF NOT EXISTS time_limit_seconds INTEGER CHECK (time_limit_seconds > 0);
+ALTER TABLE categories ADD COLUMN IF NOT EXISTS overtime_deduction REAL CHECK (overtime_deduction > 0);
+
+CREATE TABLE IF NOT EXISTS performance_timings (
+    started_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    stopped_at TIMESTAMPTZ,
+    duration_seconds INTEGER,
+    -- Relationships
+    candidate_id UUID NOT NULL REFERENCES candidates(id) ON DELETE CASCADE,
+    category_id UUID NOT NULL REFERENCES categories(id) ON DELETE CASCADE,
+    PRIMARY KEY (candidate_id, category_id)
+);
@@ -0,0 +1,2 @@
+-- When the category was last activated for scoring, used for scoring latency
+ALTER TABLE categories ADD COLUMN IF NOT EXISTS opened_at TIMESTAMPTZ;
@@ -0,0 +1,3 @@
+-- Deduction criterias take scores from min_score up to 0 and have a max score of 0
+ALTER TABLE criterias ADD COLUMN IF NOT EXISTS is_deduction BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE criterias ADD COLUMN IF NOT EXISTS min_score INTEGER NOT NULL DEFAULT 0;
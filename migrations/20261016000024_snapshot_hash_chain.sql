@@ -0,0 +1,4 @@
+-- Tamper evidence, each snapshot's hash covers the previous snapshot's hash
+-- Snapshots taken before this have no hashes and start the chain over
+ALTER TABLE standings_snapshots ADD COLUMN IF NOT EXISTS scores_digest TEXT;
+ALTER TABLE standings_snapshots ADD COLUMN IF NOT EXISTS chain_hash TEXT;
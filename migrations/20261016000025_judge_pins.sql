@@ -0,0 +1,19 @@
+-- Short numeric PINs for judges, only valid for the event day
+CREATE TABLE IF NOT EXISTS judge_pins (
+    pin TEXT NOT NULL,
+    valid_until TIMESTAMPTZ NOT NULL,
+    -- Relationships
+    judge_id UUID PRIMARY KEY REFERENCES judges(id) ON DELETE CASCADE,
+    event_id UUID NOT NULL REFERENCES events(id) ON DELETE CASCADE,
+    UNIQUE (event_id, pin)
+);
+
+-- Sessions handed out in exchange for a PIN, scoped to the judge's event
+CREATE TABLE IF NOT EXISTS judge_sessions (
+    token TEXT PRIMARY KEY DEFAULT replace(gen_random_uuid()::TEXT || gen_random_uuid()::TEXT, '-', ''),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    -- Relationships
+    judge_id UUID NOT NULL REFERENCES judges(id) ON DELETE CASCADE,
+    event_id UUID NOT NULL REFERENCES events(id) ON DELETE CASCADE
+);
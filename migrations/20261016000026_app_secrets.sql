@@ -0,0 +1,6 @@
+-- Secrets generated by the backend itself so they survive restarts without extra config
+CREATE TABLE IF NOT EXISTS app_secrets (
+    name TEXT PRIMARY KEY,
+    value TEXT NOT NULL DEFAULT replace(gen_random_uuid()::TEXT || gen_random_uuid()::TEXT, '-', ''),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,17 @@
+-- Read-only accounts for sponsors and faculty advisers watching the tabulation
+CREATE TABLE IF NOT EXISTS observers (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name TEXT NOT NULL,
+    username TEXT NOT NULL UNIQUE,
+    password TEXT NOT NULL,
+    -- Relationships
+    event_id UUID NOT NULL REFERENCES events(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS observer_sessions (
+    token TEXT PRIMARY KEY DEFAULT replace(gen_random_uuid()::TEXT || gen_random_uuid()::TEXT, '-', ''),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL DEFAULT NOW() + INTERVAL '12 hours',
+    -- Relationships
+    observer_id UUID NOT NULL REFERENCES observers(id) ON DELETE CASCADE
+);
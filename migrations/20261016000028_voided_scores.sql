@@ -0,0 +1,9 @@
+-- Scores thrown out when a segment is re-run, kept for audit but never aggregated
+CREATE TABLE IF NOT EXISTS voided_scores (
+    LIKE scores INCLUDING DEFAULTS,
+    voided_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    void_reason TEXT NOT NULL,
+    PRIMARY KEY (id)
+);
+
+CREATE INDEX IF NOT EXISTS voided_scores_category_id_idx ON voided_scores (category_id);
@@ -0,0 +1,3 @@
+-- Duplicates merged into another candidate are kept for the audit trail but hidden everywhere
+ALTER TABLE candidates ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ;
+ALTER TABLE candidates ADD COLUMN IF NOT EXISTS merged_into UUID REFERENCES candidates(id);
@@ -0,0 +1,65 @@
+-- Scores written during tech rehearsal are tagged and left out of certified results
+ALTER TABLE events ADD COLUMN IF NOT EXISTS rehearsal_mode BOOLEAN NOT NULL DEFAULT FALSE;
+
+ALTER TABLE scores ADD COLUMN IF NOT EXISTS is_rehearsal BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE scores_archive ADD COLUMN IF NOT EXISTS is_rehearsal BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE voided_scores ADD COLUMN IF NOT EXISTS is_rehearsal BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Tagged in the database so every way of writing a score is covered
+CREATE OR REPLACE FUNCTION tag_rehearsal_score() RETURNS TRIGGER AS $$
+BEGIN
+    NEW.is_rehearsal := COALESCE((
+        SELECT e.rehearsal_mode
+        FROM categories cat
+        JOIN events e ON e.id = cat.event_id
+        WHERE cat.id = NEW.category_id
+    ), FALSE);
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS scores_tag_rehearsal ON scores;
+CREATE TRIGGER scores_tag_rehearsal
+    BEFORE INSERT ON scores
+    FOR EACH ROW EXECUTE FUNCTION tag_rehearsal_score();
+
+CREATE OR REPLACE VIEW scores_history AS
+    SELECT id, score, max, time_of_scoring, candidate_id, criteria_id, category_id, judge_id, remark, group_id, is_rehearsal
+    FROM scores
+    UNION ALL
+    SELECT id, score, max, time_of_scoring, candidate_id, criteria_id, category_id, judge_id, remark, group_id, is_rehearsal
+    FROM scores_archive;
+
+-- Exports never include rehearsal scores
+CREATE OR REPLACE VIEW score_report AS
+    SELECT
+        s.id AS score_id,
+        s.score,
+        s.max,
+        s.time_of_scoring,
+        s.remark,
+        s.group_id,
+        j.id AS judge_id,
+        j.name AS judge_name,
+        j.score_exclusion AS judge_score_exclusion,
+        can.id AS candidate_id,
+        can.candidate_number,
+        can.first_name AS candidate_first_name,
+        can.middle_name AS candidate_middle_name,
+        can.last_name AS candidate_last_name,
+        can.gender AS candidate_gender,
+        cri.id AS criteria_id,
+        cri.name AS criteria_name,
+        cat.id AS category_id,
+        cat.name AS category_name,
+        cat.weight AS category_weight,
+        e.id AS event_id,
+        e.name AS event_name
+    FROM scores_history s
+    JOIN judges j ON j.id = s.judge_id
+    JOIN candidates can ON can.id = s.candidate_id
+    JOIN criterias cri ON cri.id = s.criteria_id
+    JOIN categories cat ON cat.id = s.category_id
+    JOIN events e ON e.id = cat.event_id
+    WHERE s.is_rehearsal = FALSE;
@@ -0,0 +1,21 @@
+-- Per-event configuration, new settings go here instead of new columns on events
+CREATE TABLE IF NOT EXISTS event_settings (
+    key TEXT NOT NULL,
+    value JSONB NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- Relationships
+    event_id UUID NOT NULL REFERENCES events(id) ON DELETE CASCADE,
+    PRIMARY KEY (event_id, key)
+);
+
+-- Settings that used to be columns
+INSERT INTO event_settings (event_id, key, value)
+SELECT id, 'anonymize_judges', to_jsonb(anonymize_judges) FROM events
+ON CONFLICT DO NOTHING;
+
+INSERT INTO event_settings (event_id, key, value)
+SELECT id, 'display_decimals', to_jsonb(display_decimals) FROM events
+ON CONFLICT DO NOTHING;
+
+ALTER TABLE events DROP COLUMN IF EXISTS anonymize_judges;
+ALTER TABLE events DROP COLUMN IF EXISTS display_decimals;
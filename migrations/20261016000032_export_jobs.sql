@@ -0,0 +1,18 @@
+-- Exports generated in the background, so a big historical export can't hold up the certified file
+CREATE TABLE IF NOT EXISTS export_jobs (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    kind TEXT NOT NULL CHECK (kind IN ('spreadsheet', 'csv', 'jsonl')),
+    certified BOOLEAN NOT NULL DEFAULT FALSE,
+    -- Higher runs first, finalization exports use the highest
+    priority INTEGER NOT NULL DEFAULT 1,
+    status TEXT NOT NULL DEFAULT 'queued'
+        CHECK (status IN ('queued', 'running', 'done', 'failed', 'cancelled')),
+    result BYTEA,
+    error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    started_at TIMESTAMPTZ,
+    finished_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS export_jobs_queue_idx
+    ON export_jobs (priority DESC, created_at) WHERE status = 'queued';
@@ -0,0 +1,31 @@
+-- Lets external mirrors pull only the scores that changed since their last sync
+ALTER TABLE scores ADD COLUMN IF NOT EXISTS updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+ALTER TABLE scores_archive ADD COLUMN IF NOT EXISTS updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+ALTER TABLE voided_scores ADD COLUMN IF NOT EXISTS updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+
+UPDATE scores SET updated_at = time_of_scoring;
+UPDATE scores_archive SET updated_at = time_of_scoring;
+UPDATE voided_scores SET updated_at = time_of_scoring;
+
+CREATE INDEX IF NOT EXISTS scores_updated_at_idx ON scores (updated_at);
+
+-- Any change counts, including ones that don't touch `time_of_scoring` like candidate merges
+CREATE OR REPLACE FUNCTION touch_score_updated_at() RETURNS TRIGGER AS $$
+BEGIN
+    NEW.updated_at := NOW();
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS scores_touch_updated_at ON scores;
+CREATE TRIGGER scores_touch_updated_at
+    BEFORE UPDATE ON scores
+    FOR EACH ROW EXECUTE FUNCTION touch_score_updated_at();
+
+CREATE OR REPLACE VIEW scores_history AS
+    SELECT id, score, max, time_of_scoring, candidate_id, criteria_id, category_id, judge_id, remark, group_id, is_rehearsal, updated_at
+    FROM scores
+    UNION ALL
+    SELECT id, score, max, time_of_scoring, candidate_id, criteria_id, category_id, judge_id, remark, group_id, is_rehearsal, updated_at
+    FROM scores_archive;
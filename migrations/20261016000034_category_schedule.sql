@@ -0,0 +1,3 @@
+-- Times the scheduler opens and closes the category on its own, cleared once acted on
+ALTER TABLE categories ADD COLUMN IF NOT EXISTS scheduled_open_at TIMESTAMPTZ;
+ALTER TABLE categories ADD COLUMN IF NOT EXISTS scheduled_close_at TIMESTAMPTZ;
@@ -0,0 +1,7 @@
+-- Candidates eligible for a category when it first opened, later withdrawals don't change it
+CREATE TABLE IF NOT EXISTS round_eligibility (
+    category_id UUID NOT NULL REFERENCES categories(id) ON DELETE CASCADE,
+    candidate_id UUID NOT NULL REFERENCES candidates(id),
+    snapshot_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (category_id, candidate_id)
+);
@@ -0,0 +1,11 @@
+-- A judge's signed confirmation of their final scores for a category
+CREATE TABLE IF NOT EXISTS judge_confirmations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    confirmed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    device TEXT NOT NULL,
+    scores_digest TEXT NOT NULL,
+    signature TEXT NOT NULL,
+    -- Relationships
+    judge_id UUID NOT NULL REFERENCES judges(id) ON DELETE CASCADE,
+    category_id UUID NOT NULL REFERENCES categories(id) ON DELETE CASCADE
+);
@@ -0,0 +1,8 @@
+-- Kept apart from candidates so listing candidates doesn't load the images
+CREATE TABLE IF NOT EXISTS candidate_photos (
+    content_type TEXT NOT NULL,
+    data BYTEA NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- Relationships
+    candidate_id UUID PRIMARY KEY REFERENCES candidates(id) ON DELETE CASCADE
+);
@@ -0,0 +1,5 @@
+-- Unguessable handle for the public results embed, so event IDs never leave the venue network
+ALTER TABLE events ADD COLUMN IF NOT EXISTS public_token TEXT NOT NULL
+    DEFAULT replace(gen_random_uuid()::TEXT, '-', '');
+
+CREATE UNIQUE INDEX IF NOT EXISTS events_public_token_idx ON events (public_token);
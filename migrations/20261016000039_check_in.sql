@@ -0,0 +1,3 @@
+-- When candidates and judges signed in at the registration desk
+ALTER TABLE candidates ADD COLUMN IF NOT EXISTS checked_in_at TIMESTAMPTZ;
+ALTER TABLE judges ADD COLUMN IF NOT EXISTS checked_in_at TIMESTAMPTZ;
@@ -0,0 +1,9 @@
+-- Existing duplicates within an event get a numbered suffix so the index can be built
+UPDATE judges j SET username = j.username || dup.n
+FROM (
+    SELECT id, ROW_NUMBER() OVER (PARTITION BY event_id, username ORDER BY id) AS n
+    FROM judges
+) dup
+WHERE dup.id = j.id AND dup.n > 1;
+
+CREATE UNIQUE INDEX IF NOT EXISTS judges_event_username_idx ON judges (event_id, username);
@@ -0,0 +1,3 @@
+-- Sessions an admin opened to act as the judge, never issued through a login
+ALTER TABLE judge_sessions ADD COLUMN IF NOT EXISTS impersonated_by TEXT;
+ALTER TABLE judge_sessions ADD COLUMN IF NOT EXISTS impersonation_reason TEXT;
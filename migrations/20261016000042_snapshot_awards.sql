@@ -0,0 +1,3 @@
+-- Award titles given out when the event was finalized, frozen with the snapshot
+-- so changing the titles afterwards never rewrites published results
+ALTER TABLE standings_snapshots ADD COLUMN IF NOT EXISTS awards JSONB;
@@ -0,0 +1,2 @@
+-- Role of each member on the tabulation committee, printed on the report cover sheet
+ALTER TABLE committee_members ADD COLUMN IF NOT EXISTS role TEXT NOT NULL DEFAULT 'Member';
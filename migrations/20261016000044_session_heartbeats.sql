@@ -0,0 +1,15 @@
+-- Judge devices ping while the scoring page is open, sessions that stop are reaped
+ALTER TABLE judge_sessions ADD COLUMN IF NOT EXISTS last_heartbeat_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+
+-- Impersonation sessions are an admin at the desk, not the judge at their tablet
+CREATE OR REPLACE VIEW judge_presence AS
+    SELECT
+        j.id AS judge_id,
+        j.event_id,
+        j.name,
+        MAX(s.last_heartbeat_at) AS last_heartbeat_at,
+        COUNT(s.token) > 0 AS online
+    FROM judges j
+    LEFT JOIN judge_sessions s
+        ON s.judge_id = j.id AND s.expires_at > NOW() AND s.impersonated_by IS NULL
+    GROUP BY j.id;
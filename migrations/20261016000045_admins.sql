@@ -0,0 +1,10 @@
+-- Staff accounts, admins set up the event while tabulators only run reports and exports
+CREATE TABLE IF NOT EXISTS admins (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name TEXT NOT NULL,
+    username TEXT NOT NULL UNIQUE,
+    -- Argon2 hash
+    password TEXT NOT NULL,
+    role TEXT NOT NULL DEFAULT 'admin' CHECK (role IN ('admin', 'tabulator')),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
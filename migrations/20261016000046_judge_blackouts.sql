@@ -0,0 +1,9 @@
+-- Candidates a judge must not score, their standings are averaged over the remaining judges
+CREATE TABLE IF NOT EXISTS judge_blackouts (
+    reason TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- Relationships
+    judge_id UUID NOT NULL REFERENCES judges(id) ON DELETE CASCADE,
+    candidate_id UUID NOT NULL REFERENCES candidates(id) ON DELETE CASCADE,
+    PRIMARY KEY (judge_id, candidate_id)
+);
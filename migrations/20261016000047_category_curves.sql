@@ -0,0 +1,58 @@
+-- Optional grading on a curve, as percentages of each criteria's max score
+ALTER TABLE categories ADD COLUMN IF NOT EXISTS curve_mean REAL CHECK (curve_mean BETWEEN 0 AND 100);
+ALTER TABLE categories ADD COLUMN IF NOT EXISTS curve_std_dev REAL CHECK (curve_std_dev > 0 AND curve_std_dev <= 50);
+
+-- Each judge's scores on a criteria are standardized, then mapped onto the category's target
+-- mean and standard deviation, clamped to the criteria's range
+-- Deductions and categories without a curve keep their raw scores
+CREATE OR REPLACE VIEW curved_scores AS
+    SELECT
+        s.id,
+        CASE
+            WHEN cat.curve_mean IS NULL OR cat.curve_std_dev IS NULL OR s.max <= 0 THEN s.score::DOUBLE PRECISION
+            ELSE LEAST(GREATEST(
+                s.max * (cat.curve_mean + COALESCE(
+                    (s.score - AVG(s.score) OVER judge_criteria) / NULLIF(STDDEV_POP(s.score) OVER judge_criteria, 0),
+                    0
+                ) * cat.curve_std_dev) / 100.0,
+                0
+            ), s.max)
+        END AS score
+    FROM scores_history s
+    JOIN categories cat ON cat.id = s.category_id
+    WINDOW judge_criteria AS (PARTITION BY s.judge_id, s.criteria_id, s.is_rehearsal);
+
+-- Exports show the curved score next to the raw one
+CREATE OR REPLACE VIEW score_report AS
+    SELECT
+        s.id AS score_id,
+        s.score,
+        s.max,
+        s.time_of_scoring,
+        s.remark,
+        s.group_id,
+        j.id AS judge_id,
+        j.name AS judge_name,
+        j.score_exclusion AS judge_score_exclusion,
+        can.id AS candidate_id,
+        can.candidate_number,
+        can.first_name AS candidate_first_name,
+        can.middle_name AS candidate_middle_name,
+        can.last_name AS candidate_last_name,
+        can.gender AS candidate_gender,
+        cri.id AS criteria_id,
+        cri.name AS criteria_name,
+        cat.id AS category_id,
+        cat.name AS category_name,
+        cat.weight AS category_weight,
+        e.id AS event_id,
+        e.name AS event_name,
+        cs.score AS curved_score
+    FROM scores_history s
+    JOIN judges j ON j.id = s.judge_id
+    JOIN candidates can ON can.id = s.candidate_id
+    JOIN criterias cri ON cri.id = s.criteria_id
+    JOIN categories cat ON cat.id = s.category_id
+    JOIN events e ON e.id = cat.event_id
+    JOIN curved_scores cs ON cs.id = s.id
+    WHERE s.is_rehearsal = FALSE;
@@ -0,0 +1,22 @@
+-- A judge has one score per candidate and criteria, re-submitting replaces it
+-- Duplicates written before this are voided, keeping the latest submission
+WITH duplicates AS (
+    SELECT id FROM (
+        SELECT id, ROW_NUMBER() OVER (
+            PARTITION BY judge_id, candidate_id, criteria_id
+            ORDER BY time_of_scoring DESC, updated_at DESC, id
+        ) AS position
+        FROM scores
+    ) ranked
+    WHERE position > 1
+), voided AS (
+    INSERT INTO voided_scores (id, score, max, time_of_scoring, candidate_id, criteria_id, category_id, judge_id, remark, group_id, is_rehearsal, updated_at, void_reason)
+    SELECT id, score, max, time_of_scoring, candidate_id, criteria_id, category_id, judge_id, remark, group_id, is_rehearsal, updated_at, 'Duplicate submission'
+    FROM scores
+    WHERE id IN (SELECT id FROM duplicates)
+    RETURNING id
+)
+DELETE FROM scores WHERE id IN (SELECT id FROM voided);
+
+CREATE UNIQUE INDEX IF NOT EXISTS scores_judge_candidate_criteria_idx
+    ON scores (judge_id, candidate_id, criteria_id);
@@ -0,0 +1,13 @@
+-- How long score submissions took to reach the server and to be stored, to tell slow Wi-Fi from a slow backend
+CREATE TABLE IF NOT EXISTS submission_latencies (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    -- NULL when the device didn't send its clock
+    network_ms DOUBLE PRECISION,
+    processing_ms DOUBLE PRECISION NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- Relationships
+    judge_id UUID NOT NULL REFERENCES judges(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS submission_latencies_judge_id_idx ON submission_latencies (judge_id);
+CREATE INDEX IF NOT EXISTS submission_latencies_created_at_idx ON submission_latencies (created_at);
@@ -0,0 +1,15 @@
+-- Rank changes caused by an override after the event was finalized, re-finalizing waits for the committee to acknowledge them
+CREATE TABLE IF NOT EXISTS standings_rechecks (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    -- Every candidate whose rank or score differs from the finalized snapshot
+    changes JSONB NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    acknowledged_at TIMESTAMPTZ,
+    -- Relationships
+    event_id UUID NOT NULL REFERENCES events(id) ON DELETE CASCADE,
+    snapshot_id UUID NOT NULL REFERENCES standings_snapshots(id) ON DELETE CASCADE,
+    score_id UUID NOT NULL,
+    acknowledged_by UUID REFERENCES committee_members(id) ON DELETE SET NULL
+);
+
+CREATE INDEX IF NOT EXISTS standings_rechecks_pending_idx ON standings_rechecks (event_id) WHERE acknowledged_at IS NULL;
@@ -0,0 +1,16 @@
+-- Ordered rules for breaking exact ties, used when the event's `tie_break_strategy` is `tie_breakers`
+CREATE TABLE IF NOT EXISTS tie_breakers (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    -- 1 is applied first
+    position INTEGER NOT NULL,
+    kind TEXT NOT NULL CHECK (kind IN ('category', 'head_judge', 'earliest_submission')),
+    -- Relationships
+    event_id UUID NOT NULL REFERENCES events(id) ON DELETE CASCADE,
+    -- The category compared by `category`
+    category_id UUID REFERENCES categories(id) ON DELETE CASCADE,
+    -- The head judge compared by `head_judge`
+    judge_id UUID REFERENCES judges(id) ON DELETE CASCADE,
+    UNIQUE (event_id, position),
+    CHECK ((kind = 'category') = (category_id IS NOT NULL)),
+    CHECK ((kind = 'head_judge') = (judge_id IS NOT NULL))
+);
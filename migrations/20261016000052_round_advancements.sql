@@ -0,0 +1,13 @@
+-- Who made the cut after a round (a category), eliminated candidates can't be scored in later rounds
+CREATE TABLE IF NOT EXISTS round_advancements (
+    -- Rank in the round's standings when it was advanced
+    rank INTEGER NOT NULL,
+    advanced BOOLEAN NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- Relationships
+    category_id UUID NOT NULL REFERENCES categories(id) ON DELETE CASCADE,
+    candidate_id UUID NOT NULL REFERENCES candidates(id) ON DELETE CASCADE,
+    PRIMARY KEY (category_id, candidate_id)
+);
+
+CREATE INDEX IF NOT EXISTS round_advancements_eliminated_idx ON round_advancements (candidate_id) WHERE advanced = FALSE;
@@ -0,0 +1,51 @@
+-- Rounds group an event's categories, e.g. preliminaries then finals
+CREATE TABLE IF NOT EXISTS rounds (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name TEXT NOT NULL,
+    -- Rounds are held in this order, advancement only eliminates from later rounds
+    position INTEGER NOT NULL,
+    -- Categories of an inactive round don't accept scores
+    is_active BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- Relationships
+    event_id UUID NOT NULL REFERENCES events(id) ON DELETE CASCADE
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS rounds_one_active_idx ON rounds (event_id) WHERE is_active;
+
+-- Categories outside of any round accept scores as before
+ALTER TABLE categories ADD COLUMN IF NOT EXISTS round_id UUID REFERENCES rounds(id) ON DELETE SET NULL;
+
+-- Advancement was per category until rounds existed, each category advanced on becomes a round of its own,
+-- held in the order they were advanced
+ALTER TABLE round_advancements ADD COLUMN IF NOT EXISTS round_id UUID REFERENCES rounds(id) ON DELETE CASCADE;
+
+WITH advanced_categories AS (
+    SELECT
+        cat.id,
+        cat.name,
+        cat.event_id,
+        ROW_NUMBER() OVER (PARTITION BY cat.event_id ORDER BY MIN(ra.created_at), cat.id)::INTEGER AS position
+    FROM round_advancements ra
+    JOIN categories cat ON cat.id = ra.category_id
+    WHERE cat.round_id IS NULL
+    GROUP BY cat.id
+),
+new_rounds AS (
+    INSERT INTO rounds (name, position, event_id)
+    SELECT name, position, event_id FROM advanced_categories
+    RETURNING id, position, event_id
+)
+UPDATE categories cat SET round_id = nr.id
+FROM advanced_categories ac
+JOIN new_rounds nr ON nr.event_id = ac.event_id AND nr.position = ac.position
+WHERE cat.id = ac.id;
+
+UPDATE round_advancements ra SET round_id = cat.round_id
+FROM categories cat
+WHERE cat.id = ra.category_id AND ra.round_id IS NULL;
+
+ALTER TABLE round_advancements DROP CONSTRAINT IF EXISTS round_advancements_pkey;
+ALTER TABLE round_advancements DROP COLUMN IF EXISTS category_id;
+ALTER TABLE round_advancements ALTER COLUMN round_id SET NOT NULL;
+ALTER TABLE round_advancements ADD PRIMARY KEY (round_id, candidate_id);
@@ -0,0 +1,44 @@
+-- Candidate numbers are unique per division (gender) or across the whole event, per the event's `candidate_numbering` setting
+-- Candidates belong to an event through their category, deleted candidates don't hold on to their numbers
+CREATE OR REPLACE FUNCTION check_candidate_number() RETURNS TRIGGER AS $$
+DECLARE
+    candidate_event_id UUID;
+    numbering TEXT;
+BEGIN
+    IF NEW.deleted_at IS NOT NULL OR NEW.category_id IS NULL THEN
+        RETURN NEW;
+    END IF;
+
+    SELECT event_id INTO candidate_event_id FROM categories WHERE id = NEW.category_id;
+
+    IF candidate_event_id IS NULL THEN
+        RETURN NEW;
+    END IF;
+
+    -- Two candidates saved at once would otherwise both see the number as free
+    PERFORM pg_advisory_xact_lock(hashtext('candidate_numbers:' || candidate_event_id::TEXT));
+
+    SELECT value #>> '{}' INTO numbering FROM event_settings
+    WHERE event_id = candidate_event_id AND key = 'candidate_numbering';
+
+    IF EXISTS (
+        SELECT 1 FROM candidates c
+        JOIN categories cat ON cat.id = c.category_id
+        WHERE cat.event_id = candidate_event_id
+            AND c.id <> NEW.id
+            AND c.deleted_at IS NULL
+            AND c.candidate_number = NEW.candidate_number
+            AND (COALESCE(numbering, 'per_division') = 'per_event' OR c.gender = NEW.gender)
+    ) THEN
+        RAISE EXCEPTION 'candidate number % is already taken', NEW.candidate_number
+            USING ERRCODE = 'unique_violation', CONSTRAINT = 'candidates_number_unique';
+    END IF;
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS candidates_number_unique ON candidates;
+CREATE TRIGGER candidates_number_unique
+    BEFORE INSERT OR UPDATE OF candidate_number, gender, category_id, deleted_at ON candidates
+    FOR EACH ROW EXECUTE FUNCTION check_candidate_number();
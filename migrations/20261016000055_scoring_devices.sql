@@ -0,0 +1,15 @@
+-- Keypads and clickers whose bridge posts scores for a judge, one criteria per device
+CREATE TABLE IF NOT EXISTS scoring_devices (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    -- The ID the hardware reports itself with
+    device_id TEXT NOT NULL,
+    -- Sent by the bridge as a bearer token
+    token TEXT NOT NULL UNIQUE DEFAULT replace(gen_random_uuid()::TEXT || gen_random_uuid()::TEXT, '-', ''),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_packet_at TIMESTAMPTZ,
+    -- Relationships
+    event_id UUID NOT NULL REFERENCES events(id) ON DELETE CASCADE,
+    judge_id UUID NOT NULL REFERENCES judges(id) ON DELETE CASCADE,
+    criteria_id UUID NOT NULL REFERENCES criterias(id) ON DELETE CASCADE,
+    UNIQUE (event_id, device_id)
+);
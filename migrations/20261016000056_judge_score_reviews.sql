@@ -0,0 +1,9 @@
+-- The scores a judge last went through on the review screen, confirming needs the same ones
+CREATE TABLE IF NOT EXISTS judge_score_reviews (
+    reviewed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    scores_digest TEXT NOT NULL,
+    -- Relationships
+    judge_id UUID NOT NULL REFERENCES judges(id) ON DELETE CASCADE,
+    category_id UUID NOT NULL REFERENCES categories(id) ON DELETE CASCADE,
+    PRIMARY KEY (judge_id, category_id)
+);
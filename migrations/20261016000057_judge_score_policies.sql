@@ -0,0 +1,59 @@
+-- Row-level security beneath the app's own checks: Postgres logins in the `tabulation_judge` role
+-- can only read and write the scores of the judge their login is mapped to in `judge_db_logins`
+-- The app's own role owns the tables and isn't affected
+CREATE TABLE IF NOT EXISTS judge_db_logins (
+    -- The Postgres login, compared with `current_user`
+    rolname TEXT PRIMARY KEY,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- Relationships
+    judge_id UUID NOT NULL REFERENCES judges(id) ON DELETE CASCADE
+);
+
+-- Runs as the table owner, so judge logins never need to read the mapping themselves
+-- `current_user` would be the owner in here, so the policies pass the caller's in
+CREATE OR REPLACE FUNCTION db_login_judge_id(login NAME) RETURNS UUID
+LANGUAGE sql STABLE SECURITY DEFINER AS $$
+    SELECT judge_id FROM judge_db_logins WHERE rolname = login
+$$;
+
+-- Pinned to the schema being migrated (organizations have their own), with the caller's temporary tables last
+DO $$
+BEGIN
+    EXECUTE format(
+        'ALTER FUNCTION db_login_judge_id(NAME) SET search_path = %I, pg_temp',
+        current_schema()
+    );
+END $$;
+
+REVOKE ALL ON FUNCTION db_login_judge_id(NAME) FROM PUBLIC;
+
+ALTER TABLE scores ENABLE ROW LEVEL SECURITY;
+
+-- Every other role keeps the access its grants give it
+DROP POLICY IF EXISTS scores_unrestricted ON scores;
+CREATE POLICY scores_unrestricted ON scores USING (TRUE) WITH CHECK (TRUE);
+
+-- Creating roles needs CREATEROLE, databases without it skip the judge role, run this block by hand there
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_roles WHERE rolname = 'tabulation_judge') THEN
+        CREATE ROLE tabulation_judge NOLOGIN;
+    END IF;
+
+    EXECUTE format('GRANT USAGE ON SCHEMA %I TO tabulation_judge', current_schema());
+    GRANT EXECUTE ON FUNCTION db_login_judge_id(NAME) TO tabulation_judge;
+    GRANT SELECT ON events, categories, criterias, candidates TO tabulation_judge;
+    -- Never the usernames or password hashes
+    GRANT SELECT (id, name, is_active, is_locked, event_id, substitute_for, substituted_at)
+        ON judges TO tabulation_judge;
+    GRANT SELECT, INSERT, UPDATE ON scores TO tabulation_judge;
+
+    DROP POLICY IF EXISTS scores_own_judge ON scores;
+    -- Restrictive, so it narrows `scores_unrestricted` instead of adding to it
+    CREATE POLICY scores_own_judge ON scores AS RESTRICTIVE TO tabulation_judge
+        USING (judge_id = db_login_judge_id(current_user))
+        WITH CHECK (judge_id = db_login_judge_id(current_user));
+EXCEPTION
+    WHEN insufficient_privilege THEN
+        RAISE NOTICE 'Skipping the tabulation_judge role, creating it needs CREATEROLE';
+END $$;
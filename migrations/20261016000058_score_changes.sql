@@ -0,0 +1,120 @@
+-- Every change to the scores, in the order mirrors should apply them
+-- Replaces syncing by `updated_at`, which misses scores committed after a later one was read and never shows deletions
+CREATE TABLE IF NOT EXISTS score_changes (
+    seq BIGSERIAL PRIMARY KEY,
+    -- A sync only returns changes of transactions older than every one still running, so none commit behind its cursor
+    txid XID8 NOT NULL DEFAULT pg_current_xact_id(),
+    -- 'upsert' for created or updated scores, 'void' for voided ones, 'delete' for removed ones
+    change TEXT NOT NULL CHECK (change IN ('upsert', 'void', 'delete')),
+    changed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- The score as of the change
+    score_id UUID NOT NULL,
+    score INTEGER NOT NULL,
+    max INTEGER NOT NULL,
+    time_of_scoring TIMESTAMPTZ NOT NULL,
+    remark TEXT,
+    void_reason TEXT,
+    is_rehearsal BOOLEAN NOT NULL,
+    -- Relationships
+    candidate_id UUID NOT NULL,
+    criteria_id UUID NOT NULL,
+    category_id UUID NOT NULL,
+    judge_id UUID NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS score_changes_txid_idx ON score_changes (txid, seq);
+
+-- Archived scores stay part of the history, moving them isn't a change
+-- Runs as the table owner so judge logins (see `judge_score_policies`) can still write scores
+CREATE OR REPLACE FUNCTION record_score_change() RETURNS TRIGGER
+LANGUAGE plpgsql SECURITY DEFINER AS $$
+DECLARE
+    change_kind TEXT := 'upsert';
+    row_data scores%ROWTYPE;
+    reason TEXT;
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        row_data := OLD;
+
+        SELECT void_reason INTO reason FROM voided_scores WHERE id = OLD.id;
+
+        IF FOUND THEN
+            change_kind := 'void';
+        ELSIF EXISTS (SELECT 1 FROM scores_archive WHERE id = OLD.id) THEN
+            RETURN NULL;
+        ELSE
+            change_kind := 'delete';
+        END IF;
+    ELSE
+        row_data := NEW;
+    END IF;
+
+    INSERT INTO score_changes (
+        change, score_id, score, max, time_of_scoring, remark, void_reason, is_rehearsal,
+        candidate_id, criteria_id, category_id, judge_id
+    )
+    VALUES (
+        change_kind, row_data.id, row_data.score, row_data.max, row_data.time_of_scoring, row_data.remark,
+        reason, row_data.is_rehearsal, row_data.candidate_id, row_data.criteria_id, row_data.category_id,
+        row_data.judge_id
+    );
+
+    RETURN NULL;
+END;
+$$;
+
+DO $$
+BEGIN
+    EXECUTE format(
+        'ALTER FUNCTION record_score_change() SET search_path = %I, pg_temp',
+        current_schema()
+    );
+END $$;
+
+REVOKE ALL ON FUNCTION record_score_change() FROM PUBLIC;
+
+DROP TRIGGER IF EXISTS scores_record_change ON scores;
+CREATE TRIGGER scores_record_change
+    AFTER INSERT OR UPDATE OR DELETE ON scores
+    FOR EACH ROW EXECUTE FUNCTION record_score_change();
+
+-- Scores imported straight into the archive
+CREATE OR REPLACE FUNCTION record_archived_score() RETURNS TRIGGER
+LANGUAGE plpgsql AS $$
+BEGIN
+    INSERT INTO score_changes (
+        change, score_id, score, max, time_of_scoring, remark, is_rehearsal,
+        candidate_id, criteria_id, category_id, judge_id
+    )
+    SELECT 'upsert', NEW.id, NEW.score, NEW.max, NEW.time_of_scoring, NEW.remark, NEW.is_rehearsal,
+        NEW.candidate_id, NEW.criteria_id, NEW.category_id, NEW.judge_id
+    WHERE NOT EXISTS (SELECT 1 FROM scores WHERE id = NEW.id);
+
+    RETURN NULL;
+END;
+$$;
+
+DROP TRIGGER IF EXISTS scores_archive_record_change ON scores_archive;
+CREATE TRIGGER scores_archive_record_change
+    AFTER INSERT ON scores_archive
+    FOR EACH ROW EXECUTE FUNCTION record_archived_score();
+
+-- What mirrors would have seen so far, so a first sync still gets everything
+INSERT INTO score_changes (
+    change, changed_at, score_id, score, max, time_of_scoring, remark, void_reason, is_rehearsal,
+    candidate_id, criteria_id, category_id, judge_id
+)
+SELECT
+    change, changed_at, id, score, max, time_of_scoring, remark, void_reason, is_rehearsal,
+    candidate_id, criteria_id, category_id, judge_id
+FROM (
+    SELECT 'upsert' AS change, updated_at AS changed_at, id, score, max, time_of_scoring, remark,
+        NULL::TEXT AS void_reason, is_rehearsal, candidate_id, criteria_id, category_id, judge_id
+    FROM scores_history
+    UNION ALL
+    SELECT 'void', voided_at, id, score, max, time_of_scoring, remark, void_reason, is_rehearsal,
+        candidate_id, criteria_id, category_id, judge_id
+    FROM voided_scores
+) existing
+WHERE NOT EXISTS (SELECT 1 FROM score_changes)
+ORDER BY changed_at, id;
@@ -0,0 +1,87 @@
+use std::collections::HashSet;
+use std::time::Duration;
+
+use sqlx::PgPool;
+use tokio::sync::{broadcast, mpsc};
+
+use crate::event_lock;
+use crate::handlers::realtime::{self, RealtimeMessage};
+use crate::handlers::score::{
+    compute_event_final_scores, fetch_display_decimals, round_final_scores,
+};
+
+// Standings of an event are recomputed at most once per window
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+// Handle used by handlers to ask for an event's standings to be recomputed
+#[derive(Clone)]
+pub struct Aggregator {
+    tx: mpsc::UnboundedSender<uuid::Uuid>,
+}
+
+impl Aggregator {
+    pub fn request(&self, event_id: uuid::Uuid) {
+        if self.tx.send(event_id).is_err() {
+            eprintln!("Aggregation worker is not running");
+        }
+    }
+}
+
+// Collects recompute requests and pushes fresh standings over the realtime channel
+// When every judge submits at once, the event is still only recomputed once per window
+pub fn spawn_worker(pool: PgPool, realtime_tx: broadcast::Sender<String>) -> Aggregator {
+    let (tx, mut rx) = mpsc::unbounded_channel::<uuid::Uuid>();
+
+    tokio::spawn(async move {
+        let mut pending: HashSet<uuid::Uuid> = HashSet::new();
+        let mut ticker = tokio::time::interval(DEBOUNCE);
+
+        loop {
+            tokio::select! {
+                event_id = rx.recv() => match event_id {
+                    Some(event_id) => {
+                        pending.insert(event_id);
+                    }
+                    None => break,
+                },
+                _ = ticker.tick() => {
+                    for event_id in pending.drain() {
+                        let lock = match event_lock::lock(&pool, &event_id).await {
+                            Ok(lock) => lock,
+                            Err(err) => {
+                                eprintln!("Failed to lock event for recompute: {err:?}");
+                                continue;
+                            }
+                        };
+
+                        let recomputed = compute_event_final_scores(&pool, &event_id).await;
+
+                        if let Err(err) = lock.release().await {
+                            eprintln!("Failed to unlock event after recompute: {err:?}");
+                        }
+
+                        match recomputed {
+                            Ok(mut standings) => {
+                                match fetch_display_decimals(&pool, &event_id).await {
+                                    Ok(decimals) => round_final_scores(&mut standings, decimals),
+                                    Err(err) => eprintln!("Failed to get display decimals: {err:?}"),
+                                }
+
+                                realtime::publish(
+                                    &realtime_tx,
+                                    &RealtimeMessage::Standings {
+                                        event_id,
+                                        standings,
+                                    },
+                                );
+                            }
+                            Err(err) => eprintln!("Failed to recompute standings: {err:?}"),
+                        }
+                    }
+                }
+            }
+        }
+    });
+
+    Aggregator { tx }
+}
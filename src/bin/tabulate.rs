@@ -0,0 +1,130 @@
+// Regenerates results straight from the database, e.g. a restored backup, without the HTTP server
+//
+// Usage: tabulate export --event <id> --format <xlsx|csv|jsonl> [--certified] [--output <path>]
+//        tabulate rotate-keys
+
+use std::env;
+
+use anyhow::{bail, Context};
+use dotenv::dotenv;
+
+use mmu_tabulation_backend::field_crypto;
+use mmu_tabulation_backend::tabulation::{self, ExportFormat};
+
+const USAGE: &str = concat!(
+    "Usage: tabulate export --event <id> --format <xlsx|csv|jsonl> [--certified] [--output <path>]\n",
+    "       tabulate rotate-keys",
+);
+
+enum Command {
+    Export(ExportArgs),
+    // Re-seals encrypted fields with FIELD_ENCRYPTION_KEY
+    RotateKeys,
+}
+
+struct ExportArgs {
+    event_id: uuid::Uuid,
+    format: ExportFormat,
+    certified: bool,
+    output: Option<String>,
+}
+
+fn parse_export_args(args: &[String]) -> anyhow::Result<ExportArgs> {
+    let mut event_id = None;
+    let mut format = ExportFormat::Xlsx;
+    let mut certified = false;
+    let mut output = None;
+
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--event" => {
+                let value = args.next().context("--event needs a value")?;
+                event_id = Some(value.parse().context("--event is not a valid ID")?);
+            }
+            "--format" => {
+                let value = args.next().context("--format needs a value")?;
+                format = value.parse().map_err(anyhow::Error::msg)?;
+            }
+            "--output" => output = Some(args.next().context("--output needs a value")?.clone()),
+            "--certified" => certified = true,
+            _ => bail!("Unknown argument: {}\n{}", arg, USAGE),
+        }
+    }
+
+    Ok(ExportArgs {
+        event_id: event_id.context(USAGE)?,
+        format,
+        certified,
+        output,
+    })
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    dotenv().ok();
+
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let command = match args.split_first() {
+        Some((command, rest)) if command == "export" => Command::Export(parse_export_args(rest)?),
+        Some((command, _)) if command == "rotate-keys" => Command::RotateKeys,
+        _ => bail!(USAGE),
+    };
+
+    field_crypto::init().map_err(anyhow::Error::msg)?;
+
+    let db_url = env::var("DATABASE_URL").context("DATABASE_URL env not found.")?;
+
+    let pool = sqlx::postgres::PgPoolOptions::new()
+        .max_connections(2)
+        .connect(&db_url)
+        .await?;
+
+    match command {
+        Command::Export(export) => run_export(&pool, export).await,
+        Command::RotateKeys => rotate_keys(&pool).await,
+    }
+}
+
+async fn run_export(pool: &sqlx::PgPool, export: ExportArgs) -> anyhow::Result<()> {
+    // Backups are restored as is, the schema is never migrated from here
+    let event_name = sqlx::query_scalar::<_, String>("SELECT name FROM events WHERE id = ($1)")
+        .bind(export.event_id)
+        .fetch_optional(pool)
+        .await?
+        .context("Event not found")?;
+
+    let bytes =
+        tabulation::generate_export(pool, export.format, export.certified, Some(export.event_id))
+            .await
+            .map_err(|err| anyhow::anyhow!("Failed to generate export: {:?}", err))?;
+
+    let output = export
+        .output
+        .unwrap_or_else(|| format!("{}.{}", event_name, export.format.extension()));
+
+    std::fs::write(&output, bytes).with_context(|| format!("Failed to write {}", output))?;
+
+    println!("Exported {} to {}", event_name, output);
+
+    Ok(())
+}
+
+async fn rotate_keys(pool: &sqlx::PgPool) -> anyhow::Result<()> {
+    if !field_crypto::keyring().is_enabled() {
+        bail!("FIELD_ENCRYPTION_KEY is not set, there is no key to rotate to");
+    }
+
+    let report = field_crypto::rotate(pool)
+        .await
+        .map_err(|err| anyhow::anyhow!("Failed to rotate keys: {:?}", err))?;
+
+    println!(
+        "Re-encrypted {} remarks and {} audit entries",
+        report.remarks, report.audit_entries
+    );
+
+    Ok(())
+}
@@ -54,6 +54,10 @@ impl IntoResponse for AppError {
     fn into_response(self) -> Response {
         println!("->> {self:?}\n");
 
+        if self.code.is_server_error() {
+            crate::reporting::report_error(self.code, &self.message);
+        }
+
         (self.code, self.message).into_response()
     }
 }
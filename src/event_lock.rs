@@ -0,0 +1,94 @@
+use axum::http;
+use sqlx::pool::PoolConnection;
+use sqlx::{PgPool, Postgres};
+
+use crate::error::AppError;
+
+// Per-event Postgres advisory lock, so it also holds across backend instances
+// Finalization and recomputes take it exclusively, score writes share it
+pub struct EventLock {
+    conn: Option<PoolConnection<Postgres>>,
+    key: String,
+    shared: bool,
+}
+
+fn lock_key(event_id: &uuid::Uuid) -> String {
+    format!("tabulation:{}", event_id)
+}
+
+async fn acquire(
+    pool: &PgPool,
+    event_id: &uuid::Uuid,
+    shared: bool,
+) -> Result<EventLock, AppError> {
+    let mut conn = pool.acquire().await?;
+    let key = lock_key(event_id);
+
+    let query = if shared {
+        "SELECT pg_advisory_lock_shared(hashtextextended($1, 0))"
+    } else {
+        "SELECT pg_advisory_lock(hashtextextended($1, 0))"
+    };
+
+    sqlx::query(query).bind(&key).execute(&mut *conn).await?;
+
+    Ok(EventLock {
+        conn: Some(conn),
+        key,
+        shared,
+    })
+}
+
+// Waits for in-flight score writes and other recomputes or finalizations of the event
+pub async fn lock(pool: &PgPool, event_id: &uuid::Uuid) -> Result<EventLock, AppError> {
+    acquire(pool, event_id, false).await
+}
+
+// Score writes only wait for a running finalization or recompute, not for each other
+pub async fn lock_shared(pool: &PgPool, event_id: &uuid::Uuid) -> Result<EventLock, AppError> {
+    acquire(pool, event_id, true).await
+}
+
+pub async fn lock_category_shared(
+    pool: &PgPool,
+    category_id: &uuid::Uuid,
+) -> Result<EventLock, AppError> {
+    let event_id: uuid::Uuid =
+        sqlx::query_scalar("SELECT event_id FROM categories WHERE id = ($1)")
+            .bind(category_id)
+            .fetch_optional(pool)
+            .await?
+            .ok_or_else(|| AppError::new(http::StatusCode::NOT_FOUND, "Category not found"))?;
+
+    lock_shared(pool, &event_id).await
+}
+
+impl EventLock {
+    pub async fn release(mut self) -> Result<(), AppError> {
+        let Some(mut conn) = self.conn.take() else {
+            return Ok(());
+        };
+
+        let query = if self.shared {
+            "SELECT pg_advisory_unlock_shared(hashtextextended($1, 0))"
+        } else {
+            "SELECT pg_advisory_unlock(hashtextextended($1, 0))"
+        };
+
+        sqlx::query(query)
+            .bind(&self.key)
+            .execute(&mut *conn)
+            .await?;
+
+        Ok(())
+    }
+}
+
+// Early returns skip `release`, closing the connection ends the session and its locks with it
+impl Drop for EventLock {
+    fn drop(&mut self) {
+        if let Some(conn) = self.conn.take() {
+            drop(conn.detach());
+        }
+    }
+}
@@ -0,0 +1,157 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use sqlx::PgPool;
+use tokio::sync::Notify;
+
+use crate::error::AppError;
+use crate::tabulation::{self, ExportFormat};
+
+// Exports running at the same time, the rest wait in the queue
+const DEFAULT_CONCURRENCY: usize = 2;
+
+// Workers also check the queue on this interval in case a wake-up was missed
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+// Running jobs check on this interval whether they were cancelled
+const CANCEL_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+// Handle used by handlers to wake the workers after queueing a job
+#[derive(Clone)]
+pub struct ExportQueue {
+    notify: Arc<Notify>,
+}
+
+impl ExportQueue {
+    pub fn wake(&self) {
+        self.notify.notify_one();
+    }
+}
+
+// Jobs left running by a previous process never finish, so they are queued again
+pub async fn requeue_interrupted(pool: &PgPool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE export_jobs SET status = 'queued', started_at = NULL WHERE status = 'running'",
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub fn spawn_workers(pool: PgPool) -> ExportQueue {
+    let concurrency = std::env::var("EXPORT_CONCURRENCY")
+        .ok()
+        .and_then(|value| value.parse::<usize>().ok())
+        .filter(|value| *value > 0)
+        .unwrap_or(DEFAULT_CONCURRENCY);
+
+    let queue = ExportQueue {
+        notify: Arc::new(Notify::new()),
+    };
+
+    for _ in 0..concurrency {
+        let pool = pool.clone();
+        let notify = queue.notify.clone();
+
+        tokio::spawn(async move {
+            loop {
+                match run_next_job(&pool).await {
+                    Ok(true) => continue,
+                    Ok(false) => {}
+                    Err(err) => eprintln!("Failed to run export job: {err:?}"),
+                }
+
+                let _ = tokio::time::timeout(POLL_INTERVAL, notify.notified()).await;
+            }
+        });
+    }
+
+    queue
+}
+
+// Claims the highest priority job, returns false when the queue is empty
+async fn run_next_job(pool: &PgPool) -> Result<bool, sqlx::Error> {
+    let job = sqlx::query_as::<_, (uuid::Uuid, String, bool)>(
+        r#"
+        UPDATE export_jobs
+        SET status = 'running', started_at = NOW()
+        WHERE id = (
+            SELECT id FROM export_jobs
+            WHERE status = 'queued'
+            ORDER BY priority DESC, created_at
+            LIMIT 1
+            FOR UPDATE SKIP LOCKED
+        )
+        RETURNING id, kind, certified
+        "#,
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    let Some((job_id, kind, certified)) = job else {
+        return Ok(false);
+    };
+
+    // Dropping the export stops it at its next query, the cancelled status stays as it is
+    let generated = tokio::select! {
+        generated = generate(pool, &kind, certified) => generated,
+        _ = cancelled(pool, &job_id) => return Ok(true),
+    };
+
+    // Jobs cancelled after the last check keep their status and the output is dropped
+    match generated {
+        Ok(bytes) => {
+            sqlx::query(
+                r#"
+                UPDATE export_jobs SET status = 'done', result = ($2), finished_at = NOW()
+                WHERE id = ($1) AND status = 'running'
+                "#,
+            )
+            .bind(&job_id)
+            .bind(&bytes)
+            .execute(pool)
+            .await?;
+        }
+        Err(err) => {
+            sqlx::query(
+                r#"
+                UPDATE export_jobs SET status = 'failed', error = ($2), finished_at = NOW()
+                WHERE id = ($1) AND status = 'running'
+                "#,
+            )
+            .bind(&job_id)
+            .bind(format!("{err:?}"))
+            .execute(pool)
+            .await?;
+        }
+    }
+
+    Ok(true)
+}
+
+// Resolves once the job stops running, lookups that fail are tried again on the next check
+pub async fn cancelled(pool: &PgPool, job_id: &uuid::Uuid) {
+    let mut interval = tokio::time::interval(CANCEL_CHECK_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        let running = sqlx::query_scalar::<_, bool>(
+            "SELECT EXISTS (SELECT 1 FROM export_jobs WHERE id = ($1) AND status = 'running')",
+        )
+        .bind(job_id)
+        .fetch_one(pool)
+        .await;
+
+        if let Ok(false) = running {
+            return;
+        }
+    }
+}
+
+async fn generate(pool: &PgPool, kind: &str, certified: bool) -> Result<Vec<u8>, AppError> {
+    let format = kind.parse().unwrap_or(ExportFormat::Jsonl);
+
+    tabulation::generate_export(pool, format, certified, None).await
+}
@@ -0,0 +1,280 @@
+use std::collections::HashMap;
+use std::env;
+use std::sync::OnceLock;
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use serde::{Deserialize, Serialize};
+use sqlx::encode::IsNull;
+use sqlx::error::BoxDynError;
+use sqlx::postgres::{PgArgumentBuffer, PgTypeInfo, PgValueRef};
+use sqlx::{Decode, Encode, PgPool, Postgres, Type};
+
+use crate::error::AppError;
+use crate::handlers::audit;
+
+// Optional encryption of free-text score remarks and audit payloads at rest
+// Score values are not encrypted, every standing is computed in SQL and needs to read them
+//
+// FIELD_ENCRYPTION_KEY=<id>:<64 hex characters> turns it on
+// FIELD_ENCRYPTION_OLD_KEYS=<id>:<hex>,... keeps older keys readable until rotated
+
+const PREFIX: &str = "enc:v1:";
+const NONCE_LEN: usize = 12;
+
+pub struct Keyring {
+    current: Option<String>,
+    keys: HashMap<String, Aes256Gcm>,
+}
+
+fn parse_key(spec: &str) -> Result<(String, Aes256Gcm), String> {
+    let (id, hex_key) = spec
+        .trim()
+        .split_once(':')
+        .ok_or_else(|| "Keys are written as <id>:<64 hex characters>".to_string())?;
+
+    if id.is_empty() {
+        return Err("Keys need an ID".to_string());
+    }
+
+    let bytes = hex::decode(hex_key).map_err(|err| format!("Key {} is not hex: {}", id, err))?;
+
+    if bytes.len() != 32 {
+        return Err(format!("Key {} must be 32 bytes", id));
+    }
+
+    Ok((
+        id.to_string(),
+        Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&bytes)),
+    ))
+}
+
+impl Keyring {
+    pub fn new(current: Option<&str>, old: Option<&str>) -> Result<Self, String> {
+        let mut keys = HashMap::new();
+
+        for spec in old
+            .unwrap_or("")
+            .split(',')
+            .filter(|spec| !spec.trim().is_empty())
+        {
+            let (id, cipher) = parse_key(spec)?;
+            keys.insert(id, cipher);
+        }
+
+        let current = match current.filter(|spec| !spec.trim().is_empty()) {
+            Some(spec) => {
+                let (id, cipher) = parse_key(spec)?;
+                keys.insert(id.clone(), cipher);
+                Some(id)
+            }
+            None => None,
+        };
+
+        Ok(Self { current, keys })
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.current.is_some()
+    }
+
+    pub fn is_sealed(stored: &str) -> bool {
+        stored.starts_with(PREFIX)
+    }
+
+    // Left as is while encryption is off
+    pub fn seal(&self, plain: &str) -> String {
+        let Some(id) = &self.current else {
+            return plain.to_string();
+        };
+
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        // Only fails for plaintexts in the gigabytes
+        let ciphertext = self.keys[id]
+            .encrypt(&nonce, plain.as_bytes())
+            .expect("Failed to encrypt field");
+
+        format!(
+            "{}{}:{}{}",
+            PREFIX,
+            id,
+            hex::encode(nonce),
+            hex::encode(ciphertext)
+        )
+    }
+
+    // Values written before encryption was turned on are returned as is
+    pub fn open(&self, stored: &str) -> Result<String, String> {
+        let Some(sealed) = stored.strip_prefix(PREFIX) else {
+            return Ok(stored.to_string());
+        };
+
+        let (id, payload) = sealed
+            .split_once(':')
+            .ok_or_else(|| "Malformed encrypted field".to_string())?;
+
+        let cipher = self
+            .keys
+            .get(id)
+            .ok_or_else(|| format!("Encryption key {} is not configured", id))?;
+
+        let bytes = hex::decode(payload).map_err(|_| "Malformed encrypted field".to_string())?;
+
+        if bytes.len() < NONCE_LEN {
+            return Err("Malformed encrypted field".to_string());
+        }
+
+        let (nonce, ciphertext) = bytes.split_at(NONCE_LEN);
+
+        let plain = cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| format!("Failed to decrypt a field sealed with key {}", id))?;
+
+        String::from_utf8(plain).map_err(|_| "Decrypted field is not text".to_string())
+    }
+
+    // Plaintext or sealed with an older key
+    pub fn needs_rotation(&self, stored: &str) -> bool {
+        match &self.current {
+            Some(id) => !stored.starts_with(&format!("{}{}:", PREFIX, id)),
+            None => false,
+        }
+    }
+}
+
+static KEYRING: OnceLock<Keyring> = OnceLock::new();
+
+// Called on startup so a bad key fails there instead of on the first score
+pub fn init() -> Result<(), String> {
+    let keyring = Keyring::new(
+        env::var("FIELD_ENCRYPTION_KEY").ok().as_deref(),
+        env::var("FIELD_ENCRYPTION_OLD_KEYS").ok().as_deref(),
+    )?;
+
+    let _ = KEYRING.set(keyring);
+
+    Ok(())
+}
+
+pub fn keyring() -> &'static Keyring {
+    KEYRING.get_or_init(|| {
+        Keyring::new(
+            env::var("FIELD_ENCRYPTION_KEY").ok().as_deref(),
+            env::var("FIELD_ENCRYPTION_OLD_KEYS").ok().as_deref(),
+        )
+        .expect("Invalid field encryption keys")
+    })
+}
+
+// TEXT column that's sealed when written and opened when read, so handlers only see plaintext
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SealedText(pub String);
+
+impl SealedText {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Type<Postgres> for SealedText {
+    fn type_info() -> PgTypeInfo {
+        <String as Type<Postgres>>::type_info()
+    }
+
+    fn compatible(ty: &PgTypeInfo) -> bool {
+        <String as Type<Postgres>>::compatible(ty)
+    }
+}
+
+impl<'r> Decode<'r, Postgres> for SealedText {
+    fn decode(value: PgValueRef<'r>) -> Result<Self, BoxDynError> {
+        let stored = <String as Decode<Postgres>>::decode(value)?;
+
+        Ok(SealedText(keyring().open(&stored)?))
+    }
+}
+
+impl Encode<'_, Postgres> for SealedText {
+    fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> IsNull {
+        <String as Encode<Postgres>>::encode_by_ref(&keyring().seal(&self.0), buf)
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct RotationReport {
+    pub remarks: u64,
+    pub audit_entries: u64,
+}
+
+// Re-seals everything under the current key, also encrypts what was written before encryption was on
+// Rows are only replaced if they still hold what was read, a remark edited meanwhile is already sealed
+// with the current key and is left alone
+pub async fn rotate(pool: &PgPool) -> Result<RotationReport, AppError> {
+    let keyring = keyring();
+    let mut report = RotationReport::default();
+
+    if !keyring.is_enabled() {
+        return Ok(report);
+    }
+
+    // Bumps `updated_at` on live scores, so mirrors pull the re-sealed remarks too
+    for table in ["scores", "scores_archive", "voided_scores"] {
+        let remarks = sqlx::query_as::<_, (uuid::Uuid, String)>(&format!(
+            "SELECT id, remark FROM {} WHERE remark IS NOT NULL",
+            table
+        ))
+        .fetch_all(pool)
+        .await?;
+
+        for (id, remark) in remarks {
+            if !keyring.needs_rotation(&remark) {
+                continue;
+            }
+
+            let plain = keyring
+                .open(&remark)
+                .map_err(|err| AppError::new(axum::http::StatusCode::INTERNAL_SERVER_ERROR, err))?;
+
+            let res = sqlx::query(&format!(
+                "UPDATE {} SET remark = ($1) WHERE id = ($2) AND remark = ($3)",
+                table
+            ))
+            .bind(keyring.seal(&plain))
+            .bind(&id)
+            .bind(&remark)
+            .execute(pool)
+            .await?;
+
+            report.remarks += res.rows_affected();
+        }
+    }
+
+    let entries = sqlx::query_as::<_, (uuid::Uuid, sqlx::types::Json<serde_json::Value>)>(
+        "SELECT id, details FROM audit_log",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    for (id, details) in entries {
+        if !audit::details_need_rotation(&details.0) {
+            continue;
+        }
+
+        let plain = audit::open_details(details.0.clone())
+            .map_err(|err| AppError::new(axum::http::StatusCode::INTERNAL_SERVER_ERROR, err))?;
+
+        let res = sqlx::query(
+            "UPDATE audit_log SET details = ($1) WHERE id = ($2) AND details = ($3)",
+        )
+        .bind(sqlx::types::Json(audit::seal_details(plain)))
+        .bind(&id)
+        .bind(&details)
+        .execute(pool)
+        .await?;
+
+        report.audit_entries += res.rows_affected();
+    }
+
+    Ok(report)
+}
@@ -0,0 +1,390 @@
+use axum::extract::{MatchedPath, Request, State};
+use axum::http;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response, Result};
+use axum::Extension;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+
+use crate::error::AppError;
+use crate::tenancy::Organization;
+
+use super::audit;
+use super::auth;
+use super::observer;
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct Admin {
+    pub id: uuid::Uuid,
+    pub name: String,
+    pub username: String,
+    // Argon2 hash
+    #[serde(skip_serializing)]
+    pub password: String,
+    pub role: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Role {
+    Admin,
+    // Runs the reports and exports, can't change the event setup
+    Tabulator,
+}
+
+impl Role {
+    pub fn parse(role: &str) -> Option<Self> {
+        match role {
+            "admin" => Some(Self::Admin),
+            "tabulator" => Some(Self::Tabulator),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Admin => "admin",
+            Self::Tabulator => "tabulator",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StaffClaims {
+    // Admin ID
+    pub sub: uuid::Uuid,
+    pub role: String,
+    pub exp: i64,
+    // Organization the token was issued in, None without schema isolation
+    #[serde(default)]
+    pub org: Option<String>,
+}
+
+// Creates the first admin from `ADMIN_USERNAME` and `ADMIN_PASSWORD`, nobody could log in otherwise
+pub async fn init(pool: &PgPool) -> anyhow::Result<()> {
+    let (Ok(username), Ok(password)) = (
+        std::env::var("ADMIN_USERNAME"),
+        std::env::var("ADMIN_PASSWORD"),
+    ) else {
+        return Ok(());
+    };
+
+    let password = auth::hash_password(&password)
+        .map_err(|_| anyhow::anyhow!("Failed to hash the password of admin {}", username))?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO admins (name, username, password, role)
+        VALUES ($1, $1, $2, 'admin')
+        ON CONFLICT (username) DO NOTHING
+        "#,
+    )
+    .bind(&username)
+    .bind(&password)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateAdmin {
+    name: String,
+    username: String,
+    password: String,
+    role: Option<String>,
+}
+
+pub async fn create_admin(
+    State(pool): State<PgPool>,
+    Extension(staff): Extension<StaffClaims>,
+    axum::Json(payload): axum::Json<CreateAdmin>,
+) -> Result<(http::StatusCode, axum::Json<Admin>), AppError> {
+    let role = match payload.role.as_deref() {
+        None => Role::Admin,
+        Some(role) => Role::parse(role).ok_or_else(|| {
+            AppError::new(
+                http::StatusCode::UNPROCESSABLE_ENTITY,
+                "Role must be either 'admin' or 'tabulator'",
+            )
+        })?,
+    };
+
+    let admin = sqlx::query_as::<_, Admin>(
+        r#"
+        INSERT INTO admins (name, username, password, role)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (username) DO NOTHING
+        RETURNING *
+        "#,
+    )
+    .bind(&payload.name)
+    .bind(&payload.username)
+    .bind(auth::hash_password(&payload.password)?)
+    .bind(role.as_str())
+    .fetch_optional(&pool)
+    .await?
+    .ok_or_else(|| AppError::new(http::StatusCode::CONFLICT, "Username is already taken"))?;
+
+    audit::record(
+        &pool,
+        staff.sub,
+        "admin",
+        Some(&admin.id),
+        "create",
+        serde_json::json!({ "username": admin.username, "role": admin.role }),
+    )
+    .await?;
+
+    Ok((http::StatusCode::CREATED, axum::Json(admin)))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AdminLogin {
+    username: String,
+    password: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AdminSession {
+    token: String,
+    expires_at: chrono::DateTime<chrono::Utc>,
+    admin: Admin,
+}
+
+pub async fn admin_login(
+    State(pool): State<PgPool>,
+    organization: Option<Extension<Organization>>,
+    axum::Json(login): axum::Json<AdminLogin>,
+) -> Result<axum::Json<AdminSession>, AppError> {
+    let admin = sqlx::query_as::<_, Admin>("SELECT * FROM admins WHERE username = ($1)")
+        .bind(&login.username)
+        .fetch_optional(&pool)
+        .await?
+        .filter(|admin| auth::verify_password(&login.password, &admin.password))
+        .ok_or_else(|| AppError::new(http::StatusCode::UNAUTHORIZED, "Invalid credentials"))?;
+
+    let expires_at = chrono::Utc::now() + chrono::Duration::hours(auth::TOKEN_HOURS);
+
+    let token = auth::encode_token(
+        auth::token_secret()?,
+        &StaffClaims {
+            sub: admin.id,
+            role: admin.role.clone(),
+            exp: expires_at.timestamp(),
+            org: organization.map(|Extension(organization)| organization.0),
+        },
+    )?;
+
+    Ok(axum::Json(AdminSession {
+        token,
+        expires_at,
+        admin,
+    }))
+}
+
+// Writes that check their own credentials (judges, devices, the committee) or are how those are obtained,
+// every other write needs an admin unless it's in `STAFF_ROUTES`
+const OPEN_ROUTES: [(&str, &str); 20] = [
+    ("POST", "/login"),
+    ("POST", "/login/pin"),
+    ("POST", "/logout"),
+    ("POST", "/admins/login"),
+    ("POST", "/observers/login"),
+    ("POST", "/committee/login"),
+    ("DELETE", "/sessions/current"),
+    ("POST", "/sessions/current/heartbeat"),
+    ("POST", "/scores"),
+    ("POST", "/scores/update"),
+    ("POST", "/scores/batch"),
+    ("POST", "/scores/:score_id/correction-request"),
+    ("POST", "/devices/scores"),
+    ("POST", "/groups/:group_id/scores"),
+    ("POST", "/categories/:category_id/confirmations"),
+    ("POST", "/categories/:category_id/preview-score"),
+    ("POST", "/brackets/:bracket_id/matches/:match_id/ballots"),
+    ("POST", "/notes"),
+    ("POST", "/events/:event_id/approvals"),
+    ("POST", "/events/:event_id/rechecks/:recheck_id/acknowledge"),
+];
+
+// Exports, reports and running the floor, open to tabulators as well
+const STAFF_ROUTES: [(&str, &str); 20] = [
+    ("GET", "/scores/download"),
+    ("GET", "/scores/audit"),
+    ("GET", "/scores/export.jsonl"),
+    ("GET", "/exports"),
+    ("POST", "/exports"),
+    ("GET", "/exports/:job_id"),
+    ("GET", "/exports/:job_id/download"),
+    ("POST", "/exports/:job_id/cancel"),
+    (
+        "GET",
+        "/events/:event_id/categories/:category_id/standings.csv",
+    ),
+    ("GET", "/events/:event_id/candidates/export.csv"),
+    ("GET", "/events/:event_id/judges/export.csv"),
+    ("GET", "/events/:event_id/report/cover.pdf"),
+    ("GET", "/events/:event_id/announcement-script"),
+    ("GET", "/events/:event_id/score-stream"),
+    ("GET", "/audit"),
+    ("GET", "/audit/export.csv"),
+    (
+        "POST",
+        "/events/:event_id/categories/:category_id/candidates/:candidate_id/timer/start",
+    ),
+    (
+        "POST",
+        "/events/:event_id/categories/:category_id/candidates/:candidate_id/timer/stop",
+    ),
+    ("POST", "/candidates/:candidate_id/check-in"),
+    ("POST", "/judges/:judge_id/check-in"),
+];
+
+// Standings and dashboards, closed to anonymous callers while the event runs
+// The audience has the compact standings, the approved and public results and the leaderboard socket
+const WATCH_ROUTES: [(&str, &str); 15] = [
+    ("GET", "/scores/final"),
+    ("GET", "/events/:event_id/standings"),
+    ("GET", "/events/:event_id/rankings"),
+    ("GET", "/events/:event_id/standings/history"),
+    ("GET", "/events/:event_id/standings/diff"),
+    ("GET", "/events/:event_id/standings/verify"),
+    ("GET", "/events/:event_id/categories/:category_id/standings"),
+    ("GET", "/events/:event_id/groups/standings"),
+    ("GET", "/events/:event_id/judges/:judge_id/ranking"),
+    ("GET", "/brackets/:bracket_id/standings"),
+    ("GET", "/events/:event_id/integrity"),
+    ("GET", "/events/:event_id/quorum"),
+    ("GET", "/events/:event_id/analytics/scoring-latency"),
+    ("GET", "/events/:event_id/analytics/submission-latency"),
+    ("GET", "/events/:event_id/analytics/judge-agreement"),
+];
+
+// Staff routes observers can read with their own session, on top of `WATCH_ROUTES`
+const OBSERVER_EXPORT_ROUTES: [(&str, &str); 11] = [
+    ("GET", "/scores/download"),
+    ("GET", "/scores/audit"),
+    ("GET", "/scores/export.jsonl"),
+    ("GET", "/exports"),
+    ("GET", "/exports/:job_id"),
+    ("GET", "/exports/:job_id/download"),
+    (
+        "GET",
+        "/events/:event_id/categories/:category_id/standings.csv",
+    ),
+    ("GET", "/events/:event_id/candidates/export.csv"),
+    ("GET", "/events/:event_id/judges/export.csv"),
+    ("GET", "/events/:event_id/report/cover.pdf"),
+    ("GET", "/events/:event_id/score-stream"),
+];
+
+// Writes that bring their credentials in the body or change nothing, every other write needs a bearer token
+const TOKENLESS_ROUTES: [(&str, &str); 8] = [
+    ("POST", "/login"),
+    ("POST", "/login/pin"),
+    ("POST", "/admins/login"),
+    ("POST", "/observers/login"),
+    ("POST", "/committee/login"),
+    ("POST", "/categories/:category_id/preview-score"),
+    ("POST", "/events/:event_id/approvals"),
+    ("POST", "/events/:event_id/rechecks/:recheck_id/acknowledge"),
+];
+
+// Roles allowed on a route, None when it's open (judges are checked by the score handlers)
+pub fn allowed_roles(method: &http::Method, path: &str) -> Option<&'static [Role]> {
+    let matches = |routes: &[(&str, &str)]| is_listed(routes, method, path);
+
+    if matches(&STAFF_ROUTES) || matches(&WATCH_ROUTES) {
+        Some(&[Role::Admin, Role::Tabulator])
+    } else if matches(&OPEN_ROUTES) || method.is_safe() {
+        None
+    } else {
+        Some(&[Role::Admin])
+    }
+}
+
+pub fn observers_allowed(method: &http::Method, path: &str) -> bool {
+    is_listed(&WATCH_ROUTES, method, path) || is_listed(&OBSERVER_EXPORT_ROUTES, method, path)
+}
+
+pub fn needs_token(method: &http::Method, path: &str) -> bool {
+    !method.is_safe() && !is_listed(&TOKENLESS_ROUTES, method, path)
+}
+
+fn is_listed(routes: &[(&str, &str)], method: &http::Method, path: &str) -> bool {
+    routes
+        .iter()
+        .any(|(route_method, route)| method.as_str() == *route_method && path == *route)
+}
+
+// Staff tokens only count in the organization they were issued in and while the admin still exists
+// The role is read again on every request, so deleting or demoting an admin takes effect right away
+async fn authenticate_staff(
+    pool: &PgPool,
+    headers: &http::HeaderMap,
+    organization: Option<&Organization>,
+) -> Result<StaffClaims, AppError> {
+    let login_required = || AppError::new(http::StatusCode::UNAUTHORIZED, "Staff login required");
+
+    let token = auth::bearer_token(headers)?;
+    let mut claims = auth::decode_token::<StaffClaims>(auth::token_secret()?, token)
+        .ok_or_else(login_required)?;
+
+    if claims.org.as_deref() != organization.map(|organization| organization.0.as_str()) {
+        return Err(login_required());
+    }
+
+    claims.role = sqlx::query_scalar("SELECT role FROM admins WHERE id = ($1)")
+        .bind(&claims.sub)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(login_required)?;
+
+    Ok(claims)
+}
+
+// Writes are limited to admins unless listed otherwise, reads stay open except the exports, standings and dashboards
+pub async fn restrict_staff_routes(
+    State(pool): State<PgPool>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let Some(path) = request.extensions().get::<MatchedPath>().cloned() else {
+        return next.run(request).await;
+    };
+
+    let Some(allowed) = allowed_roles(request.method(), path.as_str()) else {
+        return next.run(request).await;
+    };
+
+    // Observers only get to read, their sessions are tokens in `observer_sessions` rather than JWTs
+    if observers_allowed(request.method(), path.as_str()) {
+        if let Ok(token) = auth::bearer_token(request.headers()).map(str::to_string) {
+            match observer::is_observer_session(&pool, &token).await {
+                Ok(true) => return next.run(request).await,
+                Ok(false) => {}
+                Err(err) => return err.into_response(),
+            }
+        }
+    }
+
+    let organization = request.extensions().get::<Organization>();
+
+    let claims = match authenticate_staff(&pool, request.headers(), organization).await {
+        Ok(claims) => claims,
+        Err(err) => return err.into_response(),
+    };
+
+    if !Role::parse(&claims.role).is_some_and(|role| allowed.contains(&role)) {
+        return AppError::new(
+            http::StatusCode::FORBIDDEN,
+            "Your role doesn't have access to this route",
+        )
+        .into_response();
+    }
+
+    request.extensions_mut().insert(claims);
+
+    next.run(request).await
+}
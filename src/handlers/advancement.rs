@@ -0,0 +1,202 @@
+use axum::extract::{Extension, Path, State};
+use axum::http;
+use axum::response::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+
+use crate::error::AppError;
+
+use super::admin::StaffClaims;
+use super::audit;
+use super::round;
+use super::score::{
+    fetch_display_decimals, fetch_round_final_scores, rank_with_shared_ties, round_final_scores,
+    CandidateFinalScore2,
+};
+use super::standings::{self, RankedCandidate};
+
+// Whether candidate `c` was eliminated in a round held before category `cat`'s round,
+// or in any round when the category is outside of them
+pub const ELIMINATED_EARLIER: &str = r#"EXISTS (
+    SELECT 1 FROM round_advancements ra
+    JOIN rounds er ON er.id = ra.round_id
+    LEFT JOIN rounds r ON r.id = cat.round_id
+    WHERE ra.candidate_id = c.id AND ra.advanced = FALSE
+        AND (cat.round_id IS NULL OR er.position < r.position)
+)"#;
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct RoundAdvancement {
+    rank: i32,
+    advanced: bool,
+    created_at: chrono::DateTime<chrono::Utc>,
+    // Relationships
+    round_id: uuid::Uuid,
+    candidate_id: uuid::Uuid,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AdvanceRound {
+    // Candidates who proceed, per division when `per_gender` is set
+    count: usize,
+    #[serde(default)]
+    per_gender: bool,
+}
+
+// Everyone ranked `count` or better advances, so a tie at the cutoff lets all of them through
+pub fn pick_advancing(
+    standings: &[CandidateFinalScore2],
+    count: usize,
+    per_gender: bool,
+) -> Vec<(RankedCandidate, bool)> {
+    let ranked = if per_gender {
+        standings::divide_standings_by(standings, rank_with_shared_ties)
+            .divisions
+            .into_iter()
+            .flat_map(|division| division.standings)
+            .collect()
+    } else {
+        rank_with_shared_ties(standings.to_vec())
+    };
+
+    ranked
+        .into_iter()
+        .map(|ranked| {
+            let advanced = ranked.rank <= count;
+
+            (ranked, advanced)
+        })
+        .collect()
+}
+
+// Scores from the round a candidate was eliminated in, or before it, stay editable
+pub async fn ensure_not_eliminated(
+    pool: &PgPool,
+    category_id: &uuid::Uuid,
+    candidate_ids: &[uuid::Uuid],
+) -> Result<(), AppError> {
+    let eliminated: bool = sqlx::query_scalar(&format!(
+        r#"
+        SELECT EXISTS (
+            SELECT 1 FROM candidates c
+            JOIN categories cat ON cat.id = ($1)
+            WHERE c.id = ANY($2) AND {eliminated_earlier}
+        )
+        "#,
+        eliminated_earlier = ELIMINATED_EARLIER,
+    ))
+    .bind(category_id)
+    .bind(candidate_ids)
+    .fetch_one(pool)
+    .await?;
+
+    if eliminated {
+        return Err(AppError::new(
+            http::StatusCode::FORBIDDEN,
+            "Candidate was eliminated in an earlier round",
+        ));
+    }
+
+    Ok(())
+}
+
+pub async fn get_round_advancements(
+    State(pool): State<PgPool>,
+    Path((_event_id, round_id)): Path<(uuid::Uuid, uuid::Uuid)>,
+) -> Result<axum::Json<Vec<RoundAdvancement>>, AppError> {
+    let advancements = sqlx::query_as::<_, RoundAdvancement>(
+        "SELECT * FROM round_advancements WHERE round_id = ($1) ORDER BY rank",
+    )
+    .bind(&round_id)
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(axum::Json(advancements))
+}
+
+// Ranks the candidates on the round's categories alone
+// Advancing a round again replaces its earlier results, e.g. after a score correction
+pub async fn advance_round(
+    State(pool): State<PgPool>,
+    Extension(staff): Extension<StaffClaims>,
+    Path((event_id, round_id)): Path<(uuid::Uuid, uuid::Uuid)>,
+    axum::Json(payload): axum::Json<AdvanceRound>,
+) -> Result<axum::Json<Vec<RoundAdvancement>>, AppError> {
+    if payload.count == 0 {
+        return Err(AppError::new(
+            http::StatusCode::UNPROCESSABLE_ENTITY,
+            "At least one candidate has to advance",
+        ));
+    }
+
+    round::fetch_round(&pool, &event_id, &round_id).await?;
+
+    let category_ids: Vec<uuid::Uuid> =
+        sqlx::query_scalar("SELECT id FROM categories WHERE round_id = ($1)")
+            .bind(&round_id)
+            .fetch_all(&pool)
+            .await?;
+
+    if category_ids.is_empty() {
+        return Err(AppError::new(
+            http::StatusCode::CONFLICT,
+            "The round has no categories to rank the candidates on",
+        ));
+    }
+
+    let mut standings = fetch_round_final_scores(&pool, &category_ids).await?;
+    let decimals = fetch_display_decimals(&pool, &event_id).await?;
+
+    round_final_scores(&mut standings, decimals);
+
+    let picked = pick_advancing(&standings, payload.count, payload.per_gender);
+
+    let mut txn = pool.begin().await?;
+
+    sqlx::query("DELETE FROM round_advancements WHERE round_id = ($1)")
+        .bind(&round_id)
+        .execute(&mut *txn)
+        .await?;
+
+    let mut advancements = Vec::with_capacity(picked.len());
+
+    for (ranked, advanced) in picked.iter() {
+        let advancement = sqlx::query_as::<_, RoundAdvancement>(
+            r#"
+            INSERT INTO round_advancements (rank, advanced, round_id, candidate_id)
+            VALUES ($1, $2, $3, $4)
+            RETURNING *
+            "#,
+        )
+        .bind(ranked.rank as i32)
+        .bind(advanced)
+        .bind(&round_id)
+        .bind(&ranked.candidate.candidate_id)
+        .fetch_one(&mut *txn)
+        .await?;
+
+        advancements.push(advancement);
+    }
+
+    audit::record(
+        &mut *txn,
+        staff.sub,
+        "round",
+        Some(&round_id),
+        "advance",
+        serde_json::json!({
+            "count": payload.count,
+            "per_gender": payload.per_gender,
+            "advanced": picked
+                .iter()
+                .filter(|(_, advanced)| *advanced)
+                .map(|(ranked, _)| ranked.candidate.candidate_id)
+                .collect::<Vec<_>>(),
+        }),
+    )
+    .await?;
+
+    txn.commit().await?;
+
+    Ok(axum::Json(advancements))
+}
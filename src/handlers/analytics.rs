@@ -0,0 +1,372 @@
+use std::collections::HashMap;
+
+use axum::extract::{Path, State};
+use axum::response::Result;
+use serde::Serialize;
+use sqlx::{FromRow, PgPool};
+
+use crate::error::AppError;
+
+// Judges whose median is this much slower than the panel's are flagged
+const LAG_FACTOR: f64 = 1.25;
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct JudgeLatency {
+    pub judge_id: uuid::Uuid,
+    pub judge_name: String,
+    pub candidates_scored: i64,
+    // Seconds from the category opening to the judge's last score of a candidate
+    pub median_seconds: f64,
+    #[sqlx(default)]
+    pub is_lagging: bool,
+}
+
+pub fn median(values: &[f64]) -> Option<f64> {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+
+    let mid = sorted.len() / 2;
+
+    match sorted.len() {
+        0 => None,
+        len if len % 2 == 0 => Some((sorted[mid - 1] + sorted[mid]) / 2.0),
+        _ => Some(sorted[mid]),
+    }
+}
+
+pub fn flag_lagging_judges(latencies: &mut [JudgeLatency]) {
+    let medians: Vec<f64> = latencies
+        .iter()
+        .map(|latency| latency.median_seconds)
+        .collect();
+
+    let Some(panel_median) = median(&medians) else {
+        return;
+    };
+
+    for latency in latencies.iter_mut() {
+        latency.is_lagging = latency.median_seconds > panel_median * LAG_FACTOR;
+    }
+}
+
+// For floor staff to see which judges need help keeping up
+pub async fn get_scoring_latency(
+    State(pool): State<PgPool>,
+    Path(event_id): Path<uuid::Uuid>,
+) -> Result<axum::Json<Vec<JudgeLatency>>, AppError> {
+    let mut latencies = sqlx::query_as::<_, JudgeLatency>(
+        r#"
+        WITH per_candidate AS (
+            SELECT
+                r.judge_id,
+                r.judge_name,
+                EXTRACT(EPOCH FROM MAX(r.time_of_scoring) - cat.opened_at)::FLOAT8 AS seconds
+            FROM score_report r
+            JOIN categories cat ON cat.id = r.category_id
+            WHERE r.event_id = ($1) AND r.time_of_scoring >= cat.opened_at
+            GROUP BY r.judge_id, r.judge_name, r.category_id, r.candidate_id, cat.opened_at
+        )
+        SELECT
+            judge_id,
+            judge_name,
+            COUNT(*) AS candidates_scored,
+            percentile_cont(0.5) WITHIN GROUP (ORDER BY seconds) AS median_seconds
+        FROM per_candidate
+        GROUP BY judge_id, judge_name
+        ORDER BY median_seconds DESC
+        "#,
+    )
+    .bind(&event_id)
+    .fetch_all(&pool)
+    .await?;
+
+    flag_lagging_judges(&mut latencies);
+
+    Ok(axum::Json(latencies))
+}
+
+// Recent enough to reflect how the venue's network is doing right now
+const RECENT_LATENCY_MINUTES: i64 = 10;
+
+const LATENCY_PERCENTILES: &str = r#"
+    COUNT(*) AS submissions,
+    percentile_cont(0.5) WITHIN GROUP (ORDER BY l.network_ms) AS network_p50,
+    percentile_cont(0.9) WITHIN GROUP (ORDER BY l.network_ms) AS network_p90,
+    percentile_cont(0.99) WITHIN GROUP (ORDER BY l.network_ms) AS network_p99,
+    percentile_cont(0.5) WITHIN GROUP (ORDER BY l.processing_ms) AS processing_p50,
+    percentile_cont(0.9) WITHIN GROUP (ORDER BY l.processing_ms) AS processing_p90,
+    percentile_cont(0.99) WITHIN GROUP (ORDER BY l.processing_ms) AS processing_p99"#;
+
+// Milliseconds, None when there are no submissions to measure
+#[derive(Debug, Serialize, FromRow)]
+pub struct LatencyPercentiles {
+    pub submissions: i64,
+    // From the device sending the score to the server receiving it
+    pub network_p50: Option<f64>,
+    pub network_p90: Option<f64>,
+    pub network_p99: Option<f64>,
+    // From the server receiving the score to it being stored
+    pub processing_p50: Option<f64>,
+    pub processing_p90: Option<f64>,
+    pub processing_p99: Option<f64>,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct JudgeSubmissionLatency {
+    pub judge_id: uuid::Uuid,
+    pub judge_name: String,
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub latency: LatencyPercentiles,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SubmissionLatency {
+    overall: LatencyPercentiles,
+    judges: Vec<JudgeSubmissionLatency>,
+}
+
+// (network, processing) in milliseconds
+pub fn submission_latency_ms(
+    client_sent_at: Option<chrono::DateTime<chrono::Utc>>,
+    received_at: chrono::DateTime<chrono::Utc>,
+    stored_at: chrono::DateTime<chrono::Utc>,
+) -> (Option<f64>, f64) {
+    // A device clock running ahead of the server's would give negative times
+    let network =
+        client_sent_at.map(|sent_at| (received_at - sent_at).num_milliseconds().max(0) as f64);
+    let processing = (stored_at - received_at).num_milliseconds().max(0) as f64;
+
+    (network, processing)
+}
+
+// Called once a submission is stored, `received_at` being when its request arrived
+pub fn record_submission_latency(
+    pool: &PgPool,
+    judge_id: uuid::Uuid,
+    client_sent_at: Option<chrono::DateTime<chrono::Utc>>,
+    received_at: chrono::DateTime<chrono::Utc>,
+) {
+    let (network_ms, processing_ms) =
+        submission_latency_ms(client_sent_at, received_at, chrono::Utc::now());
+    let pool = pool.clone();
+
+    // Like the request log, measuring must never slow down or fail the submission
+    tokio::spawn(async move {
+        let res = sqlx::query(
+            "INSERT INTO submission_latencies (network_ms, processing_ms, judge_id) VALUES ($1, $2, $3)",
+        )
+        .bind(network_ms)
+        .bind(processing_ms)
+        .bind(judge_id)
+        .execute(&pool)
+        .await;
+
+        if let Err(err) = res {
+            eprintln!("Failed to store submission latency: {err:?}");
+        }
+    });
+}
+
+// Across every event, for the operators' status page
+pub async fn fetch_recent_latency(pool: &PgPool) -> Result<LatencyPercentiles, AppError> {
+    let latency = sqlx::query_as::<_, LatencyPercentiles>(&format!(
+        r#"
+        SELECT {percentiles}
+        FROM submission_latencies l
+        WHERE l.created_at > NOW() - make_interval(mins => $1)
+        "#,
+        percentiles = LATENCY_PERCENTILES,
+    ))
+    .bind(RECENT_LATENCY_MINUTES as i32)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(latency)
+}
+
+// Whether the venue Wi-Fi or the backend is holding up the judges' submissions
+pub async fn get_submission_latency(
+    State(pool): State<PgPool>,
+    Path(event_id): Path<uuid::Uuid>,
+) -> Result<axum::Json<SubmissionLatency>, AppError> {
+    let overall = sqlx::query_as::<_, LatencyPercentiles>(&format!(
+        r#"
+        SELECT {percentiles}
+        FROM submission_latencies l
+        JOIN judges j ON j.id = l.judge_id
+        WHERE j.event_id = ($1)
+        "#,
+        percentiles = LATENCY_PERCENTILES,
+    ))
+    .bind(&event_id)
+    .fetch_one(&pool)
+    .await?;
+
+    let judges = sqlx::query_as::<_, JudgeSubmissionLatency>(&format!(
+        r#"
+        SELECT j.id AS judge_id, j.name AS judge_name, {percentiles}
+        FROM submission_latencies l
+        JOIN judges j ON j.id = l.judge_id
+        WHERE j.event_id = ($1)
+        GROUP BY j.id, j.name
+        ORDER BY network_p90 DESC NULLS LAST
+        "#,
+        percentiles = LATENCY_PERCENTILES,
+    ))
+    .bind(&event_id)
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(axum::Json(SubmissionLatency { overall, judges }))
+}
+
+// Rank of each value, highest first, ties get the average of the ranks they span
+pub fn average_ranks(values: &[f64]) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|a, b| values[*b].total_cmp(&values[*a]));
+
+    let mut ranks = vec![0.0; values.len()];
+    let mut start = 0;
+
+    while start < order.len() {
+        let mut end = start;
+
+        while end + 1 < order.len() && values[order[end + 1]] == values[order[start]] {
+            end += 1;
+        }
+
+        let rank = (start + end) as f64 / 2.0 + 1.0;
+
+        for idx in &order[start..=end] {
+            ranks[*idx] = rank;
+        }
+
+        start = end + 1;
+    }
+
+    ranks
+}
+
+// Spearman's rank correlation, None when there is nothing to correlate
+pub fn spearman(a: &[f64], b: &[f64]) -> Option<f64> {
+    if a.len() != b.len() || a.len() < 2 {
+        return None;
+    }
+
+    let (ranks_a, ranks_b) = (average_ranks(a), average_ranks(b));
+    let n = a.len() as f64;
+    let mean = (n + 1.0) / 2.0;
+
+    let mut covariance = 0.0;
+    let mut variance_a = 0.0;
+    let mut variance_b = 0.0;
+
+    for (rank_a, rank_b) in ranks_a.iter().zip(ranks_b.iter()) {
+        covariance += (rank_a - mean) * (rank_b - mean);
+        variance_a += (rank_a - mean).powi(2);
+        variance_b += (rank_b - mean).powi(2);
+    }
+
+    if variance_a == 0.0 || variance_b == 0.0 {
+        return None;
+    }
+
+    Some(covariance / (variance_a * variance_b).sqrt())
+}
+
+#[derive(Debug, Serialize)]
+pub struct AgreementJudge {
+    judge_id: uuid::Uuid,
+    judge_name: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CategoryAgreement {
+    category_id: uuid::Uuid,
+    category_name: String,
+    judges: Vec<AgreementJudge>,
+    // matrix[i][j] is the correlation between judges[i] and judges[j], over the candidates both scored
+    matrix: Vec<Vec<Option<f64>>>,
+}
+
+// Quantifies panel agreement for the committee's report
+pub async fn get_judge_agreement(
+    State(pool): State<PgPool>,
+    Path(event_id): Path<uuid::Uuid>,
+) -> Result<axum::Json<Vec<CategoryAgreement>>, AppError> {
+    let rows = sqlx::query_as::<_, (uuid::Uuid, String, uuid::Uuid, String, uuid::Uuid, i64)>(
+        r#"
+        SELECT category_id, category_name, judge_id, judge_name, candidate_id, SUM(score)
+        FROM score_report
+        WHERE event_id = ($1) AND judge_score_exclusion = FALSE
+        GROUP BY category_id, category_name, judge_id, judge_name, candidate_id
+        ORDER BY category_name, judge_name
+        "#,
+    )
+    .bind(&event_id)
+    .fetch_all(&pool)
+    .await?;
+
+    let mut agreements: Vec<CategoryAgreement> = Vec::new();
+    // (category_id, judge_id) -> candidate ID -> total
+    let mut totals: HashMap<(uuid::Uuid, uuid::Uuid), HashMap<uuid::Uuid, f64>> = HashMap::new();
+
+    for (category_id, category_name, judge_id, judge_name, candidate_id, total) in rows {
+        if agreements.last().map(|agreement| agreement.category_id) != Some(category_id) {
+            agreements.push(CategoryAgreement {
+                category_id,
+                category_name,
+                judges: Vec::new(),
+                matrix: Vec::new(),
+            });
+        }
+
+        let agreement = agreements.last_mut().unwrap();
+
+        if !agreement
+            .judges
+            .iter()
+            .any(|judge| judge.judge_id == judge_id)
+        {
+            agreement.judges.push(AgreementJudge {
+                judge_id,
+                judge_name,
+            });
+        }
+
+        totals
+            .entry((category_id, judge_id))
+            .or_default()
+            .insert(candidate_id, total as f64);
+    }
+
+    for agreement in agreements.iter_mut() {
+        let judge_totals: Vec<&HashMap<uuid::Uuid, f64>> = agreement
+            .judges
+            .iter()
+            .map(|judge| &totals[&(agreement.category_id, judge.judge_id)])
+            .collect();
+
+        agreement.matrix = judge_totals
+            .iter()
+            .map(|a| {
+                judge_totals
+                    .iter()
+                    .map(|b| {
+                        let (a_values, b_values): (Vec<f64>, Vec<f64>) = a
+                            .iter()
+                            .filter_map(|(candidate_id, total)| {
+                                b.get(candidate_id).map(|other| (*total, *other))
+                            })
+                            .unzip();
+
+                        spearman(&a_values, &b_values)
+                    })
+                    .collect()
+            })
+            .collect();
+    }
+
+    Ok(axum::Json(agreements))
+}
@@ -0,0 +1,289 @@
+use std::collections::BTreeMap;
+
+use axum::extract::{Path, Query, State};
+use axum::http;
+use axum::response::{IntoResponse, Result};
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use sqlx::{FromRow, PgPool};
+use tokio::sync::broadcast;
+
+use crate::error::AppError;
+use crate::pdf::{Document, Font};
+
+use super::history::{self, AwardWinner};
+use super::realtime::{self, RealtimeMessage};
+use super::score::{compute_event_final_scores, fetch_round_final_scores, round_final_scores};
+use super::settings;
+use super::tie_break::{self, TieBreakKeys};
+
+// A4 portrait, in points
+const PAGE_WIDTH: f32 = 595.28;
+const PAGE_HEIGHT: f32 = 841.89;
+const MARGIN: f32 = 56.0;
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct Announcement {
+    id: uuid::Uuid,
+    message: String,
+    created_at: chrono::DateTime<chrono::Utc>,
+    // Relationships
+    event_id: uuid::Uuid,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateAnnouncement {
+    message: String,
+}
+
+pub async fn create_announcement(
+    State(pool): State<PgPool>,
+    State(tx): State<broadcast::Sender<String>>,
+    Path(event_id): Path<uuid::Uuid>,
+    axum::Json(payload): axum::Json<CreateAnnouncement>,
+) -> Result<(http::StatusCode, axum::Json<Announcement>), AppError> {
+    if payload.message.trim().is_empty() {
+        return Err(AppError::new(
+            http::StatusCode::UNPROCESSABLE_ENTITY,
+            "Announcement message can't be empty",
+        ));
+    }
+
+    let announcement = sqlx::query_as::<_, Announcement>(
+        "INSERT INTO announcements (message, event_id) VALUES ($1, $2) RETURNING *",
+    )
+    .bind(payload.message.trim())
+    .bind(&event_id)
+    .fetch_one(&pool)
+    .await?;
+
+    realtime::publish(&tx, &RealtimeMessage::Announcement(announcement.clone()));
+
+    Ok((http::StatusCode::CREATED, axum::Json(announcement)))
+}
+
+// For judges who connect after the announcements were sent
+pub async fn get_announcements(
+    State(pool): State<PgPool>,
+    Path(event_id): Path<uuid::Uuid>,
+) -> Result<axum::Json<Vec<Announcement>>, AppError> {
+    let announcements = sqlx::query_as::<_, Announcement>(
+        "SELECT * FROM announcements WHERE event_id = ($1) ORDER BY created_at",
+    )
+    .bind(&event_id)
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(axum::Json(announcements))
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScriptFormat {
+    #[default]
+    Text,
+    Pdf,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ScriptParam {
+    #[serde(default)]
+    format: ScriptFormat,
+}
+
+// Each division's placements from the lowest up, so the winners are announced last
+// Divisions take turns at each placement, male first like `pick_award_winners` lists them
+pub fn order_placements(winners: Vec<AwardWinner>) -> Vec<AwardWinner> {
+    let mut places: BTreeMap<String, usize> = BTreeMap::new();
+
+    let mut placed: Vec<(usize, AwardWinner)> = winners
+        .into_iter()
+        .map(|winner| {
+            let place = places.entry(winner.division.clone()).or_default();
+
+            *place += 1;
+
+            (*place, winner)
+        })
+        .collect();
+
+    placed.sort_by_key(|(place, _)| std::cmp::Reverse(*place));
+    placed.into_iter().map(|(_, winner)| winner).collect()
+}
+
+// e.g. "Candidate #7, Juan Dela Cruz, with a score of 92.50"
+pub fn announcement_line(winner: &AwardWinner, decimals: usize) -> String {
+    let name = [&winner.first_name, &winner.middle_name, &winner.last_name]
+        .into_iter()
+        .filter(|name| !name.trim().is_empty())
+        .map(|name| name.trim())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    format!(
+        "Candidate #{}, {}, with a score of {:.*}",
+        winner.candidate_number, name, decimals, winner.final_score
+    )
+}
+
+fn award_heading(winner: &AwardWinner) -> String {
+    let division = match winner.division.as_str() {
+        "male" => "Male",
+        "female" => "Female",
+        division => division,
+    };
+
+    format!("{} ({})", winner.award, division)
+}
+
+// The top candidate of each division in every scored category, as "Best in <category>"
+async fn fetch_special_awards(
+    pool: &PgPool,
+    event_id: &uuid::Uuid,
+    decimals: usize,
+) -> Result<Vec<AwardWinner>, AppError> {
+    let categories = sqlx::query_as::<_, (uuid::Uuid, String)>(
+        "SELECT id, name FROM categories WHERE event_id = ($1) ORDER BY name",
+    )
+    .bind(event_id)
+    .fetch_all(pool)
+    .await?;
+
+    let mut awards = Vec::new();
+
+    for (category_id, name) in categories {
+        let mut standings = fetch_round_final_scores(pool, &[category_id]).await?;
+
+        if !standings
+            .iter()
+            .any(|candidate| candidate.final_score > 0.0)
+        {
+            continue;
+        }
+
+        round_final_scores(&mut standings, decimals);
+
+        let title = vec![format!("Best in {}", name)];
+        let titles = BTreeMap::from([
+            ("male".to_string(), title.clone()),
+            ("female".to_string(), title),
+        ]);
+
+        // The event's tie-breakers are meant for the overall standings
+        awards.extend(history::pick_award_winners(
+            &standings,
+            &titles,
+            &TieBreakKeys::new(),
+        ));
+    }
+
+    Ok(awards)
+}
+
+// The finalized awards if the results are final, otherwise the current standings
+async fn fetch_placements(
+    pool: &PgPool,
+    event_id: &uuid::Uuid,
+    settings: &settings::EventSettings,
+) -> Result<Vec<AwardWinner>, AppError> {
+    let finalized: Option<Option<Json<Vec<AwardWinner>>>> = sqlx::query_scalar(
+        r#"
+        SELECT ss.awards FROM events e
+        JOIN standings_snapshots ss ON ss.id = e.finalized_snapshot_id
+        WHERE e.id = ($1)
+        "#,
+    )
+    .bind(event_id)
+    .fetch_optional(pool)
+    .await?;
+
+    if let Some(Some(Json(awards))) = finalized {
+        return Ok(awards);
+    }
+
+    let mut standings = compute_event_final_scores(pool, event_id).await?;
+
+    round_final_scores(&mut standings, settings.display_decimals as usize);
+
+    let keys = tie_break::fetch_tie_break_keys(pool, event_id)
+        .await?
+        .unwrap_or_default();
+
+    Ok(history::pick_award_winners(
+        &standings,
+        &settings.award_titles,
+        &keys,
+    ))
+}
+
+// Special awards first, then the placements up to the winners, for the emcee to read out
+pub async fn get_announcement_script(
+    State(pool): State<PgPool>,
+    Path(event_id): Path<uuid::Uuid>,
+    Query(param): Query<ScriptParam>,
+) -> Result<impl IntoResponse, AppError> {
+    let event_name: String = sqlx::query_scalar("SELECT name FROM events WHERE id = ($1)")
+        .bind(&event_id)
+        .fetch_optional(&pool)
+        .await?
+        .ok_or_else(|| AppError::new(http::StatusCode::NOT_FOUND, "Event not found"))?;
+
+    let settings = settings::fetch_settings(&pool, &event_id).await?;
+    let decimals = settings.display_decimals as usize;
+
+    let mut winners = fetch_special_awards(&pool, &event_id, decimals).await?;
+
+    winners.extend(order_placements(
+        fetch_placements(&pool, &event_id, &settings).await?,
+    ));
+
+    let entries: Vec<(String, String)> = winners
+        .iter()
+        .map(|winner| (award_heading(winner), announcement_line(winner, decimals)))
+        .collect();
+
+    let (content_type, extension, bytes) = match param.format {
+        ScriptFormat::Text => {
+            let mut text = format!("{}\n\n", event_name);
+
+            for (heading, line) in entries.iter() {
+                text.push_str(&format!("{}\n{}\n\n", heading, line));
+            }
+
+            ("text/plain; charset=utf-8", "txt", text.into_bytes())
+        }
+        ScriptFormat::Pdf => {
+            let mut document = Document::new(PAGE_WIDTH, PAGE_HEIGHT);
+            let mut page = document.add_page();
+            let mut y = PAGE_HEIGHT - MARGIN - 20.0;
+
+            page.centered_text(PAGE_WIDTH / 2.0, y, 18.0, Font::HelveticaBold, &event_name);
+            y -= 40.0;
+
+            for (heading, line) in entries.iter() {
+                if y < MARGIN + 36.0 {
+                    page = document.add_page();
+                    y = PAGE_HEIGHT - MARGIN;
+                }
+
+                page.text(MARGIN, y, 13.0, Font::HelveticaBold, heading);
+                y -= 18.0;
+                page.text(MARGIN, y, 12.0, Font::Helvetica, line);
+                y -= 30.0;
+            }
+
+            ("application/pdf", "pdf", document.to_bytes())
+        }
+    };
+
+    Ok((
+        [
+            (http::header::CONTENT_TYPE, content_type.to_string()),
+            (
+                http::header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"announcement-script.{}\"", extension),
+            ),
+        ],
+        bytes,
+    ))
+}
@@ -0,0 +1,362 @@
+use axum::extract::{Extension, Path, State};
+use axum::http;
+use axum::response::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use sqlx::{FromRow, PgPool};
+
+use crate::error::AppError;
+use crate::event_lock;
+
+use super::admin::StaffClaims;
+use super::audit;
+use super::auth;
+use super::history;
+use super::quorum;
+use super::recheck;
+use super::settings;
+use super::standings::{self, StandingsSnapshot};
+use super::tie_break;
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct CommitteeMember {
+    pub id: uuid::Uuid,
+    pub name: String,
+    pub username: String,
+    // Argon2 hash
+    #[serde(skip_serializing)]
+    pub password: String,
+    pub role: String,
+    // Relationships
+    pub event_id: uuid::Uuid,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct ResultApproval {
+    id: uuid::Uuid,
+    approved_at: chrono::DateTime<chrono::Utc>,
+    // Relationships
+    snapshot_id: uuid::Uuid,
+    committee_member_id: uuid::Uuid,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateCommitteeMember {
+    name: String,
+    username: String,
+    password: String,
+    // e.g. Chairperson or Tabulator, defaults to Member
+    role: Option<String>,
+}
+
+// Hashes the passwords of committee members added before they were hashed
+pub async fn init(pool: &PgPool) -> anyhow::Result<()> {
+    let plaintext = sqlx::query_as::<_, (uuid::Uuid, String)>(
+        "SELECT id, password FROM committee_members WHERE password NOT LIKE '$argon2%'",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    for (member_id, password) in plaintext {
+        sqlx::query("UPDATE committee_members SET password = ($1) WHERE id = ($2)")
+            .bind(auth::hash_password(&password).map_err(|_| {
+                anyhow::anyhow!("Failed to hash password of committee member {}", member_id)
+            })?)
+            .bind(&member_id)
+            .execute(pool)
+            .await?;
+    }
+
+    Ok(())
+}
+
+pub async fn create_committee_member(
+    State(pool): State<PgPool>,
+    Path(event_id): Path<uuid::Uuid>,
+    axum::Json(payload): axum::Json<CreateCommitteeMember>,
+) -> Result<(http::StatusCode, axum::Json<CommitteeMember>), AppError> {
+    let member = sqlx::query_as::<_, CommitteeMember>(
+        r#"
+        INSERT INTO committee_members (name, username, password, role, event_id)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING *
+        "#,
+    )
+    .bind(&payload.name)
+    .bind(&payload.username)
+    .bind(auth::hash_password(&payload.password)?)
+    .bind(payload.role.as_deref().unwrap_or("Member"))
+    .bind(&event_id)
+    .fetch_one(&pool)
+    .await?;
+
+    Ok((http::StatusCode::CREATED, axum::Json(member)))
+}
+
+pub async fn get_committee_members(
+    State(pool): State<PgPool>,
+    Path(event_id): Path<uuid::Uuid>,
+) -> Result<axum::Json<Vec<CommitteeMember>>, AppError> {
+    let members = sqlx::query_as::<_, CommitteeMember>(
+        "SELECT * FROM committee_members WHERE event_id = ($1) ORDER BY name",
+    )
+    .bind(&event_id)
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(axum::Json(members))
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct RosterEntry {
+    pub name: String,
+    pub role: String,
+    // When they approved the finalized results, if they have
+    pub approved_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+pub async fn fetch_committee_roster(
+    pool: &PgPool,
+    event_id: &uuid::Uuid,
+) -> Result<Vec<RosterEntry>, AppError> {
+    let roster = sqlx::query_as::<_, RosterEntry>(
+        r#"
+        SELECT cm.name, cm.role, ra.approved_at
+        FROM committee_members cm
+        JOIN events e ON e.id = cm.event_id
+        LEFT JOIN result_approvals ra
+            ON ra.committee_member_id = cm.id AND ra.snapshot_id = e.finalized_snapshot_id
+        WHERE cm.event_id = ($1)
+        ORDER BY cm.role = 'Member', cm.role, cm.name
+        "#,
+    )
+    .bind(event_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(roster)
+}
+
+// Who sits on the tabulation committee, without their login details
+pub async fn get_committee_roster(
+    State(pool): State<PgPool>,
+    Path(event_id): Path<uuid::Uuid>,
+) -> Result<axum::Json<Vec<RosterEntry>>, AppError> {
+    Ok(axum::Json(fetch_committee_roster(&pool, &event_id).await?))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CommitteeLogin {
+    username: String,
+    password: String,
+}
+
+pub async fn authenticate_member(
+    pool: &PgPool,
+    login: &CommitteeLogin,
+) -> Result<CommitteeMember, AppError> {
+    sqlx::query_as::<_, CommitteeMember>("SELECT * FROM committee_members WHERE username = ($1)")
+        .bind(&login.username)
+        .fetch_optional(pool)
+        .await?
+        .filter(|member| auth::verify_password(&login.password, &member.password))
+        .ok_or_else(|| AppError::new(http::StatusCode::UNAUTHORIZED, "Invalid credentials"))
+}
+
+pub async fn committee_login(
+    State(pool): State<PgPool>,
+    axum::Json(login): axum::Json<CommitteeLogin>,
+) -> Result<axum::Json<CommitteeMember>, AppError> {
+    let member = authenticate_member(&pool, &login).await?;
+
+    println!("Welcome, {}!", member.name);
+
+    Ok(axum::Json(member))
+}
+
+// Freezes the current standings as the event's results, pending committee approval
+pub async fn finalize_event(
+    State(pool): State<PgPool>,
+    Extension(staff): Extension<StaffClaims>,
+    Path(event_id): Path<uuid::Uuid>,
+) -> Result<axum::Json<StandingsSnapshot>, AppError> {
+    let rehearsal_mode: bool =
+        sqlx::query_scalar("SELECT rehearsal_mode FROM events WHERE id = ($1)")
+            .bind(&event_id)
+            .fetch_one(&pool)
+            .await?;
+
+    if rehearsal_mode {
+        return Err(AppError::new(
+            http::StatusCode::CONFLICT,
+            "Turn off rehearsal mode before finalizing the results",
+        ));
+    }
+
+    recheck::ensure_rechecks_acknowledged(&pool, &event_id).await?;
+
+    // Late score writes and other finalizations wait until the snapshot is stored
+    let lock = event_lock::lock(&pool, &event_id).await?;
+
+    quorum::ensure_quorum(&pool, &event_id, None).await?;
+
+    let snapshot = standings::store_snapshot(&pool, &event_id, None).await?;
+
+    // Titles are given out now, so the results and exports keep them even if the settings change
+    let settings = settings::fetch_settings(&pool, &event_id).await?;
+    let keys = tie_break::fetch_tie_break_keys(&pool, &event_id)
+        .await?
+        .unwrap_or_default();
+    let awards = history::pick_award_winners(&snapshot.standings, &settings.award_titles, &keys);
+
+    let snapshot = sqlx::query_as::<_, StandingsSnapshot>(
+        "UPDATE standings_snapshots SET awards = ($1) WHERE id = ($2) RETURNING *",
+    )
+    .bind(Json(&awards))
+    .bind(&snapshot.id)
+    .fetch_one(&pool)
+    .await?;
+
+    sqlx::query("UPDATE events SET finalized_snapshot_id = ($1) WHERE id = ($2)")
+        .bind(&snapshot.id)
+        .bind(&event_id)
+        .execute(&pool)
+        .await?;
+
+    lock.release().await?;
+
+    audit::record(
+        &pool,
+        staff.sub,
+        "event",
+        Some(&event_id),
+        "finalize",
+        serde_json::json!({ "snapshot_version": snapshot.version }),
+    )
+    .await?;
+
+    Ok(axum::Json(snapshot))
+}
+
+// Committee members log in again to acknowledge the finalized results
+pub async fn approve_results(
+    State(pool): State<PgPool>,
+    Path(event_id): Path<uuid::Uuid>,
+    axum::Json(login): axum::Json<CommitteeLogin>,
+) -> Result<(http::StatusCode, axum::Json<ResultApproval>), AppError> {
+    let member = authenticate_member(&pool, &login).await?;
+
+    if member.event_id != event_id {
+        return Err(AppError::new(
+            http::StatusCode::FORBIDDEN,
+            "Committee member does not belong to this event",
+        ));
+    }
+
+    let snapshot_id: uuid::Uuid = sqlx::query_scalar::<_, Option<uuid::Uuid>>(
+        "SELECT finalized_snapshot_id FROM events WHERE id = ($1)",
+    )
+    .bind(&event_id)
+    .fetch_one(&pool)
+    .await?
+    .ok_or_else(|| {
+        AppError::new(
+            http::StatusCode::CONFLICT,
+            "Event has not been finalized yet",
+        )
+    })?;
+
+    let approval = sqlx::query_as::<_, ResultApproval>(
+        r#"
+        INSERT INTO result_approvals (snapshot_id, committee_member_id)
+        VALUES ($1, $2)
+        ON CONFLICT (snapshot_id, committee_member_id) DO UPDATE SET approved_at = result_approvals.approved_at
+        RETURNING *
+        "#,
+    )
+    .bind(&snapshot_id)
+    .bind(&member.id)
+    .fetch_one(&pool)
+    .await?;
+
+    audit::record(
+        &pool,
+        &member.username,
+        "event",
+        Some(&event_id),
+        "approve_results",
+        serde_json::json!({ "snapshot_id": snapshot_id }),
+    )
+    .await?;
+
+    Ok((http::StatusCode::CREATED, axum::Json(approval)))
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct ApproverEntry {
+    committee_member_id: uuid::Uuid,
+    name: String,
+    approved_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ApprovedResults {
+    snapshot: StandingsSnapshot,
+    approvals: Vec<ApproverEntry>,
+}
+
+// Finalized results, only served once enough committee members approved them
+pub async fn get_event_results(
+    State(pool): State<PgPool>,
+    Path(event_id): Path<uuid::Uuid>,
+) -> Result<axum::Json<ApprovedResults>, AppError> {
+    let (snapshot_id, required_approvals) = sqlx::query_as::<_, (Option<uuid::Uuid>, i32)>(
+        "SELECT finalized_snapshot_id, required_approvals FROM events WHERE id = ($1)",
+    )
+    .bind(&event_id)
+    .fetch_optional(&pool)
+    .await?
+    .ok_or_else(|| AppError::new(http::StatusCode::NOT_FOUND, "Event not found"))?;
+
+    let snapshot_id = snapshot_id.ok_or_else(|| {
+        AppError::new(
+            http::StatusCode::CONFLICT,
+            "Event has not been finalized yet",
+        )
+    })?;
+
+    let approvals = sqlx::query_as::<_, ApproverEntry>(
+        r#"
+        SELECT ra.committee_member_id, cm.name, ra.approved_at
+        FROM result_approvals ra
+        JOIN committee_members cm ON cm.id = ra.committee_member_id
+        WHERE ra.snapshot_id = ($1)
+        ORDER BY ra.approved_at
+        "#,
+    )
+    .bind(&snapshot_id)
+    .fetch_all(&pool)
+    .await?;
+
+    if (approvals.len() as i32) < required_approvals {
+        return Err(AppError::new(
+            http::StatusCode::CONFLICT,
+            format!(
+                "Results need {} committee approvals, only {} so far",
+                required_approvals,
+                approvals.len()
+            ),
+        ));
+    }
+
+    let snapshot =
+        sqlx::query_as::<_, StandingsSnapshot>("SELECT * FROM standings_snapshots WHERE id = ($1)")
+            .bind(&snapshot_id)
+            .fetch_one(&pool)
+            .await?;
+
+    Ok(axum::Json(ApprovedResults {
+        snapshot,
+        approvals,
+    }))
+}
@@ -0,0 +1,95 @@
+use axum::extract::{Extension, Path, State};
+use axum::http;
+use axum::response::Result;
+use serde::Serialize;
+use sqlx::PgPool;
+
+use crate::error::AppError;
+
+use super::admin::StaffClaims;
+use super::audit;
+
+#[derive(Debug, Serialize)]
+pub struct ArchiveResult {
+    event_id: uuid::Uuid,
+    archived_scores: u64,
+}
+
+// Moves the scores of a closed event out of the live scores table
+// Exports read from the `scores_history` view so they keep working afterwards
+pub async fn archive_event(
+    State(pool): State<PgPool>,
+    Extension(staff): Extension<StaffClaims>,
+    Path(event_id): Path<uuid::Uuid>,
+) -> Result<axum::Json<ArchiveResult>, AppError> {
+    let open_categories: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM categories WHERE event_id = ($1) AND is_locked = FALSE",
+    )
+    .bind(&event_id)
+    .fetch_one(&pool)
+    .await?;
+
+    if open_categories > 0 {
+        return Err(AppError::new(
+            http::StatusCode::CONFLICT,
+            format!(
+                "Event still has {} open categories, lock them before archiving",
+                open_categories
+            ),
+        ));
+    }
+
+    let mut txn = pool.begin().await?;
+
+    let res = sqlx::query(
+        r#"
+        INSERT INTO scores_archive (id, score, max, time_of_scoring, candidate_id, criteria_id, category_id, judge_id, remark, group_id, is_rehearsal, updated_at)
+        SELECT s.id, s.score, s.max, s.time_of_scoring, s.candidate_id, s.criteria_id, s.category_id, s.judge_id, s.remark, s.group_id, s.is_rehearsal, s.updated_at
+        FROM scores s
+        JOIN categories cat ON cat.id = s.category_id
+        WHERE cat.event_id = ($1)
+        "#,
+    )
+    .bind(&event_id)
+    .execute(&mut *txn)
+    .await?;
+
+    sqlx::query(
+        r#"
+        DELETE FROM scores s
+        USING categories cat
+        WHERE cat.id = s.category_id AND cat.event_id = ($1)
+        "#,
+    )
+    .bind(&event_id)
+    .execute(&mut *txn)
+    .await?;
+
+    sqlx::query("UPDATE events SET is_archived = TRUE WHERE id = ($1)")
+        .bind(&event_id)
+        .execute(&mut *txn)
+        .await?;
+
+    audit::record(
+        &mut *txn,
+        staff.sub,
+        "event",
+        Some(&event_id),
+        "archive",
+        serde_json::json!({ "archived_scores": res.rows_affected() }),
+    )
+    .await?;
+
+    txn.commit().await?;
+
+    println!(
+        "Archived {} scores for event {}",
+        res.rows_affected(),
+        event_id
+    );
+
+    Ok(axum::Json(ArchiveResult {
+        event_id,
+        archived_scores: res.rows_affected(),
+    }))
+}
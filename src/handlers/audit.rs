@@ -0,0 +1,195 @@
+use axum::extract::{Query, State};
+use axum::http;
+use axum::response::{IntoResponse, Result};
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use sqlx::{FromRow, PgExecutor, PgPool};
+
+use crate::error::AppError;
+use crate::field_crypto::{self, Keyring};
+
+// Actor used for scheduled actions done by the backend itself
+pub const SCHEDULER: &str = "scheduler";
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct AuditEntry {
+    id: uuid::Uuid,
+    actor: String,
+    entity_type: String,
+    entity_id: Option<uuid::Uuid>,
+    action: String,
+    details: Json<serde_json::Value>,
+    created_at: chrono::DateTime<chrono::Utc>,
+}
+
+// Only `candidate_id` stays readable, the audit log is filtered by it
+pub fn seal_details(details: serde_json::Value) -> serde_json::Value {
+    let keyring = field_crypto::keyring();
+
+    if !keyring.is_enabled() {
+        return details;
+    }
+
+    let mut sealed = serde_json::json!({ "sealed": keyring.seal(&details.to_string()) });
+
+    if let Some(candidate_id) = details.get("candidate_id") {
+        sealed["candidate_id"] = candidate_id.clone();
+    }
+
+    sealed
+}
+
+pub fn open_details(details: serde_json::Value) -> Result<serde_json::Value, String> {
+    match details.get("sealed").and_then(|sealed| sealed.as_str()) {
+        Some(sealed) if Keyring::is_sealed(sealed) => {
+            let plain = field_crypto::keyring().open(sealed)?;
+
+            serde_json::from_str(&plain).map_err(|err| err.to_string())
+        }
+        _ => Ok(details),
+    }
+}
+
+pub fn details_need_rotation(details: &serde_json::Value) -> bool {
+    let keyring = field_crypto::keyring();
+
+    match details.get("sealed").and_then(|sealed| sealed.as_str()) {
+        Some(sealed) if Keyring::is_sealed(sealed) => keyring.needs_rotation(sealed),
+        _ => keyring.is_enabled(),
+    }
+}
+
+pub async fn record<'e, E: PgExecutor<'e>>(
+    executor: E,
+    actor: impl ToString,
+    entity_type: &str,
+    entity_id: Option<&uuid::Uuid>,
+    action: &str,
+    details: serde_json::Value,
+) -> Result<(), AppError> {
+    sqlx::query(
+        r#"
+        INSERT INTO audit_log (actor, entity_type, entity_id, action, details)
+        VALUES ($1, $2, $3, $4, $5)
+        "#,
+    )
+    .bind(actor.to_string())
+    .bind(entity_type)
+    .bind(entity_id)
+    .bind(action)
+    .bind(Json(seal_details(details)))
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AuditParam {
+    actor: Option<String>,
+    entity_type: Option<String>,
+    entity_id: Option<uuid::Uuid>,
+    action: Option<String>,
+    // Matches entries about a candidate's scores
+    candidate_id: Option<uuid::Uuid>,
+    from: Option<chrono::DateTime<chrono::Utc>>,
+    to: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+async fn fetch_audit_entries(
+    pool: &PgPool,
+    param: &AuditParam,
+) -> Result<Vec<AuditEntry>, AppError> {
+    let mut entries = sqlx::query_as::<_, AuditEntry>(
+        r#"
+        SELECT * FROM audit_log
+        WHERE (($1)::TEXT IS NULL OR actor = ($1))
+            AND (($2)::TEXT IS NULL OR entity_type = ($2))
+            AND (($3)::UUID IS NULL OR entity_id = ($3))
+            AND (($4)::TEXT IS NULL OR action = ($4))
+            AND (($5)::UUID IS NULL OR details->>'candidate_id' = ($5)::TEXT)
+            AND (($6)::TIMESTAMPTZ IS NULL OR created_at >= ($6))
+            AND (($7)::TIMESTAMPTZ IS NULL OR created_at <= ($7))
+        ORDER BY created_at
+        "#,
+    )
+    .bind(&param.actor)
+    .bind(&param.entity_type)
+    .bind(&param.entity_id)
+    .bind(&param.action)
+    .bind(&param.candidate_id)
+    .bind(&param.from)
+    .bind(&param.to)
+    .fetch_all(pool)
+    .await?;
+
+    for entry in entries.iter_mut() {
+        let details = std::mem::take(&mut entry.details.0);
+
+        entry.details.0 = open_details(details)
+            .map_err(|err| AppError::new(http::StatusCode::INTERNAL_SERVER_ERROR, err))?;
+    }
+
+    Ok(entries)
+}
+
+pub async fn get_audit_log(
+    State(pool): State<PgPool>,
+    Query(param): Query<AuditParam>,
+) -> Result<axum::Json<Vec<AuditEntry>>, AppError> {
+    let entries = fetch_audit_entries(&pool, &param).await?;
+
+    Ok(axum::Json(entries))
+}
+
+pub async fn export_audit_log(
+    State(pool): State<PgPool>,
+    Query(param): Query<AuditParam>,
+) -> Result<impl IntoResponse, AppError> {
+    let entries = fetch_audit_entries(&pool, &param).await?;
+
+    let mut csv_writer = csv::Writer::from_writer(Vec::new());
+
+    let headers = [
+        "Time",
+        "Actor",
+        "Entity Type",
+        "Entity ID",
+        "Action",
+        "Details",
+    ];
+
+    csv_writer.write_record(&headers).map_err(|err| {
+        AppError::new(
+            http::StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to write record for headers: {}", err),
+        )
+    })?;
+
+    for entry in entries.iter() {
+        csv_writer
+            .write_record(vec![
+                entry.created_at.to_rfc3339(),
+                entry.actor.clone(),
+                entry.entity_type.clone(),
+                entry.entity_id.map(|id| id.to_string()).unwrap_or_default(),
+                entry.action.clone(),
+                entry.details.0.to_string(),
+            ])
+            .map_err(|err| {
+                AppError::new(
+                    http::StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Failed to serialize record: {}", err),
+                )
+            })?;
+    }
+
+    let csv_bytes = csv_writer.into_inner().map_err(|err| {
+        AppError::new(
+            http::StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to generate CSV file: {}", err),
+        )
+    })?;
+
+    Ok(([(http::header::CONTENT_TYPE, "text/csv")], csv_bytes))
+}
@@ -1,58 +1,209 @@
-use axum::extract::State;
+use std::sync::OnceLock;
+
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use axum::async_trait;
+use axum::extract::{Extension, FromRef, FromRequestParts, Path, State};
 use axum::http;
+use axum::http::request::Parts;
 use axum::response::Result;
-use serde::Deserialize;
-use sqlx::PgPool;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
 
 use crate::error::AppError;
+use crate::handlers::admin::StaffClaims;
+use crate::handlers::audit;
 use crate::handlers::judge::Judge;
 
+// Failed PIN logins allowed per event within a minute, PINs are easy to guess otherwise
+const MAX_PIN_ATTEMPTS: i64 = 10;
+
 #[derive(Debug, Deserialize)]
 pub struct User {
     username: String,
     password: String,
 }
 
+// Hours a login token stays valid, enough for the whole night
+pub const TOKEN_HOURS: i64 = 12;
+
+static TOKEN_SECRET: OnceLock<Vec<u8>> = OnceLock::new();
+
+// Uses `JWT_SECRET` if set, otherwise a secret generated once and kept in the database
+// Passwords stored before they were hashed are hashed here, once
+pub async fn init(pool: &PgPool) -> anyhow::Result<()> {
+    let secret = match std::env::var("JWT_SECRET") {
+        Ok(secret) => secret,
+        Err(_) => {
+            sqlx::query(
+                "INSERT INTO app_secrets (name) VALUES ('judge_tokens') ON CONFLICT DO NOTHING",
+            )
+            .execute(pool)
+            .await?;
+
+            sqlx::query_scalar("SELECT value FROM app_secrets WHERE name = 'judge_tokens'")
+                .fetch_one(pool)
+                .await?
+        }
+    };
+
+    let _ = TOKEN_SECRET.set(secret.into_bytes());
+
+    let plaintext = sqlx::query_as::<_, (uuid::Uuid, String)>(
+        "SELECT id, password FROM judges WHERE password NOT LIKE '$argon2%'",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    for (judge_id, password) in plaintext {
+        sqlx::query("UPDATE judges SET password = ($1) WHERE id = ($2)")
+            .bind(
+                hash_password(&password).map_err(|_| {
+                    anyhow::anyhow!("Failed to hash password of judge {}", judge_id)
+                })?,
+            )
+            .bind(&judge_id)
+            .execute(pool)
+            .await?;
+    }
+
+    Ok(())
+}
+
+pub fn token_secret() -> Result<&'static [u8], AppError> {
+    TOKEN_SECRET
+        .get()
+        .map(|secret| secret.as_slice())
+        .ok_or_else(|| {
+            AppError::new(
+                http::StatusCode::INTERNAL_SERVER_ERROR,
+                "Judge logins are not set up",
+            )
+        })
+}
+
+pub fn hash_password(password: &str) -> Result<String, AppError> {
+    let salt = SaltString::generate(&mut OsRng);
+
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|err| {
+            AppError::new(
+                http::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to hash password: {}", err),
+            )
+        })
+}
+
+pub fn verify_password(password: &str, hash: &str) -> bool {
+    let Ok(hash) = PasswordHash::new(hash) else {
+        return false;
+    };
+
+    Argon2::default()
+        .verify_password(password.as_bytes(), &hash)
+        .is_ok()
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JudgeClaims {
+    // Judge ID
+    pub sub: uuid::Uuid,
+    pub event_id: uuid::Uuid,
+    pub exp: i64,
+    // Token of the `judge_sessions` row behind this login, ending the session revokes the token
+    pub jti: String,
+}
+
+pub fn encode_token<T: Serialize>(secret: &[u8], claims: &T) -> Result<String, AppError> {
+    jsonwebtoken::encode(
+        &jsonwebtoken::Header::default(),
+        claims,
+        &jsonwebtoken::EncodingKey::from_secret(secret),
+    )
+    .map_err(|err| {
+        AppError::new(
+            http::StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to issue token: {}", err),
+        )
+    })
+}
+
+// None for anything that isn't a valid, unexpired token signed by us
+pub fn decode_token<T: DeserializeOwned>(secret: &[u8], token: &str) -> Option<T> {
+    jsonwebtoken::decode::<T>(
+        token,
+        &jsonwebtoken::DecodingKey::from_secret(secret),
+        &jsonwebtoken::Validation::default(),
+    )
+    .ok()
+    .map(|data| data.claims)
+}
+
 pub async fn login(
     State(pool): State<PgPool>,
     axum::Json(user): axum::Json<User>,
-) -> Result<axum::Json<Judge>, AppError> {
-    let res = sqlx::query_as::<_, Judge>(
-        "SELECT * FROM judges WHERE username = ($1) AND password = ($2)",
+) -> Result<axum::Json<JudgeSession>, AppError> {
+    // Usernames are only unique within an event
+    let judges = sqlx::query_as::<_, Judge>("SELECT * FROM judges WHERE username = ($1)")
+        .bind(&user.username)
+        .fetch_all(&pool)
+        .await?;
+
+    let judge = judges
+        .into_iter()
+        .find(|judge| verify_password(&user.password, &judge.password))
+        .ok_or_else(|| AppError::new(http::StatusCode::UNAUTHORIZED, "Invalid credentials"))?;
+
+    let mut txn = pool.begin().await?;
+
+    sqlx::query("UPDATE judges SET is_active = TRUE WHERE id = ($1)")
+        .bind(&judge.id)
+        .execute(&mut *txn)
+        .await?;
+
+    // Whole seconds, the token can't carry more
+    let expires_at = chrono::DateTime::from_timestamp(
+        (chrono::Utc::now() + chrono::Duration::hours(TOKEN_HOURS)).timestamp(),
+        0,
     )
-    .bind(&user.username)
-    .bind(&user.password)
-    .fetch_one(&pool)
-    .await;
+    .unwrap_or_default();
 
-    match res {
-        Ok(judge) => {
-            sqlx::query("UPDATE judges SET is_active = TRUE WHERE id = ($1)")
-                .bind(&judge.id)
-                .execute(&pool)
-                .await
-                .map_err(|err| {
-                    eprintln!("Failed to set is_active to TRUE");
-                    AppError::new(
-                        http::StatusCode::INTERNAL_SERVER_ERROR,
-                        format!("Failed to set is_active to TRUE: {}", err),
-                    )
-                })?;
-
-            println!("Welcome, {}!", judge.name);
-            println!("Details: {:?}\n", judge);
-
-            Ok(axum::Json(judge))
-        }
-        Err(err) => {
-            eprintln!("Failed to login: {err:?}");
+    let session_token: String = sqlx::query_scalar(
+        r#"
+        INSERT INTO judge_sessions (expires_at, judge_id, event_id)
+        VALUES ($1, $2, $3)
+        RETURNING token
+        "#,
+    )
+    .bind(&expires_at)
+    .bind(&judge.id)
+    .bind(&judge.event_id)
+    .fetch_one(&mut *txn)
+    .await?;
 
-            Err(AppError::new(
-                http::StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Failed to login: {}", err),
-            ))
-        }
-    }
+    let token = encode_token(
+        token_secret()?,
+        &JudgeClaims {
+            sub: judge.id,
+            event_id: judge.event_id,
+            exp: expires_at.timestamp(),
+            jti: session_token,
+        },
+    )?;
+
+    txn.commit().await?;
+
+    println!("Welcome, {}!", judge.name);
+
+    Ok(axum::Json(JudgeSession {
+        token,
+        expires_at,
+        judge,
+    }))
 }
 
 #[derive(Debug, Deserialize)]
@@ -62,8 +213,11 @@ pub struct LogOut {
 
 pub async fn logout(
     State(pool): State<PgPool>,
+    judge: AuthenticatedJudge,
     axum::Json(logout): axum::Json<LogOut>,
 ) -> Result<http::StatusCode, AppError> {
+    judge.ensure_judge(&logout.user_id)?;
+
     let res = sqlx::query("UPDATE judges SET is_active = FALSE WHERE id = ($1)")
         .bind(&logout.user_id)
         .execute(&pool)
@@ -85,3 +239,312 @@ pub async fn logout(
         }
     }
 }
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct JudgePin {
+    judge_id: uuid::Uuid,
+    judge_name: String,
+    pin: String,
+    valid_until: chrono::DateTime<chrono::Utc>,
+}
+
+// Gives every judge of the event a new 6 digit PIN, valid until the end of the day
+pub async fn generate_judge_pins(
+    State(pool): State<PgPool>,
+    Extension(staff): Extension<StaffClaims>,
+    Path(event_id): Path<uuid::Uuid>,
+) -> Result<axum::Json<Vec<JudgePin>>, AppError> {
+    let mut txn = pool.begin().await?;
+
+    sqlx::query("DELETE FROM judge_pins WHERE event_id = ($1)")
+        .bind(&event_id)
+        .execute(&mut *txn)
+        .await?;
+
+    let judge_ids = sqlx::query_scalar::<_, uuid::Uuid>(
+        "SELECT id FROM judges WHERE event_id = ($1) AND substituted_at IS NULL",
+    )
+    .bind(&event_id)
+    .fetch_all(&mut *txn)
+    .await?;
+
+    for judge_id in judge_ids.iter() {
+        // Retry on the rare collision with another judge's PIN
+        loop {
+            let res = sqlx::query(
+                r#"
+                INSERT INTO judge_pins (pin, valid_until, judge_id, event_id)
+                VALUES (
+                    lpad(floor(random() * 1000000)::INT::TEXT, 6, '0'),
+                    date_trunc('day', NOW()) + INTERVAL '1 day',
+                    ($1),
+                    ($2)
+                )
+                ON CONFLICT (event_id, pin) DO NOTHING
+                "#,
+            )
+            .bind(judge_id)
+            .bind(&event_id)
+            .execute(&mut *txn)
+            .await?;
+
+            if res.rows_affected() > 0 {
+                break;
+            }
+        }
+    }
+
+    let pins = sqlx::query_as::<_, JudgePin>(
+        r#"
+        SELECT p.judge_id, j.name AS judge_name, p.pin, p.valid_until
+        FROM judge_pins p
+        JOIN judges j ON j.id = p.judge_id
+        WHERE p.event_id = ($1)
+        ORDER BY j.name
+        "#,
+    )
+    .bind(&event_id)
+    .fetch_all(&mut *txn)
+    .await?;
+
+    audit::record(
+        &mut *txn,
+        staff.sub,
+        "event",
+        Some(&event_id),
+        "generate_pins",
+        serde_json::json!({ "judges": pins.len() }),
+    )
+    .await?;
+
+    txn.commit().await?;
+
+    Ok(axum::Json(pins))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PinLogin {
+    event_id: uuid::Uuid,
+    pin: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct JudgeSession {
+    token: String,
+    expires_at: chrono::DateTime<chrono::Utc>,
+    judge: Judge,
+}
+
+pub async fn pin_login(
+    State(pool): State<PgPool>,
+    axum::Json(login): axum::Json<PinLogin>,
+) -> Result<axum::Json<JudgeSession>, AppError> {
+    let recent_failures: i64 = sqlx::query_scalar(
+        r#"
+        SELECT COUNT(*) FROM audit_log
+        WHERE entity_type = 'event' AND entity_id = ($1) AND action = 'pin_login_failed'
+            AND created_at > NOW() - INTERVAL '1 minute'
+        "#,
+    )
+    .bind(&login.event_id)
+    .fetch_one(&pool)
+    .await?;
+
+    if recent_failures >= MAX_PIN_ATTEMPTS {
+        return Err(AppError::new(
+            http::StatusCode::TOO_MANY_REQUESTS,
+            "Too many failed PIN attempts, try again in a minute",
+        ));
+    }
+
+    let judge = sqlx::query_as::<_, Judge>(
+        r#"
+        SELECT j.* FROM judge_pins p
+        JOIN judges j ON j.id = p.judge_id
+        WHERE p.event_id = ($1) AND p.pin = ($2) AND p.valid_until > NOW()
+        "#,
+    )
+    .bind(&login.event_id)
+    .bind(&login.pin)
+    .fetch_optional(&pool)
+    .await?;
+
+    let Some(judge) = judge else {
+        audit::record(
+            &pool,
+            "anonymous",
+            "event",
+            Some(&login.event_id),
+            "pin_login_failed",
+            serde_json::json!({}),
+        )
+        .await?;
+
+        return Err(AppError::new(http::StatusCode::UNAUTHORIZED, "Invalid PIN"));
+    };
+
+    let mut txn = pool.begin().await?;
+
+    sqlx::query("UPDATE judges SET is_active = TRUE WHERE id = ($1)")
+        .bind(&judge.id)
+        .execute(&mut *txn)
+        .await?;
+
+    let (token, expires_at) = sqlx::query_as::<_, (String, chrono::DateTime<chrono::Utc>)>(
+        r#"
+        INSERT INTO judge_sessions (expires_at, judge_id, event_id)
+        SELECT valid_until, judge_id, event_id FROM judge_pins WHERE judge_id = ($1)
+        RETURNING token, expires_at
+        "#,
+    )
+    .bind(&judge.id)
+    .fetch_one(&mut *txn)
+    .await?;
+
+    txn.commit().await?;
+
+    println!("Welcome, {}!", judge.name);
+
+    Ok(axum::Json(JudgeSession {
+        token,
+        expires_at,
+        judge,
+    }))
+}
+
+// The `judge_sessions` token behind a bearer token, login tokens carry theirs as `jti`
+pub fn session_key(secret: &[u8], token: &str) -> String {
+    decode_token::<JudgeClaims>(secret, token)
+        .map(|claims| claims.jti)
+        .unwrap_or_else(|| token.to_string())
+}
+
+// Judge of a login token or a PIN session that has not expired or been ended yet
+pub async fn authenticate_session(pool: &PgPool, token: &str) -> Result<Judge, AppError> {
+    sqlx::query_as::<_, Judge>(
+        r#"
+        SELECT j.* FROM judge_sessions s
+        JOIN judges j ON j.id = s.judge_id
+        WHERE s.token = ($1) AND s.expires_at > NOW()
+        "#,
+    )
+    .bind(session_key(token_secret()?, token))
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::new(http::StatusCode::UNAUTHORIZED, "Session expired or invalid"))
+}
+
+// The judge making the request, for handlers that write under a judge's name
+pub struct AuthenticatedJudge(pub Judge);
+
+impl AuthenticatedJudge {
+    pub fn ensure_judge(&self, judge_id: &uuid::Uuid) -> Result<(), AppError> {
+        if &self.0.id != judge_id {
+            return Err(AppError::new(
+                http::StatusCode::FORBIDDEN,
+                "Judges can only submit scores under their own judge ID",
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for AuthenticatedJudge
+where
+    PgPool: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let pool = PgPool::from_ref(state);
+        let judge = authenticate_session(&pool, bearer_token(&parts.headers)?).await?;
+
+        Ok(Self(judge))
+    }
+}
+
+pub fn bearer_token(headers: &http::HeaderMap) -> Result<&str, AppError> {
+    headers
+        .get(http::header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .ok_or_else(|| AppError::new(http::StatusCode::UNAUTHORIZED, "Missing session token"))
+}
+
+pub async fn get_session(
+    State(pool): State<PgPool>,
+    headers: http::HeaderMap,
+) -> Result<axum::Json<Judge>, AppError> {
+    let judge = authenticate_session(&pool, bearer_token(&headers)?).await?;
+
+    Ok(axum::Json(judge))
+}
+
+// Sent by the scoring page every few seconds, sessions that stop are ended by the reaper
+pub async fn heartbeat(
+    State(pool): State<PgPool>,
+    headers: http::HeaderMap,
+) -> Result<http::StatusCode, AppError> {
+    sqlx::query_scalar::<_, uuid::Uuid>(
+        r#"
+        UPDATE judge_sessions SET last_heartbeat_at = NOW()
+        WHERE token = ($1) AND expires_at > NOW()
+        RETURNING judge_id
+        "#,
+    )
+    .bind(session_key(token_secret()?, bearer_token(&headers)?))
+    .fetch_optional(&pool)
+    .await?
+    .ok_or_else(|| AppError::new(http::StatusCode::UNAUTHORIZED, "Session expired or invalid"))?;
+
+    Ok(http::StatusCode::NO_CONTENT)
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct JudgePresence {
+    judge_id: uuid::Uuid,
+    name: String,
+    last_heartbeat_at: Option<chrono::DateTime<chrono::Utc>>,
+    online: bool,
+}
+
+pub async fn get_judge_presence(
+    State(pool): State<PgPool>,
+    Path(event_id): Path<uuid::Uuid>,
+) -> Result<axum::Json<Vec<JudgePresence>>, AppError> {
+    let presence = sqlx::query_as::<_, JudgePresence>(
+        r#"
+        SELECT judge_id, name, last_heartbeat_at, online FROM judge_presence
+        WHERE event_id = ($1)
+        ORDER BY name
+        "#,
+    )
+    .bind(&event_id)
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(axum::Json(presence))
+}
+
+pub async fn end_session(
+    State(pool): State<PgPool>,
+    headers: http::HeaderMap,
+) -> Result<http::StatusCode, AppError> {
+    let judge_id = sqlx::query_scalar::<_, uuid::Uuid>(
+        "DELETE FROM judge_sessions WHERE token = ($1) RETURNING judge_id",
+    )
+    .bind(session_key(token_secret()?, bearer_token(&headers)?))
+    .fetch_optional(&pool)
+    .await?
+    .ok_or_else(|| AppError::new(http::StatusCode::UNAUTHORIZED, "Session expired or invalid"))?;
+
+    sqlx::query("UPDATE judges SET is_active = FALSE WHERE id = ($1)")
+        .bind(&judge_id)
+        .execute(&pool)
+        .await?;
+
+    Ok(http::StatusCode::OK)
+}
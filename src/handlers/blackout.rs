@@ -0,0 +1,167 @@
+use axum::extract::{Extension, Path, State};
+use axum::http;
+use axum::response::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+
+use crate::aggregation::Aggregator;
+use crate::error::AppError;
+
+use super::admin::StaffClaims;
+use super::audit;
+use super::judge::Judge;
+use super::score;
+
+// A judge blinded from one candidate, e.g. a relative, while still scoring everyone else
+// Unlike `score_exclusion` the judge's other scores count as usual
+#[derive(Debug, Serialize, FromRow)]
+pub struct JudgeBlackout {
+    reason: Option<String>,
+    created_at: chrono::DateTime<chrono::Utc>,
+    // Relationships
+    judge_id: uuid::Uuid,
+    candidate_id: uuid::Uuid,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateBlackout {
+    reason: Option<String>,
+}
+
+async fn fetch_judge(pool: &PgPool, judge_id: &uuid::Uuid) -> Result<Judge, AppError> {
+    sqlx::query_as::<_, Judge>("SELECT * FROM judges WHERE id = ($1)")
+        .bind(judge_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::new(http::StatusCode::NOT_FOUND, "Judge not found"))
+}
+
+pub async fn create_blackout(
+    State(pool): State<PgPool>,
+    State(aggregator): State<Aggregator>,
+    Extension(staff): Extension<StaffClaims>,
+    Path((judge_id, candidate_id)): Path<(uuid::Uuid, uuid::Uuid)>,
+    axum::Json(payload): axum::Json<CreateBlackout>,
+) -> Result<(http::StatusCode, axum::Json<JudgeBlackout>), AppError> {
+    let judge = fetch_judge(&pool, &judge_id).await?;
+
+    let mut txn = pool.begin().await?;
+
+    let blackout = sqlx::query_as::<_, JudgeBlackout>(
+        r#"
+        INSERT INTO judge_blackouts (reason, judge_id, candidate_id)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (judge_id, candidate_id) DO UPDATE SET reason = EXCLUDED.reason
+        RETURNING *
+        "#,
+    )
+    .bind(&payload.reason)
+    .bind(&judge_id)
+    .bind(&candidate_id)
+    .fetch_one(&mut *txn)
+    .await?;
+
+    // Anything the judge already gave the candidate would otherwise still count
+    let voided = score::void_scores(
+        &mut *txn,
+        score::VoidedScores::JudgeCandidate(judge_id, candidate_id),
+        "Judge blacked out from the candidate",
+    )
+    .await?;
+
+    audit::record(
+        &mut *txn,
+        staff.sub,
+        "judge",
+        Some(&judge_id),
+        "blackout",
+        serde_json::json!({
+            "candidate_id": candidate_id,
+            "reason": blackout.reason,
+            "voided_scores": voided,
+        }),
+    )
+    .await?;
+
+    txn.commit().await?;
+
+    aggregator.request(judge.event_id);
+
+    Ok((http::StatusCode::CREATED, axum::Json(blackout)))
+}
+
+pub async fn delete_blackout(
+    State(pool): State<PgPool>,
+    State(aggregator): State<Aggregator>,
+    Extension(staff): Extension<StaffClaims>,
+    Path((judge_id, candidate_id)): Path<(uuid::Uuid, uuid::Uuid)>,
+) -> Result<http::StatusCode, AppError> {
+    let judge = fetch_judge(&pool, &judge_id).await?;
+
+    let deleted =
+        sqlx::query("DELETE FROM judge_blackouts WHERE judge_id = ($1) AND candidate_id = ($2)")
+            .bind(&judge_id)
+            .bind(&candidate_id)
+            .execute(&pool)
+            .await?
+            .rows_affected();
+
+    if deleted == 0 {
+        return Err(AppError::new(
+            http::StatusCode::NOT_FOUND,
+            "Judge is not blacked out from this candidate",
+        ));
+    }
+
+    audit::record(
+        &pool,
+        staff.sub,
+        "judge",
+        Some(&judge_id),
+        "lift_blackout",
+        serde_json::json!({ "candidate_id": candidate_id }),
+    )
+    .await?;
+
+    aggregator.request(judge.event_id);
+
+    Ok(http::StatusCode::NO_CONTENT)
+}
+
+pub async fn get_blackouts(
+    State(pool): State<PgPool>,
+    Path(judge_id): Path<uuid::Uuid>,
+) -> Result<axum::Json<Vec<JudgeBlackout>>, AppError> {
+    let blackouts = sqlx::query_as::<_, JudgeBlackout>(
+        "SELECT * FROM judge_blackouts WHERE judge_id = ($1) ORDER BY created_at",
+    )
+    .bind(&judge_id)
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(axum::Json(blackouts))
+}
+
+// Checked on every score write, the scoring screen hides these candidates but clients can be stale
+pub async fn ensure_not_blacked_out(
+    pool: &PgPool,
+    judge_id: &uuid::Uuid,
+    candidate_ids: &[uuid::Uuid],
+) -> Result<(), AppError> {
+    let blacked_out: bool = sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM judge_blackouts WHERE judge_id = ($1) AND candidate_id = ANY($2))",
+    )
+    .bind(judge_id)
+    .bind(candidate_ids)
+    .fetch_one(pool)
+    .await?;
+
+    if blacked_out {
+        return Err(AppError::new(
+            http::StatusCode::FORBIDDEN,
+            "Judge is blacked out from scoring this candidate",
+        ));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,508 @@
+use std::collections::HashMap;
+
+use axum::extract::{Path, State};
+use axum::http;
+use axum::response::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgExecutor, PgPool, Postgres, Transaction};
+
+use crate::error::AppError;
+
+use super::auth::AuthenticatedJudge;
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct Bracket {
+    id: uuid::Uuid,
+    name: String,
+    format: String,
+    current_round: i32,
+    // Relationships
+    category_id: uuid::Uuid,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct BracketMatch {
+    id: uuid::Uuid,
+    round_number: i32,
+    match_number: i32,
+    // Relationships
+    bracket_id: uuid::Uuid,
+    candidate_a_id: uuid::Uuid,
+    candidate_b_id: Option<uuid::Uuid>,
+    winner_id: Option<uuid::Uuid>,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct MatchBallot {
+    id: uuid::Uuid,
+    time_of_scoring: chrono::DateTime<chrono::Utc>,
+    // Relationships
+    match_id: uuid::Uuid,
+    judge_id: uuid::Uuid,
+    winner_id: uuid::Uuid,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BracketDetails {
+    bracket: Bracket,
+    matches: Vec<BracketMatch>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BracketStanding {
+    candidate_id: uuid::Uuid,
+    wins: i32,
+    losses: i32,
+}
+
+// Every candidate meets every other candidate once
+pub fn round_robin_pairings(candidate_ids: &[uuid::Uuid]) -> Vec<(uuid::Uuid, Option<uuid::Uuid>)> {
+    let mut pairings = Vec::new();
+
+    for (i, a) in candidate_ids.iter().enumerate() {
+        for b in candidate_ids.iter().skip(i + 1) {
+            pairings.push((*a, Some(*b)));
+        }
+    }
+
+    pairings
+}
+
+// Candidates are paired in seed order, the last one gets a bye when the count is odd
+pub fn elimination_pairings(candidate_ids: &[uuid::Uuid]) -> Vec<(uuid::Uuid, Option<uuid::Uuid>)> {
+    candidate_ids
+        .chunks(2)
+        .map(|pair| (pair[0], pair.get(1).copied()))
+        .collect()
+}
+
+// The candidate picked by most judges wins, a split panel has no winner yet
+pub fn majority_winner(ballot_winners: &[uuid::Uuid]) -> Option<uuid::Uuid> {
+    let mut votes: HashMap<uuid::Uuid, usize> = HashMap::new();
+
+    for winner in ballot_winners {
+        *votes.entry(*winner).or_insert(0) += 1;
+    }
+
+    let mut counts: Vec<(uuid::Uuid, usize)> = votes.into_iter().collect();
+    counts.sort_by(|(_, a), (_, b)| b.cmp(a));
+
+    match counts.as_slice() {
+        [] => None,
+        [(winner, _)] => Some(*winner),
+        [(winner, first), (_, second), ..] if first > second => Some(*winner),
+        _ => None,
+    }
+}
+
+async fn insert_matches(
+    txn: &mut Transaction<'_, Postgres>,
+    bracket_id: &uuid::Uuid,
+    round_number: i32,
+    pairings: &[(uuid::Uuid, Option<uuid::Uuid>)],
+) -> Result<Vec<BracketMatch>, AppError> {
+    let mut matches = Vec::new();
+
+    for (match_idx, (candidate_a_id, candidate_b_id)) in pairings.iter().enumerate() {
+        // A bye advances on its own
+        let winner_id = match candidate_b_id {
+            Some(_) => None,
+            None => Some(*candidate_a_id),
+        };
+
+        let bracket_match = sqlx::query_as::<_, BracketMatch>(
+            r#"
+            INSERT INTO bracket_matches (round_number, match_number, bracket_id, candidate_a_id, candidate_b_id, winner_id)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING *
+            "#,
+        )
+        .bind(round_number)
+        .bind(match_idx as i32 + 1)
+        .bind(bracket_id)
+        .bind(candidate_a_id)
+        .bind(candidate_b_id)
+        .bind(winner_id)
+        .fetch_one(&mut **txn)
+        .await?;
+
+        matches.push(bracket_match);
+    }
+
+    Ok(matches)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateBracket {
+    name: String,
+    format: String,
+    category_id: uuid::Uuid,
+    // In seed order
+    candidate_ids: Vec<uuid::Uuid>,
+}
+
+pub async fn create_bracket(
+    State(pool): State<PgPool>,
+    axum::Json(payload): axum::Json<CreateBracket>,
+) -> Result<(http::StatusCode, axum::Json<BracketDetails>), AppError> {
+    let pairings = match payload.format.as_str() {
+        "round_robin" => round_robin_pairings(&payload.candidate_ids),
+        "elimination" => elimination_pairings(&payload.candidate_ids),
+        _ => {
+            return Err(AppError::new(
+                http::StatusCode::UNPROCESSABLE_ENTITY,
+                "Bracket format must be either 'round_robin' or 'elimination'",
+            ))
+        }
+    };
+
+    if payload.candidate_ids.len() < 2 {
+        return Err(AppError::new(
+            http::StatusCode::UNPROCESSABLE_ENTITY,
+            "A bracket needs at least 2 candidates",
+        ));
+    }
+
+    let mut seeded = payload.candidate_ids.clone();
+    seeded.sort();
+    seeded.dedup();
+
+    if seeded.len() != payload.candidate_ids.len() {
+        return Err(AppError::new(
+            http::StatusCode::UNPROCESSABLE_ENTITY,
+            "A candidate can only be seeded once",
+        ));
+    }
+
+    let event_id: uuid::Uuid =
+        sqlx::query_scalar("SELECT event_id FROM categories WHERE id = ($1)")
+            .bind(&payload.category_id)
+            .fetch_optional(&pool)
+            .await?
+            .ok_or_else(|| AppError::new(http::StatusCode::NOT_FOUND, "Category not found"))?;
+
+    let event_candidates: i64 = sqlx::query_scalar(
+        r#"
+        SELECT COUNT(*) FROM candidates c
+        JOIN categories home ON home.id = c.category_id
+        WHERE c.id = ANY($1) AND home.event_id = ($2) AND c.deleted_at IS NULL
+        "#,
+    )
+    .bind(&payload.candidate_ids)
+    .bind(&event_id)
+    .fetch_one(&pool)
+    .await?;
+
+    if event_candidates != payload.candidate_ids.len() as i64 {
+        return Err(AppError::new(
+            http::StatusCode::UNPROCESSABLE_ENTITY,
+            "The bracket can only have the event's candidates",
+        ));
+    }
+
+    let mut txn = pool.begin().await?;
+
+    let bracket = sqlx::query_as::<_, Bracket>(
+        "INSERT INTO brackets (name, format, category_id) VALUES ($1, $2, $3) RETURNING *",
+    )
+    .bind(&payload.name)
+    .bind(&payload.format)
+    .bind(&payload.category_id)
+    .fetch_one(&mut *txn)
+    .await?;
+
+    let matches = insert_matches(&mut txn, &bracket.id, 1, &pairings).await?;
+
+    txn.commit().await?;
+
+    Ok((
+        http::StatusCode::CREATED,
+        axum::Json(BracketDetails { bracket, matches }),
+    ))
+}
+
+async fn fetch_bracket(pool: &PgPool, bracket_id: &uuid::Uuid) -> Result<Bracket, AppError> {
+    sqlx::query_as::<_, Bracket>("SELECT * FROM brackets WHERE id = ($1)")
+        .bind(bracket_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::new(http::StatusCode::NOT_FOUND, "Bracket not found"))
+}
+
+pub async fn get_bracket(
+    State(pool): State<PgPool>,
+    Path(bracket_id): Path<uuid::Uuid>,
+) -> Result<axum::Json<BracketDetails>, AppError> {
+    let bracket = fetch_bracket(&pool, &bracket_id).await?;
+
+    let matches = sqlx::query_as::<_, BracketMatch>(
+        "SELECT * FROM bracket_matches WHERE bracket_id = ($1) ORDER BY round_number, match_number",
+    )
+    .bind(&bracket_id)
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(axum::Json(BracketDetails { bracket, matches }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SubmitBallot {
+    judge_id: uuid::Uuid,
+    winner_id: uuid::Uuid,
+}
+
+pub async fn submit_ballot(
+    State(pool): State<PgPool>,
+    Path((bracket_id, match_id)): Path<(uuid::Uuid, uuid::Uuid)>,
+    judge: AuthenticatedJudge,
+    axum::Json(payload): axum::Json<SubmitBallot>,
+) -> Result<(http::StatusCode, axum::Json<MatchBallot>), AppError> {
+    judge.ensure_judge(&payload.judge_id)?;
+
+    let bracket_match = sqlx::query_as::<_, BracketMatch>(
+        "SELECT * FROM bracket_matches WHERE id = ($1) AND bracket_id = ($2)",
+    )
+    .bind(&match_id)
+    .bind(&bracket_id)
+    .fetch_optional(&pool)
+    .await?
+    .ok_or_else(|| AppError::new(http::StatusCode::NOT_FOUND, "Match not found"))?;
+
+    if bracket_match.winner_id.is_some() {
+        return Err(AppError::new(
+            http::StatusCode::CONFLICT,
+            "Match has already been decided",
+        ));
+    }
+
+    if payload.winner_id != bracket_match.candidate_a_id
+        && Some(payload.winner_id) != bracket_match.candidate_b_id
+    {
+        return Err(AppError::new(
+            http::StatusCode::UNPROCESSABLE_ENTITY,
+            "Winner must be one of the candidates in the match",
+        ));
+    }
+
+    // Same rules as scoring, the judge must be on the panel of the bracket's event
+    let is_panel_judge: bool = sqlx::query_scalar(
+        r#"
+        SELECT EXISTS (
+            SELECT 1 FROM judges j
+            JOIN categories cat ON cat.event_id = j.event_id
+            JOIN brackets b ON b.category_id = cat.id
+            WHERE j.id = ($1) AND b.id = ($2) AND j.score_exclusion = FALSE
+        )
+        "#,
+    )
+    .bind(&payload.judge_id)
+    .bind(&bracket_id)
+    .fetch_one(&pool)
+    .await?;
+
+    if !is_panel_judge {
+        return Err(AppError::new(
+            http::StatusCode::FORBIDDEN,
+            "Judge is not on the panel for this bracket",
+        ));
+    }
+
+    let ballot = sqlx::query_as::<_, MatchBallot>(
+        r#"
+        INSERT INTO match_ballots (match_id, judge_id, winner_id)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (match_id, judge_id)
+        DO UPDATE SET winner_id = EXCLUDED.winner_id, time_of_scoring = NOW()
+        RETURNING *
+        "#,
+    )
+    .bind(&match_id)
+    .bind(&payload.judge_id)
+    .bind(&payload.winner_id)
+    .fetch_one(&pool)
+    .await?;
+
+    Ok((http::StatusCode::CREATED, axum::Json(ballot)))
+}
+
+async fn ballot_winners<'e, E: PgExecutor<'e>>(
+    executor: E,
+    bracket_id: &uuid::Uuid,
+) -> Result<HashMap<uuid::Uuid, Vec<uuid::Uuid>>, AppError> {
+    let ballots = sqlx::query_as::<_, (uuid::Uuid, uuid::Uuid)>(
+        r#"
+        SELECT mb.match_id, mb.winner_id
+        FROM match_ballots mb
+        JOIN bracket_matches m ON m.id = mb.match_id
+        WHERE m.bracket_id = ($1)
+        "#,
+    )
+    .bind(bracket_id)
+    .fetch_all(executor)
+    .await?;
+
+    let mut winners: HashMap<uuid::Uuid, Vec<uuid::Uuid>> = HashMap::new();
+
+    for (match_id, winner_id) in ballots {
+        winners.entry(match_id).or_default().push(winner_id);
+    }
+
+    Ok(winners)
+}
+
+// Wins and losses per candidate, counting decided matches and the current ballot majority
+pub async fn get_bracket_standings(
+    State(pool): State<PgPool>,
+    Path(bracket_id): Path<uuid::Uuid>,
+) -> Result<axum::Json<Vec<BracketStanding>>, AppError> {
+    let matches =
+        sqlx::query_as::<_, BracketMatch>("SELECT * FROM bracket_matches WHERE bracket_id = ($1)")
+            .bind(&bracket_id)
+            .fetch_all(&pool)
+            .await?;
+
+    let winners = ballot_winners(&pool, &bracket_id).await?;
+    let mut standings: HashMap<uuid::Uuid, (i32, i32)> = HashMap::new();
+
+    for bracket_match in matches.iter() {
+        let Some(candidate_b_id) = bracket_match.candidate_b_id else {
+            continue;
+        };
+
+        let winner_id = bracket_match.winner_id.or_else(|| {
+            winners
+                .get(&bracket_match.id)
+                .and_then(|ballots| majority_winner(ballots))
+        });
+
+        let Some(winner_id) = winner_id else {
+            continue;
+        };
+
+        let loser_id = if winner_id == candidate_b_id {
+            bracket_match.candidate_a_id
+        } else {
+            candidate_b_id
+        };
+
+        standings.entry(winner_id).or_insert((0, 0)).0 += 1;
+        standings.entry(loser_id).or_insert((0, 0)).1 += 1;
+    }
+
+    let mut standings: Vec<BracketStanding> = standings
+        .into_iter()
+        .map(|(candidate_id, (wins, losses))| BracketStanding {
+            candidate_id,
+            wins,
+            losses,
+        })
+        .collect();
+
+    standings.sort_by(|a, b| b.wins.cmp(&a.wins).then(a.losses.cmp(&b.losses)));
+
+    Ok(axum::Json(standings))
+}
+
+// Decides the current round from the ballots and pairs the winners for the next one
+// The bracket stays locked until then, so two advances can't pair the same round twice
+pub async fn advance_bracket(
+    State(pool): State<PgPool>,
+    Path(bracket_id): Path<uuid::Uuid>,
+) -> Result<axum::Json<BracketDetails>, AppError> {
+    let mut txn = pool.begin().await?;
+
+    let bracket = sqlx::query_as::<_, Bracket>("SELECT * FROM brackets WHERE id = ($1) FOR UPDATE")
+        .bind(&bracket_id)
+        .fetch_optional(&mut *txn)
+        .await?
+        .ok_or_else(|| AppError::new(http::StatusCode::NOT_FOUND, "Bracket not found"))?;
+
+    if bracket.format != "elimination" {
+        return Err(AppError::new(
+            http::StatusCode::UNPROCESSABLE_ENTITY,
+            "Only elimination brackets can be advanced",
+        ));
+    }
+
+    let matches = sqlx::query_as::<_, BracketMatch>(
+        r#"
+        SELECT * FROM bracket_matches
+        WHERE bracket_id = ($1) AND round_number = ($2)
+        ORDER BY match_number
+        FOR UPDATE
+        "#,
+    )
+    .bind(&bracket_id)
+    .bind(&bracket.current_round)
+    .fetch_all(&mut *txn)
+    .await?;
+
+    if matches.len() <= 1 && matches.iter().all(|m| m.winner_id.is_some()) {
+        return Err(AppError::new(
+            http::StatusCode::CONFLICT,
+            "Bracket is already finished",
+        ));
+    }
+
+    let winners = ballot_winners(&mut *txn, &bracket_id).await?;
+    let mut advancing: Vec<uuid::Uuid> = Vec::new();
+
+    for bracket_match in matches.iter() {
+        let winner_id = match bracket_match.winner_id {
+            Some(winner_id) => winner_id,
+            None => {
+                let winner_id = winners
+                    .get(&bracket_match.id)
+                    .and_then(|ballots| majority_winner(ballots))
+                    .ok_or_else(|| {
+                        AppError::new(
+                            http::StatusCode::CONFLICT,
+                            format!("Match {} has no majority winner yet", bracket_match.id),
+                        )
+                    })?;
+
+                sqlx::query("UPDATE bracket_matches SET winner_id = ($1) WHERE id = ($2)")
+                    .bind(&winner_id)
+                    .bind(&bracket_match.id)
+                    .execute(&mut *txn)
+                    .await?;
+
+                winner_id
+            }
+        };
+
+        advancing.push(winner_id);
+    }
+
+    let next_round = bracket.current_round + 1;
+    let mut next_matches = Vec::new();
+
+    if advancing.len() > 1 {
+        next_matches = insert_matches(
+            &mut txn,
+            &bracket_id,
+            next_round,
+            &elimination_pairings(&advancing),
+        )
+        .await?;
+    }
+
+    let bracket = sqlx::query_as::<_, Bracket>(
+        "UPDATE brackets SET current_round = ($1) WHERE id = ($2) RETURNING *",
+    )
+    .bind(if advancing.len() > 1 {
+        next_round
+    } else {
+        bracket.current_round
+    })
+    .bind(&bracket_id)
+    .fetch_one(&mut *txn)
+    .await?;
+
+    txn.commit().await?;
+
+    Ok(axum::Json(BracketDetails {
+        bracket,
+        matches: next_matches,
+    }))
+}
@@ -1,11 +1,23 @@
+use std::collections::{HashMap, HashSet};
+
+use axum::extract::Extension;
 use axum::extract::Path;
+use axum::extract::Query;
 use axum::extract::State;
 use axum::http;
-use axum::response::Result;
+use axum::response::{IntoResponse, Result};
+use qrcode::{Color, QrCode};
 use serde::{Deserialize, Serialize};
 use sqlx::{FromRow, PgPool};
 
+use crate::aggregation::Aggregator;
 use crate::error::AppError;
+use crate::pdf::{self, Document, Font};
+
+use super::admin::StaffClaims;
+use super::audit;
+use super::score;
+use super::settings::{self, CandidateNumbering};
 
 #[derive(Debug, Serialize, Deserialize, FromRow)]
 pub struct Candidate {
@@ -32,13 +44,179 @@ pub struct CreateCandidate {
     category_id: uuid::Uuid,
 }
 
+// Raised by the `candidates_number_unique` trigger, see the `candidate_numbering` setting
+const NUMBER_CONSTRAINT: &str = "candidates_number_unique";
+
+fn is_number_violation(err: &sqlx::Error) -> bool {
+    err.as_database_error()
+        .and_then(|err| err.constraint())
+        .is_some_and(|constraint| constraint == NUMBER_CONSTRAINT)
+}
+
+fn number_taken(candidate_number: i32) -> AppError {
+    AppError::new(
+        http::StatusCode::CONFLICT,
+        format!(
+            "Candidate number {} is already taken, check the event's candidate numbering",
+            candidate_number
+        ),
+    )
+}
+
+// Numbers used more than once under `numbering`, given each candidate's (gender, number)
+pub fn number_collisions(candidates: &[(i32, i32)], numbering: CandidateNumbering) -> Vec<i32> {
+    let mut seen = HashSet::new();
+    let mut collisions: Vec<i32> = candidates
+        .iter()
+        .filter(|(gender, number)| {
+            let division = match numbering {
+                CandidateNumbering::PerDivision => *gender,
+                CandidateNumbering::PerEvent => 0,
+            };
+
+            !seen.insert((division, *number))
+        })
+        .map(|(_, number)| *number)
+        .collect();
+
+    collisions.sort();
+    collisions.dedup();
+    collisions
+}
+
+// Candidates belong to an event through their category, male (gender = 1) first
+pub async fn fetch_numbers(
+    pool: &PgPool,
+    event_id: &uuid::Uuid,
+) -> Result<Vec<(uuid::Uuid, i32, i32)>, AppError> {
+    let numbers = sqlx::query_as::<_, (uuid::Uuid, i32, i32)>(
+        r#"
+        SELECT c.id, c.gender, c.candidate_number FROM candidates c
+        JOIN categories cat ON cat.id = c.category_id
+        WHERE cat.event_id = ($1) AND c.deleted_at IS NULL
+        ORDER BY c.gender DESC, c.candidate_number, c.last_name, c.first_name
+        "#,
+    )
+    .bind(event_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(numbers)
+}
+
+pub async fn find_number_collisions(
+    pool: &PgPool,
+    event_id: &uuid::Uuid,
+    numbering: CandidateNumbering,
+) -> Result<Vec<i32>, AppError> {
+    let numbers: Vec<(i32, i32)> = fetch_numbers(pool, event_id)
+        .await?
+        .into_iter()
+        .map(|(_, gender, number)| (gender, number))
+        .collect();
+
+    Ok(number_collisions(&numbers, numbering))
+}
+
+// Numbers from 1 in the current order, per division or across the event, for candidates grouped by gender
+pub fn assign_numbers(genders: &[i32], numbering: CandidateNumbering) -> Vec<i32> {
+    let mut next: HashMap<i32, i32> = HashMap::new();
+
+    genders
+        .iter()
+        .map(|gender| {
+            let division = match numbering {
+                CandidateNumbering::PerDivision => *gender,
+                CandidateNumbering::PerEvent => 0,
+            };
+            let number = next.entry(division).or_insert(0);
+
+            *number += 1;
+            *number
+        })
+        .collect()
+}
+
+#[derive(Debug, Serialize)]
+pub struct Renumbered {
+    candidate_id: uuid::Uuid,
+    from: i32,
+    to: i32,
+}
+
+// Closes the gaps and collisions left by late registrations and withdrawals, keeping the current order
+pub async fn renumber_candidates(
+    State(pool): State<PgPool>,
+    State(aggregator): State<Aggregator>,
+    Extension(staff): Extension<StaffClaims>,
+    Path(event_id): Path<uuid::Uuid>,
+) -> Result<axum::Json<Vec<Renumbered>>, AppError> {
+    let numbering = settings::fetch_settings(&pool, &event_id)
+        .await?
+        .candidate_numbering;
+    let numbers = fetch_numbers(&pool, &event_id).await?;
+
+    let genders: Vec<i32> = numbers.iter().map(|(_, gender, _)| *gender).collect();
+    let ids: Vec<uuid::Uuid> = numbers.iter().map(|(id, _, _)| *id).collect();
+    let assigned = assign_numbers(&genders, numbering);
+
+    let mut txn = pool.begin().await?;
+
+    // Negated first, so swapping two numbers never collides halfway through
+    for negated in [true, false] {
+        sqlx::query(
+            r#"
+            UPDATE candidates c SET candidate_number = CASE WHEN ($3) THEN -n.number ELSE n.number END
+            FROM UNNEST($1::UUID[], $2::INTEGER[]) AS n(id, number)
+            WHERE c.id = n.id
+            "#,
+        )
+        .bind(&ids)
+        .bind(&assigned)
+        .bind(negated)
+        .execute(&mut *txn)
+        .await?;
+    }
+
+    let renumbered: Vec<Renumbered> = numbers
+        .iter()
+        .zip(assigned.iter())
+        .filter(|((_, _, from), to)| from != *to)
+        .map(|((candidate_id, _, from), to)| Renumbered {
+            candidate_id: *candidate_id,
+            from: *from,
+            to: *to,
+        })
+        .collect();
+
+    audit::record(
+        &mut *txn,
+        staff.sub,
+        "event",
+        Some(&event_id),
+        "renumber_candidates",
+        serde_json::json!({
+            "numbering": numbering,
+            "renumbered": renumbered,
+        }),
+    )
+    .await?;
+
+    txn.commit().await?;
+
+    // Standings and the display board list candidates by number
+    aggregator.request(event_id);
+
+    Ok(axum::Json(renumbered))
+}
+
 pub async fn create_candidate(
     State(pool): State<PgPool>,
     axum::Json(payload): axum::Json<CreateCandidate>,
 ) -> Result<(http::StatusCode, axum::Json<Candidate>), AppError> {
     let candidate = sqlx::query_as::<_, Candidate>(
         r#"
-        INSERT INTO candidates (first_name, middle_name, last_name, birthdate, gender, college, category_id) 
+        INSERT INTO candidates (first_name, middle_name, last_name, gender, candidate_number, college_id, category_id) 
         VALUES ($1, $2, $3, $4, $5, $6, $7)
         RETURNING *
         "#
@@ -51,17 +229,40 @@ pub async fn create_candidate(
     .bind(&payload.college_id)
     .bind(&payload.category_id)
     .fetch_one(&pool)
-    .await?;
+    .await
+    .map_err(|err| {
+        if is_number_violation(&err) {
+            number_taken(payload.candidate_number)
+        } else {
+            err.into()
+        }
+    })?;
 
     Ok((http::StatusCode::CREATED, axum::Json(candidate)))
 }
 
+#[derive(Debug, Deserialize)]
+pub struct CandidatesParam {
+    // Leaves out the candidates this judge is blacked out from
+    judge_id: Option<uuid::Uuid>,
+}
+
 pub async fn get_candidates(
     State(pool): State<PgPool>,
+    Query(param): Query<CandidatesParam>,
 ) -> Result<axum::Json<Vec<Candidate>>, AppError> {
-    let candidates = sqlx::query_as::<_, Candidate>("SELECT * FROM candidates")
-        .fetch_all(&pool)
-        .await?;
+    let candidates = sqlx::query_as::<_, Candidate>(
+        r#"
+        SELECT * FROM candidates c
+        WHERE c.deleted_at IS NULL
+            AND NOT EXISTS (
+                SELECT 1 FROM judge_blackouts jb WHERE jb.judge_id = ($1) AND jb.candidate_id = c.id
+            )
+        "#,
+    )
+    .bind(&param.judge_id)
+    .fetch_all(&pool)
+    .await?;
 
     Ok(axum::Json(candidates))
 }
@@ -77,3 +278,658 @@ pub async fn get_candidate(
 
     Ok(axum::Json(candidate))
 }
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateCandidate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    first_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    middle_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    candidate_number: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    gender: Option<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeleteCandidateParam {
+    // Voids the candidate's scores instead of refusing
+    #[serde(default)]
+    cascade: bool,
+}
+
+async fn fetch_live_candidate<'e, E: sqlx::PgExecutor<'e>>(
+    executor: E,
+    candidate_id: &uuid::Uuid,
+) -> Result<Candidate, AppError> {
+    sqlx::query_as::<_, Candidate>(
+        "SELECT * FROM candidates WHERE id = ($1) AND deleted_at IS NULL FOR UPDATE",
+    )
+    .bind(candidate_id)
+    .fetch_optional(executor)
+    .await?
+    .ok_or_else(|| AppError::new(http::StatusCode::NOT_FOUND, "Candidate not found"))
+}
+
+async fn fetch_candidate_event_id<'e, E: sqlx::PgExecutor<'e>>(
+    executor: E,
+    candidate: &Candidate,
+) -> Result<Option<uuid::Uuid>, AppError> {
+    let event_id = sqlx::query_scalar("SELECT event_id FROM categories WHERE id = ($1)")
+        .bind(&candidate.category_id)
+        .fetch_optional(executor)
+        .await?;
+
+    Ok(event_id)
+}
+
+// For typos in the registration, scores stay with the candidate
+pub async fn update_candidate(
+    State(pool): State<PgPool>,
+    State(aggregator): State<Aggregator>,
+    Extension(staff): Extension<StaffClaims>,
+    Path(candidate_id): Path<uuid::Uuid>,
+    axum::Json(payload): axum::Json<UpdateCandidate>,
+) -> Result<axum::Json<Candidate>, AppError> {
+    let blank_name = [&payload.first_name, &payload.last_name]
+        .into_iter()
+        .flatten()
+        .any(|name| name.trim().is_empty());
+
+    if blank_name {
+        return Err(AppError::new(
+            http::StatusCode::UNPROCESSABLE_ENTITY,
+            "First and last names can't be blank",
+        ));
+    }
+
+    if payload.gender.is_some_and(|gender| gender != 1 && gender != 0) {
+        return Err(AppError::new(
+            http::StatusCode::UNPROCESSABLE_ENTITY,
+            "Gender must be 1 (male) or 0 (female)",
+        ));
+    }
+
+    let mut txn = pool.begin().await?;
+
+    let previous = fetch_live_candidate(&mut *txn, &candidate_id).await?;
+
+    let candidate = sqlx::query_as::<_, Candidate>(
+        r#"
+        UPDATE candidates
+        SET first_name = COALESCE(($1), first_name),
+            middle_name = COALESCE(($2), middle_name),
+            last_name = COALESCE(($3), last_name),
+            candidate_number = COALESCE(($4), candidate_number),
+            gender = COALESCE(($5), gender)
+        WHERE id = ($6)
+        RETURNING *
+        "#,
+    )
+    .bind(payload.first_name.as_deref().map(str::trim))
+    .bind(payload.middle_name.as_deref().map(str::trim))
+    .bind(payload.last_name.as_deref().map(str::trim))
+    .bind(&payload.candidate_number)
+    .bind(&payload.gender)
+    .bind(&candidate_id)
+    .fetch_one(&mut *txn)
+    .await
+    .map_err(|err| {
+        if is_number_violation(&err) {
+            number_taken(payload.candidate_number.unwrap_or(previous.candidate_number))
+        } else {
+            err.into()
+        }
+    })?;
+
+    audit::record(
+        &mut *txn,
+        staff.sub,
+        "candidate",
+        Some(&candidate_id),
+        "update",
+        serde_json::json!({
+            "changes": payload,
+            "previous": {
+                "first_name": previous.first_name,
+                "middle_name": previous.middle_name,
+                "last_name": previous.last_name,
+                "candidate_number": previous.candidate_number,
+                "gender": previous.gender,
+            },
+        }),
+    )
+    .await?;
+
+    let event_id = fetch_candidate_event_id(&mut *txn, &candidate).await?;
+
+    txn.commit().await?;
+
+    // Names, numbers and divisions all show on the display board
+    if let Some(event_id) = event_id {
+        aggregator.request(event_id);
+    }
+
+    Ok(axum::Json(candidate))
+}
+
+// Candidates are only soft-deleted, like merged ones, so the audit trail still resolves
+// A candidate who was already scored is refused unless `cascade` voids their scores too
+pub async fn delete_candidate(
+    State(pool): State<PgPool>,
+    State(aggregator): State<Aggregator>,
+    Extension(staff): Extension<StaffClaims>,
+    Path(candidate_id): Path<uuid::Uuid>,
+    Query(param): Query<DeleteCandidateParam>,
+) -> Result<http::StatusCode, AppError> {
+    let mut txn = pool.begin().await?;
+
+    let candidate = fetch_live_candidate(&mut *txn, &candidate_id).await?;
+
+    let scores: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM scores WHERE candidate_id = ($1)")
+        .bind(&candidate_id)
+        .fetch_one(&mut *txn)
+        .await?;
+
+    if scores > 0 && !param.cascade {
+        return Err(AppError::new(
+            http::StatusCode::CONFLICT,
+            format!(
+                "Candidate #{} already has {} scores, delete with ?cascade=true to void them too",
+                candidate.candidate_number, scores
+            ),
+        ));
+    }
+
+    // The candidate's own event drops them from its standings even without scores
+    let event_ids = sqlx::query_scalar::<_, uuid::Uuid>(
+        r#"
+        SELECT cat.event_id FROM categories cat WHERE cat.id = ($2)
+        UNION
+        SELECT cat.event_id FROM scores s
+        JOIN categories cat ON cat.id = s.category_id
+        WHERE s.candidate_id = ($1)
+        "#,
+    )
+    .bind(&candidate_id)
+    .bind(&candidate.category_id)
+    .fetch_all(&mut *txn)
+    .await?;
+
+    score::void_scores(
+        &mut *txn,
+        score::VoidedScores::Candidate(candidate_id),
+        "Candidate deleted",
+    )
+    .await?;
+
+    sqlx::query("DELETE FROM lineups WHERE candidate_id = ($1)")
+        .bind(&candidate_id)
+        .execute(&mut *txn)
+        .await?;
+
+    sqlx::query("DELETE FROM performance_group_members WHERE candidate_id = ($1)")
+        .bind(&candidate_id)
+        .execute(&mut *txn)
+        .await?;
+
+    sqlx::query("UPDATE candidates SET deleted_at = NOW() WHERE id = ($1)")
+        .bind(&candidate_id)
+        .execute(&mut *txn)
+        .await?;
+
+    audit::record(
+        &mut *txn,
+        staff.sub,
+        "candidate",
+        Some(&candidate_id),
+        "delete",
+        serde_json::json!({
+            "candidate_number": candidate.candidate_number,
+            "voided_scores": scores,
+        }),
+    )
+    .await?;
+
+    txn.commit().await?;
+
+    for event_id in event_ids {
+        aggregator.request(event_id);
+    }
+
+    Ok(http::StatusCode::NO_CONTENT)
+}
+
+// Formats the spreadsheet export can embed
+const PHOTO_CONTENT_TYPES: [&str; 2] = ["image/png", "image/jpeg"];
+
+// Photos are only shown as thumbnails, anything bigger is a mistake
+const MAX_PHOTO_SIZE: usize = 2 * 1024 * 1024;
+
+pub async fn upload_candidate_photo(
+    State(pool): State<PgPool>,
+    Extension(staff): Extension<StaffClaims>,
+    Path(candidate_id): Path<uuid::Uuid>,
+    headers: http::HeaderMap,
+    body: axum::body::Bytes,
+) -> Result<http::StatusCode, AppError> {
+    let content_type = headers
+        .get(http::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| PHOTO_CONTENT_TYPES.contains(value))
+        .ok_or_else(|| {
+            AppError::new(
+                http::StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                "Photos must be PNG or JPEG",
+            )
+        })?;
+
+    if body.is_empty() || body.len() > MAX_PHOTO_SIZE {
+        return Err(AppError::new(
+            http::StatusCode::PAYLOAD_TOO_LARGE,
+            "Photos must be at most 2 MB",
+        ));
+    }
+
+    let res = sqlx::query(
+        r#"
+        INSERT INTO candidate_photos (content_type, data, candidate_id)
+        SELECT ($1), ($2), id FROM candidates WHERE id = ($3) AND deleted_at IS NULL
+        ON CONFLICT (candidate_id) DO UPDATE
+        SET content_type = EXCLUDED.content_type, data = EXCLUDED.data, updated_at = NOW()
+        "#,
+    )
+    .bind(content_type)
+    .bind(body.as_ref())
+    .bind(&candidate_id)
+    .execute(&pool)
+    .await?;
+
+    if res.rows_affected() == 0 {
+        return Err(AppError::new(
+            http::StatusCode::NOT_FOUND,
+            "Candidate not found",
+        ));
+    }
+
+    audit::record(
+        &pool,
+        staff.sub,
+        "candidate",
+        Some(&candidate_id),
+        "upload_photo",
+        serde_json::json!({ "content_type": content_type, "size": body.len() }),
+    )
+    .await?;
+
+    Ok(http::StatusCode::NO_CONTENT)
+}
+
+pub async fn get_candidate_photo(
+    State(pool): State<PgPool>,
+    Path(candidate_id): Path<uuid::Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    let (content_type, data) = sqlx::query_as::<_, (String, Vec<u8>)>(
+        "SELECT content_type, data FROM candidate_photos WHERE candidate_id = ($1)",
+    )
+    .bind(&candidate_id)
+    .fetch_optional(&pool)
+    .await?
+    .ok_or_else(|| AppError::new(http::StatusCode::NOT_FOUND, "Candidate has no photo"))?;
+
+    Ok(([(http::header::CONTENT_TYPE, content_type)], data))
+}
+
+// Size of each QR module in pixels, and the blank border around the code in modules
+const QR_MODULE_SIZE: usize = 10;
+const QR_QUIET_ZONE: usize = 4;
+
+// Renders the QR code as a grayscale PNG
+pub fn render_qr_png(data: &[u8]) -> Result<Vec<u8>, AppError> {
+    let code = QrCode::new(data).map_err(|err| {
+        AppError::new(
+            http::StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to generate QR code: {}", err),
+        )
+    })?;
+
+    let modules = code.to_colors();
+    let width = code.width();
+    let size = (width + QR_QUIET_ZONE * 2) * QR_MODULE_SIZE;
+    let mut pixels = vec![255u8; size * size];
+
+    for (idx, color) in modules.iter().enumerate() {
+        if *color != Color::Dark {
+            continue;
+        }
+
+        let x = (idx % width + QR_QUIET_ZONE) * QR_MODULE_SIZE;
+        let y = (idx / width + QR_QUIET_ZONE) * QR_MODULE_SIZE;
+
+        for row in y..y + QR_MODULE_SIZE {
+            pixels[row * size + x..row * size + x + QR_MODULE_SIZE].fill(0);
+        }
+    }
+
+    let mut buffer = Vec::new();
+    let mut encoder = png::Encoder::new(&mut buffer, size as u32, size as u32);
+    encoder.set_color(png::ColorType::Grayscale);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(&pixels))
+        .map_err(|err| {
+            AppError::new(
+                http::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to encode QR code: {}", err),
+            )
+        })?;
+
+    Ok(buffer)
+}
+
+// QR code for the candidate's number card, judges scan it to jump to the candidate
+pub async fn get_candidate_qr(
+    State(pool): State<PgPool>,
+    Path(candidate_id): Path<uuid::Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    let candidate = sqlx::query_as::<_, Candidate>("SELECT * FROM candidates WHERE id = ($1)")
+        .bind(&candidate_id)
+        .fetch_optional(&pool)
+        .await?
+        .ok_or_else(|| AppError::new(http::StatusCode::NOT_FOUND, "Candidate not found"))?;
+
+    let payload = serde_json::json!({
+        "candidate_id": candidate.id,
+        "candidate_number": candidate.candidate_number,
+    });
+
+    let png = render_qr_png(payload.to_string().as_bytes())?;
+
+    Ok(([(http::header::CONTENT_TYPE, "image/png")], png))
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CardSize {
+    A5,
+    #[default]
+    A6,
+}
+
+impl CardSize {
+    // Landscape (width, height) in points
+    fn dimensions(self) -> (f32, f32) {
+        match self {
+            CardSize::A5 => (595.28, 419.53),
+            CardSize::A6 => (419.53, 297.64),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NumberCardParam {
+    #[serde(default)]
+    size: CardSize,
+}
+
+// One card per page, read from the same records the tabulation uses so the numbers always match
+pub async fn get_number_cards(
+    State(pool): State<PgPool>,
+    Path(event_id): Path<uuid::Uuid>,
+    Query(param): Query<NumberCardParam>,
+) -> Result<impl IntoResponse, AppError> {
+    let event_name: String = sqlx::query_scalar("SELECT name FROM events WHERE id = ($1)")
+        .bind(&event_id)
+        .fetch_optional(&pool)
+        .await?
+        .ok_or_else(|| AppError::new(http::StatusCode::NOT_FOUND, "Event not found"))?;
+
+    let candidates = sqlx::query_as::<_, Candidate>(
+        r#"
+        SELECT c.* FROM candidates c
+        JOIN categories cat ON cat.id = c.category_id
+        WHERE cat.event_id = ($1) AND c.deleted_at IS NULL
+        ORDER BY 
+            CASE
+                WHEN c.gender = 1 THEN 1
+                ELSE 2
+            END,
+            c.candidate_number
+        "#,
+    )
+    .bind(&event_id)
+    .fetch_all(&pool)
+    .await?;
+
+    let (width, height) = param.size.dimensions();
+    let margin = width * 0.06;
+    let mut document = Document::new(width, height);
+
+    for candidate in candidates.iter() {
+        let page = document.add_page();
+
+        page.centered_text(
+            width / 2.0,
+            height - margin - height * 0.05,
+            height * 0.05,
+            Font::Helvetica,
+            &event_name,
+        );
+
+        let number = candidate.candidate_number.to_string();
+        let number_size =
+            (height * 0.5).min((width - margin * 2.0) / pdf::text_width(&number, 1.0));
+
+        page.centered_text(
+            width / 2.0,
+            height * 0.3,
+            number_size,
+            Font::HelveticaBold,
+            &number,
+        );
+
+        let name = format!(
+            "{} {}",
+            candidate.first_name.trim(),
+            candidate.last_name.trim()
+        )
+        .to_uppercase();
+        let name_size = (height * 0.08).min((width - margin * 2.0) / pdf::text_width(&name, 1.0));
+
+        page.centered_text(width / 2.0, margin, name_size, Font::HelveticaBold, &name);
+    }
+
+    Ok((
+        [(http::header::CONTENT_TYPE, "application/pdf")],
+        document.to_bytes(),
+    ))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MergeCandidates {
+    source_id: uuid::Uuid,
+    target_id: uuid::Uuid,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MergeResult {
+    target: Candidate,
+    moved_scores: u64,
+    moved_deductions: u64,
+    moved_notes: u64,
+}
+
+// Fixes duplicates from rushed registration, everything recorded for the source moves to the target
+// The source is only soft-deleted so the audit trail still resolves
+pub async fn merge_candidates(
+    State(pool): State<PgPool>,
+    State(aggregator): State<Aggregator>,
+    Extension(staff): Extension<StaffClaims>,
+    axum::Json(payload): axum::Json<MergeCandidates>,
+) -> Result<axum::Json<MergeResult>, AppError> {
+    if payload.source_id == payload.target_id {
+        return Err(AppError::new(
+            http::StatusCode::UNPROCESSABLE_ENTITY,
+            "Cannot merge a candidate into itself",
+        ));
+    }
+
+    let mut txn = pool.begin().await?;
+
+    let candidates = sqlx::query_as::<_, Candidate>(
+        "SELECT * FROM candidates WHERE id IN ($1, $2) AND deleted_at IS NULL FOR UPDATE",
+    )
+    .bind(&payload.source_id)
+    .bind(&payload.target_id)
+    .fetch_all(&mut *txn)
+    .await?;
+
+    if candidates.len() != 2 {
+        return Err(AppError::new(
+            http::StatusCode::NOT_FOUND,
+            "Source or target candidate not found",
+        ));
+    }
+
+    // Both records being scored on the same criteria by the same judge needs a human decision
+    let conflicts: i64 = sqlx::query_scalar(
+        r#"
+        SELECT COUNT(*) FROM scores src
+        JOIN scores tgt ON tgt.judge_id = src.judge_id AND tgt.criteria_id = src.criteria_id
+        WHERE src.candidate_id = ($1) AND tgt.candidate_id = ($2)
+        "#,
+    )
+    .bind(&payload.source_id)
+    .bind(&payload.target_id)
+    .fetch_one(&mut *txn)
+    .await?;
+
+    if conflicts > 0 {
+        return Err(AppError::new(
+            http::StatusCode::CONFLICT,
+            format!(
+                "Both candidates have {} scores for the same judge and criteria, void or correct them first",
+                conflicts
+            ),
+        ));
+    }
+
+    let moved_scores =
+        sqlx::query("UPDATE scores SET candidate_id = ($2) WHERE candidate_id = ($1)")
+            .bind(&payload.source_id)
+            .bind(&payload.target_id)
+            .execute(&mut *txn)
+            .await?
+            .rows_affected();
+
+    let moved_deductions =
+        sqlx::query("UPDATE deductions SET candidate_id = ($2) WHERE candidate_id = ($1)")
+            .bind(&payload.source_id)
+            .bind(&payload.target_id)
+            .execute(&mut *txn)
+            .await?
+            .rows_affected();
+
+    let moved_notes = sqlx::query("UPDATE notes SET candidate_id = ($2) WHERE candidate_id = ($1)")
+        .bind(&payload.source_id)
+        .bind(&payload.target_id)
+        .execute(&mut *txn)
+        .await?
+        .rows_affected();
+
+    // The target takes over the source's group memberships, running order stays the target's own
+    sqlx::query(
+        r#"
+        INSERT INTO performance_group_members (group_id, candidate_id)
+        SELECT group_id, ($2) FROM performance_group_members WHERE candidate_id = ($1)
+        ON CONFLICT DO NOTHING
+        "#,
+    )
+    .bind(&payload.source_id)
+    .bind(&payload.target_id)
+    .execute(&mut *txn)
+    .await?;
+
+    sqlx::query("DELETE FROM performance_group_members WHERE candidate_id = ($1)")
+        .bind(&payload.source_id)
+        .execute(&mut *txn)
+        .await?;
+
+    sqlx::query("DELETE FROM lineups WHERE candidate_id = ($1)")
+        .bind(&payload.source_id)
+        .execute(&mut *txn)
+        .await?;
+
+    // Both were the same person, so the target was in every round the source was in
+    sqlx::query(
+        r#"
+        INSERT INTO round_eligibility (category_id, candidate_id, snapshot_at)
+        SELECT category_id, ($2), snapshot_at FROM round_eligibility WHERE candidate_id = ($1)
+        ON CONFLICT DO NOTHING
+        "#,
+    )
+    .bind(&payload.source_id)
+    .bind(&payload.target_id)
+    .execute(&mut *txn)
+    .await?;
+
+    sqlx::query("DELETE FROM round_eligibility WHERE candidate_id = ($1)")
+        .bind(&payload.source_id)
+        .execute(&mut *txn)
+        .await?;
+
+    sqlx::query("UPDATE candidates SET deleted_at = NOW(), merged_into = ($2) WHERE id = ($1)")
+        .bind(&payload.source_id)
+        .bind(&payload.target_id)
+        .execute(&mut *txn)
+        .await?;
+
+    audit::record(
+        &mut *txn,
+        staff.sub,
+        "candidate",
+        Some(&payload.source_id),
+        "merge",
+        serde_json::json!({
+            "target_id": payload.target_id,
+            "moved_scores": moved_scores,
+            "moved_deductions": moved_deductions,
+            "moved_notes": moved_notes,
+        }),
+    )
+    .await?;
+
+    let event_ids = sqlx::query_scalar::<_, uuid::Uuid>(
+        r#"
+        SELECT DISTINCT cat.event_id FROM scores s
+        JOIN categories cat ON cat.id = s.category_id
+        WHERE s.candidate_id = ($1)
+        "#,
+    )
+    .bind(&payload.target_id)
+    .fetch_all(&mut *txn)
+    .await?;
+
+    txn.commit().await?;
+
+    for event_id in event_ids {
+        aggregator.request(event_id);
+    }
+
+    let target = candidates
+        .into_iter()
+        .find(|candidate| candidate.id == payload.target_id)
+        .unwrap();
+
+    Ok(axum::Json(MergeResult {
+        target,
+        moved_scores,
+        moved_deductions,
+        moved_notes,
+    }))
+}
@@ -1,8 +1,24 @@
+use std::collections::HashMap;
+
+use axum::response::{IntoResponse, Response};
 use axum::{extract, http, response::Result};
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, PgPool};
+use sqlx::{FromRow, PgExecutor, PgPool};
+use tokio::sync::broadcast;
 
+use crate::aggregation::Aggregator;
 use crate::error::AppError;
+use crate::event_lock;
+
+use super::admin::StaffClaims;
+use super::advancement;
+use super::audit;
+use super::criteria::{self, Criteria};
+use super::judge;
+use super::quorum;
+use super::score::{self, ScoreValue};
+use super::standings::{self, StandingsSnapshot};
+use super::Round;
 
 #[derive(Debug, Serialize, FromRow)]
 pub struct Category {
@@ -11,6 +27,7 @@ pub struct Category {
     pub weight: f32,
     // Relationships
     pub event_id: uuid::Uuid,
+    pub round_id: Option<uuid::Uuid>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -60,8 +77,9 @@ pub struct UpdateCategory {
 
 pub async fn update_category(
     extract::State(pool): extract::State<PgPool>,
-    extract::Path((event_id)): extract::Path<(uuid::Uuid)>,
-    extract::Query((payload)): extract::Query<(UpdateCategory)>,
+    extract::State(tx): extract::State<broadcast::Sender<String>>,
+    extract::Path(event_id): extract::Path<uuid::Uuid>,
+    extract::Query(payload): extract::Query<UpdateCategory>,
 ) -> Result<axum::Json<Category>, AppError> {
     let category = sqlx::query_as::<_, Category>(
         r#"
@@ -69,6 +87,10 @@ pub async fn update_category(
         SET is_active = CASE
             WHEN id = ($1) and event_id = ($2) THEN TRUE
             ELSE FALSE
+        END,
+        opened_at = CASE
+            WHEN id = ($1) and event_id = ($2) THEN NOW()
+            ELSE opened_at
         END
         RETURNING *;
         "#,
@@ -78,6 +100,11 @@ pub async fn update_category(
     .fetch_one(&pool)
     .await?;
 
+    snapshot_eligibility(&pool, &category.id).await?;
+
+    // Judges waiting on the holding screen can score the newly activated category
+    judge::lock_event_judges(&pool, &tx, &event_id, false).await?;
+
     Ok(axum::Json(category))
 }
 
@@ -95,3 +122,786 @@ pub async fn get_category(
 
     Ok(axum::Json(category))
 }
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EditCategory {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    weight: Option<f32>,
+}
+
+pub fn validate_category_edit(name: Option<&str>, weight: Option<f32>) -> Result<(), String> {
+    if name.is_some_and(|name| name.trim().is_empty()) {
+        return Err("The category's name can't be blank".to_string());
+    }
+
+    if weight.is_some_and(|weight| !weight.is_finite() || weight <= 0.0) {
+        return Err("The category's weight must be greater than 0".to_string());
+    }
+
+    Ok(())
+}
+
+// Renames or reweights a category, a new weight changes the standings right away
+pub async fn edit_category(
+    extract::State(pool): extract::State<PgPool>,
+    extract::State(aggregator): extract::State<Aggregator>,
+    extract::Extension(staff): extract::Extension<StaffClaims>,
+    extract::Path((event_id, category_id)): extract::Path<(uuid::Uuid, uuid::Uuid)>,
+    axum::Json(payload): axum::Json<EditCategory>,
+) -> Result<axum::Json<Category>, AppError> {
+    validate_category_edit(payload.name.as_deref(), payload.weight)
+        .map_err(|message| AppError::new(http::StatusCode::UNPROCESSABLE_ENTITY, message))?;
+
+    let mut txn = pool.begin().await?;
+
+    let previous = sqlx::query_as::<_, Category>(
+        "SELECT * FROM categories WHERE id = ($1) AND event_id = ($2)",
+    )
+    .bind(&category_id)
+    .bind(&event_id)
+    .fetch_optional(&mut *txn)
+    .await?
+    .ok_or_else(|| AppError::new(http::StatusCode::NOT_FOUND, "Category not found"))?;
+
+    let category = sqlx::query_as::<_, Category>(
+        r#"
+        UPDATE categories SET name = COALESCE(($1), name), weight = COALESCE(($2), weight)
+        WHERE id = ($3)
+        RETURNING *
+        "#,
+    )
+    .bind(payload.name.as_deref().map(str::trim))
+    .bind(payload.weight)
+    .bind(&category_id)
+    .fetch_one(&mut *txn)
+    .await?;
+
+    audit::record(
+        &mut *txn,
+        staff.sub,
+        "category",
+        Some(&category_id),
+        "update",
+        serde_json::json!({
+            "changes": payload,
+            "previous_name": previous.name,
+            "previous_weight": previous.weight,
+        }),
+    )
+    .await?;
+
+    txn.commit().await?;
+
+    aggregator.request(event_id);
+
+    Ok(axum::Json(category))
+}
+
+// Only categories nobody has scored yet, void their scores first otherwise
+// Candidates of the category move to another category of the event
+pub async fn delete_category(
+    extract::State(pool): extract::State<PgPool>,
+    extract::State(aggregator): extract::State<Aggregator>,
+    extract::Extension(staff): extract::Extension<StaffClaims>,
+    extract::Path((event_id, category_id)): extract::Path<(uuid::Uuid, uuid::Uuid)>,
+) -> Result<http::StatusCode, AppError> {
+    let mut txn = pool.begin().await?;
+
+    let category = sqlx::query_as::<_, Category>(
+        "SELECT * FROM categories WHERE id = ($1) AND event_id = ($2) FOR UPDATE",
+    )
+    .bind(&category_id)
+    .bind(&event_id)
+    .fetch_optional(&mut *txn)
+    .await?
+    .ok_or_else(|| AppError::new(http::StatusCode::NOT_FOUND, "Category not found"))?;
+
+    let scores: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM scores WHERE category_id = ($1)")
+        .bind(&category_id)
+        .fetch_one(&mut *txn)
+        .await?;
+
+    if scores > 0 {
+        return Err(AppError::new(
+            http::StatusCode::CONFLICT,
+            format!(
+                "The category has {} score(s), void them before deleting it",
+                scores
+            ),
+        ));
+    }
+
+    let candidates: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM candidates WHERE category_id = ($1)")
+            .bind(&category_id)
+            .fetch_one(&mut *txn)
+            .await?;
+
+    let other_category_id: Option<uuid::Uuid> = sqlx::query_scalar(
+        "SELECT id FROM categories WHERE event_id = ($1) AND id <> ($2) ORDER BY name LIMIT 1",
+    )
+    .bind(&event_id)
+    .bind(&category_id)
+    .fetch_optional(&mut *txn)
+    .await?;
+
+    // Candidates are only part of the event through their category
+    if candidates > 0 && other_category_id.is_none() {
+        return Err(AppError::new(
+            http::StatusCode::CONFLICT,
+            "The event's only category still has candidates",
+        ));
+    }
+
+    sqlx::query("UPDATE candidates SET category_id = ($1) WHERE category_id = ($2)")
+        .bind(&other_category_id)
+        .bind(&category_id)
+        .execute(&mut *txn)
+        .await?;
+
+    sqlx::query("DELETE FROM categories WHERE id = ($1)")
+        .bind(&category_id)
+        .execute(&mut *txn)
+        .await
+        .map_err(|err| {
+            if err
+                .as_database_error()
+                .is_some_and(|err| err.is_foreign_key_violation())
+            {
+                return AppError::new(
+                    http::StatusCode::CONFLICT,
+                    "The category is still used by a bracket or a standings snapshot",
+                );
+            }
+
+            err.into()
+        })?;
+
+    audit::record(
+        &mut *txn,
+        staff.sub,
+        "category",
+        Some(&category_id),
+        "delete",
+        serde_json::json!({
+            "name": category.name,
+            "weight": category.weight,
+            "moved_candidates": candidates,
+            "moved_to": other_category_id,
+        }),
+    )
+    .await?;
+
+    txn.commit().await?;
+
+    aggregator.request(event_id);
+
+    Ok(http::StatusCode::NO_CONTENT)
+}
+
+// For routes that name both, so a category can't be reached through another event
+pub async fn ensure_event_category(
+    pool: &PgPool,
+    event_id: &uuid::Uuid,
+    category_id: &uuid::Uuid,
+) -> Result<(), AppError> {
+    let exists: bool = sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM categories WHERE id = ($1) AND event_id = ($2))",
+    )
+    .bind(category_id)
+    .bind(event_id)
+    .fetch_one(pool)
+    .await?;
+
+    if !exists {
+        return Err(AppError::new(
+            http::StatusCode::NOT_FOUND,
+            "Category not found",
+        ));
+    }
+
+    Ok(())
+}
+
+// Everything that should be sorted out before a category is closed
+pub async fn find_lock_blockers(
+    pool: &PgPool,
+    event_id: &uuid::Uuid,
+    category_id: &uuid::Uuid,
+) -> Result<Vec<String>, AppError> {
+    let mut blockers = Vec::new();
+
+    // Every judge seat owes a score for every criteria of every candidate
+    let missing_scores = sqlx::query_as::<_, (i32, String, i64)>(
+        r#"
+        SELECT c.candidate_number, j.name, COUNT(*)
+        FROM candidates c
+        JOIN categories home ON home.id = c.category_id AND home.event_id = ($2)
+        CROSS JOIN criterias cri
+        CROSS JOIN judges j
+        WHERE cri.category_id = ($1)
+            AND j.event_id = ($2)
+            AND j.score_exclusion = FALSE
+            AND j.substitute_for IS NULL
+            AND c.deleted_at IS NULL
+            AND NOT EXISTS (
+                SELECT 1 FROM scores_archive a
+                JOIN categories ac ON ac.id = a.category_id
+                WHERE a.candidate_id = c.id AND ac.event_id <> ($2)
+            )
+            AND NOT EXISTS (
+                SELECT 1 FROM scores s
+                JOIN judges sj ON sj.id = s.judge_id
+                WHERE s.candidate_id = c.id
+                    AND s.criteria_id = cri.id
+                    AND COALESCE(sj.substitute_for, sj.id) = j.id
+            )
+        GROUP BY c.candidate_number, j.name
+        ORDER BY c.candidate_number, j.name
+        "#,
+    )
+    .bind(category_id)
+    .bind(event_id)
+    .fetch_all(pool)
+    .await?;
+
+    for (candidate_number, judge_name, missing) in missing_scores {
+        blockers.push(format!(
+            "Candidate #{} is missing {} score(s) from {}",
+            candidate_number, missing, judge_name
+        ));
+    }
+
+    let pending_corrections: i64 = sqlx::query_scalar(
+        r#"
+        SELECT COUNT(*) FROM score_correction_requests r
+        JOIN scores s ON s.id = r.score_id
+        WHERE r.status = 'pending' AND s.category_id = ($1)
+        "#,
+    )
+    .bind(category_id)
+    .fetch_one(pool)
+    .await?;
+
+    if pending_corrections > 0 {
+        blockers.push(format!(
+            "{} correction request(s) are still unresolved",
+            pending_corrections
+        ));
+    }
+
+    let gaps = quorum::find_quorum_gaps(pool, event_id, Some(category_id)).await?;
+
+    if !gaps.is_empty() {
+        blockers.push(quorum::describe_quorum_gaps(&gaps));
+    }
+
+    Ok(blockers)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LockParam {
+    // Closes the category even with blockers, they are kept in the audit log
+    #[serde(default)]
+    force: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LockBlockers {
+    message: String,
+    blockers: Vec<String>,
+}
+
+// Closes the category for scoring and keeps a copy of the standings at that point
+pub async fn lock_category(
+    extract::State(pool): extract::State<PgPool>,
+    extract::Extension(staff): extract::Extension<StaffClaims>,
+    extract::Path((event_id, category_id)): extract::Path<(uuid::Uuid, uuid::Uuid)>,
+    extract::Query(param): extract::Query<LockParam>,
+) -> Result<Response, AppError> {
+    let blockers = find_lock_blockers(&pool, &event_id, &category_id).await?;
+
+    if !blockers.is_empty() && !param.force {
+        return Ok((
+            http::StatusCode::CONFLICT,
+            axum::Json(LockBlockers {
+                message: "Category is not ready to be locked, use `force=true` to lock anyway"
+                    .to_string(),
+                blockers,
+            }),
+        )
+            .into_response());
+    }
+
+    let snapshot = close_category(
+        &pool,
+        &event_id,
+        &category_id,
+        &staff.sub.to_string(),
+        &blockers,
+    )
+    .await?;
+
+    Ok(axum::Json(snapshot).into_response())
+}
+
+// Locks the category and stores the standings snapshot, blockers are only recorded
+pub async fn close_category(
+    pool: &PgPool,
+    event_id: &uuid::Uuid,
+    category_id: &uuid::Uuid,
+    actor: &str,
+    blockers: &[String],
+) -> Result<StandingsSnapshot, AppError> {
+    let lock = event_lock::lock(pool, event_id).await?;
+
+    let res = sqlx::query(
+        r#"
+        UPDATE categories SET is_locked = TRUE, is_active = FALSE
+        WHERE id = ($1) AND event_id = ($2) AND is_locked = FALSE
+        "#,
+    )
+    .bind(category_id)
+    .bind(event_id)
+    .execute(pool)
+    .await?;
+
+    if res.rows_affected() == 0 {
+        return Err(AppError::new(
+            http::StatusCode::CONFLICT,
+            "Category does not exist or is already locked",
+        ));
+    }
+
+    let snapshot = standings::store_snapshot(pool, event_id, Some(category_id)).await?;
+
+    lock.release().await?;
+
+    audit::record(
+        pool,
+        actor,
+        "category",
+        Some(category_id),
+        "lock",
+        serde_json::json!({
+            "snapshot_version": snapshot.version,
+            "forced": !blockers.is_empty(),
+            "blockers": blockers,
+        }),
+    )
+    .await?;
+
+    Ok(snapshot)
+}
+
+// Only the first opening counts, reopening the category keeps the original candidate set
+// Candidates of the category's event, except those eliminated in an earlier round than the category's
+pub async fn snapshot_eligibility<'e, E: PgExecutor<'e>>(
+    executor: E,
+    category_id: &uuid::Uuid,
+) -> Result<(), AppError> {
+    sqlx::query(&format!(
+        r#"
+        INSERT INTO round_eligibility (category_id, candidate_id)
+        SELECT cat.id, c.id FROM candidates c
+        JOIN categories home ON home.id = c.category_id
+        JOIN categories cat ON cat.id = ($1) AND cat.event_id = home.event_id
+        WHERE c.deleted_at IS NULL
+            AND NOT EXISTS (SELECT 1 FROM scores_archive a WHERE a.candidate_id = c.id)
+            AND NOT {eliminated_earlier}
+            AND NOT EXISTS (SELECT 1 FROM round_eligibility e WHERE e.category_id = ($1))
+        "#,
+        eliminated_earlier = advancement::ELIMINATED_EARLIER,
+    ))
+    .bind(category_id)
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+// Makes the category the event's only active one and lets judges score it
+pub async fn open_category(
+    pool: &PgPool,
+    tx: &broadcast::Sender<String>,
+    event_id: &uuid::Uuid,
+    category_id: &uuid::Uuid,
+) -> Result<(), AppError> {
+    sqlx::query(
+        r#"
+        UPDATE categories
+        SET is_active = (id = ($1)),
+            opened_at = CASE WHEN id = ($1) THEN NOW() ELSE opened_at END
+        WHERE event_id = ($2)
+        "#,
+    )
+    .bind(category_id)
+    .bind(event_id)
+    .execute(pool)
+    .await?;
+
+    snapshot_eligibility(pool, category_id).await?;
+
+    judge::lock_event_judges(pool, tx, event_id, false).await?;
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CategorySchedule {
+    // RFC 3339 with an offset, e.g. `2026-10-16T19:30:00+08:00`, None clears it
+    open_at: Option<chrono::DateTime<chrono::Utc>>,
+    close_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct ScheduledCategory {
+    id: uuid::Uuid,
+    name: String,
+    scheduled_open_at: Option<chrono::DateTime<chrono::Utc>>,
+    scheduled_close_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+// The scheduler opens and closes the category on its own, opening or locking it by hand still works
+pub async fn set_category_schedule(
+    extract::State(pool): extract::State<PgPool>,
+    extract::Extension(staff): extract::Extension<StaffClaims>,
+    extract::Path((event_id, category_id)): extract::Path<(uuid::Uuid, uuid::Uuid)>,
+    axum::Json(payload): axum::Json<CategorySchedule>,
+) -> Result<axum::Json<ScheduledCategory>, AppError> {
+    if let (Some(open_at), Some(close_at)) = (payload.open_at, payload.close_at) {
+        if open_at >= close_at {
+            return Err(AppError::new(
+                http::StatusCode::UNPROCESSABLE_ENTITY,
+                "The category must open before it closes",
+            ));
+        }
+    }
+
+    let category = sqlx::query_as::<_, ScheduledCategory>(
+        r#"
+        UPDATE categories SET scheduled_open_at = ($1), scheduled_close_at = ($2)
+        WHERE id = ($3) AND event_id = ($4)
+        RETURNING id, name, scheduled_open_at, scheduled_close_at
+        "#,
+    )
+    .bind(payload.open_at)
+    .bind(payload.close_at)
+    .bind(&category_id)
+    .bind(&event_id)
+    .fetch_optional(&pool)
+    .await?
+    .ok_or_else(|| AppError::new(http::StatusCode::NOT_FOUND, "Category not found"))?;
+
+    audit::record(
+        &pool,
+        staff.sub,
+        "category",
+        Some(&category_id),
+        "schedule",
+        serde_json::json!({
+            "open_at": payload.open_at,
+            "close_at": payload.close_at,
+        }),
+    )
+    .await?;
+
+    Ok(axum::Json(category))
+}
+
+// Percentages of each criteria's max score, both None turns the curve off
+#[derive(Debug, Deserialize)]
+pub struct CategoryCurve {
+    mean: Option<f32>,
+    std_dev: Option<f32>,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct CurvedCategory {
+    id: uuid::Uuid,
+    name: String,
+    curve_mean: Option<f32>,
+    curve_std_dev: Option<f32>,
+}
+
+pub fn validate_curve(mean: Option<f32>, std_dev: Option<f32>) -> Result<(), String> {
+    match (mean, std_dev) {
+        (None, None) => Ok(()),
+        (Some(mean), Some(std_dev)) => {
+            if !(0.0..=100.0).contains(&mean) {
+                return Err("The curve's mean must be between 0 and 100".to_string());
+            }
+
+            if std_dev <= 0.0 || std_dev > 50.0 {
+                return Err(
+                    "The curve's standard deviation must be above 0 and at most 50".to_string(),
+                );
+            }
+
+            Ok(())
+        }
+        _ => Err("A curve needs both a mean and a standard deviation".to_string()),
+    }
+}
+
+// Standings and exports use the curved scores, judges keep seeing what they gave
+pub async fn set_category_curve(
+    extract::State(pool): extract::State<PgPool>,
+    extract::State(aggregator): extract::State<Aggregator>,
+    extract::Extension(staff): extract::Extension<StaffClaims>,
+    extract::Path((event_id, category_id)): extract::Path<(uuid::Uuid, uuid::Uuid)>,
+    axum::Json(payload): axum::Json<CategoryCurve>,
+) -> Result<axum::Json<CurvedCategory>, AppError> {
+    validate_curve(payload.mean, payload.std_dev)
+        .map_err(|message| AppError::new(http::StatusCode::UNPROCESSABLE_ENTITY, message))?;
+
+    let mut txn = pool.begin().await?;
+
+    let previous = sqlx::query_as::<_, CurvedCategory>(
+        "SELECT id, name, curve_mean, curve_std_dev FROM categories WHERE id = ($1) AND event_id = ($2)",
+    )
+    .bind(&category_id)
+    .bind(&event_id)
+    .fetch_optional(&mut *txn)
+    .await?
+    .ok_or_else(|| AppError::new(http::StatusCode::NOT_FOUND, "Category not found"))?;
+
+    let category = sqlx::query_as::<_, CurvedCategory>(
+        r#"
+        UPDATE categories SET curve_mean = ($1), curve_std_dev = ($2)
+        WHERE id = ($3)
+        RETURNING id, name, curve_mean, curve_std_dev
+        "#,
+    )
+    .bind(payload.mean)
+    .bind(payload.std_dev)
+    .bind(&category_id)
+    .fetch_one(&mut *txn)
+    .await?;
+
+    audit::record(
+        &mut *txn,
+        staff.sub,
+        "category",
+        Some(&category_id),
+        "set_curve",
+        serde_json::json!({
+            "mean": category.curve_mean,
+            "std_dev": category.curve_std_dev,
+            "previous_mean": previous.curve_mean,
+            "previous_std_dev": previous.curve_std_dev,
+        }),
+    )
+    .await?;
+
+    txn.commit().await?;
+
+    aggregator.request(event_id);
+
+    Ok(axum::Json(category))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct VoidScores {
+    reason: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VoidResult {
+    category_id: uuid::Uuid,
+    voided_scores: u64,
+}
+
+// For segments repeated after a technical failure on stage
+// The old scores move to `voided_scores` and the category opens again for fresh scoring
+pub async fn void_scores(
+    extract::State(pool): extract::State<PgPool>,
+    extract::State(tx): extract::State<broadcast::Sender<String>>,
+    extract::State(aggregator): extract::State<Aggregator>,
+    extract::Extension(staff): extract::Extension<StaffClaims>,
+    extract::Path(category_id): extract::Path<uuid::Uuid>,
+    axum::Json(payload): axum::Json<VoidScores>,
+) -> Result<axum::Json<VoidResult>, AppError> {
+    if payload.reason.trim().is_empty() {
+        return Err(AppError::new(
+            http::StatusCode::UNPROCESSABLE_ENTITY,
+            "A reason is required to void scores",
+        ));
+    }
+
+    let event_id: uuid::Uuid = sqlx::query_scalar("SELECT event_id FROM categories WHERE id = ($1)")
+        .bind(&category_id)
+        .fetch_optional(&pool)
+        .await?
+        .ok_or_else(|| AppError::new(http::StatusCode::NOT_FOUND, "Category not found"))?;
+
+    let mut txn = pool.begin().await?;
+
+    let voided = score::void_scores(
+        &mut *txn,
+        score::VoidedScores::Category(category_id),
+        payload.reason.trim(),
+    )
+    .await?;
+
+    sqlx::query(
+        r#"
+        UPDATE categories
+        SET is_active = (id = ($1)),
+            is_locked = CASE WHEN id = ($1) THEN FALSE ELSE is_locked END,
+            opened_at = CASE WHEN id = ($1) THEN NOW() ELSE opened_at END
+        WHERE event_id = ($2)
+        "#,
+    )
+    .bind(&category_id)
+    .bind(&event_id)
+    .execute(&mut *txn)
+    .await?;
+
+    audit::record(
+        &mut *txn,
+        staff.sub,
+        "category",
+        Some(&category_id),
+        "void_scores",
+        serde_json::json!({
+            "reason": payload.reason.trim(),
+            "voided_scores": voided,
+        }),
+    )
+    .await?;
+
+    txn.commit().await?;
+
+    judge::lock_event_judges(&pool, &tx, &event_id, false).await?;
+    score::request_recompute(&pool, &aggregator, &category_id).await?;
+
+    println!("Voided {} scores for category {}", voided, category_id);
+
+    Ok(axum::Json(VoidResult {
+        category_id,
+        voided_scores: voided,
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PreviewScores {
+    scores: HashMap<uuid::Uuid, ScoreValue>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CriteriaPreview {
+    pub criteria_id: uuid::Uuid,
+    pub name: String,
+    pub points: i32,
+    pub max: i32,
+}
+
+// What one judge's scores would be worth, before any curve
+#[derive(Debug, Serialize)]
+pub struct ScorePreview {
+    pub criterias: Vec<CriteriaPreview>,
+    pub points: i32,
+    pub max: i32,
+    pub percent: f64,
+    pub weight: f32,
+    pub weighted_score: f64,
+    pub weighted_max: f64,
+    // Points of the final score this category adds if every judge gives these scores
+    pub final_score_impact: f64,
+    // The most this category can add to the final score
+    pub final_score_share: f64,
+}
+
+impl ScorePreview {
+    // `event_weighted_max` is the sum of every category's max points times its weight
+    pub fn new(criterias: Vec<CriteriaPreview>, weight: f32, event_weighted_max: f64) -> Self {
+        let points: i32 = criterias.iter().map(|criteria| criteria.points).sum();
+        let max: i32 = criterias.iter().map(|criteria| criteria.max).sum();
+
+        let ratio = |value: f64, total: f64| {
+            if total > 0.0 {
+                value / total * 100.0
+            } else {
+                0.0
+            }
+        };
+
+        let weighted_score = points as f64 * weight as f64;
+        let weighted_max = max as f64 * weight as f64;
+
+        Self {
+            criterias,
+            points,
+            max,
+            percent: ratio(points as f64, max as f64).round_to_two_decimals(),
+            weight,
+            weighted_score: weighted_score.round_to_two_decimals(),
+            weighted_max: weighted_max.round_to_two_decimals(),
+            final_score_impact: ratio(weighted_score, event_weighted_max).round_to_two_decimals(),
+            final_score_share: ratio(weighted_max, event_weighted_max).round_to_two_decimals(),
+        }
+    }
+}
+
+// Nothing is stored, for designing rubrics and training judges
+pub async fn preview_score(
+    extract::State(pool): extract::State<PgPool>,
+    extract::Path(category_id): extract::Path<uuid::Uuid>,
+    axum::Json(payload): axum::Json<PreviewScores>,
+) -> Result<axum::Json<ScorePreview>, AppError> {
+    let category = sqlx::query_as::<_, Category>("SELECT * FROM categories WHERE id = ($1)")
+        .bind(&category_id)
+        .fetch_optional(&pool)
+        .await?
+        .ok_or_else(|| AppError::new(http::StatusCode::NOT_FOUND, "Category not found"))?;
+
+    let criterias =
+        sqlx::query_as::<_, Criteria>("SELECT * FROM criterias WHERE category_id = ($1)")
+            .bind(&category_id)
+            .fetch_all(&pool)
+            .await?;
+
+    let scored: Vec<uuid::Uuid> = payload.scores.keys().copied().collect();
+
+    score::check_criteria_set(&criterias, &scored)
+        .map_err(|err| AppError::new(http::StatusCode::UNPROCESSABLE_ENTITY, err))?;
+
+    let mut previews = Vec::with_capacity(payload.scores.len());
+
+    for criteria in criterias.iter() {
+        let Some(value) = payload.scores.get(&criteria.id) else {
+            continue;
+        };
+
+        let resolved = criteria::resolve_score(criteria, value)?;
+
+        previews.push(CriteriaPreview {
+            criteria_id: criteria.id,
+            name: criteria.name.clone(),
+            points: resolved.points,
+            max: resolved.max,
+        });
+    }
+
+    let event_weighted_max: f64 = sqlx::query_scalar(
+        r#"
+        SELECT COALESCE(SUM(cri.max_score * cat.weight), 0)::FLOAT8
+        FROM criterias cri
+        JOIN categories cat ON cat.id = cri.category_id
+        WHERE cat.event_id = ($1) AND cri.is_deduction = FALSE
+        "#,
+    )
+    .bind(&category.event_id)
+    .fetch_one(&pool)
+    .await?;
+
+    Ok(axum::Json(ScorePreview::new(
+        previews,
+        category.weight,
+        event_weighted_max,
+    )))
+}
@@ -0,0 +1,163 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use axum::extract::{Path, State};
+use axum::http;
+use axum::response::{IntoResponse, Response, Result};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use tokio::sync::Mutex;
+
+use crate::error::AppError;
+
+use super::score::{compute_event_final_scores, fetch_display_decimals, round_final_scores};
+use super::standings::{self, RankedCandidate};
+
+// Phones refreshing every few seconds share one computation, standings still feel live
+const CACHE_TTL: Duration = Duration::from_secs(5);
+
+const CACHE_CONTROL: &str = "public, max-age=5, stale-while-revalidate=30";
+
+// For the audience's phones, only what fits on a small screen
+#[derive(Debug, Serialize)]
+pub struct CompactStandings {
+    divisions: Vec<CompactDivision>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CompactDivision {
+    division: String,
+    standings: Vec<CompactEntry>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct CompactEntry {
+    rank: usize,
+    number: i32,
+    name: String,
+    score: f32,
+}
+
+struct CachedStandings {
+    body: String,
+    etag: String,
+    fetched_at: Instant,
+}
+
+// Serialized standings per event, shared by every request within `CACHE_TTL`
+#[derive(Clone, Default)]
+pub struct CompactStandingsCache {
+    // Held while recomputing, so a burst of requests on an expired entry computes it once
+    entries: Arc<Mutex<HashMap<uuid::Uuid, CachedStandings>>>,
+}
+
+// "Maria Santos" -> "Maria S."
+pub fn short_name(first_name: &str, last_name: &str) -> String {
+    match last_name.trim().chars().next() {
+        Some(initial) => format!("{} {}.", first_name.trim(), initial),
+        None => first_name.trim().to_string(),
+    }
+}
+
+pub fn compact_entries(standings: Vec<RankedCandidate>, points: bool) -> Vec<CompactEntry> {
+    standings
+        .into_iter()
+        .map(|ranked| CompactEntry {
+            rank: ranked.rank,
+            number: ranked.candidate.candidate_number,
+            name: short_name(&ranked.candidate.first_name, &ranked.candidate.last_name),
+            score: if points {
+                ranked.candidate.total_points
+            } else {
+                ranked.candidate.final_score
+            },
+        })
+        .collect()
+}
+
+async fn compute(pool: &PgPool, event_id: &uuid::Uuid) -> Result<CachedStandings, AppError> {
+    let standings_display: String =
+        sqlx::query_scalar("SELECT standings_display FROM events WHERE id = ($1)")
+            .bind(event_id)
+            .fetch_optional(pool)
+            .await?
+            .ok_or_else(|| AppError::new(http::StatusCode::NOT_FOUND, "Event not found"))?;
+
+    let mut final_scores = compute_event_final_scores(pool, event_id).await?;
+    let decimals = fetch_display_decimals(pool, event_id).await?;
+
+    round_final_scores(&mut final_scores, decimals);
+
+    let points = standings_display == "points";
+
+    let compact = CompactStandings {
+        divisions: standings::divide_standings(&final_scores)
+            .divisions
+            .into_iter()
+            .map(|division| CompactDivision {
+                division: division.division,
+                standings: compact_entries(division.standings, points),
+            })
+            .collect(),
+    };
+
+    let body = serde_json::to_string(&compact).map_err(|err| {
+        AppError::new(
+            http::StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to serialize standings: {}", err),
+        )
+    })?;
+
+    let etag = format!(
+        "\"{}\"",
+        &hex::encode(Sha256::digest(body.as_bytes()))[..16]
+    );
+
+    Ok(CachedStandings {
+        body,
+        etag,
+        fetched_at: Instant::now(),
+    })
+}
+
+pub async fn get_compact_standings(
+    State(pool): State<PgPool>,
+    State(cache): State<CompactStandingsCache>,
+    Path(event_id): Path<uuid::Uuid>,
+    headers: http::HeaderMap,
+) -> Result<Response, AppError> {
+    let mut entries = cache.entries.lock().await;
+
+    let is_fresh = entries
+        .get(&event_id)
+        .is_some_and(|cached| cached.fetched_at.elapsed() < CACHE_TTL);
+
+    if !is_fresh {
+        entries.insert(event_id, compute(&pool, &event_id).await?);
+    }
+
+    let cached = &entries[&event_id];
+
+    let not_modified = headers
+        .get(http::header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value == cached.etag);
+
+    let cache_headers = [
+        (http::header::CACHE_CONTROL, CACHE_CONTROL.to_string()),
+        (http::header::ETAG, cached.etag.clone()),
+    ];
+
+    if not_modified {
+        return Ok((http::StatusCode::NOT_MODIFIED, cache_headers).into_response());
+    }
+
+    Ok((
+        cache_headers,
+        [(http::header::CONTENT_TYPE, "application/json")],
+        cached.body.clone(),
+    )
+        .into_response())
+}
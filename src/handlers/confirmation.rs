@@ -0,0 +1,418 @@
+use axum::extract::{Path, Query, State};
+use axum::http;
+use axum::response::Result;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::{FromRow, PgPool};
+
+use crate::error::AppError;
+use crate::field_crypto::SealedText;
+
+use super::audit;
+use super::auth;
+use super::auth::AuthenticatedJudge;
+use super::impersonation;
+use super::receipt;
+
+// Failed confirmations allowed per judge within a minute before the judge has to wait
+const MAX_CONFIRMATION_ATTEMPTS: i64 = 5;
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct JudgeConfirmation {
+    id: uuid::Uuid,
+    confirmed_at: chrono::DateTime<chrono::Utc>,
+    device: String,
+    scores_digest: String,
+    signature: String,
+    // Relationships
+    judge_id: uuid::Uuid,
+    category_id: uuid::Uuid,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ConfirmScores {
+    // The judge's PIN or password, whichever they logged in with
+    secret: String,
+    // Falls back to the `User-Agent` header
+    device: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReviewParam {
+    category_id: uuid::Uuid,
+}
+
+#[derive(Debug, FromRow)]
+pub struct ReviewRow {
+    pub candidate_id: uuid::Uuid,
+    pub candidate_number: i32,
+    pub first_name: String,
+    pub last_name: String,
+    pub gender: i32,
+    pub criteria_id: uuid::Uuid,
+    pub criteria_name: String,
+    pub score: i32,
+    pub max: i32,
+    pub remark: Option<SealedText>,
+    pub time_of_scoring: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReviewedScore {
+    criteria_id: uuid::Uuid,
+    criteria_name: String,
+    score: i32,
+    max: i32,
+    remark: Option<SealedText>,
+    time_of_scoring: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReviewedCandidate {
+    pub candidate_id: uuid::Uuid,
+    pub candidate_number: i32,
+    pub first_name: String,
+    pub last_name: String,
+    pub gender: i32,
+    pub scores: Vec<ReviewedScore>,
+    pub total: i32,
+    pub max: i32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ScoreReview {
+    judge_id: uuid::Uuid,
+    category_id: uuid::Uuid,
+    // The scores shown, confirming needs them to be unchanged
+    scores_digest: String,
+    reviewed_at: chrono::DateTime<chrono::Utc>,
+    candidates: Vec<ReviewedCandidate>,
+}
+
+// `rows` are ordered by candidate, each candidate's scores end up under one entry with its totals
+pub fn group_review_rows(rows: Vec<ReviewRow>) -> Vec<ReviewedCandidate> {
+    let mut candidates: Vec<ReviewedCandidate> = Vec::new();
+
+    for row in rows {
+        let is_same = candidates
+            .last()
+            .is_some_and(|candidate| candidate.candidate_id == row.candidate_id);
+
+        if !is_same {
+            candidates.push(ReviewedCandidate {
+                candidate_id: row.candidate_id,
+                candidate_number: row.candidate_number,
+                first_name: row.first_name,
+                last_name: row.last_name,
+                gender: row.gender,
+                scores: Vec::new(),
+                total: 0,
+                max: 0,
+            });
+        }
+
+        let candidate = candidates.last_mut().unwrap();
+
+        candidate.total += row.score;
+        candidate.max += row.max;
+        candidate.scores.push(ReviewedScore {
+            criteria_id: row.criteria_id,
+            criteria_name: row.criteria_name,
+            score: row.score,
+            max: row.max,
+            remark: row.remark,
+            time_of_scoring: row.time_of_scoring,
+        });
+    }
+
+    candidates
+}
+
+fn confirmation_message(
+    judge_id: &uuid::Uuid,
+    category_id: &uuid::Uuid,
+    device: &str,
+    scores_digest: &str,
+    confirmed_at: &chrono::DateTime<chrono::Utc>,
+) -> String {
+    format!(
+        "{}|{}|{}|{}|{}",
+        judge_id,
+        category_id,
+        device,
+        scores_digest,
+        confirmed_at.timestamp_micros()
+    )
+}
+
+// Digest of the judge's scores in the category, so the confirmation covers the exact scores
+async fn judge_scores_digest(
+    pool: &PgPool,
+    judge_id: &uuid::Uuid,
+    category_id: &uuid::Uuid,
+) -> Result<String, AppError> {
+    let scores = sqlx::query_scalar::<_, Option<String>>(
+        r#"
+        SELECT string_agg(id::TEXT || ':' || score::TEXT, ',' ORDER BY id)
+        FROM scores WHERE judge_id = ($1) AND category_id = ($2)
+        "#,
+    )
+    .bind(judge_id)
+    .bind(category_id)
+    .fetch_one(pool)
+    .await?
+    .unwrap_or_default();
+
+    Ok(hex::encode(Sha256::digest(scores.as_bytes())))
+}
+
+// Read-only, everything the judge entered in the category, male candidates first
+// Viewing it is what lets the judge confirm these scores afterwards
+pub async fn get_score_review(
+    State(pool): State<PgPool>,
+    Path(judge_id): Path<uuid::Uuid>,
+    Query(param): Query<ReviewParam>,
+    judge: AuthenticatedJudge,
+    headers: http::HeaderMap,
+) -> Result<axum::Json<ScoreReview>, AppError> {
+    judge.ensure_judge(&judge_id)?;
+
+    let rows = sqlx::query_as::<_, ReviewRow>(
+        r#"
+        SELECT
+            c.id AS candidate_id, c.candidate_number, c.first_name, c.last_name, c.gender,
+            cri.id AS criteria_id, cri.name AS criteria_name,
+            s.score, s.max, s.remark, s.time_of_scoring
+        FROM scores s
+        JOIN candidates c ON c.id = s.candidate_id
+        JOIN criterias cri ON cri.id = s.criteria_id
+        WHERE s.judge_id = ($1) AND s.category_id = ($2) AND c.deleted_at IS NULL
+        ORDER BY c.gender DESC, c.candidate_number, c.id, cri.name
+        "#,
+    )
+    .bind(&judge_id)
+    .bind(&param.category_id)
+    .fetch_all(&pool)
+    .await?;
+
+    let scores_digest = judge_scores_digest(&pool, &judge_id, &param.category_id).await?;
+    let reviewed_at = chrono::Utc::now();
+
+    // An admin acting as the judge can look, but it doesn't count as the judge's review
+    let token = auth::bearer_token(&headers)?;
+    let acting_admin = impersonation::find_impersonation(&pool, token).await?;
+
+    if acting_admin.is_none() {
+        sqlx::query(
+            r#"
+            INSERT INTO judge_score_reviews (reviewed_at, scores_digest, judge_id, category_id)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (judge_id, category_id) DO UPDATE SET
+                reviewed_at = EXCLUDED.reviewed_at,
+                scores_digest = EXCLUDED.scores_digest
+            "#,
+        )
+        .bind(&reviewed_at)
+        .bind(&scores_digest)
+        .bind(&judge_id)
+        .bind(&param.category_id)
+        .execute(&pool)
+        .await?;
+    }
+
+    Ok(axum::Json(ScoreReview {
+        judge_id,
+        category_id: param.category_id,
+        scores_digest,
+        reviewed_at,
+        candidates: group_review_rows(rows),
+    }))
+}
+
+// Judges re-enter their PIN or password to sign off their final scores for the category
+pub async fn confirm_scores(
+    State(pool): State<PgPool>,
+    Path(category_id): Path<uuid::Uuid>,
+    headers: http::HeaderMap,
+    axum::Json(payload): axum::Json<ConfirmScores>,
+) -> Result<(http::StatusCode, axum::Json<JudgeConfirmation>), AppError> {
+    let token = auth::bearer_token(&headers)?;
+    let judge = auth::authenticate_session(&pool, token).await?;
+
+    // Certification is the judge's own act, an admin acting as them can't sign it
+    if impersonation::find_impersonation(&pool, token).await?.is_some() {
+        return Err(AppError::new(
+            http::StatusCode::FORBIDDEN,
+            "Scores can't be confirmed while impersonating a judge",
+        ));
+    }
+
+    let recent_failures: i64 = sqlx::query_scalar(
+        r#"
+        SELECT COUNT(*) FROM audit_log
+        WHERE entity_type = 'judge' AND entity_id = ($1) AND action = 'confirmation_failed'
+            AND created_at > NOW() - INTERVAL '1 minute'
+        "#,
+    )
+    .bind(&judge.id)
+    .fetch_one(&pool)
+    .await?;
+
+    if recent_failures >= MAX_CONFIRMATION_ATTEMPTS {
+        return Err(AppError::new(
+            http::StatusCode::TOO_MANY_REQUESTS,
+            "Too many failed confirmations, try again in a minute",
+        ));
+    }
+
+    let scores_digest = judge_scores_digest(&pool, &judge.id, &category_id).await?;
+
+    // Scores changed since the review have to be reviewed again
+    let reviewed: bool = sqlx::query_scalar(
+        r#"
+        SELECT EXISTS (
+            SELECT 1 FROM judge_score_reviews
+            WHERE judge_id = ($1) AND category_id = ($2) AND scores_digest = ($3)
+        )
+        "#,
+    )
+    .bind(&judge.id)
+    .bind(&category_id)
+    .bind(&scores_digest)
+    .fetch_one(&pool)
+    .await?;
+
+    if !reviewed {
+        return Err(AppError::new(
+            http::StatusCode::CONFLICT,
+            "Go through your scores on the review screen before confirming them",
+        ));
+    }
+
+    let pin_verified: bool = sqlx::query_scalar(
+        r#"
+        SELECT EXISTS (
+            SELECT 1 FROM judge_pins WHERE judge_id = ($1) AND pin = ($2) AND valid_until > NOW()
+        )
+        "#,
+    )
+    .bind(&judge.id)
+    .bind(&payload.secret)
+    .fetch_one(&pool)
+    .await?;
+    let verified = pin_verified || auth::verify_password(&payload.secret, &judge.password);
+
+    if !verified {
+        audit::record(
+            &pool,
+            judge.id,
+            "judge",
+            Some(&judge.id),
+            "confirmation_failed",
+            serde_json::json!({ "category_id": category_id }),
+        )
+        .await?;
+
+        return Err(AppError::new(
+            http::StatusCode::UNAUTHORIZED,
+            "Invalid PIN or password",
+        ));
+    }
+
+    let device = payload
+        .device
+        .or_else(|| {
+            headers
+                .get(http::header::USER_AGENT)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        })
+        .unwrap_or_else(|| "unknown".to_string());
+
+    let confirmed_at = chrono::Utc::now();
+    let signature = receipt::sign(&confirmation_message(
+        &judge.id,
+        &category_id,
+        &device,
+        &scores_digest,
+        &confirmed_at,
+    ))?;
+
+    let mut txn = pool.begin().await?;
+
+    let confirmation = sqlx::query_as::<_, JudgeConfirmation>(
+        r#"
+        INSERT INTO judge_confirmations (confirmed_at, device, scores_digest, signature, judge_id, category_id)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING *
+        "#,
+    )
+    .bind(&confirmed_at)
+    .bind(&device)
+    .bind(&scores_digest)
+    .bind(&signature)
+    .bind(&judge.id)
+    .bind(&category_id)
+    .fetch_one(&mut *txn)
+    .await?;
+
+    audit::record(
+        &mut *txn,
+        judge.id,
+        "category",
+        Some(&category_id),
+        "confirm_scores",
+        serde_json::json!({
+            "confirmation_id": confirmation.id,
+            "device": device,
+            "scores_digest": scores_digest,
+        }),
+    )
+    .await?;
+
+    txn.commit().await?;
+
+    Ok((http::StatusCode::CREATED, axum::Json(confirmation)))
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConfirmationVerification {
+    confirmation: JudgeConfirmation,
+    valid_signature: bool,
+    // The judge's scores are still the ones they confirmed
+    matches_current_scores: bool,
+}
+
+pub async fn get_confirmations(
+    State(pool): State<PgPool>,
+    Path(category_id): Path<uuid::Uuid>,
+) -> Result<axum::Json<Vec<ConfirmationVerification>>, AppError> {
+    let confirmations = sqlx::query_as::<_, JudgeConfirmation>(
+        "SELECT * FROM judge_confirmations WHERE category_id = ($1) ORDER BY confirmed_at",
+    )
+    .bind(&category_id)
+    .fetch_all(&pool)
+    .await?;
+
+    let mut verifications = Vec::with_capacity(confirmations.len());
+
+    for confirmation in confirmations {
+        let message = confirmation_message(
+            &confirmation.judge_id,
+            &confirmation.category_id,
+            &confirmation.device,
+            &confirmation.scores_digest,
+            &confirmation.confirmed_at,
+        );
+        let current_digest =
+            judge_scores_digest(&pool, &confirmation.judge_id, &confirmation.category_id).await?;
+
+        verifications.push(ConfirmationVerification {
+            valid_signature: receipt::verify(&message, &confirmation.signature)?,
+            matches_current_scores: current_digest == confirmation.scores_digest,
+            confirmation,
+        });
+    }
+
+    Ok(axum::Json(verifications))
+}
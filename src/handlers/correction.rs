@@ -0,0 +1,257 @@
+use axum::extract::{Extension, Path, Query, State};
+use axum::http;
+use axum::response::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+
+use crate::aggregation::Aggregator;
+use crate::error::AppError;
+
+use super::admin::StaffClaims;
+use super::audit;
+use super::auth::AuthenticatedJudge;
+use super::criteria;
+use super::score::{self, Score};
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct CorrectionRequest {
+    id: uuid::Uuid,
+    requested_score: i32,
+    reason: String,
+    status: String,
+    resolution_note: Option<String>,
+    created_at: chrono::DateTime<chrono::Utc>,
+    resolved_at: Option<chrono::DateTime<chrono::Utc>>,
+    // Relationships
+    score_id: uuid::Uuid,
+    judge_id: uuid::Uuid,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateCorrectionRequest {
+    judge_id: uuid::Uuid,
+    requested_score: i32,
+    reason: String,
+}
+
+pub async fn create_correction_request(
+    State(pool): State<PgPool>,
+    Path(score_id): Path<uuid::Uuid>,
+    judge: AuthenticatedJudge,
+    axum::Json(payload): axum::Json<CreateCorrectionRequest>,
+) -> Result<(http::StatusCode, axum::Json<CorrectionRequest>), AppError> {
+    judge.ensure_judge(&payload.judge_id)?;
+
+    if payload.reason.trim().is_empty() {
+        return Err(AppError::new(
+            http::StatusCode::UNPROCESSABLE_ENTITY,
+            "A reason is required for a correction request",
+        ));
+    }
+
+    let (judge_id, max, is_deduction, min_score) =
+        sqlx::query_as::<_, (uuid::Uuid, i32, bool, i32)>(
+            r#"
+            SELECT s.judge_id, s.max, cri.is_deduction, cri.min_score
+            FROM scores s
+            JOIN criterias cri ON cri.id = s.criteria_id
+            WHERE s.id = ($1)
+            "#,
+        )
+        .bind(&score_id)
+        .fetch_optional(&pool)
+        .await?
+        .ok_or_else(|| AppError::new(http::StatusCode::NOT_FOUND, "Score not found"))?;
+
+    if judge_id != payload.judge_id {
+        return Err(AppError::new(
+            http::StatusCode::FORBIDDEN,
+            "Judges can only request corrections of their own scores",
+        ));
+    }
+
+    if !criteria::points_in_range(payload.requested_score, is_deduction, min_score, max) {
+        return Err(AppError::new(
+            http::StatusCode::UNPROCESSABLE_ENTITY,
+            format!("Score of {} is out of range", payload.requested_score),
+        ));
+    }
+
+    let mut txn = pool.begin().await?;
+
+    let request = sqlx::query_as::<_, CorrectionRequest>(
+        r#"
+        INSERT INTO score_correction_requests (requested_score, reason, score_id, judge_id)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (score_id) WHERE status = 'pending' DO NOTHING
+        RETURNING *
+        "#,
+    )
+    .bind(&payload.requested_score)
+    .bind(&payload.reason)
+    .bind(&score_id)
+    .bind(&payload.judge_id)
+    .fetch_optional(&mut *txn)
+    .await?
+    .ok_or_else(|| {
+        AppError::new(
+            http::StatusCode::CONFLICT,
+            "A correction request for this score is already pending",
+        )
+    })?;
+
+    audit::record(
+        &mut *txn,
+        payload.judge_id,
+        "correction_request",
+        Some(&request.id),
+        "create",
+        serde_json::json!({
+            "score_id": score_id,
+            "requested_score": request.requested_score,
+            "reason": request.reason,
+        }),
+    )
+    .await?;
+
+    txn.commit().await?;
+
+    Ok((http::StatusCode::CREATED, axum::Json(request)))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CorrectionRequestParam {
+    status: Option<String>,
+}
+
+pub async fn get_correction_requests(
+    State(pool): State<PgPool>,
+    Query(param): Query<CorrectionRequestParam>,
+) -> Result<axum::Json<Vec<CorrectionRequest>>, AppError> {
+    let requests = sqlx::query_as::<_, CorrectionRequest>(
+        r#"
+        SELECT * FROM score_correction_requests
+        WHERE ($1)::TEXT IS NULL OR status = ($1)
+        ORDER BY created_at
+        "#,
+    )
+    .bind(&param.status)
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(axum::Json(requests))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResolveCorrectionRequest {
+    note: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ApprovedCorrection {
+    request: CorrectionRequest,
+    score: Score,
+}
+
+// Marks a pending request as resolved, requests can only be resolved once
+async fn resolve_request(
+    conn: &mut sqlx::PgConnection,
+    request_id: &uuid::Uuid,
+    status: &str,
+    note: Option<&String>,
+) -> Result<CorrectionRequest, AppError> {
+    sqlx::query_as::<_, CorrectionRequest>(
+        r#"
+        UPDATE score_correction_requests
+        SET status = ($1), resolution_note = ($2), resolved_at = NOW()
+        WHERE id = ($3) AND status = 'pending'
+        RETURNING *
+        "#,
+    )
+    .bind(status)
+    .bind(note)
+    .bind(request_id)
+    .fetch_optional(&mut *conn)
+    .await?
+    .ok_or_else(|| {
+        AppError::new(
+            http::StatusCode::CONFLICT,
+            "Correction request not found or already resolved",
+        )
+    })
+}
+
+pub async fn approve_correction_request(
+    State(pool): State<PgPool>,
+    State(aggregator): State<Aggregator>,
+    Extension(staff): Extension<StaffClaims>,
+    Path(request_id): Path<uuid::Uuid>,
+    axum::Json(payload): axum::Json<ResolveCorrectionRequest>,
+) -> Result<axum::Json<ApprovedCorrection>, AppError> {
+    let mut txn = pool.begin().await?;
+
+    let request = resolve_request(&mut txn, &request_id, "approved", payload.note.as_ref()).await?;
+
+    audit::record(
+        &mut *txn,
+        staff.sub,
+        "correction_request",
+        Some(&request.id),
+        "approve",
+        serde_json::json!({
+            "score_id": request.score_id,
+            "note": request.resolution_note,
+        }),
+    )
+    .await?;
+
+    let score = score::apply_override(
+        &mut txn,
+        &staff.sub,
+        &request.score_id,
+        request.requested_score,
+        &request.reason,
+        serde_json::json!({ "correction_request_id": request.id }),
+    )
+    .await?;
+
+    txn.commit().await?;
+
+    let category_id: uuid::Uuid =
+        sqlx::query_scalar("SELECT category_id FROM scores WHERE id = ($1)")
+            .bind(&request.score_id)
+            .fetch_one(&pool)
+            .await?;
+
+    score::request_recompute(&pool, &aggregator, &category_id).await?;
+
+    Ok(axum::Json(ApprovedCorrection { request, score }))
+}
+
+pub async fn reject_correction_request(
+    State(pool): State<PgPool>,
+    Extension(staff): Extension<StaffClaims>,
+    Path(request_id): Path<uuid::Uuid>,
+    axum::Json(payload): axum::Json<ResolveCorrectionRequest>,
+) -> Result<axum::Json<CorrectionRequest>, AppError> {
+    let mut txn = pool.begin().await?;
+
+    let request = resolve_request(&mut txn, &request_id, "rejected", payload.note.as_ref()).await?;
+
+    audit::record(
+        &mut *txn,
+        staff.sub,
+        "correction_request",
+        Some(&request.id),
+        "reject",
+        serde_json::json!({
+            "score_id": request.score_id,
+            "note": request.resolution_note,
+        }),
+    )
+    .await?;
+
+    txn.commit().await?;
+
+    Ok(axum::Json(request))
+}
@@ -0,0 +1,237 @@
+use axum::extract::{Path, State};
+use axum::http;
+use axum::response::{IntoResponse, Result};
+use rust_xlsxwriter::*;
+use serde::Serialize;
+use sqlx::{FromRow, PgPool};
+
+use crate::error::AppError;
+use crate::pdf::{Document, Font};
+
+use super::approval::{self, RosterEntry};
+
+// A4 portrait, in points
+const PAGE_WIDTH: f32 = 595.28;
+const PAGE_HEIGHT: f32 = 841.89;
+const MARGIN: f32 = 56.0;
+
+#[derive(Debug, FromRow)]
+struct CoverEvent {
+    id: uuid::Uuid,
+    name: String,
+    required_approvals: i32,
+    finalized_version: Option<i32>,
+    finalized_at: Option<chrono::DateTime<chrono::Utc>>,
+    candidate_count: i64,
+    judge_count: i64,
+    category_count: i64,
+}
+
+// What the registrar files along with the tabulation report
+#[derive(Debug, Serialize)]
+pub struct CoverSheet {
+    pub event_id: uuid::Uuid,
+    pub event_name: String,
+    pub required_approvals: i32,
+    pub finalized_version: Option<i32>,
+    pub finalized_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub candidate_count: i64,
+    pub judge_count: i64,
+    pub category_count: i64,
+    pub committee: Vec<RosterEntry>,
+    pub software_version: &'static str,
+    pub generated_at: chrono::DateTime<chrono::Utc>,
+}
+
+// Every event when `event_id` is None, the spreadsheet covers all of them
+pub async fn fetch_cover_sheets(
+    pool: &PgPool,
+    event_id: Option<&uuid::Uuid>,
+) -> Result<Vec<CoverSheet>, AppError> {
+    let events = sqlx::query_as::<_, CoverEvent>(
+        r#"
+        SELECT
+            e.id,
+            e.name,
+            e.required_approvals,
+            ss.version AS finalized_version,
+            ss.created_at AS finalized_at,
+            (
+                SELECT COUNT(*) FROM candidates c
+                JOIN categories cat ON cat.id = c.category_id AND cat.event_id = e.id
+                WHERE c.deleted_at IS NULL
+            ) AS candidate_count,
+            (SELECT COUNT(*) FROM judges j WHERE j.event_id = e.id) AS judge_count,
+            (SELECT COUNT(*) FROM categories c WHERE c.event_id = e.id) AS category_count
+        FROM events e
+        LEFT JOIN standings_snapshots ss ON ss.id = e.finalized_snapshot_id
+        WHERE ($1)::UUID IS NULL OR e.id = ($1)
+        ORDER BY e.name
+        "#,
+    )
+    .bind(event_id)
+    .fetch_all(pool)
+    .await?;
+
+    let generated_at = chrono::Utc::now();
+    let mut sheets = Vec::new();
+
+    for event in events {
+        sheets.push(CoverSheet {
+            committee: approval::fetch_committee_roster(pool, &event.id).await?,
+            event_id: event.id,
+            event_name: event.name,
+            required_approvals: event.required_approvals,
+            finalized_version: event.finalized_version,
+            finalized_at: event.finalized_at,
+            candidate_count: event.candidate_count,
+            judge_count: event.judge_count,
+            category_count: event.category_count,
+            software_version: env!("CARGO_PKG_VERSION"),
+            generated_at,
+        });
+    }
+
+    Ok(sheets)
+}
+
+fn finalization_label(sheet: &CoverSheet) -> String {
+    match (sheet.finalized_version, sheet.finalized_at) {
+        (Some(version), Some(finalized_at)) => format!(
+            "Snapshot v{} on {}",
+            version,
+            finalized_at.format("%Y-%m-%d %H:%M UTC")
+        ),
+        _ => "Not finalized".to_string(),
+    }
+}
+
+fn approval_label(entry: &RosterEntry) -> String {
+    match entry.approved_at {
+        Some(approved_at) => format!("Approved {}", approved_at.format("%Y-%m-%d %H:%M UTC")),
+        None => "Not approved".to_string(),
+    }
+}
+
+// Label/value lines shared by the spreadsheet and the PDF
+fn detail_lines(sheet: &CoverSheet) -> Vec<(&'static str, String)> {
+    vec![
+        ("Event", sheet.event_name.clone()),
+        ("Results", finalization_label(sheet)),
+        ("Required approvals", sheet.required_approvals.to_string()),
+        ("Candidates", sheet.candidate_count.to_string()),
+        ("Judges", sheet.judge_count.to_string()),
+        ("Categories", sheet.category_count.to_string()),
+        (
+            "Software version",
+            format!("mmu_tabulation_backend {}", sheet.software_version),
+        ),
+        (
+            "Generated at",
+            sheet
+                .generated_at
+                .format("%Y-%m-%d %H:%M:%S UTC")
+                .to_string(),
+        ),
+    ]
+}
+
+// Added before the score sheets so it's the first thing the workbook opens to
+pub fn write_cover_worksheet(
+    workbook: &mut Workbook,
+    sheets: &[CoverSheet],
+) -> Result<(), AppError> {
+    let heading_format = Format::new().set_font_size(13.5).set_bold();
+    let bold_format = Format::new().set_bold();
+
+    let worksheet = workbook.add_worksheet().set_name("Cover")?;
+
+    worksheet.set_column_width(0, 25)?;
+    worksheet.set_column_width(1, 40)?;
+    worksheet.set_column_width(2, 30)?;
+
+    let mut row: u32 = 0;
+
+    for sheet in sheets {
+        worksheet.write_with_format(row, 0, "Tabulation Report", &heading_format)?;
+        row += 1;
+
+        for (label, value) in detail_lines(sheet) {
+            worksheet.write_with_format(row, 0, label, &bold_format)?;
+            worksheet.write(row, 1, value)?;
+            row += 1;
+        }
+
+        row += 1;
+        worksheet.write_with_format(row, 0, "Tabulation Committee", &bold_format)?;
+        row += 1;
+
+        for entry in sheet.committee.iter() {
+            worksheet.write(row, 0, &entry.role)?;
+            worksheet.write(row, 1, &entry.name)?;
+            worksheet.write(row, 2, approval_label(entry))?;
+            row += 1;
+        }
+
+        row += 2;
+    }
+
+    Ok(())
+}
+
+pub async fn generate_cover_pdf(
+    State(pool): State<PgPool>,
+    Path(event_id): Path<uuid::Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    let sheet = fetch_cover_sheets(&pool, Some(&event_id))
+        .await?
+        .pop()
+        .ok_or_else(|| AppError::new(http::StatusCode::NOT_FOUND, "Event not found"))?;
+
+    let mut document = Document::new(PAGE_WIDTH, PAGE_HEIGHT);
+    let page = document.add_page();
+
+    let mut y = PAGE_HEIGHT - MARGIN - 20.0;
+
+    page.centered_text(
+        PAGE_WIDTH / 2.0,
+        y,
+        20.0,
+        Font::HelveticaBold,
+        "Tabulation Report",
+    );
+    y -= 48.0;
+
+    for (label, value) in detail_lines(&sheet) {
+        page.text(MARGIN, y, 11.0, Font::HelveticaBold, label);
+        page.text(MARGIN + 140.0, y, 11.0, Font::Helvetica, &value);
+        y -= 18.0;
+    }
+
+    y -= 24.0;
+    page.text(MARGIN, y, 14.0, Font::HelveticaBold, "Tabulation Committee");
+    y -= 24.0;
+
+    // Room for a signature above each name
+    for entry in sheet.committee.iter() {
+        y -= 24.0;
+        page.text(
+            MARGIN,
+            y,
+            11.0,
+            Font::Helvetica,
+            "______________________________",
+        );
+        y -= 14.0;
+        page.text(MARGIN, y, 11.0, Font::HelveticaBold, &entry.name);
+        page.text(MARGIN + 240.0, y, 11.0, Font::Helvetica, &entry.role);
+        y -= 14.0;
+        page.text(MARGIN, y, 9.0, Font::Helvetica, &approval_label(entry));
+        y -= 8.0;
+    }
+
+    Ok((
+        [(http::header::CONTENT_TYPE, "application/pdf")],
+        document.to_bytes(),
+    ))
+}
@@ -1,23 +1,235 @@
 use axum::response::Result;
 use axum::{extract, http};
 use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
 use sqlx::{FromRow, PgPool};
 
+use crate::aggregation::Aggregator;
 use crate::error::AppError;
 
+use super::admin::StaffClaims;
+use super::audit;
+use super::score::{self, ScoreValue};
+
+const CRITERIA_TYPES: [&str; 3] = ["numeric", "boolean", "selection"];
+
 #[derive(Debug, Serialize, FromRow)]
 pub struct Criteria {
-    id: uuid::Uuid,
-    name: String,
-    max_score: i32,
+    pub id: uuid::Uuid,
+    pub name: String,
+    pub max_score: i32,
+    pub criteria_type: String,
+    pub options: Option<Json<Vec<SelectionOption>>>,
+    pub is_deduction: bool,
+    pub min_score: i32,
     // Relationships
-    category_id: uuid::Uuid,
+    pub category_id: uuid::Uuid,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SelectionOption {
+    pub label: String,
+    pub points: i32,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct CreateCriteria {
     name: String,
+    description: Option<String>,
+    max_score: i32,
+    criteria_type: Option<String>,
+    options: Option<Vec<SelectionOption>>,
+    // e.g. "-2 per time violation", scored from `min_score` up to 0
+    #[serde(default)]
+    is_deduction: bool,
+    min_score: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateCriteria {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    // The fields below change what scores are worth, so they are fixed once the criteria is scored
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_score: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    criteria_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    options: Option<Vec<SelectionOption>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    is_deduction: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    min_score: Option<i32>,
+}
+
+impl UpdateCriteria {
+    fn changes_scoring(&self) -> bool {
+        self.max_score.is_some()
+            || self.criteria_type.is_some()
+            || self.options.is_some()
+            || self.is_deduction.is_some()
+            || self.min_score.is_some()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeleteCriteriaParam {
+    // Required to delete a criteria that already has scores, which are voided with it
+    #[serde(default)]
+    force: bool,
+}
+
+pub struct ResolvedScore {
+    pub points: i32,
+    // Taken from the criteria, never from the client, 0 for deductions
+    pub max: i32,
+    pub is_deduction: bool,
+}
+
+// Points a score is worth for a criteria of the given type
+pub fn score_points(
+    criteria_type: &str,
+    max_score: i32,
+    options: &[SelectionOption],
+    value: &ScoreValue,
+) -> Result<i32, String> {
+    match (criteria_type, value) {
+        ("numeric", ScoreValue::Numeric(score)) => Ok(*score),
+        ("boolean", ScoreValue::Boolean(checked)) => Ok(if *checked { max_score } else { 0 }),
+        ("selection", ScoreValue::Selection(label)) => options
+            .iter()
+            .find(|option| option.label == *label)
+            .map(|option| option.points)
+            .ok_or_else(|| format!("'{}' is not one of the options", label)),
+        _ => Err(format!("Expected a {} score", criteria_type)),
+    }
+}
+
+// Deductions only go down to their minimum, every other criteria goes from 0 up to its max
+pub fn points_in_range(points: i32, is_deduction: bool, min_score: i32, max_score: i32) -> bool {
+    if is_deduction {
+        (min_score..=0).contains(&points)
+    } else {
+        (0..=max_score).contains(&points)
+    }
+}
+
+pub async fn resolve_points(
+    pool: &PgPool,
+    criteria_id: &uuid::Uuid,
+    value: &ScoreValue,
+) -> Result<ResolvedScore, AppError> {
+    let criteria = sqlx::query_as::<_, Criteria>("SELECT * FROM criterias WHERE id = ($1)")
+        .bind(criteria_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::new(http::StatusCode::NOT_FOUND, "Criteria not found"))?;
+
+    resolve_score(&criteria, value)
+}
+
+pub fn resolve_score(criteria: &Criteria, value: &ScoreValue) -> Result<ResolvedScore, AppError> {
+    let options = criteria
+        .options
+        .as_ref()
+        .map(|options| options.0.as_slice())
+        .unwrap_or_default();
+
+    let points = score_points(&criteria.criteria_type, criteria.max_score, options, value)
+        .map_err(|err| {
+            AppError::new(
+                http::StatusCode::UNPROCESSABLE_ENTITY,
+                format!("Invalid score for {}: {}", criteria.name, err),
+            )
+        })?;
+
+    if !points_in_range(
+        points,
+        criteria.is_deduction,
+        criteria.min_score,
+        criteria.max_score,
+    ) {
+        let (min, max) = if criteria.is_deduction {
+            (criteria.min_score, 0)
+        } else {
+            (0, criteria.max_score)
+        };
+
+        return Err(AppError::new(
+            http::StatusCode::UNPROCESSABLE_ENTITY,
+            format!(
+                "Score of {} is out of range for {}, expected {} to {}",
+                points, criteria.name, min, max
+            ),
+        ));
+    }
+
+    Ok(ResolvedScore {
+        points,
+        max: if criteria.is_deduction {
+            0
+        } else {
+            criteria.max_score
+        },
+        is_deduction: criteria.is_deduction,
+    })
+}
+
+// Shared by creating and updating, `max_score` is ignored for deductions
+pub fn validate_criteria(
+    criteria_type: &str,
     max_score: i32,
+    options: Option<&[SelectionOption]>,
+    is_deduction: bool,
+    min_score: i32,
+) -> Result<(), String> {
+    if !CRITERIA_TYPES.contains(&criteria_type) {
+        return Err(format!(
+            "Criteria type must be one of {}",
+            CRITERIA_TYPES.join(", ")
+        ));
+    }
+
+    let has_options = options.is_some_and(|options| !options.is_empty());
+
+    if criteria_type == "selection" && !has_options {
+        return Err("Selection criteria need at least one option".to_string());
+    }
+
+    if is_deduction && (criteria_type != "numeric" || min_score >= 0) {
+        return Err("Deduction criteria must be numeric with a negative minimum score".to_string());
+    }
+
+    if !is_deduction && max_score <= 0 {
+        return Err("The max score must be greater than 0".to_string());
+    }
+
+    Ok(())
+}
+
+async fn fetch_criteria(
+    pool: &PgPool,
+    category_id: &uuid::Uuid,
+    criteria_id: &uuid::Uuid,
+) -> Result<Criteria, AppError> {
+    sqlx::query_as::<_, Criteria>("SELECT * FROM criterias WHERE category_id = ($1) AND id = ($2)")
+        .bind(category_id)
+        .bind(criteria_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::new(http::StatusCode::NOT_FOUND, "Criteria not found"))
+}
+
+async fn count_scores(pool: &PgPool, criteria_id: &uuid::Uuid) -> Result<i64, AppError> {
+    let count = sqlx::query_scalar("SELECT COUNT(*) FROM scores WHERE criteria_id = ($1)")
+        .bind(criteria_id)
+        .fetch_one(pool)
+        .await?;
+
+    Ok(count)
 }
 
 // POST
@@ -26,16 +238,40 @@ pub async fn create_criteria(
     extract::Path((_event_id, category_id)): extract::Path<(uuid::Uuid, uuid::Uuid)>,
     axum::Json(payload): axum::Json<CreateCriteria>,
 ) -> Result<(http::StatusCode, axum::Json<Criteria>), AppError> {
+    let criteria_type = payload.criteria_type.as_deref().unwrap_or("numeric");
+    let min_score = payload.min_score.unwrap_or(0);
+
+    validate_criteria(
+        criteria_type,
+        payload.max_score,
+        payload.options.as_deref(),
+        payload.is_deduction,
+        min_score,
+    )
+    .map_err(|err| AppError::new(http::StatusCode::UNPROCESSABLE_ENTITY, err))?;
+
+    // Deductions never add to the category's max
+    let max_score = if payload.is_deduction {
+        0
+    } else {
+        payload.max_score
+    };
+
     let res = sqlx::query_as::<_, Criteria>(
         r#"
-        INSERT INTO criterias (name, description, max_score, category_id) 
-        VALUES ($1, $2, $3, $4)
+        INSERT INTO criterias (name, description, max_score, category_id, criteria_type, options, is_deduction, min_score) 
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
         RETURNING *
         "#,
     )
     .bind(&payload.name)
-    .bind(&payload.max_score)
+    .bind(&payload.description)
+    .bind(&max_score)
     .bind(&category_id)
+    .bind(criteria_type)
+    .bind(payload.options.as_ref().map(Json))
+    .bind(&payload.is_deduction)
+    .bind(&min_score)
     .fetch_one(&pool)
     .await;
 
@@ -92,3 +328,167 @@ pub async fn get_criteria(
         }
     }
 }
+
+pub async fn update_criteria(
+    extract::State(pool): extract::State<PgPool>,
+    extract::State(aggregator): extract::State<Aggregator>,
+    extract::Extension(staff): extract::Extension<StaffClaims>,
+    extract::Path((event_id, category_id, criteria_id)): extract::Path<(
+        uuid::Uuid,
+        uuid::Uuid,
+        uuid::Uuid,
+    )>,
+    axum::Json(payload): axum::Json<UpdateCriteria>,
+) -> Result<axum::Json<Criteria>, AppError> {
+    let criteria = fetch_criteria(&pool, &category_id, &criteria_id).await?;
+
+    if payload.changes_scoring() && count_scores(&pool, &criteria_id).await? > 0 {
+        return Err(AppError::new(
+            http::StatusCode::CONFLICT,
+            "The criteria already has scores, only its name and description can change",
+        ));
+    }
+
+    if payload
+        .name
+        .as_ref()
+        .is_some_and(|name| name.trim().is_empty())
+    {
+        return Err(AppError::new(
+            http::StatusCode::UNPROCESSABLE_ENTITY,
+            "The criteria's name can't be blank",
+        ));
+    }
+
+    let criteria_type = payload
+        .criteria_type
+        .as_deref()
+        .unwrap_or(&criteria.criteria_type);
+    let is_deduction = payload.is_deduction.unwrap_or(criteria.is_deduction);
+    let min_score = payload.min_score.unwrap_or(criteria.min_score);
+    let options = match &payload.options {
+        Some(options) => Some(options.clone()),
+        None => criteria.options.map(|options| options.0),
+    };
+
+    // Deductions never add to the category's max
+    let max_score = if is_deduction {
+        0
+    } else {
+        payload.max_score.unwrap_or(criteria.max_score)
+    };
+
+    validate_criteria(
+        criteria_type,
+        max_score,
+        options.as_deref(),
+        is_deduction,
+        min_score,
+    )
+    .map_err(|err| AppError::new(http::StatusCode::UNPROCESSABLE_ENTITY, err))?;
+
+    let mut txn = pool.begin().await?;
+
+    let updated = sqlx::query_as::<_, Criteria>(
+        r#"
+        UPDATE criterias
+        SET name = COALESCE(($1), name),
+            description = COALESCE(($2), description),
+            max_score = ($3),
+            criteria_type = ($4),
+            options = ($5),
+            is_deduction = ($6),
+            min_score = ($7)
+        WHERE id = ($8)
+        RETURNING *
+        "#,
+    )
+    .bind(payload.name.as_deref().map(str::trim))
+    .bind(&payload.description)
+    .bind(max_score)
+    .bind(criteria_type)
+    .bind(options.as_ref().map(Json))
+    .bind(is_deduction)
+    .bind(min_score)
+    .bind(&criteria_id)
+    .fetch_one(&mut *txn)
+    .await?;
+
+    audit::record(
+        &mut *txn,
+        staff.sub,
+        "criteria",
+        Some(&criteria_id),
+        "update",
+        serde_json::to_value(&payload).unwrap_or_default(),
+    )
+    .await?;
+
+    txn.commit().await?;
+
+    // The category's max changes with the criteria's
+    aggregator.request(event_id);
+
+    Ok(axum::Json(updated))
+}
+
+// Scores of the criteria are voided along with it, see `DeleteCriteriaParam`
+pub async fn delete_criteria(
+    extract::State(pool): extract::State<PgPool>,
+    extract::State(aggregator): extract::State<Aggregator>,
+    extract::Extension(staff): extract::Extension<StaffClaims>,
+    extract::Path((event_id, category_id, criteria_id)): extract::Path<(
+        uuid::Uuid,
+        uuid::Uuid,
+        uuid::Uuid,
+    )>,
+    extract::Query(param): extract::Query<DeleteCriteriaParam>,
+) -> Result<http::StatusCode, AppError> {
+    let criteria = fetch_criteria(&pool, &category_id, &criteria_id).await?;
+
+    let scores = count_scores(&pool, &criteria_id).await?;
+
+    if scores > 0 && !param.force {
+        return Err(AppError::new(
+            http::StatusCode::CONFLICT,
+            format!(
+                "The criteria has {} score(s), delete it with ?force=true to void them",
+                scores
+            ),
+        ));
+    }
+
+    let mut txn = pool.begin().await?;
+
+    let voided = score::void_scores(
+        &mut *txn,
+        score::VoidedScores::Criteria(criteria_id),
+        "Criteria deleted",
+    )
+    .await?;
+
+    sqlx::query("DELETE FROM criterias WHERE id = ($1)")
+        .bind(&criteria_id)
+        .execute(&mut *txn)
+        .await?;
+
+    audit::record(
+        &mut *txn,
+        staff.sub,
+        "criteria",
+        Some(&criteria_id),
+        "delete",
+        serde_json::json!({
+            "name": criteria.name,
+            "category_id": category_id,
+            "voided_scores": voided,
+        }),
+    )
+    .await?;
+
+    txn.commit().await?;
+
+    aggregator.request(event_id);
+
+    Ok(http::StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,131 @@
+use axum::extract::{Extension, Path, State};
+use axum::http;
+use axum::response::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+
+use crate::aggregation::Aggregator;
+use crate::error::AppError;
+
+use super::admin::StaffClaims;
+use super::audit;
+use super::score;
+
+// Deductions are subtracted from the candidate's category score before it is weighted
+#[derive(Debug, Serialize, FromRow)]
+pub struct Deduction {
+    id: uuid::Uuid,
+    points: f32,
+    reason: String,
+    source: String,
+    created_at: chrono::DateTime<chrono::Utc>,
+    // Relationships
+    candidate_id: uuid::Uuid,
+    category_id: uuid::Uuid,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateDeduction {
+    points: f32,
+    reason: String,
+    candidate_id: uuid::Uuid,
+    category_id: uuid::Uuid,
+}
+
+pub async fn create_deduction(
+    State(pool): State<PgPool>,
+    State(aggregator): State<Aggregator>,
+    Extension(staff): Extension<StaffClaims>,
+    axum::Json(payload): axum::Json<CreateDeduction>,
+) -> Result<(http::StatusCode, axum::Json<Deduction>), AppError> {
+    if payload.points <= 0.0 {
+        return Err(AppError::new(
+            http::StatusCode::UNPROCESSABLE_ENTITY,
+            "Deducted points must be positive",
+        ));
+    }
+
+    let deduction = sqlx::query_as::<_, Deduction>(
+        r#"
+        INSERT INTO deductions (points, reason, candidate_id, category_id)
+        VALUES ($1, $2, $3, $4)
+        RETURNING *
+        "#,
+    )
+    .bind(&payload.points)
+    .bind(&payload.reason)
+    .bind(&payload.candidate_id)
+    .bind(&payload.category_id)
+    .fetch_one(&pool)
+    .await?;
+
+    audit::record(
+        &pool,
+        staff.sub,
+        "deduction",
+        Some(&deduction.id),
+        "create",
+        serde_json::json!({
+            "candidate_id": deduction.candidate_id,
+            "category_id": deduction.category_id,
+            "points": deduction.points,
+            "reason": deduction.reason,
+        }),
+    )
+    .await?;
+
+    score::request_recompute(&pool, &aggregator, &deduction.category_id).await?;
+
+    Ok((http::StatusCode::CREATED, axum::Json(deduction)))
+}
+
+pub async fn get_deductions(
+    State(pool): State<PgPool>,
+    Path(event_id): Path<uuid::Uuid>,
+) -> Result<axum::Json<Vec<Deduction>>, AppError> {
+    let deductions = sqlx::query_as::<_, Deduction>(
+        r#"
+        SELECT d.* FROM deductions d
+        JOIN categories cat ON cat.id = d.category_id
+        WHERE cat.event_id = ($1)
+        ORDER BY d.created_at
+        "#,
+    )
+    .bind(&event_id)
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(axum::Json(deductions))
+}
+
+pub async fn delete_deduction(
+    State(pool): State<PgPool>,
+    State(aggregator): State<Aggregator>,
+    Extension(staff): Extension<StaffClaims>,
+    Path(deduction_id): Path<uuid::Uuid>,
+) -> Result<http::StatusCode, AppError> {
+    let deduction =
+        sqlx::query_as::<_, Deduction>("DELETE FROM deductions WHERE id = ($1) RETURNING *")
+            .bind(&deduction_id)
+            .fetch_optional(&pool)
+            .await?
+            .ok_or_else(|| AppError::new(http::StatusCode::NOT_FOUND, "Deduction not found"))?;
+
+    audit::record(
+        &pool,
+        staff.sub,
+        "deduction",
+        Some(&deduction.id),
+        "delete",
+        serde_json::json!({
+            "candidate_id": deduction.candidate_id,
+            "category_id": deduction.category_id,
+            "points": deduction.points,
+        }),
+    )
+    .await?;
+
+    score::request_recompute(&pool, &aggregator, &deduction.category_id).await?;
+
+    Ok(http::StatusCode::NO_CONTENT)
+}
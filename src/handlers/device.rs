@@ -0,0 +1,260 @@
+use axum::extract::{Extension, Path, State};
+use axum::http;
+use axum::response::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+
+use crate::aggregation::Aggregator;
+use crate::error::AppError;
+use crate::score_batcher::ScoreBatcher;
+
+use super::admin::StaffClaims;
+use super::audit;
+use super::auth;
+use super::judge::Judge;
+use super::score::{self, CreateScore, ScoreValue, ScoreWithReceipt};
+use super::score_feed::ScoreFeed;
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct ScoringDevice {
+    id: uuid::Uuid,
+    device_id: String,
+    // Only shown when the device is added
+    #[serde(skip_serializing)]
+    token: String,
+    created_at: chrono::DateTime<chrono::Utc>,
+    last_packet_at: Option<chrono::DateTime<chrono::Utc>>,
+    // Relationships
+    event_id: uuid::Uuid,
+    judge_id: uuid::Uuid,
+    criteria_id: uuid::Uuid,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NewScoringDevice {
+    #[serde(flatten)]
+    device: ScoringDevice,
+    token: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateScoringDevice {
+    device_id: String,
+    judge_id: uuid::Uuid,
+    criteria_id: uuid::Uuid,
+}
+
+// What a keypad or clicker bridge sends for each button press
+#[derive(Debug, Deserialize)]
+pub struct ScorePacket {
+    device_id: String,
+    candidate_number: i32,
+    value: i32,
+    // Needed when male and female candidates share the number
+    gender: Option<i32>,
+    sent_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+// The judge and criteria have to be part of the device's event
+pub async fn create_scoring_device(
+    State(pool): State<PgPool>,
+    Extension(staff): Extension<StaffClaims>,
+    Path(event_id): Path<uuid::Uuid>,
+    axum::Json(payload): axum::Json<CreateScoringDevice>,
+) -> Result<(http::StatusCode, axum::Json<NewScoringDevice>), AppError> {
+    if payload.device_id.trim().is_empty() {
+        return Err(AppError::new(
+            http::StatusCode::UNPROCESSABLE_ENTITY,
+            "The device ID can't be blank",
+        ));
+    }
+
+    let device = sqlx::query_as::<_, ScoringDevice>(
+        r#"
+        INSERT INTO scoring_devices (device_id, event_id, judge_id, criteria_id)
+        SELECT ($1), ($2), ($3), ($4)
+        WHERE EXISTS (SELECT 1 FROM judges WHERE id = ($3) AND event_id = ($2))
+        AND EXISTS (
+            SELECT 1 FROM criterias cr
+            JOIN categories cat ON cat.id = cr.category_id
+            WHERE cr.id = ($4) AND cat.event_id = ($2)
+        )
+        RETURNING *
+        "#,
+    )
+    .bind(payload.device_id.trim())
+    .bind(&event_id)
+    .bind(&payload.judge_id)
+    .bind(&payload.criteria_id)
+    .fetch_optional(&pool)
+    .await
+    .map_err(|err| {
+        if err
+            .as_database_error()
+            .is_some_and(|err| err.is_unique_violation())
+        {
+            return AppError::new(
+                http::StatusCode::CONFLICT,
+                format!("Device {} is already set up", payload.device_id.trim()),
+            );
+        }
+
+        err.into()
+    })?
+    .ok_or_else(|| {
+        AppError::new(
+            http::StatusCode::UNPROCESSABLE_ENTITY,
+            "The judge and criteria must belong to the event",
+        )
+    })?;
+
+    audit::record(
+        &pool,
+        staff.sub,
+        "scoring_device",
+        Some(&device.id),
+        "create",
+        serde_json::json!({
+            "device_id": device.device_id,
+            "judge_id": device.judge_id,
+            "criteria_id": device.criteria_id,
+        }),
+    )
+    .await?;
+
+    let token = device.token.clone();
+
+    Ok((
+        http::StatusCode::CREATED,
+        axum::Json(NewScoringDevice { device, token }),
+    ))
+}
+
+pub async fn get_scoring_devices(
+    State(pool): State<PgPool>,
+    Path(event_id): Path<uuid::Uuid>,
+) -> Result<axum::Json<Vec<ScoringDevice>>, AppError> {
+    let devices = sqlx::query_as::<_, ScoringDevice>(
+        "SELECT * FROM scoring_devices WHERE event_id = ($1) ORDER BY device_id",
+    )
+    .bind(&event_id)
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(axum::Json(devices))
+}
+
+pub async fn delete_scoring_device(
+    State(pool): State<PgPool>,
+    Extension(staff): Extension<StaffClaims>,
+    Path((event_id, id)): Path<(uuid::Uuid, uuid::Uuid)>,
+) -> Result<http::StatusCode, AppError> {
+    let device_id: String = sqlx::query_scalar(
+        "DELETE FROM scoring_devices WHERE id = ($1) AND event_id = ($2) RETURNING device_id",
+    )
+    .bind(&id)
+    .bind(&event_id)
+    .fetch_optional(&pool)
+    .await?
+    .ok_or_else(|| AppError::new(http::StatusCode::NOT_FOUND, "Scoring device not found"))?;
+
+    audit::record(
+        &pool,
+        staff.sub,
+        "scoring_device",
+        Some(&id),
+        "delete",
+        serde_json::json!({ "device_id": device_id }),
+    )
+    .await?;
+
+    Ok(http::StatusCode::NO_CONTENT)
+}
+
+// The bridge authenticates with the device's token, the score is then handled like the judge's own
+pub async fn submit_device_score(
+    State(pool): State<PgPool>,
+    State(aggregator): State<Aggregator>,
+    State(batcher): State<ScoreBatcher>,
+    State(feed): State<ScoreFeed>,
+    headers: http::HeaderMap,
+    axum::Json(packet): axum::Json<ScorePacket>,
+) -> Result<(http::StatusCode, axum::Json<ScoreWithReceipt>), AppError> {
+    let received_at = chrono::Utc::now();
+
+    let device = sqlx::query_as::<_, ScoringDevice>(
+        r#"
+        UPDATE scoring_devices SET last_packet_at = NOW()
+        WHERE token = ($1) AND device_id = ($2)
+        RETURNING *
+        "#,
+    )
+    .bind(auth::bearer_token(&headers)?)
+    .bind(&packet.device_id)
+    .fetch_optional(&pool)
+    .await?
+    .ok_or_else(|| AppError::new(http::StatusCode::UNAUTHORIZED, "Unknown device or token"))?;
+
+    let judge = sqlx::query_as::<_, Judge>("SELECT * FROM judges WHERE id = ($1)")
+        .bind(&device.judge_id)
+        .fetch_one(&pool)
+        .await?;
+
+    let category_id: uuid::Uuid =
+        sqlx::query_scalar("SELECT category_id FROM criterias WHERE id = ($1)")
+            .bind(&device.criteria_id)
+            .fetch_one(&pool)
+            .await?;
+
+    let candidate_ids: Vec<uuid::Uuid> = sqlx::query_scalar(
+        r#"
+        SELECT c.id FROM candidates c
+        JOIN categories cat ON cat.id = c.category_id
+        WHERE cat.event_id = ($1) AND c.deleted_at IS NULL
+            AND c.candidate_number = ($2) AND (($3)::INT IS NULL OR c.gender = ($3))
+        "#,
+    )
+    .bind(&device.event_id)
+    .bind(packet.candidate_number)
+    .bind(&packet.gender)
+    .fetch_all(&pool)
+    .await?;
+
+    let candidate_id = match candidate_ids.as_slice() {
+        [candidate_id] => *candidate_id,
+        [] => {
+            return Err(AppError::new(
+                http::StatusCode::UNPROCESSABLE_ENTITY,
+                format!("No candidate #{}", packet.candidate_number),
+            ))
+        }
+        _ => {
+            return Err(AppError::new(
+                http::StatusCode::UNPROCESSABLE_ENTITY,
+                format!(
+                    "More than one candidate #{}, send their gender",
+                    packet.candidate_number
+                ),
+            ))
+        }
+    };
+
+    score::store_score(
+        &pool,
+        &aggregator,
+        &batcher,
+        &feed,
+        &judge,
+        CreateScore {
+            score: ScoreValue::Numeric(packet.value),
+            candidate_id,
+            criteria_id: device.criteria_id,
+            category_id,
+            judge_id: device.judge_id,
+            remark: None,
+            client_sent_at: packet.sent_at,
+        },
+        received_at,
+    )
+    .await
+}
@@ -1,14 +1,27 @@
+use axum::extract::Extension;
 use axum::extract::Path;
 use axum::response::Result;
 use axum::{extract::State, http};
 use serde::{Deserialize, Serialize};
 use sqlx::{FromRow, PgPool};
 
+use crate::aggregation::Aggregator;
+use crate::error::AppError;
+
+use super::admin::StaffClaims;
+use super::audit;
+
 #[derive(Debug, Serialize, FromRow)]
 pub struct Event {
     id: uuid::Uuid,
     name: String,
     active_event: bool,
+    remark_low_threshold: Option<f32>,
+    remark_on_perfect: bool,
+    standings_display: String,
+    required_approvals: i32,
+    finalized_snapshot_id: Option<uuid::Uuid>,
+    rehearsal_mode: bool,
 }
 
 #[derive(Debug, Deserialize)]
@@ -70,3 +83,143 @@ pub async fn get_event(
         }
     }
 }
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateEvent {
+    name: Option<String>,
+    remark_low_threshold: Option<f32>,
+    remark_on_perfect: Option<bool>,
+    standings_display: Option<String>,
+    required_approvals: Option<i32>,
+    rehearsal_mode: Option<bool>,
+}
+
+pub async fn update_event(
+    State(pool): State<PgPool>,
+    State(aggregator): State<Aggregator>,
+    Path(id): Path<uuid::Uuid>,
+    axum::Json(payload): axum::Json<UpdateEvent>,
+) -> Result<axum::Json<Event>, http::StatusCode> {
+    if let Some(display) = &payload.standings_display {
+        if display != "percentage" && display != "points" {
+            return Err(http::StatusCode::UNPROCESSABLE_ENTITY);
+        }
+    }
+
+    let res = sqlx::query_as::<_, Event>(
+        r#"
+        UPDATE events
+        SET name = COALESCE(($1), name),
+            remark_low_threshold = COALESCE(($2), remark_low_threshold),
+            remark_on_perfect = COALESCE(($3), remark_on_perfect),
+            standings_display = COALESCE(($4), standings_display),
+            required_approvals = COALESCE(($5), required_approvals),
+            rehearsal_mode = COALESCE(($6), rehearsal_mode)
+        WHERE id = ($7)
+        RETURNING *
+        "#,
+    )
+    .bind(&payload.name)
+    .bind(&payload.remark_low_threshold)
+    .bind(&payload.remark_on_perfect)
+    .bind(&payload.standings_display)
+    .bind(&payload.required_approvals)
+    .bind(&payload.rehearsal_mode)
+    .bind(&id)
+    .fetch_one(&pool)
+    .await;
+
+    match res {
+        Ok(event) => {
+            // Rehearsal scores drop out of (or back into) the live standings
+            if payload.rehearsal_mode.is_some() {
+                aggregator.request(id);
+            }
+
+            Ok(axum::Json(event))
+        }
+        Err(err) => {
+            eprintln!("Failed to update event: {err:?}");
+
+            Err(http::StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct RehearsalPurge {
+    event_id: uuid::Uuid,
+    purged_scores: u64,
+}
+
+// Clears everything scored during tech rehearsal in one go
+pub async fn purge_rehearsal_scores(
+    State(pool): State<PgPool>,
+    State(aggregator): State<Aggregator>,
+    Extension(staff): Extension<StaffClaims>,
+    Path(event_id): Path<uuid::Uuid>,
+) -> Result<axum::Json<RehearsalPurge>, AppError> {
+    let mut txn = pool.begin().await?;
+
+    sqlx::query(
+        r#"
+        UPDATE score_correction_requests
+        SET status = 'rejected', resolution_note = 'Rehearsal scores were purged', resolved_at = NOW()
+        WHERE status = 'pending' AND score_id IN (
+            SELECT s.id FROM scores s
+            JOIN categories cat ON cat.id = s.category_id
+            WHERE cat.event_id = ($1) AND s.is_rehearsal = TRUE
+        )
+        "#,
+    )
+    .bind(&event_id)
+    .execute(&mut *txn)
+    .await?;
+
+    let res = sqlx::query(
+        r#"
+        DELETE FROM scores s
+        USING categories cat
+        WHERE cat.id = s.category_id AND cat.event_id = ($1) AND s.is_rehearsal = TRUE
+        "#,
+    )
+    .bind(&event_id)
+    .execute(&mut *txn)
+    .await?;
+
+    sqlx::query(
+        r#"
+        DELETE FROM voided_scores s
+        USING categories cat
+        WHERE cat.id = s.category_id AND cat.event_id = ($1) AND s.is_rehearsal = TRUE
+        "#,
+    )
+    .bind(&event_id)
+    .execute(&mut *txn)
+    .await?;
+
+    audit::record(
+        &mut *txn,
+        staff.sub,
+        "event",
+        Some(&event_id),
+        "purge_rehearsal",
+        serde_json::json!({ "purged_scores": res.rows_affected() }),
+    )
+    .await?;
+
+    txn.commit().await?;
+
+    aggregator.request(event_id);
+
+    println!(
+        "Purged {} rehearsal scores for event {}",
+        res.rows_affected(),
+        event_id
+    );
+
+    Ok(axum::Json(RehearsalPurge {
+        event_id,
+        purged_scores: res.rows_affected(),
+    }))
+}
@@ -0,0 +1,181 @@
+use axum::extract::{Extension, Path, State};
+use axum::http;
+use axum::response::{IntoResponse, Result};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+
+use crate::error::AppError;
+use crate::exports::ExportQueue;
+
+use super::admin::StaffClaims;
+use super::audit;
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct ExportJob {
+    id: uuid::Uuid,
+    kind: String,
+    certified: bool,
+    priority: i32,
+    status: String,
+    error: Option<String>,
+    created_at: chrono::DateTime<chrono::Utc>,
+    started_at: Option<chrono::DateTime<chrono::Utc>>,
+    finished_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+const EXPORT_JOB_COLUMNS: &str =
+    "id, kind, certified, priority, status, error, created_at, started_at, finished_at";
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportPriority {
+    // Historical and bulk exports
+    Low,
+    Normal,
+    // The certified results file at the end of the night jumps the queue
+    Finalization,
+}
+
+impl ExportPriority {
+    fn level(self) -> i32 {
+        match self {
+            ExportPriority::Low => 0,
+            ExportPriority::Normal => 1,
+            ExportPriority::Finalization => 2,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateExportJob {
+    kind: String,
+    #[serde(default)]
+    certified: bool,
+    priority: Option<ExportPriority>,
+}
+
+pub async fn create_export_job(
+    State(pool): State<PgPool>,
+    State(queue): State<ExportQueue>,
+    axum::Json(payload): axum::Json<CreateExportJob>,
+) -> Result<(http::StatusCode, axum::Json<ExportJob>), AppError> {
+    if !["spreadsheet", "csv", "jsonl"].contains(&payload.kind.as_str()) {
+        return Err(AppError::new(
+            http::StatusCode::UNPROCESSABLE_ENTITY,
+            format!("Unknown export kind: {}", payload.kind),
+        ));
+    }
+
+    let priority = payload.priority.unwrap_or(ExportPriority::Normal);
+
+    let job = sqlx::query_as::<_, ExportJob>(&format!(
+        "INSERT INTO export_jobs (kind, certified, priority) VALUES ($1, $2, $3) RETURNING {}",
+        EXPORT_JOB_COLUMNS
+    ))
+    .bind(&payload.kind)
+    .bind(payload.certified)
+    .bind(priority.level())
+    .fetch_one(&pool)
+    .await?;
+
+    queue.wake();
+
+    Ok((http::StatusCode::ACCEPTED, axum::Json(job)))
+}
+
+pub async fn get_export_jobs(
+    State(pool): State<PgPool>,
+) -> Result<axum::Json<Vec<ExportJob>>, AppError> {
+    let jobs = sqlx::query_as::<_, ExportJob>(&format!(
+        r#"
+        SELECT {} FROM export_jobs
+        ORDER BY
+            CASE status WHEN 'running' THEN 0 WHEN 'queued' THEN 1 ELSE 2 END,
+            priority DESC,
+            created_at DESC
+        LIMIT 100
+        "#,
+        EXPORT_JOB_COLUMNS
+    ))
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(axum::Json(jobs))
+}
+
+pub async fn get_export_job(
+    State(pool): State<PgPool>,
+    Path(job_id): Path<uuid::Uuid>,
+) -> Result<axum::Json<ExportJob>, AppError> {
+    let job = sqlx::query_as::<_, ExportJob>(&format!(
+        "SELECT {} FROM export_jobs WHERE id = ($1)",
+        EXPORT_JOB_COLUMNS
+    ))
+    .bind(&job_id)
+    .fetch_optional(&pool)
+    .await?
+    .ok_or_else(|| AppError::new(http::StatusCode::NOT_FOUND, "Export job not found"))?;
+
+    Ok(axum::Json(job))
+}
+
+pub async fn download_export(
+    State(pool): State<PgPool>,
+    Path(job_id): Path<uuid::Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    let (kind, result) = sqlx::query_as::<_, (String, Option<Vec<u8>>)>(
+        "SELECT kind, result FROM export_jobs WHERE id = ($1) AND status = 'done'",
+    )
+    .bind(&job_id)
+    .fetch_optional(&pool)
+    .await?
+    .ok_or_else(|| AppError::new(http::StatusCode::NOT_FOUND, "Export is not ready"))?;
+
+    let content_type = match kind.as_str() {
+        "spreadsheet" => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        "csv" => "text/csv",
+        _ => "application/x-ndjson",
+    };
+
+    Ok((
+        [(http::header::CONTENT_TYPE, content_type)],
+        result.unwrap_or_default(),
+    ))
+}
+
+// Queued jobs never start, running ones stop at their next query
+pub async fn cancel_export_job(
+    State(pool): State<PgPool>,
+    Extension(staff): Extension<StaffClaims>,
+    Path(job_id): Path<uuid::Uuid>,
+) -> Result<axum::Json<ExportJob>, AppError> {
+    let job = sqlx::query_as::<_, ExportJob>(&format!(
+        r#"
+        UPDATE export_jobs SET status = 'cancelled', finished_at = NOW()
+        WHERE id = ($1) AND status IN ('queued', 'running')
+        RETURNING {}
+        "#,
+        EXPORT_JOB_COLUMNS
+    ))
+    .bind(&job_id)
+    .fetch_optional(&pool)
+    .await?
+    .ok_or_else(|| {
+        AppError::new(
+            http::StatusCode::CONFLICT,
+            "Export job not found or already finished",
+        )
+    })?;
+
+    audit::record(
+        &pool,
+        staff.sub,
+        "export_job",
+        Some(&job.id),
+        "cancel",
+        serde_json::json!({ "kind": job.kind }),
+    )
+    .await?;
+
+    Ok(axum::Json(job))
+}
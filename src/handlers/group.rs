@@ -0,0 +1,311 @@
+use std::collections::HashMap;
+
+use axum::extract::{Path, State};
+use axum::http;
+use axum::response::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+
+use crate::aggregation::Aggregator;
+use crate::error::AppError;
+use crate::field_crypto::SealedText;
+
+use super::advancement;
+use super::analytics;
+use super::audit;
+use super::blackout;
+use super::auth::AuthenticatedJudge;
+use super::criteria;
+use super::judge;
+use super::score::{self, Score, ScoreValue, UpsertedScore};
+use super::score_feed::ScoreFeed;
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct PerformanceGroup {
+    id: uuid::Uuid,
+    name: String,
+    score_mode: String,
+    // Relationships
+    event_id: uuid::Uuid,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PerformanceGroupDetails {
+    #[serde(flatten)]
+    group: PerformanceGroup,
+    candidate_ids: Vec<uuid::Uuid>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreatePerformanceGroup {
+    name: String,
+    score_mode: String,
+    candidate_ids: Vec<uuid::Uuid>,
+}
+
+pub async fn create_group(
+    State(pool): State<PgPool>,
+    Path(event_id): Path<uuid::Uuid>,
+    axum::Json(payload): axum::Json<CreatePerformanceGroup>,
+) -> Result<(http::StatusCode, axum::Json<PerformanceGroupDetails>), AppError> {
+    if payload.score_mode != "fan_out" && payload.score_mode != "group" {
+        return Err(AppError::new(
+            http::StatusCode::UNPROCESSABLE_ENTITY,
+            "Score mode must be either 'fan_out' or 'group'",
+        ));
+    }
+
+    if payload.candidate_ids.is_empty() {
+        return Err(AppError::new(
+            http::StatusCode::UNPROCESSABLE_ENTITY,
+            "A group needs at least one candidate",
+        ));
+    }
+
+    let mut txn = pool.begin().await?;
+
+    let group = sqlx::query_as::<_, PerformanceGroup>(
+        "INSERT INTO performance_groups (name, score_mode, event_id) VALUES ($1, $2, $3) RETURNING *",
+    )
+    .bind(&payload.name)
+    .bind(&payload.score_mode)
+    .bind(&event_id)
+    .fetch_one(&mut *txn)
+    .await?;
+
+    for candidate_id in payload.candidate_ids.iter() {
+        sqlx::query(
+            "INSERT INTO performance_group_members (group_id, candidate_id) VALUES ($1, $2)",
+        )
+        .bind(&group.id)
+        .bind(candidate_id)
+        .execute(&mut *txn)
+        .await?;
+    }
+
+    txn.commit().await?;
+
+    Ok((
+        http::StatusCode::CREATED,
+        axum::Json(PerformanceGroupDetails {
+            group,
+            candidate_ids: payload.candidate_ids,
+        }),
+    ))
+}
+
+async fn fetch_members(pool: &PgPool, group_id: &uuid::Uuid) -> Result<Vec<uuid::Uuid>, AppError> {
+    let members = sqlx::query_scalar::<_, uuid::Uuid>(
+        "SELECT candidate_id FROM performance_group_members WHERE group_id = ($1)",
+    )
+    .bind(group_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(members)
+}
+
+pub async fn get_groups(
+    State(pool): State<PgPool>,
+    Path(event_id): Path<uuid::Uuid>,
+) -> Result<axum::Json<Vec<PerformanceGroupDetails>>, AppError> {
+    let groups = sqlx::query_as::<_, PerformanceGroup>(
+        "SELECT * FROM performance_groups WHERE event_id = ($1) ORDER BY name",
+    )
+    .bind(&event_id)
+    .fetch_all(&pool)
+    .await?;
+
+    let mut details = Vec::new();
+
+    for group in groups {
+        let candidate_ids = fetch_members(&pool, &group.id).await?;
+
+        details.push(PerformanceGroupDetails {
+            group,
+            candidate_ids,
+        });
+    }
+
+    Ok(axum::Json(details))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateGroupScore {
+    score: ScoreValue,
+    criteria_id: uuid::Uuid,
+    category_id: uuid::Uuid,
+    judge_id: uuid::Uuid,
+    remark: Option<String>,
+    client_sent_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+// One score for the whole group, stored once per member
+pub async fn submit_group_score(
+    State(pool): State<PgPool>,
+    State(aggregator): State<Aggregator>,
+    State(feed): State<ScoreFeed>,
+    Path(group_id): Path<uuid::Uuid>,
+    judge: AuthenticatedJudge,
+    axum::Json(payload): axum::Json<CreateGroupScore>,
+) -> Result<(http::StatusCode, axum::Json<Vec<Score>>), AppError> {
+    let received_at = chrono::Utc::now();
+
+    judge.ensure_judge(&payload.judge_id)?;
+    score::validate_score_references(
+        &pool,
+        &payload.judge_id,
+        &payload.category_id,
+        Some(&payload.criteria_id),
+    )
+    .await?;
+
+    let resolved = criteria::resolve_points(&pool, &payload.criteria_id, &payload.score).await?;
+
+    score::validate_remark(
+        &pool,
+        &payload.category_id,
+        resolved.points,
+        resolved.max,
+        payload.remark.as_ref(),
+    )
+    .await?;
+
+    let members = fetch_members(&pool, &group_id).await?;
+
+    if members.is_empty() {
+        return Err(AppError::new(
+            http::StatusCode::NOT_FOUND,
+            "Group not found or has no members",
+        ));
+    }
+
+    judge::validate_judge_seat(
+        &pool,
+        &payload.judge_id,
+        &payload.category_id,
+        &members,
+        Some(&payload.criteria_id),
+    )
+    .await?;
+    blackout::ensure_not_blacked_out(&pool, &payload.judge_id, &members).await?;
+    advancement::ensure_not_eliminated(&pool, &payload.category_id, &members).await?;
+
+    let mut txn = pool.begin().await?;
+    let mut upserted = Vec::new();
+
+    for candidate_id in members.iter() {
+        // Scoring the group again replaces each member's earlier score
+        let score = sqlx::query_as::<_, UpsertedScore>(&format!(
+            r#"
+            INSERT INTO scores (score, max, candidate_id, criteria_id, category_id, judge_id, remark, group_id)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            {upsert}
+            "#,
+            upsert = score::UPSERT_SCORE,
+        ))
+        .bind(&resolved.points)
+        .bind(&resolved.max)
+        .bind(candidate_id)
+        .bind(&payload.criteria_id)
+        .bind(&payload.category_id)
+        .bind(&payload.judge_id)
+        .bind(payload.remark.clone().map(SealedText))
+        .bind(&group_id)
+        .fetch_one(&mut *txn)
+        .await?;
+
+        upserted.push(score);
+    }
+
+    audit::record(
+        &mut *txn,
+        payload.judge_id,
+        "performance_group",
+        Some(&group_id),
+        "score",
+        serde_json::json!({
+            "criteria_id": payload.criteria_id,
+            "category_id": payload.category_id,
+            "score": resolved.points,
+            "members": members,
+        }),
+    )
+    .await?;
+
+    txn.commit().await?;
+
+    score::request_recompute(&pool, &aggregator, &payload.category_id).await?;
+
+    analytics::record_submission_latency(
+        &pool,
+        payload.judge_id,
+        payload.client_sent_at,
+        received_at,
+    );
+
+    let mut scores = Vec::with_capacity(upserted.len());
+
+    for UpsertedScore { score, created } in upserted {
+        feed.publish(&judge.0, &score, if created { "create" } else { "replace" });
+        scores.push(score);
+    }
+
+    Ok((http::StatusCode::CREATED, axum::Json(scores)))
+}
+
+#[derive(Debug, Serialize)]
+pub struct GroupStanding {
+    group_id: uuid::Uuid,
+    name: String,
+    final_score: f64,
+}
+
+// Standings of the groups themselves, each member's copy of a group score is counted once
+pub async fn get_group_standings(
+    State(pool): State<PgPool>,
+    Path(event_id): Path<uuid::Uuid>,
+) -> Result<axum::Json<Vec<GroupStanding>>, AppError> {
+    let rows = sqlx::query_as::<_, (uuid::Uuid, String, f64, f64)>(
+        r#"
+        SELECT
+            g.id,
+            g.name,
+            (SUM(s.score)::FLOAT8 / COUNT(DISTINCT s.candidate_id)) * cat.weight AS weighted_score,
+            (SUM(s.max)::FLOAT8 / COUNT(DISTINCT s.candidate_id)) * cat.weight AS weighted_max
+        FROM performance_groups g
+        JOIN scores_history s ON s.group_id = g.id
+        JOIN categories cat ON cat.id = s.category_id
+        WHERE g.event_id = ($1)
+        GROUP BY g.id, g.name, cat.id, cat.weight
+        "#,
+    )
+    .bind(&event_id)
+    .fetch_all(&pool)
+    .await?;
+
+    // Group ID -> (name, weighted scores sum, weighted max sum)
+    let mut totals: HashMap<uuid::Uuid, (String, f64, f64)> = HashMap::new();
+
+    for (group_id, name, weighted_score, weighted_max) in rows {
+        let total = totals.entry(group_id).or_insert((name, 0.0, 0.0));
+
+        total.1 += weighted_score;
+        total.2 += weighted_max;
+    }
+
+    let mut standings: Vec<GroupStanding> = totals
+        .into_iter()
+        .map(
+            |(group_id, (name, weighted_score, weighted_max))| GroupStanding {
+                group_id,
+                name,
+                final_score: (weighted_score / weighted_max) * 100.0,
+            },
+        )
+        .collect();
+
+    standings.sort_by(|a, b| b.final_score.total_cmp(&a.final_score));
+
+    Ok(axum::Json(standings))
+}
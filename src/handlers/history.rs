@@ -0,0 +1,161 @@
+use axum::extract::{Query, State};
+use axum::response::Result;
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use sqlx::PgPool;
+
+use crate::error::AppError;
+
+use super::score::CandidateFinalScore2;
+use super::tie_break::{self, TieBreakKeys};
+
+// Placements announced per division, in order, unless the event configures its own
+pub const DEFAULT_AWARD_TITLES: [&str; 3] = ["Winner", "1st Runner-up", "2nd Runner-up"];
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AwardWinner {
+    pub award: String,
+    pub division: String,
+    pub candidate_id: uuid::Uuid,
+    pub candidate_number: i32,
+    pub first_name: String,
+    pub middle_name: String,
+    pub last_name: String,
+    pub final_score: f32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HistoricalResult {
+    event_id: uuid::Uuid,
+    event_name: String,
+    year: i32,
+    certified_at: chrono::DateTime<chrono::Utc>,
+    winners: Vec<AwardWinner>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HistoryParam {
+    year: Option<i32>,
+}
+
+// Top placements of each division, male (gender = 1) first
+// `titles` maps a division to its titles by rank, divisions without one use the defaults
+// Exact ties go to whoever wins the tie-breakers, then the lower candidate number
+pub fn pick_award_winners(
+    standings: &[CandidateFinalScore2],
+    titles: &BTreeMap<String, Vec<String>>,
+    keys: &TieBreakKeys,
+) -> Vec<AwardWinner> {
+    let mut winners = Vec::new();
+
+    for (division, is_male) in [("male", true), ("female", false)] {
+        let division_titles: Vec<&str> = match titles.get(division) {
+            Some(titles) => titles.iter().map(|title| title.as_str()).collect(),
+            None => DEFAULT_AWARD_TITLES.to_vec(),
+        };
+
+        let division_standings: Vec<CandidateFinalScore2> = standings
+            .iter()
+            .filter(|candidate| (candidate.gender == 1) == is_male)
+            .cloned()
+            .collect();
+
+        let ranked = tie_break::rank_with_tie_breakers(division_standings, keys);
+
+        for (award, candidate) in division_titles
+            .iter()
+            .zip(ranked.iter().map(|ranked| &ranked.candidate))
+        {
+            winners.push(AwardWinner {
+                award: award.to_string(),
+                division: division.to_string(),
+                candidate_id: candidate.candidate_id,
+                candidate_number: candidate.candidate_number,
+                first_name: candidate.first_name.clone(),
+                middle_name: candidate.middle_name.clone(),
+                last_name: candidate.last_name.clone(),
+                final_score: candidate.final_score,
+            });
+        }
+    }
+
+    winners
+}
+
+// Only events whose finalized snapshot has all the committee approvals it needs are
+// listed, so the hall-of-fame page never shows provisional results
+pub async fn get_results_history(
+    State(pool): State<PgPool>,
+    Query(param): Query<HistoryParam>,
+) -> Result<axum::Json<Vec<HistoricalResult>>, AppError> {
+    let rows = sqlx::query_as::<
+        _,
+        (
+            uuid::Uuid,
+            String,
+            i32,
+            chrono::DateTime<chrono::Utc>,
+            Json<Vec<CandidateFinalScore2>>,
+            Option<Json<Vec<AwardWinner>>>,
+        ),
+    >(
+        r#"
+        SELECT
+            e.id,
+            e.name,
+            EXTRACT(YEAR FROM ss.created_at)::INT4 AS year,
+            ss.created_at,
+            ss.standings,
+            ss.awards
+        FROM events e
+        JOIN standings_snapshots ss ON ss.id = e.finalized_snapshot_id
+        WHERE
+            (SELECT COUNT(*) FROM result_approvals ra WHERE ra.snapshot_id = ss.id) >= e.required_approvals
+            AND (($1)::INT4 IS NULL OR EXTRACT(YEAR FROM ss.created_at)::INT4 = ($1))
+        ORDER BY ss.created_at DESC
+        "#,
+    )
+    .bind(&param.year)
+    .fetch_all(&pool)
+    .await?;
+
+    let results = rows
+        .into_iter()
+        .map(
+            |(event_id, event_name, year, certified_at, standings, awards)| HistoricalResult {
+                event_id,
+                event_name,
+                year,
+                certified_at,
+                // Events finalized before award titles were frozen get the default titles
+                winners: match awards {
+                    Some(awards) => awards.0,
+                    None => pick_award_winners(&standings, &BTreeMap::new(), &TieBreakKeys::new()),
+                },
+            },
+        )
+        .collect();
+
+    Ok(axum::Json(results))
+}
+
+// Years that have at least one certified event, newest first
+pub async fn get_results_years(
+    State(pool): State<PgPool>,
+) -> Result<axum::Json<Vec<i32>>, AppError> {
+    let years = sqlx::query_scalar::<_, i32>(
+        r#"
+        SELECT DISTINCT EXTRACT(YEAR FROM ss.created_at)::INT4 AS year
+        FROM events e
+        JOIN standings_snapshots ss ON ss.id = e.finalized_snapshot_id
+        WHERE (SELECT COUNT(*) FROM result_approvals ra WHERE ra.snapshot_id = ss.id) >= e.required_approvals
+        ORDER BY year DESC
+        "#,
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(axum::Json(years))
+}
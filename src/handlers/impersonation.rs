@@ -0,0 +1,222 @@
+use axum::extract::{Extension, Path, Request, State};
+use axum::http;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response, Result};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+
+use crate::error::AppError;
+
+use super::admin::{Admin, StaffClaims};
+use super::audit;
+use super::judge::Judge;
+
+const DEFAULT_MINUTES: i64 = 15;
+const MAX_MINUTES: i64 = 60;
+
+// Set on every response made with an impersonation session, clients show a banner for it
+pub const IMPERSONATION_HEADER: &str = "x-impersonated-by";
+pub const IMPERSONATION_EXPIRES_HEADER: &str = "x-impersonation-expires";
+
+#[derive(Debug, Deserialize)]
+pub struct StartImpersonation {
+    reason: String,
+    minutes: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImpersonationSession {
+    token: String,
+    expires_at: chrono::DateTime<chrono::Utc>,
+    impersonated_by: String,
+    judge: Judge,
+}
+
+// Lets an admin reproduce an issue on a judge's account without asking for their password
+// The admin logged in is recorded as the actor of everything done with the session
+pub async fn start_impersonation(
+    State(pool): State<PgPool>,
+    Path(judge_id): Path<uuid::Uuid>,
+    Extension(claims): Extension<StaffClaims>,
+    axum::Json(payload): axum::Json<StartImpersonation>,
+) -> Result<(http::StatusCode, axum::Json<ImpersonationSession>), AppError> {
+    if payload.reason.trim().is_empty() {
+        return Err(AppError::new(
+            http::StatusCode::UNPROCESSABLE_ENTITY,
+            "Impersonation needs a reason",
+        ));
+    }
+
+    let admin = sqlx::query_as::<_, Admin>("SELECT * FROM admins WHERE id = ($1)")
+        .bind(&claims.sub)
+        .fetch_optional(&pool)
+        .await?
+        .ok_or_else(|| AppError::new(http::StatusCode::UNAUTHORIZED, "Staff account not found"))?;
+
+    let minutes = payload.minutes.unwrap_or(DEFAULT_MINUTES);
+
+    if !(1..=MAX_MINUTES).contains(&minutes) {
+        return Err(AppError::new(
+            http::StatusCode::UNPROCESSABLE_ENTITY,
+            format!("Impersonation lasts between 1 and {} minutes", MAX_MINUTES),
+        ));
+    }
+
+    let judge = sqlx::query_as::<_, Judge>("SELECT * FROM judges WHERE id = ($1)")
+        .bind(&judge_id)
+        .fetch_optional(&pool)
+        .await?
+        .ok_or_else(|| AppError::new(http::StatusCode::NOT_FOUND, "Judge not found"))?;
+
+    let mut txn = pool.begin().await?;
+
+    let (token, expires_at) = sqlx::query_as::<_, (String, chrono::DateTime<chrono::Utc>)>(
+        r#"
+        INSERT INTO judge_sessions (expires_at, judge_id, event_id, impersonated_by, impersonation_reason)
+        VALUES (NOW() + make_interval(mins => ($1)), ($2), ($3), ($4), ($5))
+        RETURNING token, expires_at
+        "#,
+    )
+    .bind(minutes as i32)
+    .bind(&judge.id)
+    .bind(&judge.event_id)
+    .bind(&admin.username)
+    .bind(payload.reason.trim())
+    .fetch_one(&mut *txn)
+    .await?;
+
+    audit::record(
+        &mut *txn,
+        &admin.username,
+        "judge",
+        Some(&judge.id),
+        "start_impersonation",
+        serde_json::json!({
+            "reason": payload.reason.trim(),
+            "expires_at": expires_at,
+        }),
+    )
+    .await?;
+
+    txn.commit().await?;
+
+    Ok((
+        http::StatusCode::CREATED,
+        axum::Json(ImpersonationSession {
+            token,
+            expires_at,
+            impersonated_by: admin.username,
+            judge,
+        }),
+    ))
+}
+
+// Ends every impersonation of the judge before it expires on its own
+pub async fn end_impersonation(
+    State(pool): State<PgPool>,
+    Path(judge_id): Path<uuid::Uuid>,
+) -> Result<http::StatusCode, AppError> {
+    let admins = sqlx::query_scalar::<_, String>(
+        r#"
+        DELETE FROM judge_sessions
+        WHERE judge_id = ($1) AND impersonated_by IS NOT NULL
+        RETURNING impersonated_by
+        "#,
+    )
+    .bind(&judge_id)
+    .fetch_all(&pool)
+    .await?;
+
+    for admin in admins.iter() {
+        audit::record(
+            &pool,
+            admin,
+            "judge",
+            Some(&judge_id),
+            "end_impersonation",
+            serde_json::json!({}),
+        )
+        .await?;
+    }
+
+    Ok(http::StatusCode::NO_CONTENT)
+}
+
+#[derive(Debug, FromRow)]
+pub struct ActiveImpersonation {
+    pub judge_id: uuid::Uuid,
+    pub impersonated_by: String,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+}
+
+pub async fn find_impersonation(
+    pool: &PgPool,
+    token: &str,
+) -> Result<Option<ActiveImpersonation>, sqlx::Error> {
+    sqlx::query_as::<_, ActiveImpersonation>(
+        r#"
+        SELECT judge_id, impersonated_by, expires_at FROM judge_sessions
+        WHERE token = ($1) AND impersonated_by IS NOT NULL AND expires_at > NOW()
+        "#,
+    )
+    .bind(token)
+    .fetch_optional(pool)
+    .await
+}
+
+// Flags responses made while impersonating and audits every write done that way
+pub async fn mark_impersonation(
+    State(pool): State<PgPool>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let token = request
+        .headers()
+        .get(http::header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(|token| token.to_string());
+
+    let Some(token) = token else {
+        return next.run(request).await;
+    };
+
+    let impersonation = match find_impersonation(&pool, &token).await {
+        Ok(Some(impersonation)) => impersonation,
+        Ok(None) => return next.run(request).await,
+        Err(err) => return AppError::from(err).into_response(),
+    };
+
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+
+    let mut response = next.run(request).await;
+
+    if method != http::Method::GET {
+        let _ = audit::record(
+            &pool,
+            &impersonation.impersonated_by,
+            "judge",
+            Some(&impersonation.judge_id),
+            "impersonated_request",
+            serde_json::json!({
+                "method": method.as_str(),
+                "path": path,
+                "status": response.status().as_u16(),
+            }),
+        )
+        .await;
+    }
+
+    let headers = response.headers_mut();
+
+    if let Ok(value) = http::HeaderValue::from_str(&impersonation.impersonated_by) {
+        headers.insert(IMPERSONATION_HEADER, value);
+    }
+
+    if let Ok(value) = http::HeaderValue::from_str(&impersonation.expires_at.to_rfc3339()) {
+        headers.insert(IMPERSONATION_EXPIRES_HEADER, value);
+    }
+
+    response
+}
@@ -0,0 +1,97 @@
+use axum::extract::{Path, State};
+use axum::response::Result;
+use serde::Serialize;
+use sqlx::PgPool;
+
+use crate::error::AppError;
+
+#[derive(Debug, Serialize)]
+pub struct IntegrityCheck {
+    check: &'static str,
+    description: &'static str,
+    count: usize,
+    // IDs of the offending scores
+    score_ids: Vec<uuid::Uuid>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct IntegrityReport {
+    // True when every check came back empty
+    ok: bool,
+    checks: Vec<IntegrityCheck>,
+}
+
+// Each query returns the IDs of the event's offending scores
+const CHECKS: [(&str, &str, &str); 4] = [
+    (
+        "criteria_category_mismatch",
+        "Scores whose criteria belongs to another category",
+        r#"
+        SELECT s.id FROM scores s
+        JOIN categories cat ON cat.id = s.category_id
+        JOIN criterias cri ON cri.id = s.criteria_id
+        WHERE cat.event_id = ($1) AND cri.category_id <> s.category_id
+        "#,
+    ),
+    (
+        "judge_event_mismatch",
+        "Scores whose judge belongs to another event",
+        r#"
+        SELECT s.id FROM scores s
+        JOIN categories cat ON cat.id = s.category_id
+        JOIN judges j ON j.id = s.judge_id
+        WHERE cat.event_id = ($1) AND j.event_id <> cat.event_id
+        "#,
+    ),
+    (
+        "ineligible_candidate",
+        "Scores of candidates who were not eligible when the category opened",
+        r#"
+        SELECT s.id FROM scores s
+        JOIN categories cat ON cat.id = s.category_id
+        WHERE cat.event_id = ($1)
+            AND EXISTS (SELECT 1 FROM round_eligibility e WHERE e.category_id = s.category_id)
+            AND NOT EXISTS (
+                SELECT 1 FROM round_eligibility e
+                WHERE e.category_id = s.category_id AND e.candidate_id = s.candidate_id
+            )
+        "#,
+    ),
+    (
+        "deleted_candidate",
+        "Scores still attached to a merged or deleted candidate",
+        r#"
+        SELECT s.id FROM scores s
+        JOIN categories cat ON cat.id = s.category_id
+        JOIN candidates c ON c.id = s.candidate_id
+        WHERE cat.event_id = ($1) AND c.deleted_at IS NOT NULL
+        "#,
+    ),
+];
+
+// Read-only, nothing is fixed automatically
+pub async fn get_event_integrity(
+    State(pool): State<PgPool>,
+    Path(event_id): Path<uuid::Uuid>,
+) -> Result<axum::Json<IntegrityReport>, AppError> {
+    let mut checks = Vec::with_capacity(CHECKS.len());
+
+    for (check, description, query) in CHECKS {
+        let score_ids = sqlx::query_scalar::<_, uuid::Uuid>(query)
+            .bind(&event_id)
+            .fetch_all(&pool)
+            .await?;
+
+        checks.push(IntegrityCheck {
+            check,
+            description,
+            count: score_ids.len(),
+            score_ids,
+        });
+    }
+
+    Ok(axum::Json(IntegrityReport {
+        ok: checks.iter().all(|check| check.count == 0),
+        checks,
+    }))
+}
@@ -1,19 +1,31 @@
 use axum::response::Result;
 use axum::{extract, http};
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, PgPool};
+use sqlx::{FromRow, PgConnection, PgPool};
+use tokio::sync::broadcast;
 
 use crate::error::AppError;
 
+use super::admin::StaffClaims;
+use super::audit;
+use super::auth;
+use super::realtime::{self, RealtimeMessage};
+use super::settings::{self, EventSettings};
+
 #[derive(Debug, Serialize, FromRow)]
 pub struct Judge {
     pub id: uuid::Uuid,
     pub name: String,
     pub username: String,
+    // Argon2 hash
+    #[serde(skip_serializing)]
     pub password: String,
     pub is_active: bool,
+    pub is_locked: bool,
+    pub substituted_at: Option<chrono::DateTime<chrono::Utc>>,
     // Relationships
     pub event_id: uuid::Uuid,
+    pub substitute_for: Option<uuid::Uuid>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -25,27 +37,136 @@ pub struct CreateJudge {
     event_id: uuid::Uuid,
 }
 
+// Index backing per-event uniqueness, see migration `judge_username_unique`
+const USERNAME_INDEX: &str = "judges_event_username_idx";
+
+// Usernames are unique within the event, or across every event with `JUDGE_USERNAME_SCOPE=global`
+// since the username/password login doesn't know the event
+fn username_scope_is_global() -> bool {
+    std::env::var("JUDGE_USERNAME_SCOPE").is_ok_and(|scope| scope == "global")
+}
+
+// First of `base2`, `base3`, ... that nobody has taken yet
+pub fn suggest_username(base: &str, taken: &[String]) -> String {
+    (2..)
+        .map(|n| format!("{}{}", base, n))
+        .find(|candidate| !taken.contains(candidate))
+        .unwrap_or_default()
+}
+
+async fn username_conflict(pool: &PgPool, event_id: &uuid::Uuid, username: &str) -> AppError {
+    let taken = sqlx::query_scalar::<_, String>(
+        r#"
+        SELECT username FROM judges
+        WHERE starts_with(username, ($1)) AND (($3) OR event_id = ($2))
+        "#,
+    )
+    .bind(username)
+    .bind(event_id)
+    .bind(username_scope_is_global())
+    .fetch_all(pool)
+    .await
+    .unwrap_or_default();
+
+    AppError::new(
+        http::StatusCode::CONFLICT,
+        format!(
+            "The username {} is already taken, try {}",
+            username,
+            suggest_username(username, &taken)
+        ),
+    )
+}
+
+fn is_username_violation(err: &sqlx::Error) -> bool {
+    err.as_database_error()
+        .and_then(|err| err.constraint())
+        .is_some_and(|constraint| constraint == USERNAME_INDEX)
+}
+
+// The index only covers one event, so inserts of the same username take turns instead
+// and the insert itself skips a username another event already has when it's global
+async fn lock_username(conn: &mut PgConnection, username: &str) -> Result<(), AppError> {
+    sqlx::query("SELECT pg_advisory_xact_lock(hashtextextended('judge_username:' || ($1), 0))")
+        .bind(username)
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+// Seats the panel would have after adding one, substitutes take over an existing seat
+pub fn panel_warnings(seats: u32, settings: &EventSettings) -> Result<Vec<String>, String> {
+    if settings.max_judges > 0 && seats > settings.max_judges {
+        return Err(format!("The panel is limited to {} judges", settings.max_judges));
+    }
+
+    if settings.tablet_count > 0 && seats > settings.tablet_count {
+        return Ok(vec![format!(
+            "The venue only has {} tablets for {} judges",
+            settings.tablet_count, seats
+        )]);
+    }
+
+    Ok(Vec::new())
+}
+
+#[derive(Debug, Serialize)]
+pub struct CreatedJudge {
+    #[serde(flatten)]
+    judge: Judge,
+    warnings: Vec<String>,
+}
+
 pub async fn create_judge(
     extract::State(pool): extract::State<PgPool>,
     axum::Json(payload): axum::Json<CreateJudge>,
-) -> Result<(http::StatusCode, axum::Json<Judge>), AppError> {
+) -> Result<(http::StatusCode, axum::Json<CreatedJudge>), AppError> {
+    let seats: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM judges WHERE event_id = ($1) AND substitute_for IS NULL",
+    )
+    .bind(&payload.event_id)
+    .fetch_one(&pool)
+    .await?;
+
+    let settings = settings::fetch_settings(&pool, &payload.event_id).await?;
+    let warnings = panel_warnings(seats as u32 + 1, &settings)
+        .map_err(|message| AppError::new(http::StatusCode::CONFLICT, message))?;
+
+    let mut txn = pool.begin().await?;
+    lock_username(&mut txn, &payload.username).await?;
+
     let res = sqlx::query_as::<_, Judge>(
         r#"
-        INSERT INTO judges (name, username, password, is_active, event_id) 
-        VALUES ($1, $2, $3, $4, $5) 
+        INSERT INTO judges (name, username, password, is_active, event_id)
+        SELECT $1, $2, $3, $4, $5
+        WHERE NOT (($6) AND EXISTS (SELECT 1 FROM judges WHERE username = ($2)))
         RETURNING *
         "#,
     )
     .bind(&payload.name)
     .bind(&payload.username)
-    .bind(&payload.password)
+    .bind(auth::hash_password(&payload.password)?)
     .bind(&payload.is_active)
     .bind(&payload.event_id)
-    .fetch_one(&pool)
+    .bind(username_scope_is_global())
+    .fetch_optional(&mut *txn)
     .await;
 
     match res {
-        Ok(judge) => Ok((http::StatusCode::CREATED, axum::Json(judge))),
+        Ok(Some(judge)) => {
+            txn.commit().await?;
+
+            Ok((
+                http::StatusCode::CREATED,
+                axum::Json(CreatedJudge { judge, warnings }),
+            ))
+        }
+        // The username is taken, the insert is what decides since a check beforehand can race
+        Ok(None) => Err(username_conflict(&pool, &payload.event_id, &payload.username).await),
+        Err(err) if is_username_violation(&err) => {
+            Err(username_conflict(&pool, &payload.event_id, &payload.username).await)
+        }
         Err(err) => Err(AppError::new(
             http::StatusCode::INTERNAL_SERVER_ERROR,
             format!("Failed to create judge: {}", err),
@@ -86,3 +207,300 @@ pub async fn get_judge(
         )),
     }
 }
+
+#[derive(Debug, Deserialize)]
+pub struct JudgeLock {
+    is_locked: bool,
+}
+
+// Puts a judge's client into (or out of) the waiting state
+pub async fn set_judge_lock(
+    extract::State(pool): extract::State<PgPool>,
+    extract::State(tx): extract::State<broadcast::Sender<String>>,
+    extract::Path(judge_id): extract::Path<uuid::Uuid>,
+    axum::Json(payload): axum::Json<JudgeLock>,
+) -> Result<axum::Json<Judge>, AppError> {
+    let judge =
+        sqlx::query_as::<_, Judge>("UPDATE judges SET is_locked = ($1) WHERE id = ($2) RETURNING *")
+            .bind(&payload.is_locked)
+            .bind(&judge_id)
+            .fetch_optional(&pool)
+            .await?
+            .ok_or_else(|| AppError::new(http::StatusCode::NOT_FOUND, "Judge not found"))?;
+
+    realtime::publish(
+        &tx,
+        &RealtimeMessage::JudgeLock {
+            judge_id: judge.id,
+            is_locked: judge.is_locked,
+        },
+    );
+
+    Ok(axum::Json(judge))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct JudgeNudge {
+    // Written out from the candidate and category when not given
+    message: Option<String>,
+    candidate_id: Option<uuid::Uuid>,
+    category_id: Option<uuid::Uuid>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NudgeResult {
+    judge_id: uuid::Uuid,
+    message: String,
+}
+
+// Reminds a judge on their device to finish scoring, e.g. "Please complete Candidate 5 - Talent"
+pub async fn nudge_judge(
+    extract::State(pool): extract::State<PgPool>,
+    extract::State(tx): extract::State<broadcast::Sender<String>>,
+    extract::Extension(staff): extract::Extension<StaffClaims>,
+    extract::Path(judge_id): extract::Path<uuid::Uuid>,
+    axum::Json(payload): axum::Json<JudgeNudge>,
+) -> Result<axum::Json<NudgeResult>, AppError> {
+    let judge = sqlx::query_as::<_, Judge>("SELECT * FROM judges WHERE id = ($1)")
+        .bind(&judge_id)
+        .fetch_optional(&pool)
+        .await?
+        .ok_or_else(|| AppError::new(http::StatusCode::NOT_FOUND, "Judge not found"))?;
+
+    let message = match payload.message.filter(|message| !message.trim().is_empty()) {
+        Some(message) => message,
+        None => {
+            let candidate_number = match payload.candidate_id {
+                Some(candidate_id) => sqlx::query_scalar::<_, i32>(
+                    "SELECT candidate_number FROM candidates WHERE id = ($1)",
+                )
+                .bind(&candidate_id)
+                .fetch_optional(&pool)
+                .await?,
+                None => None,
+            };
+
+            let category_name = match payload.category_id {
+                Some(category_id) => {
+                    sqlx::query_scalar::<_, String>("SELECT name FROM categories WHERE id = ($1)")
+                        .bind(&category_id)
+                        .fetch_optional(&pool)
+                        .await?
+                }
+                None => None,
+            };
+
+            match (candidate_number, category_name) {
+                (Some(number), Some(category)) => {
+                    format!("Please complete Candidate {} - {}", number, category)
+                }
+                (Some(number), None) => format!("Please complete Candidate {}", number),
+                (None, Some(category)) => format!("Please complete your scores for {}", category),
+                (None, None) => "Please complete your scores".to_string(),
+            }
+        }
+    };
+
+    realtime::publish(
+        &tx,
+        &RealtimeMessage::JudgeNudge {
+            judge_id: judge.id,
+            message: message.clone(),
+        },
+    );
+
+    audit::record(
+        &pool,
+        staff.sub,
+        "judge",
+        Some(&judge.id),
+        "nudge",
+        serde_json::json!({
+            "message": message,
+            "candidate_id": payload.candidate_id,
+            "category_id": payload.category_id,
+        }),
+    )
+    .await?;
+
+    Ok(axum::Json(NudgeResult {
+        judge_id: judge.id,
+        message,
+    }))
+}
+
+// Locks or unlocks every judge of the event at once
+pub async fn set_event_judges_lock(
+    extract::State(pool): extract::State<PgPool>,
+    extract::State(tx): extract::State<broadcast::Sender<String>>,
+    extract::Path(event_id): extract::Path<uuid::Uuid>,
+    axum::Json(payload): axum::Json<JudgeLock>,
+) -> Result<axum::Json<Vec<Judge>>, AppError> {
+    let judges = lock_event_judges(&pool, &tx, &event_id, payload.is_locked).await?;
+
+    Ok(axum::Json(judges))
+}
+
+pub async fn lock_event_judges(
+    pool: &PgPool,
+    tx: &broadcast::Sender<String>,
+    event_id: &uuid::Uuid,
+    is_locked: bool,
+) -> Result<Vec<Judge>, AppError> {
+    let judges = sqlx::query_as::<_, Judge>(
+        "UPDATE judges SET is_locked = ($1) WHERE event_id = ($2) RETURNING *",
+    )
+    .bind(is_locked)
+    .bind(event_id)
+    .fetch_all(pool)
+    .await?;
+
+    for judge in judges.iter() {
+        realtime::publish(
+            tx,
+            &RealtimeMessage::JudgeLock {
+                judge_id: judge.id,
+                is_locked: judge.is_locked,
+            },
+        );
+    }
+
+    Ok(judges)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SubstituteJudge {
+    name: String,
+    username: String,
+    password: String,
+}
+
+// Replaces an absent judge mid-event
+// Categories the original judge already scored stand, the substitute scores the rest
+pub async fn substitute_judge(
+    extract::State(pool): extract::State<PgPool>,
+    extract::Extension(staff): extract::Extension<StaffClaims>,
+    extract::Path(judge_id): extract::Path<uuid::Uuid>,
+    axum::Json(payload): axum::Json<SubstituteJudge>,
+) -> Result<(http::StatusCode, axum::Json<Judge>), AppError> {
+    let event_id: uuid::Uuid = sqlx::query_scalar("SELECT event_id FROM judges WHERE id = ($1)")
+        .bind(&judge_id)
+        .fetch_optional(&pool)
+        .await?
+        .ok_or_else(|| AppError::new(http::StatusCode::NOT_FOUND, "Judge not found"))?;
+
+    let mut txn = pool.begin().await?;
+    lock_username(&mut txn, &payload.username).await?;
+
+    let original = sqlx::query_as::<_, Judge>(
+        r#"
+        UPDATE judges SET is_active = FALSE, substituted_at = NOW()
+        WHERE id = ($1) AND substituted_at IS NULL
+        RETURNING *
+        "#,
+    )
+    .bind(&judge_id)
+    .fetch_optional(&mut *txn)
+    .await?
+    .ok_or_else(|| {
+        AppError::new(
+            http::StatusCode::CONFLICT,
+            "Judge does not exist or has already been substituted",
+        )
+    })?;
+
+    let substitute = sqlx::query_as::<_, Judge>(
+        r#"
+        INSERT INTO judges (name, username, password, is_active, event_id, score_exclusion, substitute_for)
+        SELECT ($1), ($2), ($3), FALSE, event_id, score_exclusion, ($4)
+        FROM judges WHERE id = ($5)
+        AND NOT (($6) AND EXISTS (SELECT 1 FROM judges WHERE username = ($2)))
+        RETURNING *
+        "#,
+    )
+    .bind(&payload.name)
+    .bind(&payload.username)
+    .bind(auth::hash_password(&payload.password)?)
+    .bind(original.substitute_for.unwrap_or(original.id))
+    .bind(&original.id)
+    .bind(username_scope_is_global())
+    .fetch_optional(&mut *txn)
+    .await;
+
+    let substitute = match substitute {
+        Ok(Some(substitute)) => substitute,
+        Ok(None) => return Err(username_conflict(&pool, &event_id, &payload.username).await),
+        Err(err) if is_username_violation(&err) => {
+            return Err(username_conflict(&pool, &event_id, &payload.username).await);
+        }
+        Err(err) => return Err(err.into()),
+    };
+
+    audit::record(
+        &mut *txn,
+        staff.sub,
+        "judge",
+        Some(&original.id),
+        "substitute",
+        serde_json::json!({ "substitute_id": substitute.id }),
+    )
+    .await?;
+
+    txn.commit().await?;
+
+    println!("{} has been substituted by {}", original.name, substitute.name);
+
+    Ok((http::StatusCode::CREATED, axum::Json(substitute)))
+}
+
+// Scores can only come from whoever currently holds the panel seat,
+// and a substitute can't rescore candidates the original judge already scored (on that criteria, if given)
+pub async fn validate_judge_seat(
+    pool: &PgPool,
+    judge_id: &uuid::Uuid,
+    category_id: &uuid::Uuid,
+    candidate_ids: &[uuid::Uuid],
+    criteria_id: Option<&uuid::Uuid>,
+) -> Result<(), AppError> {
+    let (is_substituted, is_covered) = sqlx::query_as::<_, (bool, bool)>(
+        r#"
+        SELECT
+            j.substituted_at IS NOT NULL,
+            EXISTS (
+                SELECT 1 FROM scores s
+                JOIN judges seat ON seat.id = s.judge_id
+                WHERE s.category_id = ($2)
+                    AND s.candidate_id = ANY($3)
+                    AND (($4)::UUID IS NULL OR s.criteria_id = ($4))
+                    AND seat.id <> j.id
+                    AND seat.substituted_at IS NOT NULL
+                    AND COALESCE(seat.substitute_for, seat.id) = j.substitute_for
+            )
+        FROM judges j
+        WHERE j.id = ($1)
+        "#,
+    )
+    .bind(judge_id)
+    .bind(category_id)
+    .bind(candidate_ids)
+    .bind(criteria_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::new(http::StatusCode::NOT_FOUND, "Judge not found"))?;
+
+    if is_substituted {
+        return Err(AppError::new(
+            http::StatusCode::FORBIDDEN,
+            "Judge has been substituted and can no longer submit scores",
+        ));
+    }
+
+    if is_covered {
+        return Err(AppError::new(
+            http::StatusCode::CONFLICT,
+            "This candidate was already scored by the judge you are substituting for",
+        ));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,588 @@
+use std::collections::{BTreeMap, HashMap};
+use std::io::Cursor;
+
+use axum::extract::{Extension, Multipart, State};
+use axum::http;
+use axum::response::Result;
+use calamine::Reader;
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use sqlx::{PgPool, Postgres, QueryBuilder};
+
+use crate::error::AppError;
+
+use super::admin::StaffClaims;
+use super::audit;
+use super::history;
+use super::score::CandidateFinalScore2;
+use super::tie_break;
+
+// How the columns of a legacy tabulation file map to ours
+// Every row is one judge's scores of one candidate in one category
+#[derive(Debug, Deserialize)]
+pub struct LegacyTemplate {
+    pub event_name: String,
+    // Day of the event, the hall of fame groups results by its year
+    pub held_on: chrono::NaiveDate,
+    // First sheet when not set
+    pub sheet: Option<String>,
+    // 1-based row holding the column names
+    #[serde(default = "default_header_row")]
+    pub header_row: usize,
+    pub columns: LegacyColumns,
+    pub criteria: Vec<LegacyCriteria>,
+    // Category name -> weight, categories left out share the rest equally
+    #[serde(default)]
+    pub category_weights: HashMap<String, f32>,
+}
+
+fn default_header_row() -> usize {
+    1
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LegacyColumns {
+    pub candidate_number: String,
+    pub first_name: String,
+    pub middle_name: Option<String>,
+    pub last_name: String,
+    pub gender: String,
+    pub college: Option<String>,
+    pub category: String,
+    pub judge: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LegacyCriteria {
+    pub column: String,
+    // The column name when not set
+    pub name: Option<String>,
+    pub max_score: i32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LegacyRow {
+    pub candidate_number: i32,
+    pub first_name: String,
+    pub middle_name: String,
+    pub last_name: String,
+    pub gender: i32,
+    pub college: Option<String>,
+    pub category: String,
+    pub judge: String,
+    // Index into the template's criteria -> points
+    pub scores: Vec<(usize, i32)>,
+}
+
+// Old files spell it out in all sorts of ways
+pub fn parse_gender(value: &str) -> Option<i32> {
+    match value.trim().to_lowercase().as_str() {
+        "1" | "m" | "male" | "mr" | "mr." => Some(1),
+        "0" | "f" | "female" | "ms" | "ms." => Some(0),
+        _ => None,
+    }
+}
+
+pub fn find_column(headers: &[String], name: &str) -> Result<usize, String> {
+    headers
+        .iter()
+        .position(|header| header.trim().eq_ignore_ascii_case(name.trim()))
+        .ok_or_else(|| format!("Column {} not found", name))
+}
+
+// `rows` are the cells of the sheet as text, starting at the first row of the sheet
+pub fn parse_rows(
+    rows: &[Vec<String>],
+    template: &LegacyTemplate,
+) -> Result<Vec<LegacyRow>, String> {
+    let header_idx = template.header_row.saturating_sub(1);
+    let headers = rows
+        .get(header_idx)
+        .ok_or_else(|| format!("Row {} doesn't exist", template.header_row))?;
+
+    let columns = &template.columns;
+
+    let candidate_number_col = find_column(headers, &columns.candidate_number)?;
+    let first_name_col = find_column(headers, &columns.first_name)?;
+    let middle_name_col = match &columns.middle_name {
+        Some(column) => Some(find_column(headers, column)?),
+        None => None,
+    };
+    let last_name_col = find_column(headers, &columns.last_name)?;
+    let gender_col = find_column(headers, &columns.gender)?;
+    let college_col = match &columns.college {
+        Some(column) => Some(find_column(headers, column)?),
+        None => None,
+    };
+    let category_col = find_column(headers, &columns.category)?;
+    let judge_col = find_column(headers, &columns.judge)?;
+
+    let criteria_cols = template
+        .criteria
+        .iter()
+        .map(|criteria| find_column(headers, &criteria.column))
+        .collect::<Result<Vec<usize>, String>>()?;
+
+    let mut parsed = Vec::new();
+
+    for (idx, row) in rows.iter().enumerate().skip(header_idx + 1) {
+        let cell = |col: usize| row.get(col).map(|value| value.trim()).unwrap_or("");
+        let line = idx + 1;
+
+        // Totals and spacer rows have no candidate number
+        if cell(candidate_number_col).is_empty() {
+            continue;
+        }
+
+        let candidate_number = cell(candidate_number_col)
+            .parse::<f64>()
+            .map_err(|_| format!("Row {}: invalid candidate number", line))?
+            as i32;
+
+        let gender = parse_gender(cell(gender_col))
+            .ok_or_else(|| format!("Row {}: unknown gender {}", line, cell(gender_col)))?;
+
+        if cell(category_col).is_empty() || cell(judge_col).is_empty() {
+            return Err(format!("Row {}: missing category or judge", line));
+        }
+
+        let mut scores = Vec::new();
+
+        for (criteria_idx, (criteria, col)) in template
+            .criteria
+            .iter()
+            .zip(criteria_cols.iter())
+            .enumerate()
+        {
+            // Criteria of other categories are left blank
+            if cell(*col).is_empty() {
+                continue;
+            }
+
+            let points = cell(*col)
+                .parse::<f64>()
+                .map_err(|_| format!("Row {}: invalid score for {}", line, criteria.column))?
+                .round() as i32;
+
+            if points < 0 || points > criteria.max_score {
+                return Err(format!(
+                    "Row {}: {} is out of range for {} (0-{})",
+                    line, points, criteria.column, criteria.max_score
+                ));
+            }
+
+            scores.push((criteria_idx, points));
+        }
+
+        parsed.push(LegacyRow {
+            candidate_number,
+            first_name: cell(first_name_col).to_string(),
+            middle_name: middle_name_col.map(cell).unwrap_or("").to_string(),
+            last_name: cell(last_name_col).to_string(),
+            gender,
+            college: college_col
+                .map(cell)
+                .filter(|college| !college.is_empty())
+                .map(|college| college.to_string()),
+            category: cell(category_col).to_string(),
+            judge: cell(judge_col).to_string(),
+            scores,
+        });
+    }
+
+    if parsed.is_empty() {
+        return Err("The sheet has no score rows".to_string());
+    }
+
+    Ok(parsed)
+}
+
+// Reads .xlsx, .xls and .ods files alike
+pub fn read_sheet(bytes: Vec<u8>, sheet: Option<&str>) -> Result<Vec<Vec<String>>, String> {
+    let mut workbook = calamine::open_workbook_auto_from_rs(Cursor::new(bytes))
+        .map_err(|err| format!("Failed to open the spreadsheet: {}", err))?;
+
+    let range = match sheet {
+        Some(sheet) => workbook.worksheet_range(sheet),
+        None => workbook
+            .worksheet_range_at(0)
+            .ok_or_else(|| "The spreadsheet has no sheets".to_string())?,
+    }
+    .map_err(|err| format!("Failed to read the sheet: {}", err))?;
+
+    // Ranges start at the first used cell, pad them so row numbers match the file
+    let (start_row, start_col) = range.start().unwrap_or((0, 0));
+    let mut rows = vec![Vec::new(); start_row as usize];
+
+    for row in range.rows() {
+        let mut cells = vec![String::new(); start_col as usize];
+        cells.extend(row.iter().map(|cell| cell.to_string()));
+        rows.push(cells);
+    }
+
+    Ok(rows)
+}
+
+// Category name -> weight, the template's weights first and an equal split of the rest
+fn category_weights(categories: &[String], template: &LegacyTemplate) -> HashMap<String, f32> {
+    let assigned: f32 = categories
+        .iter()
+        .filter_map(|category| template.category_weights.get(category))
+        .sum();
+    let unassigned = categories
+        .iter()
+        .filter(|category| !template.category_weights.contains_key(*category))
+        .count();
+
+    categories
+        .iter()
+        .map(|category| {
+            let weight = match template.category_weights.get(category) {
+                Some(weight) => *weight,
+                None => (1.0 - assigned).max(0.0) / unassigned as f32,
+            };
+
+            (category.clone(), weight)
+        })
+        .collect()
+}
+
+// Weighted percentage over every category, the same formula as `calculate_final_scores`
+pub fn legacy_standings(
+    rows: &[LegacyRow],
+    template: &LegacyTemplate,
+    candidate_ids: &HashMap<(i32, i32), uuid::Uuid>,
+) -> Vec<CandidateFinalScore2> {
+    let categories: Vec<String> = rows
+        .iter()
+        .map(|row| row.category.clone())
+        .collect::<std::collections::BTreeSet<String>>()
+        .into_iter()
+        .collect();
+    let weights = category_weights(&categories, template);
+
+    // (gender, candidate number) -> (standing, weighted scores sum, weighted max sum)
+    let mut standings: BTreeMap<(i32, i32), (CandidateFinalScore2, f32, f32)> = BTreeMap::new();
+
+    for row in rows {
+        let key = (row.gender, row.candidate_number);
+        let weight = weights.get(&row.category).copied().unwrap_or(0.0);
+
+        let (standing, weighted_scores_sum, weighted_max_sum) =
+            standings.entry(key).or_insert_with(|| {
+                (
+                    CandidateFinalScore2 {
+                        candidate_id: candidate_ids.get(&key).copied().unwrap_or_default(),
+                        candidate_number: row.candidate_number,
+                        first_name: row.first_name.clone(),
+                        middle_name: row.middle_name.clone(),
+                        last_name: row.last_name.clone(),
+                        gender: row.gender,
+                        final_score: 0.0,
+                        total_points: 0.0,
+                    },
+                    0.0,
+                    0.0,
+                )
+            });
+
+        for (criteria_idx, points) in row.scores.iter() {
+            let max_score = template.criteria[*criteria_idx].max_score;
+
+            standing.total_points += *points as f32;
+            *weighted_scores_sum += *points as f32 * weight;
+            *weighted_max_sum += max_score as f32 * weight;
+        }
+    }
+
+    standings
+        .into_values()
+        .map(|(mut standing, weighted_scores_sum, weighted_max_sum)| {
+            if weighted_max_sum > 0.0 {
+                standing.final_score = weighted_scores_sum / weighted_max_sum * 100.0;
+            }
+
+            standing
+        })
+        .collect()
+}
+
+#[derive(Debug, Serialize)]
+pub struct LegacyImport {
+    event_id: uuid::Uuid,
+    categories: usize,
+    judges: usize,
+    candidates: usize,
+    scores: usize,
+}
+
+// Multipart form with a `template` JSON field and the spreadsheet as `file`
+// Creates an archived, finalized event so the hall of fame and analytics cover it
+pub async fn import_legacy_results(
+    State(pool): State<PgPool>,
+    Extension(staff): Extension<StaffClaims>,
+    mut multipart: Multipart,
+) -> Result<(http::StatusCode, axum::Json<LegacyImport>), AppError> {
+    let mut template: Option<LegacyTemplate> = None;
+    let mut file: Option<Vec<u8>> = None;
+
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|err| AppError::new(http::StatusCode::BAD_REQUEST, err.to_string()))?
+    {
+        let name = field.name().unwrap_or("").to_string();
+        let data = field
+            .bytes()
+            .await
+            .map_err(|err| AppError::new(http::StatusCode::BAD_REQUEST, err.to_string()))?;
+
+        match name.as_str() {
+            "template" => {
+                template = Some(serde_json::from_slice(&data).map_err(|err| {
+                    AppError::new(
+                        http::StatusCode::UNPROCESSABLE_ENTITY,
+                        format!("Invalid template: {}", err),
+                    )
+                })?)
+            }
+            "file" => file = Some(data.to_vec()),
+            _ => {}
+        }
+    }
+
+    let (Some(template), Some(file)) = (template, file) else {
+        return Err(AppError::new(
+            http::StatusCode::BAD_REQUEST,
+            "Send both the `template` and the `file` fields",
+        ));
+    };
+
+    let unprocessable = |err: String| AppError::new(http::StatusCode::UNPROCESSABLE_ENTITY, err);
+
+    let sheet = read_sheet(file, template.sheet.as_deref()).map_err(unprocessable)?;
+    let rows = parse_rows(&sheet, &template).map_err(unprocessable)?;
+
+    let held_at = template
+        .held_on
+        .and_hms_opt(12, 0, 0)
+        .unwrap_or_default()
+        .and_utc();
+
+    let mut txn = pool.begin().await?;
+
+    // Nobody scores an imported event, so it starts out archived with nothing left to approve
+    let event_id: uuid::Uuid = sqlx::query_scalar(
+        "INSERT INTO events (name, is_archived, required_approvals) VALUES ($1, TRUE, 0) RETURNING id",
+    )
+    .bind(&template.event_name)
+    .fetch_one(&mut *txn)
+    .await?;
+
+    let category_names: Vec<String> = rows
+        .iter()
+        .map(|row| row.category.clone())
+        .collect::<std::collections::BTreeSet<String>>()
+        .into_iter()
+        .collect();
+    let weights = category_weights(&category_names, &template);
+
+    // Category name -> ID, (category name, criteria index) -> ID
+    let mut category_ids: HashMap<String, uuid::Uuid> = HashMap::new();
+    let mut criteria_ids: HashMap<(String, usize), uuid::Uuid> = HashMap::new();
+
+    for name in category_names.iter() {
+        let category_id: uuid::Uuid = sqlx::query_scalar(
+            "INSERT INTO categories (name, weight, event_id, is_locked) VALUES ($1, $2, $3, TRUE) RETURNING id",
+        )
+        .bind(name)
+        .bind(weights.get(name).copied().unwrap_or(0.0))
+        .bind(&event_id)
+        .fetch_one(&mut *txn)
+        .await?;
+
+        category_ids.insert(name.clone(), category_id);
+
+        // Only the criteria that were scored in this category
+        let mut scored: Vec<usize> = rows
+            .iter()
+            .filter(|row| &row.category == name)
+            .flat_map(|row| row.scores.iter().map(|(criteria_idx, _)| *criteria_idx))
+            .collect();
+        scored.sort();
+        scored.dedup();
+
+        for criteria_idx in scored {
+            let criteria = &template.criteria[criteria_idx];
+
+            let criteria_id: uuid::Uuid = sqlx::query_scalar(
+                r#"
+                INSERT INTO criterias (name, description, max_score, category_id, criteria_type, is_deduction, min_score)
+                VALUES ($1, NULL, $2, $3, 'numeric', FALSE, 0)
+                RETURNING id
+                "#,
+            )
+            .bind(criteria.name.as_ref().unwrap_or(&criteria.column))
+            .bind(criteria.max_score)
+            .bind(&category_id)
+            .fetch_one(&mut *txn)
+            .await?;
+
+            criteria_ids.insert((name.clone(), criteria_idx), criteria_id);
+        }
+    }
+
+    let mut judge_ids: HashMap<String, uuid::Uuid> = HashMap::new();
+
+    for row in rows.iter() {
+        if judge_ids.contains_key(&row.judge) {
+            continue;
+        }
+
+        // Never logged into, the password is just something nobody knows
+        let judge_id: uuid::Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO judges (name, username, password, is_active, event_id)
+            VALUES ($1, $2, replace(gen_random_uuid()::TEXT, '-', ''), FALSE, $3)
+            RETURNING id
+            "#,
+        )
+        .bind(&row.judge)
+        .bind(format!(
+            "legacy{}-{}",
+            template.held_on.format("%Y"),
+            judge_ids.len() + 1
+        ))
+        .bind(&event_id)
+        .fetch_one(&mut *txn)
+        .await?;
+
+        judge_ids.insert(row.judge.clone(), judge_id);
+    }
+
+    let first_category_id = category_ids[&category_names[0]];
+    let mut candidate_ids: HashMap<(i32, i32), uuid::Uuid> = HashMap::new();
+
+    for row in rows.iter() {
+        let key = (row.gender, row.candidate_number);
+
+        if candidate_ids.contains_key(&key) {
+            continue;
+        }
+
+        // Candidates aren't scoped to events, deleting them keeps them off the live lineup
+        let candidate_id: uuid::Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO candidates (first_name, middle_name, last_name, gender, college_id, category_id, candidate_number, deleted_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, NOW())
+            RETURNING id
+            "#,
+        )
+        .bind(&row.first_name)
+        .bind(&row.middle_name)
+        .bind(&row.last_name)
+        .bind(row.gender)
+        .bind(&row.college)
+        .bind(&first_category_id)
+        .bind(row.candidate_number)
+        .fetch_one(&mut *txn)
+        .await?;
+
+        candidate_ids.insert(key, candidate_id);
+    }
+
+    // (candidate, criteria, category, judge, points, max)
+    let mut scores: Vec<(uuid::Uuid, uuid::Uuid, uuid::Uuid, uuid::Uuid, i32, i32)> = Vec::new();
+
+    for row in rows.iter() {
+        for (criteria_idx, points) in row.scores.iter() {
+            scores.push((
+                candidate_ids[&(row.gender, row.candidate_number)],
+                criteria_ids[&(row.category.clone(), *criteria_idx)],
+                category_ids[&row.category],
+                judge_ids[&row.judge],
+                *points,
+                template.criteria[*criteria_idx].max_score,
+            ));
+        }
+    }
+
+    // Straight into the archive, like an event archived after it closed
+    for chunk in scores.chunks(1000) {
+        let mut query_builder: QueryBuilder<Postgres> = QueryBuilder::new(
+            "INSERT INTO scores_archive (score, max, time_of_scoring, candidate_id, criteria_id, category_id, judge_id) ",
+        );
+
+        query_builder.push_values(
+            chunk,
+            |mut builder, (candidate_id, criteria_id, category_id, judge_id, points, max)| {
+                builder
+                    .push_bind(*points)
+                    .push_bind(*max)
+                    .push_bind(held_at)
+                    .push_bind(*candidate_id)
+                    .push_bind(*criteria_id)
+                    .push_bind(*category_id)
+                    .push_bind(*judge_id);
+            },
+        );
+
+        query_builder.build().execute(&mut *txn).await?;
+    }
+
+    // The snapshot gets no hashes, the chain only covers results tabulated here
+    let standings = legacy_standings(&rows, &template, &candidate_ids);
+    let awards = history::pick_award_winners(
+        &standings,
+        &BTreeMap::new(),
+        &tie_break::TieBreakKeys::new(),
+    );
+
+    let snapshot_id: uuid::Uuid = sqlx::query_scalar(
+        r#"
+        INSERT INTO standings_snapshots (version, standings, event_id, created_at, awards)
+        VALUES (1, $1, $2, $3, $4)
+        RETURNING id
+        "#,
+    )
+    .bind(Json(&standings))
+    .bind(&event_id)
+    .bind(held_at)
+    .bind(Json(&awards))
+    .fetch_one(&mut *txn)
+    .await?;
+
+    sqlx::query("UPDATE events SET finalized_snapshot_id = ($1) WHERE id = ($2)")
+        .bind(&snapshot_id)
+        .bind(&event_id)
+        .execute(&mut *txn)
+        .await?;
+
+    let summary = LegacyImport {
+        event_id,
+        categories: category_ids.len(),
+        judges: judge_ids.len(),
+        candidates: candidate_ids.len(),
+        scores: scores.len(),
+    };
+
+    audit::record(
+        &mut *txn,
+        staff.sub,
+        "event",
+        Some(&event_id),
+        "import_legacy",
+        serde_json::json!({
+            "held_on": template.held_on,
+            "categories": summary.categories,
+            "judges": summary.judges,
+            "candidates": summary.candidates,
+            "scores": summary.scores,
+        }),
+    )
+    .await?;
+
+    txn.commit().await?;
+
+    Ok((http::StatusCode::CREATED, axum::Json(summary)))
+}
@@ -0,0 +1,210 @@
+use axum::extract::{Extension, Path, Query, State};
+use axum::http;
+use axum::response::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgConnection, PgPool};
+
+use crate::error::AppError;
+
+use super::admin::StaffClaims;
+use super::audit;
+use super::candidate::Candidate;
+use super::category;
+
+// Each category is performed as its own segment, so the order is kept per category
+#[derive(Debug, Serialize)]
+pub struct LineupEntry {
+    // None for candidates added after the order was set, they go last
+    position: Option<i32>,
+    #[serde(flatten)]
+    candidate: Candidate,
+}
+
+#[derive(Debug, FromRow)]
+struct LineupRow {
+    position: Option<i32>,
+    #[sqlx(flatten)]
+    candidate: Candidate,
+}
+
+// The candidates of the category's event
+// With a judge, candidates they are blacked out from are left out of their scoring screen
+async fn fetch_lineup(
+    pool: &PgPool,
+    category_id: &uuid::Uuid,
+    judge_id: Option<&uuid::Uuid>,
+) -> Result<Vec<LineupEntry>, AppError> {
+    let rows = sqlx::query_as::<_, LineupRow>(
+        r#"
+        SELECT l.position, c.*
+        FROM candidates c
+        JOIN categories home ON home.id = c.category_id
+        JOIN categories cat ON cat.id = ($1) AND cat.event_id = home.event_id
+        LEFT JOIN lineups l ON l.candidate_id = c.id AND l.category_id = cat.id
+        WHERE c.deleted_at IS NULL
+            AND NOT EXISTS (
+                SELECT 1 FROM judge_blackouts jb WHERE jb.judge_id = ($2) AND jb.candidate_id = c.id
+            )
+        ORDER BY l.position NULLS LAST, c.candidate_number
+        "#,
+    )
+    .bind(category_id)
+    .bind(judge_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| LineupEntry {
+            position: row.position,
+            candidate: row.candidate,
+        })
+        .collect())
+}
+
+// Candidates in performance order, what judges and the emcee go through
+#[derive(Debug, Deserialize)]
+pub struct LineupParam {
+    judge_id: Option<uuid::Uuid>,
+}
+
+pub async fn get_lineup(
+    State(pool): State<PgPool>,
+    Path((event_id, category_id)): Path<(uuid::Uuid, uuid::Uuid)>,
+    Query(param): Query<LineupParam>,
+) -> Result<axum::Json<Vec<LineupEntry>>, AppError> {
+    category::ensure_event_category(&pool, &event_id, &category_id).await?;
+
+    let lineup = fetch_lineup(&pool, &category_id, param.judge_id.as_ref()).await?;
+
+    Ok(axum::Json(lineup))
+}
+
+async fn replace_lineup(
+    conn: &mut PgConnection,
+    category_id: &uuid::Uuid,
+    candidate_ids: &[uuid::Uuid],
+) -> Result<(), AppError> {
+    sqlx::query("DELETE FROM lineups WHERE category_id = ($1)")
+        .bind(category_id)
+        .execute(&mut *conn)
+        .await?;
+
+    for (idx, candidate_id) in candidate_ids.iter().enumerate() {
+        sqlx::query(
+            "INSERT INTO lineups (position, category_id, candidate_id) VALUES ($1, $2, $3)",
+        )
+        .bind(idx as i32 + 1)
+        .bind(category_id)
+        .bind(candidate_id)
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateLineup {
+    candidate_ids: Vec<uuid::Uuid>,
+}
+
+pub async fn update_lineup(
+    State(pool): State<PgPool>,
+    Extension(staff): Extension<StaffClaims>,
+    Path((event_id, category_id)): Path<(uuid::Uuid, uuid::Uuid)>,
+    axum::Json(payload): axum::Json<UpdateLineup>,
+) -> Result<axum::Json<Vec<LineupEntry>>, AppError> {
+    category::ensure_event_category(&pool, &event_id, &category_id).await?;
+
+    let mut candidate_ids = payload.candidate_ids.clone();
+    candidate_ids.sort();
+    candidate_ids.dedup();
+
+    if candidate_ids.len() != payload.candidate_ids.len() {
+        return Err(AppError::new(
+            http::StatusCode::UNPROCESSABLE_ENTITY,
+            "A candidate can only appear once in the lineup",
+        ));
+    }
+
+    let event_candidates: i64 = sqlx::query_scalar(
+        r#"
+        SELECT COUNT(*) FROM candidates c
+        JOIN categories cat ON cat.id = c.category_id
+        WHERE c.id = ANY($1) AND cat.event_id = ($2) AND c.deleted_at IS NULL
+        "#,
+    )
+    .bind(&candidate_ids)
+    .bind(&event_id)
+    .fetch_one(&pool)
+    .await?;
+
+    if event_candidates != candidate_ids.len() as i64 {
+        return Err(AppError::new(
+            http::StatusCode::UNPROCESSABLE_ENTITY,
+            "The lineup can only have the event's candidates",
+        ));
+    }
+
+    let mut txn = pool.begin().await?;
+
+    replace_lineup(&mut txn, &category_id, &payload.candidate_ids).await?;
+
+    audit::record(
+        &mut *txn,
+        staff.sub,
+        "category",
+        Some(&category_id),
+        "arrange_lineup",
+        serde_json::json!({ "candidate_ids": payload.candidate_ids }),
+    )
+    .await?;
+
+    txn.commit().await?;
+
+    let lineup = fetch_lineup(&pool, &category_id, None).await?;
+
+    Ok(axum::Json(lineup))
+}
+
+// Random order for every candidate of the event, shuffled by Postgres
+pub async fn draw_lineup(
+    State(pool): State<PgPool>,
+    Extension(staff): Extension<StaffClaims>,
+    Path((event_id, category_id)): Path<(uuid::Uuid, uuid::Uuid)>,
+) -> Result<axum::Json<Vec<LineupEntry>>, AppError> {
+    category::ensure_event_category(&pool, &event_id, &category_id).await?;
+
+    let mut txn = pool.begin().await?;
+
+    let candidate_ids = sqlx::query_scalar::<_, uuid::Uuid>(
+        r#"
+        SELECT c.id FROM candidates c
+        JOIN categories cat ON cat.id = c.category_id
+        WHERE cat.event_id = ($1) AND c.deleted_at IS NULL
+        ORDER BY random()
+        "#,
+    )
+    .bind(&event_id)
+    .fetch_all(&mut *txn)
+    .await?;
+
+    replace_lineup(&mut txn, &category_id, &candidate_ids).await?;
+
+    audit::record(
+        &mut *txn,
+        staff.sub,
+        "category",
+        Some(&category_id),
+        "draw_lineup",
+        serde_json::json!({ "candidate_ids": candidate_ids }),
+    )
+    .await?;
+
+    txn.commit().await?;
+
+    let lineup = fetch_lineup(&pool, &category_id, None).await?;
+
+    Ok(axum::Json(lineup))
+}
@@ -1,22 +1,65 @@
-use sqlx::FromRow;
-
+pub mod admin;
+pub mod advancement;
+pub mod analytics;
+pub mod announcement;
+pub mod approval;
+pub mod archive;
+pub mod audit;
 pub mod auth;
+pub mod blackout;
+pub mod bracket;
 pub mod candidate;
 pub mod category;
 pub mod college;
+pub mod compact;
+pub mod confirmation;
+pub mod correction;
+pub mod cover;
 pub mod criteria;
+pub mod deduction;
+pub mod device;
 pub mod event;
+pub mod export;
+pub mod group;
+pub mod history;
+pub mod impersonation;
+pub mod integrity;
 pub mod judge;
+pub mod legacy;
+pub mod lineup;
 pub mod note;
+pub mod observer;
+pub mod public;
+pub mod quorum;
+pub mod readiness;
+pub mod realtime;
+pub mod receipt;
+pub mod recheck;
+pub mod registration;
+pub mod report;
+pub mod round;
 pub mod score;
+pub mod score_feed;
+pub mod settings;
+pub mod standings;
+#[cfg(test)]
 pub mod tests;
+pub mod tie_break;
+pub mod timing;
 
 pub trait Round {
     fn round_to_two_decimals(&self) -> f64;
+    fn round_to_decimals(&self, decimals: u32) -> f64;
 }
 
 impl Round for f64 {
     fn round_to_two_decimals(&self) -> f64 {
         (self * 100.0).round() / 100.0
     }
+
+    fn round_to_decimals(&self, decimals: u32) -> f64 {
+        let factor = 10_f64.powi(decimals as i32);
+
+        (self * factor).round() / factor
+    }
 }
@@ -4,6 +4,10 @@ use axum::response::Result;
 use serde::{Deserialize, Serialize};
 use sqlx::{FromRow, PgPool};
 
+use crate::error::AppError;
+
+use super::auth::AuthenticatedJudge;
+
 #[derive(Debug, Serialize, FromRow)]
 pub struct Note {
     id: uuid::Uuid,
@@ -23,8 +27,11 @@ pub struct CreateNote {
 
 pub async fn create_note(
     State(pool): State<PgPool>,
+    judge: AuthenticatedJudge,
     axum::Json(payload): axum::Json<CreateNote>,
-) -> Result<(http::StatusCode, axum::Json<Note>), http::StatusCode> {
+) -> Result<(http::StatusCode, axum::Json<Note>), AppError> {
+    judge.ensure_judge(&payload.judge_id)?;
+
     let res = sqlx::query_as::<_, Note>(
         r#"
         INSERT INTO notes (note, candidate_id, judge_id) 
@@ -43,7 +50,10 @@ pub async fn create_note(
         Err(err) => {
             eprintln!("Failed to create note: {err:?}");
 
-            Err(http::StatusCode::INTERNAL_SERVER_ERROR)
+            Err(AppError::new(
+                http::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to create note: {}", err),
+            ))
         }
     }
 }
@@ -0,0 +1,199 @@
+use axum::extract::{Extension, Path, State};
+use axum::http;
+use axum::response::{IntoResponse, Response, Result};
+use serde::Serialize;
+use sqlx::types::Json;
+use sqlx::{FromRow, PgPool};
+
+use crate::error::AppError;
+
+use super::admin::StaffClaims;
+use super::audit;
+use super::score::{fetch_display_decimals, round_final_scores, CandidateFinalScore2};
+use super::standings;
+
+// Approved results never change, unless a correction is finalized under a new snapshot
+const FINAL_CACHE_CONTROL: &str = "public, max-age=86400";
+
+// Shape for the org's website, kept separate so internal standings can change freely
+#[derive(Debug, Serialize)]
+pub struct PublicResults {
+    event: String,
+    // `pending` until the results are finalized and approved
+    status: &'static str,
+    published_at: Option<chrono::DateTime<chrono::Utc>>,
+    divisions: Vec<PublicDivision>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PublicDivision {
+    name: String,
+    placements: Vec<PublicPlacement>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PublicPlacement {
+    rank: usize,
+    candidate_number: i32,
+    name: String,
+    // Percentage or raw points, following the event's `standings_display`
+    score: f32,
+}
+
+#[derive(Debug, FromRow)]
+struct PublishedEvent {
+    id: uuid::Uuid,
+    name: String,
+    standings_display: String,
+    snapshot_id: Option<uuid::Uuid>,
+    approved: bool,
+}
+
+fn placements(standings: Vec<standings::RankedCandidate>, points: bool) -> Vec<PublicPlacement> {
+    standings
+        .into_iter()
+        .map(|ranked| PublicPlacement {
+            rank: ranked.rank,
+            candidate_number: ranked.candidate.candidate_number,
+            name: format!(
+                "{} {}",
+                ranked.candidate.first_name.trim(),
+                ranked.candidate.last_name.trim()
+            ),
+            score: if points {
+                ranked.candidate.total_points
+            } else {
+                ranked.candidate.final_score
+            },
+        })
+        .collect()
+}
+
+pub async fn get_public_results(
+    State(pool): State<PgPool>,
+    Path(token): Path<String>,
+) -> Result<Response, AppError> {
+    let event = sqlx::query_as::<_, PublishedEvent>(
+        r#"
+        SELECT e.id, e.name, e.standings_display, e.finalized_snapshot_id AS snapshot_id,
+            (
+                SELECT COUNT(*) FROM result_approvals ra WHERE ra.snapshot_id = e.finalized_snapshot_id
+            ) >= e.required_approvals AS approved
+        FROM events e
+        WHERE e.public_token = ($1)
+        "#,
+    )
+    .bind(&token)
+    .fetch_optional(&pool)
+    .await?
+    .ok_or_else(|| AppError::new(http::StatusCode::NOT_FOUND, "Results not found"))?;
+
+    let snapshot_id = match event.snapshot_id {
+        Some(snapshot_id) if event.approved => snapshot_id,
+        _ => {
+            let pending = PublicResults {
+                event: event.name,
+                status: "pending",
+                published_at: None,
+                divisions: Vec::new(),
+            };
+
+            return Ok((
+                [(http::header::CACHE_CONTROL, "no-store")],
+                axum::Json(pending),
+            )
+                .into_response());
+        }
+    };
+
+    let (mut results, published_at) =
+        sqlx::query_as::<
+            _,
+            (
+                Json<Vec<CandidateFinalScore2>>,
+                chrono::DateTime<chrono::Utc>,
+            ),
+        >("SELECT standings, created_at FROM standings_snapshots WHERE id = ($1)")
+        .bind(&snapshot_id)
+        .fetch_one(&pool)
+        .await?;
+
+    let decimals = fetch_display_decimals(&pool, &event.id).await?;
+    round_final_scores(&mut results.0, decimals);
+
+    let points = event.standings_display == "points";
+
+    let divisions = standings::divide_standings(&results.0)
+        .divisions
+        .into_iter()
+        .map(|division| PublicDivision {
+            name: division.division,
+            placements: placements(division.standings, points),
+        })
+        .collect();
+
+    let results = PublicResults {
+        event: event.name,
+        status: "final",
+        published_at: Some(published_at),
+        divisions,
+    };
+
+    Ok((
+        [
+            (http::header::CACHE_CONTROL, FINAL_CACHE_CONTROL.to_string()),
+            (http::header::ETAG, format!("\"{}\"", snapshot_id)),
+        ],
+        axum::Json(results),
+    )
+        .into_response())
+}
+
+#[derive(Debug, Serialize)]
+pub struct PublicToken {
+    token: String,
+}
+
+pub async fn get_public_token(
+    State(pool): State<PgPool>,
+    Path(event_id): Path<uuid::Uuid>,
+) -> Result<axum::Json<PublicToken>, AppError> {
+    let token = sqlx::query_scalar::<_, String>("SELECT public_token FROM events WHERE id = ($1)")
+        .bind(&event_id)
+        .fetch_optional(&pool)
+        .await?
+        .ok_or_else(|| AppError::new(http::StatusCode::NOT_FOUND, "Event not found"))?;
+
+    Ok(axum::Json(PublicToken { token }))
+}
+
+// Breaks every existing embed, for when the link was shared too early
+pub async fn rotate_public_token(
+    State(pool): State<PgPool>,
+    Extension(staff): Extension<StaffClaims>,
+    Path(event_id): Path<uuid::Uuid>,
+) -> Result<axum::Json<PublicToken>, AppError> {
+    let token = sqlx::query_scalar::<_, String>(
+        r#"
+        UPDATE events SET public_token = replace(gen_random_uuid()::TEXT, '-', '')
+        WHERE id = ($1)
+        RETURNING public_token
+        "#,
+    )
+    .bind(&event_id)
+    .fetch_optional(&pool)
+    .await?
+    .ok_or_else(|| AppError::new(http::StatusCode::NOT_FOUND, "Event not found"))?;
+
+    audit::record(
+        &pool,
+        staff.sub,
+        "event",
+        Some(&event_id),
+        "rotate_public_token",
+        serde_json::json!({}),
+    )
+    .await?;
+
+    Ok(axum::Json(PublicToken { token }))
+}
@@ -0,0 +1,152 @@
+use axum::extract::{Extension, Path, State};
+use axum::http;
+use axum::response::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+
+use crate::error::AppError;
+
+use super::admin::StaffClaims;
+use super::audit;
+
+// A candidate scored by fewer judges than their category's quorum
+#[derive(Debug, Serialize, FromRow)]
+pub struct QuorumGap {
+    pub category_id: uuid::Uuid,
+    pub category_name: String,
+    pub quorum: i32,
+    pub candidate_id: uuid::Uuid,
+    pub candidate_number: i32,
+    pub judges_scored: i64,
+}
+
+pub async fn find_quorum_gaps(
+    pool: &PgPool,
+    event_id: &uuid::Uuid,
+    category_id: Option<&uuid::Uuid>,
+) -> Result<Vec<QuorumGap>, AppError> {
+    // A substitute covers the same seat as the judge they replaced
+    let gaps = sqlx::query_as::<_, QuorumGap>(
+        r#"
+        SELECT
+            cat.id AS category_id,
+            cat.name AS category_name,
+            cat.quorum,
+            c.id AS candidate_id,
+            c.candidate_number,
+            COUNT(DISTINCT COALESCE(j.substitute_for, j.id)) AS judges_scored
+        FROM categories cat
+        JOIN categories home ON home.event_id = cat.event_id
+        JOIN candidates c ON c.category_id = home.id
+        LEFT JOIN scores_history s ON s.category_id = cat.id AND s.candidate_id = c.id
+        LEFT JOIN judges j ON j.id = s.judge_id AND j.score_exclusion = FALSE
+        WHERE cat.event_id = ($1)
+            AND cat.quorum IS NOT NULL
+            AND c.deleted_at IS NULL
+            AND (($2)::UUID IS NULL OR cat.id = ($2))
+            -- Candidates of past events are not part of this one
+            AND NOT EXISTS (
+                SELECT 1 FROM scores_archive a
+                JOIN categories ac ON ac.id = a.category_id
+                WHERE a.candidate_id = c.id AND ac.event_id <> cat.event_id
+            )
+        GROUP BY cat.id, cat.name, cat.quorum, c.id, c.candidate_number
+        HAVING COUNT(DISTINCT COALESCE(j.substitute_for, j.id)) < cat.quorum
+        ORDER BY cat.name, c.candidate_number
+        "#,
+    )
+    .bind(event_id)
+    .bind(category_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(gaps)
+}
+
+pub fn describe_quorum_gaps(gaps: &[QuorumGap]) -> String {
+    let candidates: Vec<String> = gaps
+        .iter()
+        .map(|gap| {
+            format!(
+                "#{} in {} ({} of {} judges)",
+                gap.candidate_number, gap.category_name, gap.judges_scored, gap.quorum
+            )
+        })
+        .collect();
+
+    format!("Judge quorum not met for {}", candidates.join(", "))
+}
+
+// Refuses to certify results while any candidate lacks judge coverage
+pub async fn ensure_quorum(
+    pool: &PgPool,
+    event_id: &uuid::Uuid,
+    category_id: Option<&uuid::Uuid>,
+) -> Result<(), AppError> {
+    let gaps = find_quorum_gaps(pool, event_id, category_id).await?;
+
+    if !gaps.is_empty() {
+        return Err(AppError::new(
+            http::StatusCode::CONFLICT,
+            describe_quorum_gaps(&gaps),
+        ));
+    }
+
+    Ok(())
+}
+
+pub async fn get_quorum_gaps(
+    State(pool): State<PgPool>,
+    Path(event_id): Path<uuid::Uuid>,
+) -> Result<axum::Json<Vec<QuorumGap>>, AppError> {
+    let gaps = find_quorum_gaps(&pool, &event_id, None).await?;
+
+    Ok(axum::Json(gaps))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateQuorum {
+    // None removes the quorum requirement
+    quorum: Option<i32>,
+}
+
+pub async fn set_category_quorum(
+    State(pool): State<PgPool>,
+    Extension(staff): Extension<StaffClaims>,
+    Path((event_id, category_id)): Path<(uuid::Uuid, uuid::Uuid)>,
+    axum::Json(payload): axum::Json<UpdateQuorum>,
+) -> Result<http::StatusCode, AppError> {
+    if payload.quorum.is_some_and(|quorum| quorum <= 0) {
+        return Err(AppError::new(
+            http::StatusCode::UNPROCESSABLE_ENTITY,
+            "Quorum must be at least 1 judge",
+        ));
+    }
+
+    let res =
+        sqlx::query("UPDATE categories SET quorum = ($1) WHERE id = ($2) AND event_id = ($3)")
+            .bind(&payload.quorum)
+            .bind(&category_id)
+            .bind(&event_id)
+            .execute(&pool)
+            .await?;
+
+    if res.rows_affected() == 0 {
+        return Err(AppError::new(
+            http::StatusCode::NOT_FOUND,
+            "Category not found",
+        ));
+    }
+
+    audit::record(
+        &pool,
+        staff.sub,
+        "category",
+        Some(&category_id),
+        "set_quorum",
+        serde_json::json!({ "quorum": payload.quorum }),
+    )
+    .await?;
+
+    Ok(http::StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,156 @@
+use axum::extract::{Path, State};
+use axum::response::Result;
+use serde::Serialize;
+use sqlx::PgPool;
+
+use crate::error::AppError;
+
+#[derive(Debug, Serialize)]
+pub struct ReadinessCheck {
+    name: &'static str,
+    passed: bool,
+    // What needs fixing when the check fails
+    issues: Vec<String>,
+}
+
+impl ReadinessCheck {
+    fn new(name: &'static str, issues: Vec<String>) -> Self {
+        Self {
+            name,
+            passed: issues.is_empty(),
+            issues,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Readiness {
+    ready: bool,
+    checks: Vec<ReadinessCheck>,
+}
+
+pub fn weights_sum_to_one(weights: &[f32]) -> bool {
+    (weights.iter().sum::<f32>() - 1.0).abs() < 0.0001
+}
+
+// Setup checklist to surface mistakes before the event starts
+pub async fn get_event_readiness(
+    State(pool): State<PgPool>,
+    Path(event_id): Path<uuid::Uuid>,
+) -> Result<axum::Json<Readiness>, AppError> {
+    let categories = sqlx::query_as::<_, (uuid::Uuid, String, f32)>(
+        "SELECT id, name, weight FROM categories WHERE event_id = ($1) ORDER BY name",
+    )
+    .bind(&event_id)
+    .fetch_all(&pool)
+    .await?;
+
+    let weights: Vec<f32> = categories.iter().map(|(_, _, weight)| *weight).collect();
+    let weight_issues = if weights_sum_to_one(&weights) {
+        Vec::new()
+    } else {
+        vec![format!(
+            "Category weights add up to {:.4} instead of 1",
+            weights.iter().sum::<f32>()
+        )]
+    };
+
+    let categories_without_criterias = sqlx::query_scalar::<_, String>(
+        r#"
+        SELECT cat.name FROM categories cat
+        WHERE cat.event_id = ($1)
+            AND NOT EXISTS (SELECT 1 FROM criterias cr WHERE cr.category_id = cat.id)
+        ORDER BY cat.name
+        "#,
+    )
+    .bind(&event_id)
+    .fetch_all(&pool)
+    .await?
+    .into_iter()
+    .map(|name| format!("{} has no criterias", name))
+    .collect();
+
+    let invalid_criterias = sqlx::query_as::<_, (String, String, i32)>(
+        r#"
+        SELECT cat.name, cr.name, cr.max_score FROM criterias cr
+        JOIN categories cat ON cat.id = cr.category_id
+        WHERE cat.event_id = ($1) AND cr.max_score <= 0
+        ORDER BY cat.name, cr.name
+        "#,
+    )
+    .bind(&event_id)
+    .fetch_all(&pool)
+    .await?
+    .into_iter()
+    .map(|(category, criteria, max_score)| {
+        format!(
+            "{} / {} has a max score of {}",
+            category, criteria, max_score
+        )
+    })
+    .collect();
+
+    let scoring_judges: i64 = sqlx::query_scalar(
+        r#"
+        SELECT COUNT(*) FROM judges
+        WHERE event_id = ($1) AND score_exclusion = FALSE AND substituted_at IS NULL
+        "#,
+    )
+    .bind(&event_id)
+    .fetch_one(&pool)
+    .await?;
+
+    let judge_issues = if scoring_judges > 0 {
+        Vec::new()
+    } else {
+        categories
+            .iter()
+            .map(|(_, name, _)| format!("{} has no judge that can score it", name))
+            .collect()
+    };
+
+    let candidate_number_issues = sqlx::query_as::<_, (String, String, Option<i32>, i64)>(
+        r#"
+        SELECT c.first_name, c.last_name, c.candidate_number,
+            COUNT(*) OVER (PARTITION BY c.gender, c.candidate_number) AS duplicates
+        FROM candidates c
+        JOIN categories cat ON cat.id = c.category_id
+        WHERE cat.event_id = ($1) AND c.deleted_at IS NULL
+        ORDER BY c.candidate_number
+        "#,
+    )
+    .bind(&event_id)
+    .fetch_all(&pool)
+    .await?
+    .into_iter()
+    .filter_map(
+        |(first_name, last_name, candidate_number, duplicates)| match candidate_number {
+            None | Some(..=0) => Some(format!(
+                "{} {} has no candidate number",
+                first_name.trim(),
+                last_name.trim()
+            )),
+            Some(number) if duplicates > 1 => Some(format!(
+                "{} {} shares candidate number {}",
+                first_name.trim(),
+                last_name.trim(),
+                number
+            )),
+            _ => None,
+        },
+    )
+    .collect();
+
+    let checks = vec![
+        ReadinessCheck::new("Category weights sum to 1", weight_issues),
+        ReadinessCheck::new("Every category has criterias", categories_without_criterias),
+        ReadinessCheck::new("Every criteria has a max score", invalid_criterias),
+        ReadinessCheck::new("Every category has a judge", judge_issues),
+        ReadinessCheck::new("Candidates have numbers", candidate_number_issues),
+    ];
+
+    Ok(axum::Json(Readiness {
+        ready: checks.iter().all(|check| check.passed),
+        checks,
+    }))
+}
@@ -0,0 +1,321 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Path, State};
+use axum::http;
+use axum::response::{Response, Result};
+use futures::{sink::SinkExt, stream::StreamExt};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use tokio::sync::broadcast;
+
+use crate::error::AppError;
+
+use super::analytics::{self, LatencyPercentiles};
+use super::announcement::Announcement;
+use super::score::{
+    compute_event_final_scores, fetch_display_decimals, round_final_scores, CandidateFinalScore2,
+};
+
+// Messages pushed by the backend itself, next to the Postgres notifications
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RealtimeMessage {
+    JudgeLock {
+        judge_id: uuid::Uuid,
+        is_locked: bool,
+    },
+    Standings {
+        event_id: uuid::Uuid,
+        standings: Vec<CandidateFinalScore2>,
+    },
+    Announcement(Announcement),
+    DatabaseStatus {
+        degraded: bool,
+    },
+    JudgeNudge {
+        judge_id: uuid::Uuid,
+        message: String,
+    },
+    JudgePresence {
+        event_id: uuid::Uuid,
+        judge_id: uuid::Uuid,
+        online: bool,
+        online_judges: i64,
+    },
+}
+
+// Sends a message to every connected client
+// Having no subscribers is not an error, nobody is just listening yet
+pub fn publish(tx: &broadcast::Sender<String>, message: &RealtimeMessage) {
+    match serde_json::to_string(message) {
+        Ok(payload) => {
+            let _ = tx.send(payload);
+        }
+        Err(err) => eprintln!("Failed to serialize realtime message: {err:?}"),
+    }
+}
+
+// Topic of a message on the realtime channel, for subscribers that only render some of them
+// Postgres notifications about scores are under `scores:<category_id>`
+pub fn message_topic(payload: &str) -> String {
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(payload) else {
+        return "updates".to_string();
+    };
+
+    let field = |name: &str| {
+        value
+            .get(name)
+            .or_else(|| value.get("data").and_then(|data| data.get(name)))
+            .and_then(|field| field.as_str())
+            .map(|field| field.to_string())
+    };
+
+    match value.get("type").and_then(|kind| kind.as_str()) {
+        Some("standings") => format!("standings:{}", field("event_id").unwrap_or_default()),
+        Some("announcement") => {
+            format!("announcements:{}", field("event_id").unwrap_or_default())
+        }
+        Some("judge_lock" | "judge_presence") => "judges:presence".to_string(),
+        // Each judge's device only subscribes to its own reminders
+        Some("judge_nudge") => format!("judges:{}", field("judge_id").unwrap_or_default()),
+        Some("database_status") => "system".to_string(),
+        _ => match field("category_id") {
+            Some(category_id) => format!("scores:{}", category_id),
+            None => "updates".to_string(),
+        },
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TopicParam {
+    // Comma separated, e.g. `standings:<event_id>,judges:presence`
+    topics: Option<String>,
+    // What is connecting (`judge`, `display` or `dashboard`), only used for the status page
+    pub client: Option<String>,
+    pub judge_id: Option<uuid::Uuid>,
+}
+
+impl TopicParam {
+    pub fn topics(&self) -> Vec<String> {
+        self.topics
+            .as_deref()
+            .unwrap_or_default()
+            .split(',')
+            .map(|topic| topic.trim().to_string())
+            .filter(|topic| !topic.is_empty())
+            .collect()
+    }
+}
+
+// No topics means everything, a topic without an ID (`standings`) matches all of its IDs
+pub fn is_subscribed(topics: &[String], topic: &str) -> bool {
+    topics.is_empty()
+        || topics.iter().any(|subscribed| {
+            subscribed == topic
+                || topic
+                    .strip_prefix(subscribed.as_str())
+                    .is_some_and(|rest| rest.starts_with(':'))
+        })
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ClientStatus {
+    pub id: u64,
+    pub kind: String,
+    pub judge_id: Option<uuid::Uuid>,
+    pub topics: Vec<String>,
+    pub connected_at: chrono::DateTime<chrono::Utc>,
+    pub messages_sent: u64,
+    // Messages waiting in the channel for this client
+    pub backlog: usize,
+    // Messages the client fell too far behind to ever receive
+    pub lagged_messages: u64,
+}
+
+// Every open websocket, so operators can tell whether a device is actually connected
+#[derive(Clone, Default)]
+pub struct ClientRegistry {
+    clients: Arc<Mutex<HashMap<u64, ClientStatus>>>,
+    next_id: Arc<AtomicU64>,
+}
+
+impl ClientRegistry {
+    pub fn register(&self, param: &TopicParam) -> u64 {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+
+        let kind = match param.client.as_deref() {
+            Some(kind @ ("judge" | "display" | "dashboard")) => kind.to_string(),
+            _ => "unknown".to_string(),
+        };
+
+        let status = ClientStatus {
+            id,
+            kind,
+            judge_id: param.judge_id,
+            topics: param.topics(),
+            connected_at: chrono::Utc::now(),
+            messages_sent: 0,
+            backlog: 0,
+            lagged_messages: 0,
+        };
+
+        if let Ok(mut clients) = self.clients.lock() {
+            clients.insert(id, status);
+        }
+
+        id
+    }
+
+    pub fn update(&self, id: u64, update: impl FnOnce(&mut ClientStatus)) {
+        if let Ok(mut clients) = self.clients.lock() {
+            if let Some(status) = clients.get_mut(&id) {
+                update(status);
+            }
+        }
+    }
+
+    pub fn unregister(&self, id: u64) {
+        if let Ok(mut clients) = self.clients.lock() {
+            clients.remove(&id);
+        }
+    }
+
+    pub fn clients(&self) -> Vec<ClientStatus> {
+        let mut clients: Vec<ClientStatus> = self
+            .clients
+            .lock()
+            .map(|clients| clients.values().cloned().collect())
+            .unwrap_or_default();
+
+        clients.sort_by_key(|client| client.id);
+
+        clients
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct RealtimeStatus {
+    // Client kind -> connected clients
+    connected: HashMap<String, usize>,
+    // Messages not yet received by every client
+    channel_backlog: usize,
+    clients: Vec<ClientStatus>,
+    // Score submissions over the last few minutes
+    submission_latency: LatencyPercentiles,
+}
+
+// For confirming whether a judge's tablet is connected when it stops updating
+pub async fn get_realtime_status(
+    State(pool): State<PgPool>,
+    State(registry): State<ClientRegistry>,
+    State(tx): State<broadcast::Sender<String>>,
+) -> Result<axum::Json<RealtimeStatus>, AppError> {
+    let clients = registry.clients();
+
+    let mut connected: HashMap<String, usize> = HashMap::new();
+
+    for client in clients.iter() {
+        *connected.entry(client.kind.clone()).or_insert(0) += 1;
+    }
+
+    Ok(axum::Json(RealtimeStatus {
+        connected,
+        channel_backlog: tx.len(),
+        clients,
+        submission_latency: analytics::fetch_recent_latency(&pool).await?,
+    }))
+}
+
+// Standings as the aggregator pushes them, for a client that just connected
+async fn current_standings(pool: &PgPool, event_id: uuid::Uuid) -> Result<String, AppError> {
+    let mut standings = compute_event_final_scores(pool, &event_id).await?;
+    let decimals = fetch_display_decimals(pool, &event_id).await?;
+
+    round_final_scores(&mut standings, decimals);
+
+    serde_json::to_string(&RealtimeMessage::Standings {
+        event_id,
+        standings,
+    })
+    .map_err(|err| {
+        AppError::new(
+            http::StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to serialize standings: {}", err),
+        )
+    })
+}
+
+// The tabulator screen's leaderboard, the current standings first and every recompute after that
+pub async fn leaderboard_ws(
+    ws: WebSocketUpgrade,
+    State(pool): State<PgPool>,
+    State(tx): State<broadcast::Sender<String>>,
+    State(registry): State<ClientRegistry>,
+    Path(event_id): Path<uuid::Uuid>,
+) -> Result<Response, AppError> {
+    // Subscribed before the first fetch, so a recompute in between isn't missed
+    let rx = tx.subscribe();
+    let initial = current_standings(&pool, event_id).await?;
+
+    let param = TopicParam {
+        topics: Some(format!("standings:{}", event_id)),
+        client: Some("dashboard".to_string()),
+        judge_id: None,
+    };
+
+    Ok(ws.on_upgrade(move |socket| handle_leaderboard(socket, rx, initial, registry, param)))
+}
+
+async fn handle_leaderboard(
+    socket: WebSocket,
+    mut rx: broadcast::Receiver<String>,
+    initial: String,
+    registry: ClientRegistry,
+    param: TopicParam,
+) {
+    let (mut sender, mut receiver) = socket.split();
+
+    let topics = param.topics();
+    let client_id = registry.register(&param);
+
+    if sender.send(Message::Text(initial)).await.is_ok() {
+        loop {
+            tokio::select! {
+                msg = rx.recv() => {
+                    let msg = match msg {
+                        Ok(msg) => msg,
+                        // Only the latest standings matter, the next recompute catches the client up
+                        Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                            registry.update(client_id, |status| status.lagged_messages += skipped);
+                            continue;
+                        }
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    };
+
+                    if !is_subscribed(&topics, &message_topic(&msg)) {
+                        continue;
+                    }
+
+                    if sender.send(Message::Text(msg)).await.is_err() {
+                        break;
+                    }
+
+                    registry.update(client_id, |status| {
+                        status.messages_sent += 1;
+                        status.backlog = rx.len();
+                    });
+                }
+                // Read-only, anything the client sends is ignored until it closes
+                incoming = receiver.next() => match incoming {
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                    Some(Ok(_)) => {}
+                },
+            }
+        }
+    }
+
+    registry.unregister(client_id);
+}
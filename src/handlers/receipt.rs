@@ -0,0 +1,149 @@
+use std::sync::OnceLock;
+
+use axum::extract::{Query, State};
+use axum::http;
+use axum::response::Result;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use sqlx::PgPool;
+
+use crate::error::AppError;
+
+static RECEIPT_SECRET: OnceLock<Vec<u8>> = OnceLock::new();
+
+// Uses `RECEIPT_SECRET` if set, otherwise a secret generated once and kept in the database
+pub async fn init(pool: &PgPool) -> Result<(), sqlx::Error> {
+    let secret = match std::env::var("RECEIPT_SECRET") {
+        Ok(secret) => secret,
+        Err(_) => {
+            sqlx::query(
+                "INSERT INTO app_secrets (name) VALUES ('score_receipts') ON CONFLICT DO NOTHING",
+            )
+            .execute(pool)
+            .await?;
+
+            sqlx::query_scalar("SELECT value FROM app_secrets WHERE name = 'score_receipts'")
+                .fetch_one(pool)
+                .await?
+        }
+    };
+
+    let _ = RECEIPT_SECRET.set(secret.into_bytes());
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Receipt {
+    pub score_id: uuid::Uuid,
+    pub judge_id: uuid::Uuid,
+    pub score: i32,
+    pub issued_at: chrono::DateTime<chrono::Utc>,
+    pub signature: String,
+}
+
+fn receipt_message(
+    score_id: &uuid::Uuid,
+    judge_id: &uuid::Uuid,
+    score: i32,
+    issued_at: &chrono::DateTime<chrono::Utc>,
+) -> String {
+    format!(
+        "{}|{}|{}|{}",
+        score_id,
+        judge_id,
+        score,
+        issued_at.timestamp_micros()
+    )
+}
+
+pub fn sign_receipt(secret: &[u8], message: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("HMAC takes keys of any size");
+    mac.update(message.as_bytes());
+
+    hex::encode(mac.finalize().into_bytes())
+}
+
+pub fn verify_signature(secret: &[u8], message: &str, signature: &str) -> bool {
+    let Ok(signature) = hex::decode(signature) else {
+        return false;
+    };
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("HMAC takes keys of any size");
+    mac.update(message.as_bytes());
+
+    mac.verify_slice(&signature).is_ok()
+}
+
+fn secret() -> Result<&'static [u8], AppError> {
+    RECEIPT_SECRET
+        .get()
+        .map(|secret| secret.as_slice())
+        .ok_or_else(|| {
+            AppError::new(
+                http::StatusCode::INTERNAL_SERVER_ERROR,
+                "Score receipts are not set up",
+            )
+        })
+}
+
+// Signs other certification records with the receipt secret
+pub fn sign(message: &str) -> Result<String, AppError> {
+    Ok(sign_receipt(secret()?, message))
+}
+
+pub fn verify(message: &str, signature: &str) -> Result<bool, AppError> {
+    Ok(verify_signature(secret()?, message, signature))
+}
+
+// Proof for the judge's device that the backend accepted the score
+pub fn issue(score_id: uuid::Uuid, judge_id: uuid::Uuid, score: i32) -> Result<Receipt, AppError> {
+    let issued_at = chrono::Utc::now();
+    let message = receipt_message(&score_id, &judge_id, score, &issued_at);
+
+    Ok(Receipt {
+        score_id,
+        judge_id,
+        score,
+        issued_at,
+        signature: sign_receipt(secret()?, &message),
+    })
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReceiptVerification {
+    // The receipt was issued by this backend and has not been altered
+    valid_signature: bool,
+    score_exists: bool,
+    // The stored score is still the one on the receipt
+    matches_current_score: bool,
+}
+
+pub async fn verify_receipt(
+    State(pool): State<PgPool>,
+    Query(receipt): Query<Receipt>,
+) -> Result<axum::Json<ReceiptVerification>, AppError> {
+    let message = receipt_message(
+        &receipt.score_id,
+        &receipt.judge_id,
+        receipt.score,
+        &receipt.issued_at,
+    );
+    let valid_signature = verify_signature(secret()?, &message, &receipt.signature);
+
+    let current = sqlx::query_as::<_, (uuid::Uuid, i32)>(
+        "SELECT judge_id, score FROM scores_history WHERE id = ($1)",
+    )
+    .bind(&receipt.score_id)
+    .fetch_optional(&pool)
+    .await?;
+
+    Ok(axum::Json(ReceiptVerification {
+        valid_signature,
+        score_exists: current.is_some(),
+        matches_current_score: current.is_some_and(|(judge_id, score)| {
+            judge_id == receipt.judge_id && score == receipt.score
+        }),
+    }))
+}
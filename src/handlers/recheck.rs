@@ -0,0 +1,167 @@
+use axum::extract::{Path, State};
+use axum::http;
+use axum::response::Result;
+use serde::Serialize;
+use sqlx::types::Json;
+use sqlx::{FromRow, PgConnection, PgPool};
+
+use crate::error::AppError;
+
+use super::approval::{self, CommitteeLogin};
+use super::audit;
+use super::score::{self, CandidateFinalScore2, Score};
+use super::standings::{self, StandingChange};
+
+// Rank changes an override caused after the event was finalized
+#[derive(Debug, Serialize, FromRow)]
+pub struct StandingsRecheck {
+    id: uuid::Uuid,
+    changes: Json<Vec<StandingChange>>,
+    created_at: chrono::DateTime<chrono::Utc>,
+    acknowledged_at: Option<chrono::DateTime<chrono::Utc>>,
+    // Relationships
+    event_id: uuid::Uuid,
+    snapshot_id: uuid::Uuid,
+    score_id: uuid::Uuid,
+    acknowledged_by: Option<uuid::Uuid>,
+}
+
+pub fn changes_ranks(changes: &[StandingChange]) -> bool {
+    changes
+        .iter()
+        .any(|change| change.from_rank != change.to_rank)
+}
+
+// Diffs the finalized standings against the ones `score` leads to, None if the event isn't finalized
+// Runs in the override's transaction, so the diff goes into the same audit entry as the override
+pub async fn recheck_standings(
+    conn: &mut PgConnection,
+    score: &Score,
+) -> Result<Option<Vec<StandingChange>>, AppError> {
+    let finalized = sqlx::query_as::<_, (uuid::Uuid, uuid::Uuid, Json<Vec<CandidateFinalScore2>>)>(
+        r#"
+        SELECT e.id, ss.id, ss.standings
+        FROM categories cat
+        JOIN events e ON e.id = cat.event_id
+        JOIN standings_snapshots ss ON ss.id = e.finalized_snapshot_id
+        WHERE cat.id = ($1)
+        "#,
+    )
+    .bind(&score.category_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    let Some((event_id, snapshot_id, finalized_standings)) = finalized else {
+        return Ok(None);
+    };
+
+    let current_standings = score::compute_event_final_scores(&mut *conn, &event_id).await?;
+    let changes = standings::diff_standings(&finalized_standings, &current_standings);
+
+    // Score changes that keep every rank don't need the committee
+    if changes_ranks(&changes) {
+        sqlx::query(
+            r#"
+            INSERT INTO standings_rechecks (changes, event_id, snapshot_id, score_id)
+            VALUES ($1, $2, $3, $4)
+            "#,
+        )
+        .bind(Json(&changes))
+        .bind(&event_id)
+        .bind(&snapshot_id)
+        .bind(&score.id)
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(Some(changes))
+}
+
+// Called before finalizing again
+pub async fn ensure_rechecks_acknowledged(
+    pool: &PgPool,
+    event_id: &uuid::Uuid,
+) -> Result<(), AppError> {
+    let pending: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM standings_rechecks WHERE event_id = ($1) AND acknowledged_at IS NULL",
+    )
+    .bind(event_id)
+    .fetch_one(pool)
+    .await?;
+
+    if pending > 0 {
+        return Err(AppError::new(
+            http::StatusCode::CONFLICT,
+            format!(
+                "{} override(s) changed the ranks since the results were finalized, a committee member has to acknowledge them first",
+                pending
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+pub async fn get_rechecks(
+    State(pool): State<PgPool>,
+    Path(event_id): Path<uuid::Uuid>,
+) -> Result<axum::Json<Vec<StandingsRecheck>>, AppError> {
+    let rechecks = sqlx::query_as::<_, StandingsRecheck>(
+        "SELECT * FROM standings_rechecks WHERE event_id = ($1) ORDER BY created_at",
+    )
+    .bind(&event_id)
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(axum::Json(rechecks))
+}
+
+// Committee members log in again, like when approving the results
+pub async fn acknowledge_recheck(
+    State(pool): State<PgPool>,
+    Path((event_id, recheck_id)): Path<(uuid::Uuid, uuid::Uuid)>,
+    axum::Json(login): axum::Json<CommitteeLogin>,
+) -> Result<axum::Json<StandingsRecheck>, AppError> {
+    let member = approval::authenticate_member(&pool, &login).await?;
+
+    if member.event_id != event_id {
+        return Err(AppError::new(
+            http::StatusCode::FORBIDDEN,
+            "Committee member does not belong to this event",
+        ));
+    }
+
+    let recheck = sqlx::query_as::<_, StandingsRecheck>(
+        r#"
+        UPDATE standings_rechecks SET acknowledged_at = NOW(), acknowledged_by = ($1)
+        WHERE id = ($2) AND event_id = ($3) AND acknowledged_at IS NULL
+        RETURNING *
+        "#,
+    )
+    .bind(&member.id)
+    .bind(&recheck_id)
+    .bind(&event_id)
+    .fetch_optional(&pool)
+    .await?
+    .ok_or_else(|| {
+        AppError::new(
+            http::StatusCode::CONFLICT,
+            "Recheck not found or already acknowledged",
+        )
+    })?;
+
+    audit::record(
+        &pool,
+        &member.username,
+        "event",
+        Some(&event_id),
+        "acknowledge_recheck",
+        serde_json::json!({
+            "recheck_id": recheck.id,
+            "score_id": recheck.score_id,
+        }),
+    )
+    .await?;
+
+    Ok(axum::Json(recheck))
+}
@@ -0,0 +1,554 @@
+use axum::extract::{Extension, Multipart, Path, State};
+use axum::http;
+use axum::response::{IntoResponse, Result};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+
+use crate::aggregation::Aggregator;
+use crate::error::AppError;
+
+use super::admin::StaffClaims;
+use super::audit;
+use super::candidate;
+use super::legacy;
+use super::settings::{self, CandidateNumbering};
+
+#[derive(Debug, Deserialize)]
+pub struct CheckIn {
+    // False undoes a check-in made by mistake
+    checked_in: bool,
+}
+
+async fn set_checked_in(
+    pool: &PgPool,
+    actor: &uuid::Uuid,
+    entity_type: &str,
+    id: &uuid::Uuid,
+    checked_in: bool,
+) -> Result<http::StatusCode, AppError> {
+    let query = match entity_type {
+        "candidate" => {
+            r#"
+            UPDATE candidates SET checked_in_at = CASE WHEN ($1) THEN COALESCE(checked_in_at, NOW()) END
+            WHERE id = ($2) AND deleted_at IS NULL
+            "#
+        }
+        _ => {
+            r#"
+            UPDATE judges SET checked_in_at = CASE WHEN ($1) THEN COALESCE(checked_in_at, NOW()) END
+            WHERE id = ($2)
+            "#
+        }
+    };
+
+    let res = sqlx::query(query)
+        .bind(checked_in)
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+    if res.rows_affected() == 0 {
+        return Err(AppError::new(
+            http::StatusCode::NOT_FOUND,
+            format!("The {} does not exist", entity_type),
+        ));
+    }
+
+    audit::record(
+        pool,
+        actor,
+        entity_type,
+        Some(id),
+        if checked_in {
+            "check_in"
+        } else {
+            "undo_check_in"
+        },
+        serde_json::json!({}),
+    )
+    .await?;
+
+    Ok(http::StatusCode::NO_CONTENT)
+}
+
+pub async fn check_in_candidate(
+    State(pool): State<PgPool>,
+    Extension(staff): Extension<StaffClaims>,
+    Path(candidate_id): Path<uuid::Uuid>,
+    axum::Json(payload): axum::Json<CheckIn>,
+) -> Result<http::StatusCode, AppError> {
+    set_checked_in(
+        &pool,
+        &staff.sub,
+        "candidate",
+        &candidate_id,
+        payload.checked_in,
+    )
+    .await
+}
+
+pub async fn check_in_judge(
+    State(pool): State<PgPool>,
+    Extension(staff): Extension<StaffClaims>,
+    Path(judge_id): Path<uuid::Uuid>,
+    axum::Json(payload): axum::Json<CheckIn>,
+) -> Result<http::StatusCode, AppError> {
+    set_checked_in(&pool, &staff.sub, "judge", &judge_id, payload.checked_in).await
+}
+
+fn write_csv(headers: &[&str], rows: Vec<Vec<String>>) -> Result<Vec<u8>, AppError> {
+    let mut csv_writer = csv::Writer::from_writer(Vec::new());
+
+    csv_writer.write_record(headers).map_err(|err| {
+        AppError::new(
+            http::StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to write record for headers: {}", err),
+        )
+    })?;
+
+    for row in rows {
+        csv_writer.write_record(row).map_err(|err| {
+            AppError::new(
+                http::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to serialize record: {}", err),
+            )
+        })?;
+    }
+
+    csv_writer.into_inner().map_err(|err| {
+        AppError::new(
+            http::StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to generate CSV file: {}", err),
+        )
+    })
+}
+
+fn checked_in_label(checked_in_at: Option<chrono::DateTime<chrono::Utc>>) -> String {
+    checked_in_at
+        .map(|time| time.to_rfc3339())
+        .unwrap_or_else(|| "Not checked in".to_string())
+}
+
+#[derive(Debug, FromRow)]
+struct RegistrationCandidate {
+    candidate_number: i32,
+    first_name: String,
+    middle_name: String,
+    last_name: String,
+    gender: i32,
+    college_name: Option<String>,
+    checked_in_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+// Light list for the registration table, the scores export is far too heavy for that
+pub async fn export_candidates_csv(
+    State(pool): State<PgPool>,
+    Path(event_id): Path<uuid::Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    let candidates = sqlx::query_as::<_, RegistrationCandidate>(
+        r#"
+        SELECT c.candidate_number, c.first_name, c.middle_name, c.last_name, c.gender,
+            col.college_name, c.checked_in_at
+        FROM candidates c
+        JOIN categories cat ON cat.id = c.category_id
+        LEFT JOIN college col ON col.college_id = c.college_id
+        WHERE cat.event_id = ($1) AND c.deleted_at IS NULL
+        ORDER BY
+            CASE
+                WHEN c.gender = 1 THEN 1
+                ELSE 2
+            END,
+            c.candidate_number
+        "#,
+    )
+    .bind(&event_id)
+    .fetch_all(&pool)
+    .await?;
+
+    let rows = candidates
+        .into_iter()
+        .map(|candidate| {
+            vec![
+                candidate.candidate_number.to_string(),
+                candidate.last_name.trim().to_string(),
+                candidate.first_name.trim().to_string(),
+                candidate.middle_name.trim().to_string(),
+                if candidate.gender == 1 {
+                    "Male"
+                } else {
+                    "Female"
+                }
+                .to_string(),
+                candidate.college_name.unwrap_or_default(),
+                checked_in_label(candidate.checked_in_at),
+            ]
+        })
+        .collect();
+
+    let csv_bytes = write_csv(
+        &[
+            "Candidate #",
+            "Last Name",
+            "First Name",
+            "Middle Name",
+            "Division",
+            "College",
+            "Checked In",
+        ],
+        rows,
+    )?;
+
+    Ok(([(http::header::CONTENT_TYPE, "text/csv")], csv_bytes))
+}
+
+#[derive(Debug, FromRow)]
+struct RegistrationJudge {
+    name: String,
+    username: String,
+    substitute_for: Option<String>,
+    checked_in_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+pub async fn export_judges_csv(
+    State(pool): State<PgPool>,
+    Path(event_id): Path<uuid::Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    let judges = sqlx::query_as::<_, RegistrationJudge>(
+        r#"
+        SELECT j.name, j.username, sub.name AS substitute_for, j.checked_in_at
+        FROM judges j
+        LEFT JOIN judges sub ON sub.id = j.substitute_for
+        WHERE j.event_id = ($1)
+        ORDER BY j.name
+        "#,
+    )
+    .bind(&event_id)
+    .fetch_all(&pool)
+    .await?;
+
+    let rows = judges
+        .into_iter()
+        .map(|judge| {
+            vec![
+                judge.name,
+                judge.username,
+                judge.substitute_for.unwrap_or_default(),
+                checked_in_label(judge.checked_in_at),
+            ]
+        })
+        .collect();
+
+    let csv_bytes = write_csv(&["Name", "Username", "Substitute For", "Checked In"], rows)?;
+
+    Ok(([(http::header::CONTENT_TYPE, "text/csv")], csv_bytes))
+}
+
+// Column names of `export_candidates_csv`, so an exported list can be imported again
+const IMPORT_NUMBER: &str = "Candidate #";
+const IMPORT_LAST_NAME: &str = "Last Name";
+const IMPORT_FIRST_NAME: &str = "First Name";
+const IMPORT_MIDDLE_NAME: &str = "Middle Name";
+const IMPORT_DIVISION: &str = "Division";
+const IMPORT_COLLEGE: &str = "College";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedCandidate {
+    // 1-based, as the organizers see it in their spreadsheet
+    pub row: usize,
+    pub candidate_number: i32,
+    pub first_name: String,
+    pub middle_name: String,
+    pub last_name: String,
+    pub gender: i32,
+    // College ID or name
+    pub college: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RowError {
+    pub row: usize,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CandidateImport {
+    imported: usize,
+    // Nothing is imported unless this is empty
+    errors: Vec<RowError>,
+}
+
+// `rows` are the cells as text with the column names first, `existing` the event's (gender, number) pairs
+pub fn parse_candidate_rows(
+    rows: &[Vec<String>],
+    existing: &[(i32, i32)],
+    numbering: CandidateNumbering,
+) -> Result<(Vec<ImportedCandidate>, Vec<RowError>), String> {
+    let headers = rows.first().ok_or("The file is empty")?;
+
+    let number_col = legacy::find_column(headers, IMPORT_NUMBER)?;
+    let last_name_col = legacy::find_column(headers, IMPORT_LAST_NAME)?;
+    let first_name_col = legacy::find_column(headers, IMPORT_FIRST_NAME)?;
+    let middle_name_col = legacy::find_column(headers, IMPORT_MIDDLE_NAME).ok();
+    let division_col = legacy::find_column(headers, IMPORT_DIVISION)
+        .or_else(|_| legacy::find_column(headers, "Gender"))?;
+    let college_col = legacy::find_column(headers, IMPORT_COLLEGE).ok();
+
+    let mut candidates = Vec::new();
+    let mut errors = Vec::new();
+
+    for (idx, row) in rows.iter().enumerate().skip(1) {
+        let cell = |col: usize| row.get(col).map(|value| value.trim()).unwrap_or("");
+        let line = idx + 1;
+
+        if row.iter().all(|value| value.trim().is_empty()) {
+            continue;
+        }
+
+        let mut error = |message: String| errors.push(RowError { row: line, message });
+
+        let Some(candidate_number) = cell(number_col)
+            .parse::<f64>()
+            .ok()
+            .filter(|number| number.fract() == 0.0 && *number > 0.0)
+        else {
+            error(format!("Invalid candidate number '{}'", cell(number_col)));
+            continue;
+        };
+
+        let Some(gender) = legacy::parse_gender(cell(division_col)) else {
+            error(format!("Unknown division '{}'", cell(division_col)));
+            continue;
+        };
+
+        if cell(first_name_col).is_empty() || cell(last_name_col).is_empty() {
+            error("First and last names can't be blank".to_string());
+            continue;
+        }
+
+        candidates.push(ImportedCandidate {
+            row: line,
+            candidate_number: candidate_number as i32,
+            first_name: cell(first_name_col).to_string(),
+            middle_name: middle_name_col.map(cell).unwrap_or("").to_string(),
+            last_name: cell(last_name_col).to_string(),
+            gender,
+            college: college_col.map(cell).unwrap_or("").to_string(),
+        });
+    }
+
+    let numbers: Vec<(i32, i32)> = existing
+        .iter()
+        .copied()
+        .chain(
+            candidates
+                .iter()
+                .map(|candidate| (candidate.gender, candidate.candidate_number)),
+        )
+        .collect();
+    let collisions = candidate::number_collisions(&numbers, numbering);
+
+    for candidate in candidates.iter() {
+        // Other rows with the same number may be fine under per-division numbering
+        let taken = collisions.contains(&candidate.candidate_number)
+            && numbers
+                .iter()
+                .filter(|(gender, number)| {
+                    *number == candidate.candidate_number
+                        && (numbering == CandidateNumbering::PerEvent
+                            || *gender == candidate.gender)
+                })
+                .count()
+                > 1;
+
+        if taken {
+            errors.push(RowError {
+                row: candidate.row,
+                message: format!(
+                    "Candidate number {} is used more than once",
+                    candidate.candidate_number
+                ),
+            });
+        }
+    }
+
+    errors.sort_by_key(|error| error.row);
+
+    Ok((candidates, errors))
+}
+
+// Both .xlsx and the older .xls start with a zip or OLE signature, anything else is read as CSV
+fn read_import_file(bytes: Vec<u8>) -> Result<Vec<Vec<String>>, String> {
+    if bytes.starts_with(b"PK") || bytes.starts_with(&[0xD0, 0xCF, 0x11, 0xE0]) {
+        return legacy::read_sheet(bytes, None);
+    }
+
+    csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(bytes.as_slice())
+        .records()
+        .map(|record| {
+            record
+                .map(|record| record.iter().map(|cell| cell.to_string()).collect())
+                .map_err(|err| format!("Failed to read the CSV: {}", err))
+        })
+        .collect()
+}
+
+// Multipart form with the CSV or spreadsheet as `file` and optionally a `category_id`
+// Candidates join the event through that category, the active or else the first one by default
+pub async fn import_candidates(
+    State(pool): State<PgPool>,
+    State(aggregator): State<Aggregator>,
+    Extension(staff): Extension<StaffClaims>,
+    Path(event_id): Path<uuid::Uuid>,
+    mut multipart: Multipart,
+) -> Result<(http::StatusCode, axum::Json<CandidateImport>), AppError> {
+    let mut file: Option<Vec<u8>> = None;
+    let mut category_id: Option<uuid::Uuid> = None;
+
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|err| AppError::new(http::StatusCode::BAD_REQUEST, err.to_string()))?
+    {
+        let name = field.name().unwrap_or("").to_string();
+        let data = field
+            .bytes()
+            .await
+            .map_err(|err| AppError::new(http::StatusCode::BAD_REQUEST, err.to_string()))?;
+
+        match name.as_str() {
+            "file" => file = Some(data.to_vec()),
+            "category_id" => {
+                category_id = Some(String::from_utf8_lossy(&data).trim().parse().map_err(|_| {
+                    AppError::new(http::StatusCode::BAD_REQUEST, "Invalid category_id")
+                })?)
+            }
+            _ => {}
+        }
+    }
+
+    let Some(file) = file else {
+        return Err(AppError::new(
+            http::StatusCode::BAD_REQUEST,
+            "Send the candidates as the `file` field",
+        ));
+    };
+
+    let category_id: uuid::Uuid = sqlx::query_scalar(
+        r#"
+        SELECT id FROM categories
+        WHERE event_id = ($1) AND (($2)::UUID IS NULL OR id = ($2))
+        ORDER BY is_active DESC, name
+        LIMIT 1
+        "#,
+    )
+    .bind(&event_id)
+    .bind(&category_id)
+    .fetch_optional(&pool)
+    .await?
+    .ok_or_else(|| {
+        AppError::new(
+            http::StatusCode::UNPROCESSABLE_ENTITY,
+            "The event needs a category for the candidates to join",
+        )
+    })?;
+
+    let unprocessable = |err: String| AppError::new(http::StatusCode::UNPROCESSABLE_ENTITY, err);
+
+    let rows = read_import_file(file).map_err(unprocessable)?;
+
+    let numbering = settings::fetch_settings(&pool, &event_id)
+        .await?
+        .candidate_numbering;
+    let existing: Vec<(i32, i32)> = candidate::fetch_numbers(&pool, &event_id)
+        .await?
+        .into_iter()
+        .map(|(_, gender, number)| (gender, number))
+        .collect();
+
+    let (candidates, mut errors) =
+        parse_candidate_rows(&rows, &existing, numbering).map_err(unprocessable)?;
+
+    let mut txn = pool.begin().await?;
+    let mut imported = 0;
+
+    for candidate in candidates.iter() {
+        let college_id: Option<String> = if candidate.college.is_empty() {
+            Some(String::new())
+        } else {
+            sqlx::query_scalar(
+                "SELECT college_id FROM college WHERE college_id = ($1) OR LOWER(college_name) = LOWER(($1))",
+            )
+            .bind(&candidate.college)
+            .fetch_optional(&mut *txn)
+            .await?
+        };
+
+        let Some(college_id) = college_id else {
+            errors.push(RowError {
+                row: candidate.row,
+                message: format!("Unknown college '{}'", candidate.college),
+            });
+            continue;
+        };
+
+        // Rows after the first error are still checked, but never stored
+        if !errors.is_empty() {
+            continue;
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO candidates (first_name, middle_name, last_name, gender, candidate_number, college_id, category_id)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+        )
+        .bind(&candidate.first_name)
+        .bind(&candidate.middle_name)
+        .bind(&candidate.last_name)
+        .bind(candidate.gender)
+        .bind(candidate.candidate_number)
+        .bind(&college_id)
+        .bind(&category_id)
+        .execute(&mut *txn)
+        .await?;
+
+        imported += 1;
+    }
+
+    if !errors.is_empty() {
+        errors.sort_by_key(|error| error.row);
+
+        return Ok((
+            http::StatusCode::UNPROCESSABLE_ENTITY,
+            axum::Json(CandidateImport {
+                imported: 0,
+                errors,
+            }),
+        ));
+    }
+
+    audit::record(
+        &mut *txn,
+        staff.sub,
+        "event",
+        Some(&event_id),
+        "import_candidates",
+        serde_json::json!({
+            "imported": imported,
+            "category_id": category_id,
+        }),
+    )
+    .await?;
+
+    txn.commit().await?;
+
+    aggregator.request(event_id);
+
+    Ok((
+        http::StatusCode::CREATED,
+        axum::Json(CandidateImport { imported, errors }),
+    ))
+}
@@ -0,0 +1,578 @@
+use std::collections::HashMap;
+
+use axum::extract::{Path, Query, State};
+use axum::http;
+use axum::response::Result;
+use rust_xlsxwriter::*;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+
+use crate::error::AppError;
+use crate::field_crypto::SealedText;
+
+use super::candidate::Candidate;
+use super::settings;
+
+// Judges are referred to by letter in anything handed out to candidates
+pub fn judge_letter_label(idx: usize) -> String {
+    let letter = (b'A' + (idx % 26) as u8) as char;
+
+    match idx / 26 {
+        0 => format!("Judge {}", letter),
+        n => format!("Judge {}{}", letter, n + 1),
+    }
+}
+
+// Used in distributed exports of events with `anonymize_judges` set
+pub fn judge_number_label(idx: usize) -> String {
+    format!("Judge {}", idx + 1)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExportParam {
+    // The certified copy for the committee always keeps the real judge names
+    #[serde(default)]
+    pub certified: bool,
+    // Only this event's results, every event's without it
+    pub event_id: Option<uuid::Uuid>,
+}
+
+pub async fn should_anonymize_judges(
+    pool: &PgPool,
+    event_id: &uuid::Uuid,
+    param: &ExportParam,
+) -> Result<bool, AppError> {
+    if param.certified {
+        return Ok(false);
+    }
+
+    let settings = settings::fetch_settings(pool, event_id).await?;
+
+    Ok(settings.anonymize_judges)
+}
+
+// Scores submitted within a stretch of the program, e.g. the Q&A segment
+#[derive(Debug, Default, Deserialize)]
+pub struct TimeWindow {
+    pub from: Option<chrono::DateTime<chrono::Utc>>,
+    pub to: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl TimeWindow {
+    pub fn validate(&self) -> Result<(), AppError> {
+        if let (Some(from), Some(to)) = (self.from, self.to) {
+            if from > to {
+                return Err(AppError::new(
+                    http::StatusCode::UNPROCESSABLE_ENTITY,
+                    "`from` must not be after `to`",
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// A row of the `score_report` view
+#[derive(Debug, Serialize, FromRow)]
+pub struct ScoreReportRow {
+    pub score_id: uuid::Uuid,
+    pub score: i32,
+    pub max: i32,
+    pub time_of_scoring: chrono::DateTime<chrono::Utc>,
+    pub remark: Option<SealedText>,
+    pub group_id: Option<uuid::Uuid>,
+    pub judge_id: uuid::Uuid,
+    pub judge_name: String,
+    pub judge_score_exclusion: bool,
+    pub candidate_id: uuid::Uuid,
+    pub candidate_number: i32,
+    pub candidate_first_name: String,
+    pub candidate_middle_name: String,
+    pub candidate_last_name: String,
+    pub candidate_gender: i32,
+    pub criteria_id: uuid::Uuid,
+    pub criteria_name: String,
+    pub category_id: uuid::Uuid,
+    pub category_name: String,
+    pub category_weight: f32,
+    pub event_id: uuid::Uuid,
+    pub event_name: String,
+    // Same as `score` unless the category grades on a curve
+    pub curved_score: f64,
+}
+
+pub async fn fetch_score_report(
+    pool: &PgPool,
+    window: &TimeWindow,
+    event_id: Option<&uuid::Uuid>,
+) -> Result<Vec<ScoreReportRow>, AppError> {
+    window.validate()?;
+
+    let rows = sqlx::query_as::<_, ScoreReportRow>(
+        r#"
+        SELECT * FROM score_report
+        WHERE (($1)::TIMESTAMPTZ IS NULL OR time_of_scoring >= ($1))
+            AND (($2)::TIMESTAMPTZ IS NULL OR time_of_scoring <= ($2))
+            AND (($3)::UUID IS NULL OR event_id = ($3))
+        ORDER BY event_name, category_name, criteria_name, candidate_number, judge_name
+        "#,
+    )
+    .bind(window.from)
+    .bind(window.to)
+    .bind(event_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows)
+}
+
+// Judge ID -> label shown in place of the judge's name, for every event that hides them
+pub async fn export_judge_labels(
+    pool: &PgPool,
+    param: &ExportParam,
+) -> Result<HashMap<uuid::Uuid, String>, AppError> {
+    let mut judge_labels = HashMap::new();
+
+    if param.certified {
+        return Ok(judge_labels);
+    }
+
+    // Same numbering as the spreadsheet, excluded judges come last
+    let judges = sqlx::query_as::<_, (uuid::Uuid, uuid::Uuid)>(
+        r#"
+        SELECT j.id, j.event_id FROM judges j
+        JOIN event_settings es ON es.event_id = j.event_id
+        WHERE es.key = 'anonymize_judges' AND es.value = 'true'::JSONB
+            AND (($1)::UUID IS NULL OR j.event_id = ($1))
+        ORDER BY j.score_exclusion, j.name
+        "#,
+    )
+    .bind(param.event_id)
+    .fetch_all(pool)
+    .await?;
+
+    // Event ID -> judges numbered so far
+    let mut event_counts: HashMap<uuid::Uuid, usize> = HashMap::new();
+
+    for (judge_id, event_id) in judges {
+        let count = event_counts.entry(event_id).or_insert(0);
+
+        judge_labels.insert(judge_id, judge_number_label(*count));
+        *count += 1;
+    }
+
+    Ok(judge_labels)
+}
+
+// Every score as one JSON object per line, for loading into other tools
+pub async fn generate_jsonl(
+    State(pool): State<PgPool>,
+    Query(param): Query<ExportParam>,
+    Query(window): Query<TimeWindow>,
+) -> Result<(http::StatusCode, Vec<u8>), AppError> {
+    let judge_labels = export_judge_labels(&pool, &param).await?;
+
+    let mut jsonl_bytes = Vec::new();
+
+    for mut row in fetch_score_report(&pool, &window, param.event_id.as_ref()).await? {
+        if let Some(label) = judge_labels.get(&row.judge_id) {
+            row.judge_name = label.to_owned();
+        }
+
+        serde_json::to_writer(&mut jsonl_bytes, &row).map_err(|err| {
+            AppError::new(
+                http::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to serialize record: {}", err),
+            )
+        })?;
+        jsonl_bytes.push(b'\n');
+    }
+
+    Ok((http::StatusCode::OK, jsonl_bytes))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ScoreChangesParam {
+    // Cursor from the previous sync, everything is returned without it
+    since: Option<i64>,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct ScoreChange {
+    // 'upsert' for created or updated scores, 'void' for voided ones, 'delete' for removed ones
+    change: String,
+    score_id: uuid::Uuid,
+    score: i32,
+    max: i32,
+    time_of_scoring: chrono::DateTime<chrono::Utc>,
+    remark: Option<SealedText>,
+    void_reason: Option<String>,
+    changed_at: chrono::DateTime<chrono::Utc>,
+    // Relationships
+    candidate_id: uuid::Uuid,
+    criteria_id: uuid::Uuid,
+    category_id: uuid::Uuid,
+    judge_id: uuid::Uuid,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ScoreChanges {
+    changes: Vec<ScoreChange>,
+    // Pass as `since` on the next sync
+    cursor: i64,
+}
+
+// For external mirrors (e.g. the school's archive) to sync without re-downloading everything
+// The cursor is the oldest transaction still running, changes from it on are left for the next sync
+pub async fn get_score_changes(
+    State(pool): State<PgPool>,
+    Path(event_id): Path<uuid::Uuid>,
+    Query(param): Query<ScoreChangesParam>,
+) -> Result<axum::Json<ScoreChanges>, AppError> {
+    let mut txn = pool.begin().await?;
+
+    // Both queries have to see the same snapshot
+    sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ READ ONLY")
+        .execute(&mut *txn)
+        .await?;
+
+    let cursor: i64 =
+        sqlx::query_scalar("SELECT pg_snapshot_xmin(pg_current_snapshot())::TEXT::BIGINT")
+            .fetch_one(&mut *txn)
+            .await?;
+
+    let changes = sqlx::query_as::<_, ScoreChange>(
+        r#"
+        SELECT
+            sc.change, sc.score_id, sc.score, sc.max, sc.time_of_scoring, sc.remark,
+            sc.void_reason, sc.changed_at,
+            sc.candidate_id, sc.criteria_id, sc.category_id, sc.judge_id
+        FROM score_changes sc
+        JOIN categories cat ON cat.id = sc.category_id
+        WHERE cat.event_id = ($1)
+            AND sc.is_rehearsal = FALSE
+            AND sc.txid >= COALESCE(($2)::TEXT::XID8, '0'::XID8)
+            AND sc.txid < ($3)::TEXT::XID8
+        ORDER BY sc.txid, sc.seq
+        "#,
+    )
+    .bind(&event_id)
+    .bind(param.since)
+    .bind(cursor)
+    .fetch_all(&mut *txn)
+    .await?;
+
+    txn.commit().await?;
+
+    Ok(axum::Json(ScoreChanges { changes, cursor }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FeedbackReportParam {
+    event_id: uuid::Uuid,
+}
+
+// Criteria shown on each side of a candidate's highlights
+const HIGHLIGHT_COUNT: usize = 3;
+
+// Percentages of the criteria's max, averaged over the judges
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct CriteriaHighlight {
+    pub criteria_id: uuid::Uuid,
+    pub criteria_name: String,
+    pub category_name: String,
+    pub percent: f64,
+    // Average of every candidate's percent on the criteria
+    pub field_percent: f64,
+    #[sqlx(default)]
+    pub difference: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CandidateHighlights {
+    candidate_id: uuid::Uuid,
+    event_id: uuid::Uuid,
+    strongest: Vec<CriteriaHighlight>,
+    weakest: Vec<CriteriaHighlight>,
+}
+
+// (strongest, weakest) relative to the field, a criteria never shows up on both sides
+pub fn pick_highlights(
+    mut criterias: Vec<CriteriaHighlight>,
+    count: usize,
+) -> (Vec<CriteriaHighlight>, Vec<CriteriaHighlight>) {
+    for criteria in criterias.iter_mut() {
+        criteria.difference = criteria.percent - criteria.field_percent;
+    }
+
+    criterias.sort_by(|a, b| b.difference.total_cmp(&a.difference));
+
+    let strongest_count = count.min(criterias.len());
+    let weakest_count = count.min(criterias.len() - strongest_count);
+
+    let mut weakest = criterias.split_off(criterias.len() - weakest_count);
+    weakest.reverse();
+    criterias.truncate(strongest_count);
+
+    (criterias, weakest)
+}
+
+pub async fn fetch_highlights(
+    pool: &PgPool,
+    candidate_id: &uuid::Uuid,
+    event_id: &uuid::Uuid,
+) -> Result<(Vec<CriteriaHighlight>, Vec<CriteriaHighlight>), AppError> {
+    // Deductions have no max to compare against, excluded judges don't count in the standings either
+    let criterias = sqlx::query_as::<_, CriteriaHighlight>(
+        r#"
+        WITH per_candidate AS (
+            SELECT
+                criteria_id,
+                criteria_name,
+                category_name,
+                candidate_id,
+                AVG(score::FLOAT8 / max) * 100 AS percent
+            FROM score_report
+            WHERE event_id = ($2) AND max > 0 AND judge_score_exclusion = FALSE
+            GROUP BY criteria_id, criteria_name, category_name, candidate_id
+        )
+        SELECT
+            c.criteria_id,
+            c.criteria_name,
+            c.category_name,
+            c.percent,
+            (SELECT AVG(f.percent) FROM per_candidate f WHERE f.criteria_id = c.criteria_id) AS field_percent
+        FROM per_candidate c
+        WHERE c.candidate_id = ($1)
+        "#,
+    )
+    .bind(candidate_id)
+    .bind(event_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(pick_highlights(criterias, HIGHLIGHT_COUNT))
+}
+
+// A candidate's strongest and weakest criteria compared to the rest of the field
+pub async fn get_candidate_highlights(
+    State(pool): State<PgPool>,
+    Path(candidate_id): Path<uuid::Uuid>,
+    Query(param): Query<FeedbackReportParam>,
+) -> Result<axum::Json<CandidateHighlights>, AppError> {
+    fetch_event_candidate(&pool, &candidate_id, &param.event_id).await?;
+
+    let (strongest, weakest) = fetch_highlights(&pool, &candidate_id, &param.event_id).await?;
+
+    Ok(axum::Json(CandidateHighlights {
+        candidate_id,
+        event_id: param.event_id,
+        strongest,
+        weakest,
+    }))
+}
+
+// Another event's candidate is treated as missing
+async fn fetch_event_candidate(
+    pool: &PgPool,
+    candidate_id: &uuid::Uuid,
+    event_id: &uuid::Uuid,
+) -> Result<Candidate, AppError> {
+    sqlx::query_as::<_, Candidate>(
+        r#"
+        SELECT c.* FROM candidates c
+        JOIN categories cat ON cat.id = c.category_id
+        WHERE c.id = ($1) AND cat.event_id = ($2) AND c.deleted_at IS NULL
+        "#,
+    )
+    .bind(candidate_id)
+    .bind(event_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::new(http::StatusCode::NOT_FOUND, "Candidate not found"))
+}
+
+// Per-candidate breakdown of their scores, handed out after the event
+pub async fn generate_feedback_report(
+    State(pool): State<PgPool>,
+    Path(candidate_id): Path<uuid::Uuid>,
+    Query(param): Query<FeedbackReportParam>,
+) -> Result<(http::StatusCode, Vec<u8>), AppError> {
+    let candidate = fetch_event_candidate(&pool, &candidate_id, &param.event_id).await?;
+
+    let judges = sqlx::query_as::<_, (uuid::Uuid, String)>(
+        "SELECT id, name FROM judges WHERE event_id = ($1) AND score_exclusion = FALSE ORDER BY name",
+    )
+    .bind(&param.event_id)
+    .fetch_all(&pool)
+    .await?;
+
+    let judge_labels: HashMap<uuid::Uuid, String> = judges
+        .iter()
+        .enumerate()
+        .map(|(idx, (judge_id, _))| (*judge_id, judge_letter_label(idx)))
+        .collect();
+
+    let categories = sqlx::query_as::<_, (uuid::Uuid, String)>(
+        "SELECT id, name FROM categories WHERE event_id = ($1) ORDER BY name",
+    )
+    .bind(&param.event_id)
+    .fetch_all(&pool)
+    .await?;
+
+    // (criteria_id, judge_id) -> score
+    let scores: HashMap<(uuid::Uuid, uuid::Uuid), i32> =
+        sqlx::query_as::<_, (uuid::Uuid, uuid::Uuid, i32)>(
+            r#"
+            SELECT s.criteria_id, s.judge_id, s.score
+            FROM scores_history s
+            JOIN categories cat ON cat.id = s.category_id
+            WHERE s.candidate_id = ($1) AND cat.event_id = ($2) AND s.is_rehearsal = FALSE
+            "#,
+        )
+        .bind(&candidate_id)
+        .bind(&param.event_id)
+        .fetch_all(&pool)
+        .await?
+        .into_iter()
+        .map(|(criteria_id, judge_id, score)| ((criteria_id, judge_id), score))
+        .collect();
+
+    let notes = sqlx::query_as::<_, (uuid::Uuid, String)>(
+        r#"
+        SELECT n.judge_id, n.note
+        FROM notes n
+        JOIN judges j ON j.id = n.judge_id
+        WHERE n.candidate_id = ($1) AND j.event_id = ($2)
+        ORDER BY n.last_change
+        "#,
+    )
+    .bind(&candidate_id)
+    .bind(&param.event_id)
+    .fetch_all(&pool)
+    .await?;
+
+    let mut workbook = Workbook::new();
+    let worksheet = workbook.add_worksheet();
+
+    let heading_format = Format::new().set_font_size(13.5).set_bold();
+    let bold_format = Format::new().set_bold();
+    let bold_center_format = Format::new().set_bold().set_align(FormatAlign::Center);
+
+    worksheet.set_column_width(0, 45)?;
+
+    worksheet.write_with_format(
+        0,
+        0,
+        format!(
+            "#{} {}, {} {}",
+            candidate.candidate_number,
+            candidate.last_name.trim(),
+            candidate.first_name.trim(),
+            candidate.middle_name.trim()
+        ),
+        &heading_format,
+    )?;
+
+    let mut row_offset: u32 = 2;
+
+    for (category_id, category_name) in categories.iter() {
+        // Deductions show how low they can go instead of their max of 0
+        let criterias = sqlx::query_as::<_, (uuid::Uuid, String, i32)>(
+            r#"
+            SELECT
+                id,
+                CASE WHEN is_deduction THEN name || ' (deduction)' ELSE name END,
+                CASE WHEN is_deduction THEN min_score ELSE max_score END
+            FROM criterias
+            WHERE category_id = ($1)
+            ORDER BY is_deduction, name
+            "#,
+        )
+        .bind(category_id)
+        .fetch_all(&pool)
+        .await?;
+
+        worksheet.write_with_format(row_offset, 0, category_name, &bold_format)?;
+        worksheet.write_with_format(row_offset + 1, 0, "Criteria", &bold_center_format)?;
+
+        for (judge_idx, (judge_id, _)) in judges.iter().enumerate() {
+            worksheet.set_column_width(judge_idx as u16 + 1, 12)?;
+            worksheet.write_with_format(
+                row_offset + 1,
+                judge_idx as u16 + 1,
+                &judge_labels[judge_id],
+                &bold_center_format,
+            )?;
+        }
+
+        worksheet.write_with_format(
+            row_offset + 1,
+            judges.len() as u16 + 1,
+            "Max",
+            &bold_center_format,
+        )?;
+
+        for (criteria_idx, (criteria_id, criteria_name, max_score)) in criterias.iter().enumerate()
+        {
+            let row = row_offset + 2 + criteria_idx as u32;
+
+            worksheet.write(row, 0, criteria_name)?;
+
+            for (judge_idx, (judge_id, _)) in judges.iter().enumerate() {
+                if let Some(score) = scores.get(&(*criteria_id, *judge_id)) {
+                    worksheet.write(row, judge_idx as u16 + 1, *score)?;
+                }
+            }
+
+            worksheet.write(row, judges.len() as u16 + 1, *max_score)?;
+        }
+
+        row_offset += criterias.len() as u32 + 3;
+    }
+
+    worksheet.write_with_format(row_offset, 0, "Remarks", &bold_format)?;
+
+    for (note_idx, (judge_id, note)) in notes.iter().enumerate() {
+        let label = judge_labels
+            .get(judge_id)
+            .map(|label| label.as_str())
+            .unwrap_or("Judge");
+
+        worksheet.write(row_offset + 1 + note_idx as u32, 0, label)?;
+        worksheet.write(row_offset + 1 + note_idx as u32, 1, note)?;
+    }
+
+    row_offset += notes.len() as u32 + 2;
+
+    let (strongest, weakest) = fetch_highlights(&pool, &candidate_id, &param.event_id).await?;
+
+    for (heading, highlights) in [("Strongest", &strongest), ("Weakest", &weakest)] {
+        if highlights.is_empty() {
+            continue;
+        }
+
+        worksheet.write_with_format(row_offset, 0, heading, &bold_format)?;
+        worksheet.write_with_format(row_offset, 1, "You", &bold_center_format)?;
+        worksheet.write_with_format(row_offset, 2, "Field", &bold_center_format)?;
+
+        for (idx, highlight) in highlights.iter().enumerate() {
+            let row = row_offset + 1 + idx as u32;
+
+            worksheet.write(
+                row,
+                0,
+                format!("{} ({})", highlight.criteria_name, highlight.category_name),
+            )?;
+            worksheet.write(row, 1, format!("{:.1}%", highlight.percent))?;
+            worksheet.write(row, 2, format!("{:.1}%", highlight.field_percent))?;
+        }
+
+        row_offset += highlights.len() as u32 + 2;
+    }
+
+    let workbook_buffer = workbook.save_to_buffer()?;
+
+    Ok((http::StatusCode::OK, workbook_buffer))
+}
@@ -0,0 +1,273 @@
+use axum::extract::{Extension, Path, State};
+use axum::http;
+use axum::response::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+
+use crate::error::AppError;
+
+use super::admin::StaffClaims;
+use super::audit;
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct Round {
+    pub id: uuid::Uuid,
+    pub name: String,
+    // Rounds are held in this order
+    pub position: i32,
+    // At most one round per event, its categories are the only ones of a round that accept scores
+    pub is_active: bool,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    // Relationships
+    pub event_id: uuid::Uuid,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateRound {
+    name: String,
+    // After the event's last round when omitted
+    position: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateRound {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    position: Option<i32>,
+    // Activating a round deactivates the event's other rounds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    is_active: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RoundCategories {
+    category_ids: Vec<uuid::Uuid>,
+}
+
+fn validate_name(name: &str) -> Result<(), AppError> {
+    if name.trim().is_empty() {
+        return Err(AppError::new(
+            http::StatusCode::UNPROCESSABLE_ENTITY,
+            "The round's name can't be blank",
+        ));
+    }
+
+    Ok(())
+}
+
+pub async fn fetch_round(
+    pool: &PgPool,
+    event_id: &uuid::Uuid,
+    round_id: &uuid::Uuid,
+) -> Result<Round, AppError> {
+    sqlx::query_as::<_, Round>("SELECT * FROM rounds WHERE id = ($1) AND event_id = ($2)")
+        .bind(round_id)
+        .bind(event_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::new(http::StatusCode::NOT_FOUND, "Round not found"))
+}
+
+pub async fn create_round(
+    State(pool): State<PgPool>,
+    Extension(staff): Extension<StaffClaims>,
+    Path(event_id): Path<uuid::Uuid>,
+    axum::Json(payload): axum::Json<CreateRound>,
+) -> Result<(http::StatusCode, axum::Json<Round>), AppError> {
+    validate_name(&payload.name)?;
+
+    let round = sqlx::query_as::<_, Round>(
+        r#"
+        INSERT INTO rounds (name, position, event_id)
+        SELECT ($1), COALESCE(($2), (SELECT COALESCE(MAX(position), 0) + 1 FROM rounds WHERE event_id = ($3))), ($3)
+        RETURNING *
+        "#,
+    )
+    .bind(payload.name.trim())
+    .bind(&payload.position)
+    .bind(&event_id)
+    .fetch_one(&pool)
+    .await?;
+
+    audit::record(
+        &pool,
+        staff.sub,
+        "round",
+        Some(&round.id),
+        "create",
+        serde_json::json!({
+            "name": round.name,
+            "position": round.position,
+        }),
+    )
+    .await?;
+
+    Ok((http::StatusCode::CREATED, axum::Json(round)))
+}
+
+pub async fn get_rounds(
+    State(pool): State<PgPool>,
+    Path(event_id): Path<uuid::Uuid>,
+) -> Result<axum::Json<Vec<Round>>, AppError> {
+    let rounds = sqlx::query_as::<_, Round>(
+        "SELECT * FROM rounds WHERE event_id = ($1) ORDER BY position, created_at",
+    )
+    .bind(&event_id)
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(axum::Json(rounds))
+}
+
+pub async fn get_round(
+    State(pool): State<PgPool>,
+    Path((event_id, round_id)): Path<(uuid::Uuid, uuid::Uuid)>,
+) -> Result<axum::Json<Round>, AppError> {
+    Ok(axum::Json(fetch_round(&pool, &event_id, &round_id).await?))
+}
+
+pub async fn update_round(
+    State(pool): State<PgPool>,
+    Extension(staff): Extension<StaffClaims>,
+    Path((event_id, round_id)): Path<(uuid::Uuid, uuid::Uuid)>,
+    axum::Json(payload): axum::Json<UpdateRound>,
+) -> Result<axum::Json<Round>, AppError> {
+    if let Some(name) = &payload.name {
+        validate_name(name)?;
+    }
+
+    fetch_round(&pool, &event_id, &round_id).await?;
+
+    let mut txn = pool.begin().await?;
+
+    // Cleared first, the index allows only one active round per event
+    if payload.is_active == Some(true) {
+        sqlx::query("UPDATE rounds SET is_active = FALSE WHERE event_id = ($1) AND id <> ($2)")
+            .bind(&event_id)
+            .bind(&round_id)
+            .execute(&mut *txn)
+            .await?;
+    }
+
+    let round = sqlx::query_as::<_, Round>(
+        r#"
+        UPDATE rounds
+        SET name = COALESCE(($1), name),
+            position = COALESCE(($2), position),
+            is_active = COALESCE(($3), is_active)
+        WHERE id = ($4)
+        RETURNING *
+        "#,
+    )
+    .bind(payload.name.as_deref().map(str::trim))
+    .bind(&payload.position)
+    .bind(&payload.is_active)
+    .bind(&round_id)
+    .fetch_one(&mut *txn)
+    .await?;
+
+    audit::record(
+        &mut *txn,
+        staff.sub,
+        "round",
+        Some(&round_id),
+        "update",
+        serde_json::to_value(&payload).unwrap_or_default(),
+    )
+    .await?;
+
+    txn.commit().await?;
+
+    Ok(axum::Json(round))
+}
+
+// The round's categories stay, outside of any round
+pub async fn delete_round(
+    State(pool): State<PgPool>,
+    Extension(staff): Extension<StaffClaims>,
+    Path((event_id, round_id)): Path<(uuid::Uuid, uuid::Uuid)>,
+) -> Result<http::StatusCode, AppError> {
+    let round = fetch_round(&pool, &event_id, &round_id).await?;
+
+    let mut txn = pool.begin().await?;
+
+    sqlx::query("DELETE FROM rounds WHERE id = ($1)")
+        .bind(&round_id)
+        .execute(&mut *txn)
+        .await?;
+
+    audit::record(
+        &mut *txn,
+        staff.sub,
+        "round",
+        Some(&round_id),
+        "delete",
+        serde_json::json!({
+            "name": round.name,
+            "position": round.position,
+        }),
+    )
+    .await?;
+
+    txn.commit().await?;
+
+    Ok(http::StatusCode::NO_CONTENT)
+}
+
+// Replaces the round's categories, a category moved here leaves its previous round
+pub async fn set_round_categories(
+    State(pool): State<PgPool>,
+    Extension(staff): Extension<StaffClaims>,
+    Path((event_id, round_id)): Path<(uuid::Uuid, uuid::Uuid)>,
+    axum::Json(payload): axum::Json<RoundCategories>,
+) -> Result<axum::Json<Vec<uuid::Uuid>>, AppError> {
+    fetch_round(&pool, &event_id, &round_id).await?;
+
+    let mut requested = payload.category_ids;
+
+    requested.sort();
+    requested.dedup();
+
+    let mut txn = pool.begin().await?;
+
+    sqlx::query("UPDATE categories SET round_id = NULL WHERE round_id = ($1)")
+        .bind(&round_id)
+        .execute(&mut *txn)
+        .await?;
+
+    let category_ids: Vec<uuid::Uuid> = sqlx::query_scalar(
+        r#"
+        UPDATE categories SET round_id = ($1)
+        WHERE id = ANY($2) AND event_id = ($3)
+        RETURNING id
+        "#,
+    )
+    .bind(&round_id)
+    .bind(&requested)
+    .bind(&event_id)
+    .fetch_all(&mut *txn)
+    .await?;
+
+    if category_ids.len() != requested.len() {
+        return Err(AppError::new(
+            http::StatusCode::UNPROCESSABLE_ENTITY,
+            "Every category must belong to the round's event",
+        ));
+    }
+
+    audit::record(
+        &mut *txn,
+        staff.sub,
+        "round",
+        Some(&round_id),
+        "set_categories",
+        serde_json::json!({ "category_ids": category_ids }),
+    )
+    .await?;
+
+    txn.commit().await?;
+
+    Ok(axum::Json(category_ids))
+}
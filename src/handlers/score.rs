@@ -1,114 +1,852 @@
 use std::collections::HashMap;
 
-use anyhow::Context;
-use axum::extract::{Query, State};
+use axum::extract::{Extension, Path, Query, State};
 use axum::http;
 use axum::response::Result;
 use chrono::Local;
 use rust_xlsxwriter::*;
 use serde::{Deserialize, Serialize};
-use sqlx::query::QueryAs;
-use sqlx::{FromRow, PgPool, Row};
+use sqlx::{FromRow, PgConnection, PgExecutor, PgPool};
 
+use crate::aggregation::Aggregator;
 use crate::error::AppError;
-
+use crate::event_lock;
+use crate::field_crypto::SealedText;
+use crate::score_batcher::{NewScore, ScoreBatcher};
+
+use super::admin::StaffClaims;
+use super::advancement;
+use super::analytics;
+use super::audit;
+use super::blackout;
+use super::auth::AuthenticatedJudge;
 use super::category::Category;
-use super::criteria::Criteria;
-use super::event::Event;
-use super::judge::Judge;
+use super::cover;
+use super::criteria::{self, Criteria};
+use super::history::AwardWinner;
+use super::judge::{self, Judge};
+use super::receipt::{self, Receipt};
+use super::recheck;
+use super::report::{self, ExportParam, TimeWindow};
+use super::score_feed::ScoreFeed;
+use super::settings;
+use super::standings::{self, DivisionedStandings, RankedCandidate};
+use super::tie_break;
 use super::Round;
 
 #[derive(Debug, Deserialize, Serialize, FromRow)]
 pub struct Score {
-    id: uuid::Uuid,
-    score: i32,
-    max: i32,
-    time_of_scoring: chrono::DateTime<chrono::Utc>,
+    pub id: uuid::Uuid,
+    pub score: i32,
+    pub max: i32,
+    pub time_of_scoring: chrono::DateTime<chrono::Utc>,
     // Relationships
-    candidate_id: uuid::Uuid,
-    criteria_id: uuid::Uuid,
-    category_id: uuid::Uuid,
-    judge_id: uuid::Uuid,
+    pub candidate_id: uuid::Uuid,
+    pub criteria_id: uuid::Uuid,
+    pub category_id: uuid::Uuid,
+    pub judge_id: uuid::Uuid,
+    pub remark: Option<SealedText>,
+    pub group_id: Option<uuid::Uuid>,
+    // Changes on every write, retried updates send it back as their `revision`
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+// Accepted submissions come with a receipt the judge's device keeps
+#[derive(Debug, Serialize)]
+pub struct ScoreWithReceipt {
+    #[serde(flatten)]
+    score: Score,
+    receipt: Receipt,
+    // False when the submission replaced the judge's earlier score
+    created: bool,
+}
+
+// A judge has one score per candidate and criteria, submitting again replaces it
+pub const UPSERT_SCORE: &str = r#" ON CONFLICT (judge_id, candidate_id, criteria_id) DO UPDATE SET
+    score = EXCLUDED.score,
+    max = EXCLUDED.max,
+    time_of_scoring = EXCLUDED.time_of_scoring,
+    remark = EXCLUDED.remark,
+    group_id = EXCLUDED.group_id,
+    is_rehearsal = EXCLUDED.is_rehearsal
+RETURNING *, (xmax = 0) AS created"#;
+
+#[derive(Debug, FromRow)]
+pub struct UpsertedScore {
+    #[sqlx(flatten)]
+    pub score: Score,
+    pub created: bool,
+}
+
+// Numbers for numeric criteria, true/false for boolean ones, the option label for selections
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum ScoreValue {
+    Numeric(i32),
+    Boolean(bool),
+    Selection(String),
 }
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct CreateScore {
+    pub score: ScoreValue,
+    pub candidate_id: uuid::Uuid,
+    pub criteria_id: uuid::Uuid,
+    pub category_id: uuid::Uuid,
+    pub judge_id: uuid::Uuid,
+    pub remark: Option<String>,
+    // The device's clock when it sent the score, see `analytics::get_submission_latency`
+    pub client_sent_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+// Whether the event's rules require a remark for this score
+pub fn requires_remark(
     score: i32,
     max: i32,
-    candidate_id: uuid::Uuid,
-    criteria_id: uuid::Uuid,
-    category_id: uuid::Uuid,
-    judge_id: uuid::Uuid,
+    low_threshold: Option<f32>,
+    remark_on_perfect: bool,
+) -> bool {
+    if max <= 0 {
+        return false;
+    }
+
+    let ratio = score as f32 / max as f32;
+
+    let is_low = low_threshold.is_some_and(|threshold| ratio < threshold);
+    let is_perfect = remark_on_perfect && score >= max;
+
+    is_low || is_perfect
+}
+
+// Rejects extreme scores without a remark to discourage careless extremes
+pub async fn validate_remark(
+    pool: &PgPool,
+    category_id: &uuid::Uuid,
+    score: i32,
+    max: i32,
+    remark: Option<&String>,
+) -> Result<(), AppError> {
+    let (low_threshold, remark_on_perfect) = sqlx::query_as::<_, (Option<f32>, bool)>(
+        r#"
+        SELECT e.remark_low_threshold, e.remark_on_perfect
+        FROM categories cat
+        JOIN events e ON e.id = cat.event_id
+        WHERE cat.id = ($1)
+        "#,
+    )
+    .bind(category_id)
+    .fetch_one(pool)
+    .await?;
+
+    let has_remark = remark.is_some_and(|remark| !remark.trim().is_empty());
+
+    if !has_remark && requires_remark(score, max, low_threshold, remark_on_perfect) {
+        return Err(AppError::new(
+            http::StatusCode::UNPROCESSABLE_ENTITY,
+            format!("A remark is required for a score of {} out of {}", score, max),
+        ));
+    }
+
+    Ok(())
+}
+
+// Standings are recomputed by the aggregation worker instead of on every submission
+pub async fn request_recompute(
+    pool: &PgPool,
+    aggregator: &Aggregator,
+    category_id: &uuid::Uuid,
+) -> Result<(), AppError> {
+    let event_id: uuid::Uuid = sqlx::query_scalar("SELECT event_id FROM categories WHERE id = ($1)")
+        .bind(category_id)
+        .fetch_one(pool)
+        .await?;
+
+    aggregator.request(event_id);
+
+    Ok(())
+}
+
+// The scores `void_scores` moves out of the tabulation
+#[derive(Debug, Clone, Copy)]
+pub enum VoidedScores {
+    Category(uuid::Uuid),
+    Criteria(uuid::Uuid),
+    Candidate(uuid::Uuid),
+    JudgeCandidate(uuid::Uuid, uuid::Uuid),
+}
+
+// Moves the scores to `voided_scores` and rejects the corrections still pending on them,
+// returns how many were voided
+pub async fn void_scores<'e, E: PgExecutor<'e>>(
+    executor: E,
+    scores: VoidedScores,
+    reason: &str,
+) -> Result<u64, AppError> {
+    let (filter, id, other_id) = match scores {
+        VoidedScores::Category(id) => ("category_id = ($2)", id, None),
+        VoidedScores::Criteria(id) => ("criteria_id = ($2)", id, None),
+        VoidedScores::Candidate(id) => ("candidate_id = ($2)", id, None),
+        VoidedScores::JudgeCandidate(judge_id, candidate_id) => {
+            ("judge_id = ($2) AND candidate_id = ($3)", judge_id, Some(candidate_id))
+        }
+    };
+
+    let query = format!(
+        r#"
+        WITH removed AS (
+            DELETE FROM scores WHERE {filter} RETURNING *
+        ),
+        voided AS (
+            INSERT INTO voided_scores (id, score, max, time_of_scoring, candidate_id, criteria_id, category_id, judge_id, remark, group_id, is_rehearsal, updated_at, void_reason)
+            SELECT id, score, max, time_of_scoring, candidate_id, criteria_id, category_id, judge_id, remark, group_id, is_rehearsal, updated_at, ($1)
+            FROM removed
+            RETURNING id
+        ),
+        rejected AS (
+            UPDATE score_correction_requests
+            SET status = 'rejected', resolution_note = 'Scores were voided', resolved_at = NOW()
+            WHERE status = 'pending' AND score_id IN (SELECT id FROM voided)
+        )
+        SELECT COUNT(*) FROM voided
+        "#
+    );
+
+    let mut query = sqlx::query_scalar::<_, i64>(&query).bind(reason).bind(id);
+
+    if let Some(other_id) = other_id {
+        query = query.bind(other_id);
+    }
+
+    Ok(query.fetch_one(executor).await? as u64)
+}
+
+#[derive(Debug, FromRow)]
+struct ScoreReferences {
+    criteria_category_id: Option<uuid::Uuid>,
+    category_event_id: Option<uuid::Uuid>,
+    judge_event_id: Option<uuid::Uuid>,
+    // None when the category isn't part of a round
+    round_is_active: Option<bool>,
+}
+
+// Bad client state would otherwise be stored and silently skew the tabulation
+pub async fn validate_score_references(
+    pool: &PgPool,
+    judge_id: &uuid::Uuid,
+    category_id: &uuid::Uuid,
+    criteria_id: Option<&uuid::Uuid>,
+) -> Result<(), AppError> {
+    let refs = sqlx::query_as::<_, ScoreReferences>(
+        r#"
+        SELECT
+            (SELECT category_id FROM criterias WHERE id = ($1)) AS criteria_category_id,
+            (SELECT event_id FROM categories WHERE id = ($2)) AS category_event_id,
+            (SELECT event_id FROM judges WHERE id = ($3)) AS judge_event_id,
+            (
+                SELECT r.is_active FROM categories cat
+                JOIN rounds r ON r.id = cat.round_id
+                WHERE cat.id = ($2)
+            ) AS round_is_active
+        "#,
+    )
+    .bind(criteria_id)
+    .bind(category_id)
+    .bind(judge_id)
+    .fetch_one(pool)
+    .await?;
+
+    let mismatch = |message: String| AppError::new(http::StatusCode::UNPROCESSABLE_ENTITY, message);
+
+    let Some(category_event_id) = refs.category_event_id else {
+        return Err(mismatch(format!("Category {} does not exist", category_id)));
+    };
+
+    if refs.round_is_active == Some(false) {
+        return Err(AppError::new(
+            http::StatusCode::CONFLICT,
+            format!("Category {} belongs to a round that isn't active", category_id),
+        ));
+    }
+
+    if let Some(criteria_id) = criteria_id {
+        match refs.criteria_category_id {
+            None => return Err(mismatch(format!("Criteria {} does not exist", criteria_id))),
+            Some(criteria_category_id) if criteria_category_id != *category_id => {
+                return Err(mismatch(format!(
+                    "Criteria {} belongs to category {}, not {}",
+                    criteria_id, criteria_category_id, category_id
+                )));
+            }
+            _ => {}
+        }
+    }
+
+    match refs.judge_event_id {
+        None => Err(mismatch(format!("Judge {} does not exist", judge_id))),
+        Some(judge_event_id) if judge_event_id != category_event_id => Err(mismatch(format!(
+            "Category {} belongs to event {}, not the judge's event {}",
+            category_id, category_event_id, judge_event_id
+        ))),
+        _ => Ok(()),
+    }
 }
 
 // Submit score function for each individual judge
 pub async fn submit_score(
     State(pool): State<PgPool>,
+    State(aggregator): State<Aggregator>,
+    State(batcher): State<ScoreBatcher>,
+    State(feed): State<ScoreFeed>,
+    judge: AuthenticatedJudge,
     axum::Json(payload): axum::Json<CreateScore>,
-) -> Result<(http::StatusCode, axum::Json<Score>), AppError> {
-    let res = sqlx::query_as::<_, Score>(
-        r#"
-        INSERT INTO scores (score, max, candidate_id, criteria_id, category_id, judge_id) 
-        VALUES ($1, $2, $3, $4, $5, $6)
-        RETURNING *
-        "#,
+) -> Result<(http::StatusCode, axum::Json<ScoreWithReceipt>), AppError> {
+    let received_at = chrono::Utc::now();
+
+    judge.ensure_judge(&payload.judge_id)?;
+
+    store_score(&pool, &aggregator, &batcher, &feed, &judge.0, payload, received_at).await
+}
+
+// Everything a score goes through once its judge is known, from the tablets or scoring hardware
+pub async fn store_score(
+    pool: &PgPool,
+    aggregator: &Aggregator,
+    batcher: &ScoreBatcher,
+    feed: &ScoreFeed,
+    judge: &Judge,
+    payload: CreateScore,
+    received_at: chrono::DateTime<chrono::Utc>,
+) -> Result<(http::StatusCode, axum::Json<ScoreWithReceipt>), AppError> {
+    validate_score_references(
+        pool,
+        &payload.judge_id,
+        &payload.category_id,
+        Some(&payload.criteria_id),
     )
-    .bind(&payload.score)
-    .bind(&payload.max)
-    .bind(&payload.candidate_id)
-    .bind(&payload.criteria_id)
-    .bind(&payload.category_id)
-    .bind(&payload.judge_id)
-    .fetch_one(&pool)
-    .await;
+    .await?;
+    judge::validate_judge_seat(
+        pool,
+        &payload.judge_id,
+        &payload.category_id,
+        &[payload.candidate_id],
+        Some(&payload.criteria_id),
+    )
+    .await?;
+    blackout::ensure_not_blacked_out(pool, &payload.judge_id, &[payload.candidate_id]).await?;
+    advancement::ensure_not_eliminated(pool, &payload.category_id, &[payload.candidate_id])
+        .await?;
 
-    match res {
-        Ok(score) => Ok((http::StatusCode::CREATED, axum::Json(score))),
-        Err(err) => {
-            eprintln!("Failed to submit score: {err:?}");
+    // A straggling submission can't land in the middle of a finalization
+    let lock = event_lock::lock_category_shared(pool, &payload.category_id).await?;
 
-            Err(AppError::new(
-                http::StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Failed to submit score: {}", err),
-            ))
-        }
+    let resolved = criteria::resolve_points(pool, &payload.criteria_id, &payload.score).await?;
+
+    validate_remark(
+        pool,
+        &payload.category_id,
+        resolved.points,
+        resolved.max,
+        payload.remark.as_ref(),
+    )
+    .await?;
+
+    // Concurrent submissions are written together, see `ScoreBatcher`
+    let res = batcher
+        .insert(NewScore {
+            score: resolved.points,
+            max: resolved.max,
+            candidate_id: payload.candidate_id,
+            criteria_id: payload.criteria_id,
+            category_id: payload.category_id,
+            judge_id: payload.judge_id,
+            remark: payload.remark.clone(),
+        })
+        .await;
+
+    lock.release().await?;
+
+    let UpsertedScore { score, created } = res?;
+
+    audit::record(
+        pool,
+        score.judge_id,
+        "score",
+        Some(&score.id),
+        if created { "create" } else { "replace" },
+        serde_json::json!({
+            "candidate_id": score.candidate_id,
+            "criteria_id": score.criteria_id,
+            "category_id": score.category_id,
+            "score": score.score,
+            "remark": score.remark,
+        }),
+    )
+    .await?;
+
+    request_recompute(pool, aggregator, &score.category_id).await?;
+
+    analytics::record_submission_latency(
+        pool,
+        score.judge_id,
+        payload.client_sent_at,
+        received_at,
+    );
+    feed.publish(judge, &score, if created { "create" } else { "replace" });
+
+    let receipt = receipt::issue(score.id, score.judge_id, score.score)?;
+    let status = if created {
+        http::StatusCode::CREATED
+    } else {
+        http::StatusCode::OK
+    };
+
+    Ok((
+        status,
+        axum::Json(ScoreWithReceipt {
+            score,
+            receipt,
+            created,
+        }),
+    ))
+}
+
+// The judge UI's form for one candidate, keyed by criteria
+#[derive(Debug, Deserialize)]
+pub struct CriteriaScores {
+    candidate_id: uuid::Uuid,
+    category_id: uuid::Uuid,
+    judge_id: uuid::Uuid,
+    scores: HashMap<uuid::Uuid, ScoreValue>,
+    #[serde(default)]
+    remarks: HashMap<uuid::Uuid, String>,
+    client_sent_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+// Every criteria must belong to the category, and every non-deduction criteria must be scored
+pub fn check_criteria_set(criterias: &[Criteria], scored: &[uuid::Uuid]) -> Result<(), String> {
+    if let Some(unknown) = scored
+        .iter()
+        .find(|id| !criterias.iter().any(|criteria| criteria.id == **id))
+    {
+        return Err(format!("Criteria {} does not belong to this category", unknown));
+    }
+
+    let missing: Vec<&str> = criterias
+        .iter()
+        .filter(|criteria| !criteria.is_deduction && !scored.contains(&criteria.id))
+        .map(|criteria| criteria.name.as_str())
+        .collect();
+
+    if !missing.is_empty() {
+        return Err(format!("Missing scores for {}", missing.join(", ")));
     }
+
+    Ok(())
+}
+
+// Alternative to `submit_score` that scores the whole form at once, all or nothing
+pub async fn submit_criteria_scores(
+    State(pool): State<PgPool>,
+    State(aggregator): State<Aggregator>,
+    State(feed): State<ScoreFeed>,
+    judge: AuthenticatedJudge,
+    axum::Json(payload): axum::Json<CriteriaScores>,
+) -> Result<(http::StatusCode, axum::Json<Vec<ScoreWithReceipt>>), AppError> {
+    let received_at = chrono::Utc::now();
+
+    judge.ensure_judge(&payload.judge_id)?;
+    // Criteria are checked against the category's own set below
+    validate_score_references(&pool, &payload.judge_id, &payload.category_id, None).await?;
+    judge::validate_judge_seat(
+        &pool,
+        &payload.judge_id,
+        &payload.category_id,
+        &[payload.candidate_id],
+        None,
+    )
+    .await?;
+    blackout::ensure_not_blacked_out(&pool, &payload.judge_id, &[payload.candidate_id]).await?;
+    advancement::ensure_not_eliminated(&pool, &payload.category_id, &[payload.candidate_id])
+        .await?;
+
+    let criterias =
+        sqlx::query_as::<_, Criteria>("SELECT * FROM criterias WHERE category_id = ($1)")
+            .bind(&payload.category_id)
+            .fetch_all(&pool)
+            .await?;
+
+    let scored: Vec<uuid::Uuid> = payload.scores.keys().copied().collect();
+
+    check_criteria_set(&criterias, &scored)
+        .map_err(|message| AppError::new(http::StatusCode::UNPROCESSABLE_ENTITY, message))?;
+
+    let mut new_scores = Vec::with_capacity(criterias.len());
+
+    for criteria in criterias.iter() {
+        let Some(value) = payload.scores.get(&criteria.id) else {
+            continue;
+        };
+
+        let resolved = criteria::resolve_score(criteria, value)?;
+        let remark = payload.remarks.get(&criteria.id).cloned();
+
+        validate_remark(
+            &pool,
+            &payload.category_id,
+            resolved.points,
+            resolved.max,
+            remark.as_ref(),
+        )
+        .await?;
+
+        new_scores.push((resolved.points, resolved.max, criteria.id, remark));
+    }
+
+    if new_scores.is_empty() {
+        return Err(AppError::new(
+            http::StatusCode::UNPROCESSABLE_ENTITY,
+            "There are no scores to submit",
+        ));
+    }
+
+    let lock = event_lock::lock_category_shared(&pool, &payload.category_id).await?;
+
+    let mut txn = pool.begin().await?;
+
+    let mut query = sqlx::QueryBuilder::<sqlx::Postgres>::new(
+        "INSERT INTO scores (score, max, candidate_id, criteria_id, category_id, judge_id, remark) ",
+    );
+
+    query.push_values(new_scores.iter(), |mut row, (points, max, criteria_id, remark)| {
+        row.push_bind(*points)
+            .push_bind(*max)
+            .push_bind(payload.candidate_id)
+            .push_bind(*criteria_id)
+            .push_bind(payload.category_id)
+            .push_bind(payload.judge_id)
+            .push_bind(remark.clone().map(SealedText));
+    });
+
+    query.push(UPSERT_SCORE);
+
+    let scores = query
+        .build_query_as::<UpsertedScore>()
+        .fetch_all(&mut *txn)
+        .await?;
+
+    for UpsertedScore { score, created } in scores.iter() {
+        audit::record(
+            &mut *txn,
+            score.judge_id,
+            "score",
+            Some(&score.id),
+            if *created { "create" } else { "replace" },
+            serde_json::json!({
+                "candidate_id": score.candidate_id,
+                "criteria_id": score.criteria_id,
+                "category_id": score.category_id,
+                "score": score.score,
+                "remark": score.remark,
+            }),
+        )
+        .await?;
+    }
+
+    txn.commit().await?;
+    lock.release().await?;
+
+    request_recompute(&pool, &aggregator, &payload.category_id).await?;
+
+    analytics::record_submission_latency(
+        &pool,
+        payload.judge_id,
+        payload.client_sent_at,
+        received_at,
+    );
+
+    let mut receipts = Vec::with_capacity(scores.len());
+
+    for UpsertedScore { score, created } in scores {
+        feed.publish(&judge.0, &score, if created { "create" } else { "replace" });
+
+        let receipt = receipt::issue(score.id, score.judge_id, score.score)?;
+        receipts.push(ScoreWithReceipt {
+            score,
+            receipt,
+            created,
+        });
+    }
+
+    Ok((http::StatusCode::CREATED, axum::Json(receipts)))
 }
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct UpdateScore {
     score_id: uuid::Uuid,
-    score: i32,
+    score: ScoreValue,
+    remark: Option<String>,
+    // What the device last saw, the update is refused if the score changed since
+    expected_score: Option<i32>,
+    // The score's `updated_at`
+    revision: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+// A retried update finds its own change already stored
+pub fn is_update_applied(score: &Score, points: i32, remark: Option<&String>) -> bool {
+    score.score == points
+        && remark.is_none_or(|remark| {
+            score.remark.as_ref().map(SealedText::as_str) == Some(remark.as_str())
+        })
+}
+
+fn score_changed_error() -> AppError {
+    AppError::new(
+        http::StatusCode::CONFLICT,
+        "Score was changed since it was last seen, reload it before updating",
+    )
 }
 
+// Safe to retry, an update that was already applied returns the stored row with 200
 pub async fn update_score(
     State(pool): State<PgPool>,
+    State(aggregator): State<Aggregator>,
+    State(feed): State<ScoreFeed>,
+    judge: AuthenticatedJudge,
     axum::Json(payload): axum::Json<UpdateScore>,
-) -> Result<(http::StatusCode, axum::Json<Score>), AppError> {
-    let res = sqlx::query_as::<_, Score>(
+) -> Result<(http::StatusCode, axum::Json<ScoreWithReceipt>), AppError> {
+    let current = sqlx::query_as::<_, Score>("SELECT * FROM scores WHERE id = ($1)")
+        .bind(&payload.score_id)
+        .fetch_optional(&pool)
+        .await?
+        .ok_or_else(|| AppError::new(http::StatusCode::NOT_FOUND, "Score not found"))?;
+
+    judge.ensure_judge(&current.judge_id)?;
+
+    let lock = event_lock::lock_category_shared(&pool, &current.category_id).await?;
+
+    let points = criteria::resolve_points(&pool, &current.criteria_id, &payload.score)
+        .await?
+        .points;
+
+    if is_update_applied(&current, points, payload.remark.as_ref()) {
+        lock.release().await?;
+
+        let receipt = receipt::issue(current.id, current.judge_id, current.score)?;
+
+        return Ok((
+            http::StatusCode::OK,
+            axum::Json(ScoreWithReceipt {
+                score: current,
+                receipt,
+                created: false,
+            }),
+        ));
+    }
+
+    if payload.expected_score.is_some_and(|expected| expected != current.score)
+        || payload.revision.is_some_and(|revision| revision != current.updated_at)
+    {
+        lock.release().await?;
+
+        return Err(score_changed_error());
+    }
+
+    validate_remark(
+        &pool,
+        &current.category_id,
+        points,
+        current.max,
+        payload
+            .remark
+            .as_ref()
+            .or(current.remark.as_ref().map(|remark| &remark.0)),
+    )
+    .await?;
+
+    // Only applies over the row that was checked above, a concurrent retry can't apply it twice
+    let updated = sqlx::query_as::<_, Score>(
         r#"
-        UPDATE scores SET score = ($1), time_of_scoring = ($2) 
-        WHERE id = ($3) 
+        UPDATE scores SET score = ($1), time_of_scoring = ($2), remark = COALESCE(($3), remark) 
+        WHERE id = ($4) AND updated_at = ($5)
         RETURNING *
         "#,
     )
-    .bind(&payload.score)
+    .bind(&points)
     .bind(Local::now())
+    .bind(payload.remark.clone().map(SealedText))
     .bind(&payload.score_id)
-    .fetch_one(&pool)
-    .await;
+    .bind(&current.updated_at)
+    .fetch_optional(&pool)
+    .await?;
 
-    match res {
-        Ok(score) => Ok((http::StatusCode::CREATED, axum::Json(score))),
-        Err(err) => {
-            eprintln!("Failed to submit score: {err:?}");
+    lock.release().await?;
 
-            Err(AppError::new(
-                http::StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Failed to submit score: {}", err),
-            ))
+    let Some(score) = updated else {
+        let latest = sqlx::query_as::<_, Score>("SELECT * FROM scores WHERE id = ($1)")
+            .bind(&payload.score_id)
+            .fetch_optional(&pool)
+            .await?
+            .ok_or_else(|| AppError::new(http::StatusCode::NOT_FOUND, "Score not found"))?;
+
+        if !is_update_applied(&latest, points, payload.remark.as_ref()) {
+            return Err(score_changed_error());
         }
+
+        let receipt = receipt::issue(latest.id, latest.judge_id, latest.score)?;
+
+        return Ok((
+            http::StatusCode::OK,
+            axum::Json(ScoreWithReceipt {
+                score: latest,
+                receipt,
+                created: false,
+            }),
+        ));
+    };
+
+    audit::record(
+        &pool,
+        score.judge_id,
+        "score",
+        Some(&score.id),
+        "update",
+        serde_json::json!({
+            "candidate_id": score.candidate_id,
+            "criteria_id": score.criteria_id,
+            "category_id": score.category_id,
+            "previous_score": current.score,
+            "score": score.score,
+            "remark": score.remark,
+        }),
+    )
+    .await?;
+
+    request_recompute(&pool, &aggregator, &score.category_id).await?;
+
+    feed.publish(&judge.0, &score, "update");
+
+    let receipt = receipt::issue(score.id, score.judge_id, score.score)?;
+
+    Ok((
+        http::StatusCode::CREATED,
+        axum::Json(ScoreWithReceipt {
+            score,
+            receipt,
+            created: false,
+        }),
+    ))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OverrideScore {
+    score: i32,
+    reason: String,
+}
+
+// Tabulator-side change of a score, even after the judge has been locked
+// Always requires a reason since it bypasses the judge
+pub async fn apply_override(
+    conn: &mut PgConnection,
+    actor: &uuid::Uuid,
+    score_id: &uuid::Uuid,
+    new_score: i32,
+    reason: &str,
+    details: serde_json::Value,
+) -> Result<Score, AppError> {
+    if reason.trim().is_empty() {
+        return Err(AppError::new(
+            http::StatusCode::UNPROCESSABLE_ENTITY,
+            "A reason is required to override a score",
+        ));
+    }
+
+    let current = sqlx::query_as::<_, Score>("SELECT * FROM scores WHERE id = ($1) FOR UPDATE")
+        .bind(score_id)
+        .fetch_optional(&mut *conn)
+        .await?
+        .ok_or_else(|| AppError::new(http::StatusCode::NOT_FOUND, "Score not found"))?;
+
+    let (is_deduction, min_score) = sqlx::query_as::<_, (bool, i32)>(
+        "SELECT is_deduction, min_score FROM criterias WHERE id = ($1)",
+    )
+    .bind(&current.criteria_id)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    if !criteria::points_in_range(new_score, is_deduction, min_score, current.max) {
+        return Err(AppError::new(
+            http::StatusCode::UNPROCESSABLE_ENTITY,
+            format!("Score of {} is out of range", new_score),
+        ));
+    }
+
+    let score = sqlx::query_as::<_, Score>(
+        "UPDATE scores SET score = ($1), time_of_scoring = ($2) WHERE id = ($3) RETURNING *",
+    )
+    .bind(new_score)
+    .bind(Local::now())
+    .bind(score_id)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    let mut audit_details = serde_json::json!({
+        "candidate_id": score.candidate_id,
+        "criteria_id": score.criteria_id,
+        "category_id": score.category_id,
+        "judge_id": score.judge_id,
+        "previous_score": current.score,
+        "score": score.score,
+        "reason": reason,
+    });
+
+    // Overrides after finalization keep the impact on the finalized standings with them
+    if let Some(changes) = recheck::recheck_standings(&mut *conn, &score).await? {
+        audit_details["standings_diff"] = serde_json::json!(changes);
     }
+
+    if let (Some(audit_details), serde_json::Value::Object(extra)) =
+        (audit_details.as_object_mut(), details)
+    {
+        audit_details.extend(extra);
+    }
+
+    audit::record(
+        &mut *conn,
+        actor,
+        "score",
+        Some(&score.id),
+        "override",
+        audit_details,
+    )
+    .await?;
+
+    Ok(score)
+}
+
+pub async fn override_score(
+    State(pool): State<PgPool>,
+    State(aggregator): State<Aggregator>,
+    Extension(staff): Extension<StaffClaims>,
+    Path(score_id): Path<uuid::Uuid>,
+    axum::Json(payload): axum::Json<OverrideScore>,
+) -> Result<axum::Json<Score>, AppError> {
+    let mut txn = pool.begin().await?;
+
+    let score = apply_override(
+        &mut txn,
+        &staff.sub,
+        &score_id,
+        payload.score,
+        &payload.reason,
+        serde_json::json!({}),
+    )
+    .await?;
+
+    txn.commit().await?;
+
+    request_recompute(&pool, &aggregator, &score.category_id).await?;
+
+    Ok(axum::Json(score))
 }
 
 #[derive(Debug, Deserialize)]
@@ -120,21 +858,39 @@ pub struct ScoreParam {
 pub async fn get_candidate_scores(
     State(pool): State<PgPool>,
     query: Option<Query<ScoreParam>>,
+    Query(window): Query<TimeWindow>,
 ) -> Result<axum::Json<Vec<Score>>, AppError> {
+    window.validate()?;
+
     let scores = match query {
         Some(param) => {
             sqlx::query_as::<_, Score>(
-                "SELECT * FROM scores WHERE criteria_id = ($1) or category_id = ($2)",
+                r#"
+                SELECT * FROM scores_history
+                WHERE (criteria_id = ($1) or category_id = ($2))
+                    AND (($3)::TIMESTAMPTZ IS NULL OR time_of_scoring >= ($3))
+                    AND (($4)::TIMESTAMPTZ IS NULL OR time_of_scoring <= ($4))
+                "#,
             )
             .bind(&param.criteria_id)
             .bind(&param.category_id)
+            .bind(window.from)
+            .bind(window.to)
             .fetch_all(&pool)
             .await?
         }
         None => {
-            sqlx::query_as::<_, Score>("SELECT * FROM scores")
-                .fetch_all(&pool)
-                .await?
+            sqlx::query_as::<_, Score>(
+                r#"
+                SELECT * FROM scores_history
+                WHERE (($1)::TIMESTAMPTZ IS NULL OR time_of_scoring >= ($1))
+                    AND (($2)::TIMESTAMPTZ IS NULL OR time_of_scoring <= ($2))
+                "#,
+            )
+            .bind(window.from)
+            .bind(window.to)
+            .fetch_all(&pool)
+            .await?
         }
     };
 
@@ -154,7 +910,7 @@ pub async fn get_candidate_score(
     let res = match query {
         Some(param) => {
             sqlx::query_as::<_, Score>(
-                "SELECT * FROM scores WHERE category_id = ($1) AND candidate_id = ($2)",
+                "SELECT * FROM scores_history WHERE category_id = ($1) AND candidate_id = ($2)",
             )
             .bind(&param.category_id)
             .bind(&param.candidate_id)
@@ -162,7 +918,7 @@ pub async fn get_candidate_score(
             .await
         }
         None => {
-            sqlx::query_as::<_, Score>("SELECT * FROM scores")
+            sqlx::query_as::<_, Score>("SELECT * FROM scores_history")
                 .fetch_all(&pool)
                 .await
         }
@@ -194,15 +950,18 @@ pub struct CandidateFinalScore {
 }
 
 // Temporary, might change it
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct CandidateFinalScore2 {
-    candidate_id: uuid::Uuid,
-    candidate_number: i32,
-    first_name: String,
-    middle_name: String,
-    last_name: String,
-    gender: i32,
-    final_score: f32,
+    pub candidate_id: uuid::Uuid,
+    pub candidate_number: i32,
+    pub first_name: String,
+    pub middle_name: String,
+    pub last_name: String,
+    pub gender: i32,
+    pub final_score: f32,
+    // Raw accumulated points, for organizers who announce points instead of percentages
+    #[serde(default)]
+    pub total_points: f32,
 }
 
 #[derive(Debug, Deserialize, Serialize, FromRow)]
@@ -221,19 +980,166 @@ pub struct CandidateScore {
 
 // It works but it might be inefficient
 // Immediately gets the final score of all candidates
+#[derive(Debug, Deserialize)]
+pub struct FinalScoreDisplayParam {
+    // Only the event's candidates, rounded to its display precision and ordered by rank
+    event_id: Option<uuid::Uuid>,
+}
+
+// Standard competition ranking of scores sorted highest first, exact ties share a rank (1, 2, 2, 4)
+pub fn competition_ranks(sorted_scores: &[f32]) -> Vec<usize> {
+    competition_ranks_by(sorted_scores, |a, b| a == b)
+}
+
+// Same as `competition_ranks`, with `tied` deciding whether neighbours share a rank
+pub fn competition_ranks_by<T>(sorted: &[T], tied: impl Fn(&T, &T) -> bool) -> Vec<usize> {
+    let mut ranks: Vec<usize> = Vec::with_capacity(sorted.len());
+
+    for (idx, item) in sorted.iter().enumerate() {
+        let rank = match ranks.last() {
+            Some(&previous) if tied(&sorted[idx - 1], item) => previous,
+            _ => idx + 1,
+        };
+
+        ranks.push(rank);
+    }
+
+    ranks
+}
+
+// Candidates sharing a rank are listed by candidate number
+pub fn rank_with_shared_ties(mut candidates: Vec<CandidateFinalScore2>) -> Vec<RankedCandidate> {
+    candidates.sort_by(|a, b| {
+        b.final_score
+            .total_cmp(&a.final_score)
+            .then(a.candidate_number.cmp(&b.candidate_number))
+    });
+
+    let scores: Vec<f32> = candidates
+        .iter()
+        .map(|candidate| candidate.final_score)
+        .collect();
+
+    competition_ranks(&scores)
+        .into_iter()
+        .zip(candidates)
+        .map(|(rank, candidate)| RankedCandidate { rank, candidate })
+        .collect()
+}
+
+// Ranks computed here so the frontend never has to compare floats itself
+// Scores are rounded to the event's display decimals first, so ties are the ones people see
+pub async fn get_rankings(
+    State(pool): State<PgPool>,
+    Path(event_id): Path<uuid::Uuid>,
+) -> Result<axum::Json<DivisionedStandings>, AppError> {
+    let mut final_scores = compute_event_final_scores(&pool, &event_id).await?;
+    let decimals = fetch_display_decimals(&pool, &event_id).await?;
+
+    round_final_scores(&mut final_scores, decimals);
+
+    // Tie-breakers only order exact ties, so they apply after rounding too
+    let rankings = match tie_break::fetch_tie_break_keys(&pool, &event_id).await? {
+        Some(keys) => standings::divide_standings_by(&final_scores, |candidates| {
+            tie_break::rank_with_tie_breakers(candidates, &keys)
+        }),
+        None => standings::divide_standings_by(&final_scores, rank_with_shared_ties),
+    };
+
+    Ok(axum::Json(rankings))
+}
+
 pub async fn get_candidate_final_scores(
     State(pool): State<PgPool>,
+    Query(param): Query<FinalScoreDisplayParam>,
 ) -> Result<axum::Json<Vec<CandidateFinalScore2>>, AppError> {
-    let final_scores = fetch_final_scores(State(pool)).await?;
+    let Some(event_id) = param.event_id else {
+        return Ok(axum::Json(fetch_final_scores(State(pool.clone())).await?));
+    };
 
-    Ok(axum::Json(final_scores))
+    let mut final_scores = compute_event_final_scores(&pool, &event_id).await?;
+    let decimals = fetch_display_decimals(&pool, &event_id).await?;
+
+    round_final_scores(&mut final_scores, decimals);
+
+    // Ordered like the rankings, each division best first, so ties come out the same way
+    let keys = tie_break::fetch_tie_break_keys(&pool, &event_id)
+        .await?
+        .unwrap_or_default();
+
+    let ordered = standings::divide_standings_by(&final_scores, |candidates| {
+        tie_break::rank_with_tie_breakers(candidates, &keys)
+    })
+    .divisions
+    .into_iter()
+    .flat_map(|division| division.standings)
+    .map(|ranked| ranked.candidate)
+    .collect();
+
+    Ok(axum::Json(ordered))
 }
 
-pub async fn fetch_final_scores(
-    State(pool): State<PgPool>,
-    // Query(query): Query<FinalScoreParam>,
-) -> Result<Vec<CandidateFinalScore2>, AppError> {
-    let res = sqlx::query_as::<_, CandidateScore>(
+pub async fn fetch_display_decimals(
+    pool: &PgPool,
+    event_id: &uuid::Uuid,
+) -> Result<usize, AppError> {
+    let settings = settings::fetch_settings(pool, event_id).await?;
+
+    Ok(settings.display_decimals as usize)
+}
+
+// Standings are computed at full precision and only rounded for display
+pub fn round_final_scores(final_scores: &mut [CandidateFinalScore2], decimals: usize) {
+    for final_score in final_scores.iter_mut() {
+        final_score.final_score =
+            (final_score.final_score as f64).round_to_decimals(decimals as u32) as f32;
+    }
+}
+
+// Scores of excluded judges drop out when the event averages over non-excluded judges only
+const EXCLUDED_JUDGE_FILTER: &str = r#"AND NOT EXISTS (
+                SELECT 1 FROM judges xj
+                JOIN categories xc ON xc.id = s.category_id
+                JOIN event_settings xs ON xs.event_id = xc.event_id
+                WHERE xj.id = s.judge_id
+                    AND xj.score_exclusion = TRUE
+                    AND xs.key = 'averaging_denominator'
+                    AND xs.value = '"non_excluded_judges"'::JSONB
+            )"#;
+
+// Points the candidate could have had in the category, following the event's `averaging_denominator`
+// Judges blacked out from the candidate are never part of it
+const WEIGHTED_MAX: &str = r#"CASE (
+                SELECT es.value #>> '{}' FROM event_settings es
+                WHERE es.event_id = cat.event_id AND es.key = 'averaging_denominator'
+            )
+                WHEN 'assigned_judges' THEN (
+                    SELECT COALESCE(SUM(cri.max_score), 0) FROM criterias cri
+                    WHERE cri.category_id = cat.id AND cri.is_deduction = FALSE
+                ) * (
+                    SELECT COUNT(*) FROM judges j
+                    WHERE j.event_id = cat.event_id AND j.substitute_for IS NULL
+                        AND NOT EXISTS (
+                            SELECT 1 FROM judge_blackouts jb WHERE jb.judge_id = j.id AND jb.candidate_id = c.id
+                        )
+                )
+                WHEN 'non_excluded_judges' THEN (
+                    SELECT COALESCE(SUM(cri.max_score), 0) FROM criterias cri
+                    WHERE cri.category_id = cat.id AND cri.is_deduction = FALSE
+                ) * (
+                    SELECT COUNT(*) FROM judges j
+                    WHERE j.event_id = cat.event_id AND j.substitute_for IS NULL
+                        AND j.score_exclusion = FALSE
+                        AND NOT EXISTS (
+                            SELECT 1 FROM judge_blackouts jb WHERE jb.judge_id = j.id AND jb.candidate_id = c.id
+                        )
+                )
+                ELSE COALESCE(SUM(s.max), 0)
+            END * cat.weight AS weighted_max"#;
+
+// Shared by `fetch_final_scores` and `compute_event_final_scores`, with `event_scoped` the event's ID is bound as $1
+fn final_scores_query(event_scoped: bool) -> String {
+    format!(
         r#"
         SELECT 
             c.id AS candidate_id,
@@ -244,16 +1150,36 @@ pub async fn fetch_final_scores(
             c.gender,
             COALESCE(SUM(s.score), 0) AS total_score, 
             COALESCE(SUM(s.max), 0) AS total_max,
-            COALESCE(SUM(s.score), 0) * cat.weight AS weighted_score,
-            COALESCE(SUM(s.max), 0) * cat.weight AS weighted_max
+            (COALESCE(SUM(cs.score), 0) - (
+                SELECT COALESCE(SUM(d.points), 0) FROM deductions d
+                WHERE d.candidate_id = c.id AND d.category_id = cat.id
+            )) * cat.weight AS weighted_score,
+            {weighted_max}
         FROM 
             candidates c
+        -- Archived events are scored from the scores moved out of the live table
         LEFT JOIN 
-            scores s ON s.candidate_id = c.id
+            scores_history s ON s.candidate_id = c.id
+            -- Group-level scores only count towards the group's standings
+            AND (s.group_id IS NULL OR NOT EXISTS (
+                SELECT 1 FROM performance_groups g WHERE g.id = s.group_id AND g.score_mode = 'group'
+            ))
+            -- Rehearsal scores only show while the event is still in rehearsal mode
+            AND (s.is_rehearsal = FALSE OR EXISTS (
+                SELECT 1 FROM categories rc
+                JOIN events re ON re.id = rc.event_id
+                WHERE rc.id = s.category_id AND re.rehearsal_mode = TRUE
+            ))
+            {excluded_judge_filter}
         LEFT JOIN 
             categories cat ON s.category_id = cat.id
+        -- Raw scores unless the category grades on a curve
+        LEFT JOIN
+            curved_scores cs ON cs.id = s.id
+        WHERE c.deleted_at IS NULL
+            {event_filter}
         GROUP BY
-            c.id, cat.weight
+            c.id, cat.id, cat.weight
         ORDER BY 
             CASE 
                 WHEN c.gender = 1 THEN 1
@@ -261,43 +1187,56 @@ pub async fn fetch_final_scores(
             END,
             c.candidate_number
         "#,
+        weighted_max = WEIGHTED_MAX,
+        excluded_judge_filter = EXCLUDED_JUDGE_FILTER,
+        event_filter = if event_scoped {
+            "AND EXISTS (SELECT 1 FROM categories ec WHERE ec.id = c.category_id AND ec.event_id = ($1))"
+        } else {
+            ""
+        },
     )
-    // .bind(&query.event_id)
-    .fetch_all(&pool)
-    .await;
+}
+
+// Standings of one event's candidates as the executor sees them, e.g. a transaction with an uncommitted score change
+// Unlike `fetch_final_scores`, nothing is written back to the candidates
+pub async fn compute_event_final_scores<'e, E: PgExecutor<'e>>(
+    executor: E,
+    event_id: &uuid::Uuid,
+) -> Result<Vec<CandidateFinalScore2>, AppError> {
+    let candidates = sqlx::query_as::<_, CandidateScore>(&final_scores_query(true))
+        .bind(event_id)
+        .fetch_all(executor)
+        .await?;
+
+    Ok(calculate_final_scores(&candidates))
+}
+
+pub async fn fetch_final_scores(
+    State(pool): State<PgPool>,
+    // Query(query): Query<FinalScoreParam>,
+) -> Result<Vec<CandidateFinalScore2>, AppError> {
+    let res = sqlx::query_as::<_, CandidateScore>(&final_scores_query(false))
+        // .bind(&query.event_id)
+        .fetch_all(&pool)
+        .await;
 
     let mut txn = pool.begin().await?;
 
     match res {
         Ok(candidates) => {
-            let mut candidate_final_scores: Vec<CandidateFinalScore2> = Vec::new();
-            let final_scores = calculate_final_scores(&candidates);
-
-            for (
-                candidate_id,
-                (candidate_number, gender, first_name, middle_name, last_name, final_score),
-            ) in final_scores
-            {
+            let candidate_final_scores = calculate_final_scores(&candidates);
+
+            for candidate in candidate_final_scores.iter() {
                 sqlx::query("UPDATE candidates SET final_score = ($1) WHERE id = ($2) AND final_score <> ($1)")
-                    .bind(final_score)
-                    .bind(candidate_id)
+                    .bind(candidate.final_score)
+                    .bind(candidate.candidate_id)
                     .execute(&mut *txn)
                     .await?;
 
                 println!(
                     "Candidate #: {}, Candidate Name: {}, Final Score: {}",
-                    candidate_number, last_name, final_score
+                    candidate.candidate_number, candidate.last_name, candidate.final_score
                 );
-
-                candidate_final_scores.push(CandidateFinalScore2 {
-                    candidate_id,
-                    candidate_number,
-                    first_name,
-                    middle_name,
-                    last_name,
-                    gender,
-                    final_score,
-                });
             }
 
             txn.commit().await?;
@@ -315,105 +1254,152 @@ pub async fn fetch_final_scores(
     }
 }
 
-// Tuples here could be structs
-fn calculate_final_scores(
-    scores: &Vec<CandidateScore>,
-) -> Vec<(uuid::Uuid, (i32, i32, String, String, String, f32))> {
-    let mut candidate_scores: HashMap<uuid::Uuid, (i32, i32, String, String, String, f32, f32)> =
-        HashMap::new();
+// The event's standings as if this judge (and whoever substituted in their seat) was the only one
+pub async fn fetch_judge_final_scores(
+    pool: &PgPool,
+    event_id: &uuid::Uuid,
+    judge_id: &uuid::Uuid,
+) -> Result<Vec<CandidateFinalScore2>, AppError> {
+    let scores = sqlx::query_as::<_, CandidateScore>(
+        r#"
+        SELECT
+            r.candidate_id,
+            r.candidate_number,
+            r.candidate_first_name AS first_name,
+            r.candidate_middle_name AS middle_name,
+            r.candidate_last_name AS last_name,
+            r.candidate_gender AS gender,
+            SUM(r.score) AS total_score,
+            SUM(r.max) AS total_max,
+            SUM(r.score) * r.category_weight AS weighted_score,
+            SUM(r.max) * r.category_weight AS weighted_max
+        FROM score_report r
+        WHERE r.event_id = ($1)
+            AND r.judge_id IN (SELECT id FROM judges WHERE id = ($2) OR substitute_for = ($2))
+        GROUP BY
+            r.candidate_id, r.candidate_number, r.candidate_first_name, r.candidate_middle_name,
+            r.candidate_last_name, r.candidate_gender, r.category_id, r.category_weight
+        "#,
+    )
+    .bind(event_id)
+    .bind(judge_id)
+    .fetch_all(pool)
+    .await?;
 
-    for score in scores {
-        let (
-            candidate_number,
-            gender,
-            first_name,
-            middle_name,
-            last_name,
-            weighted_scores_sum,
-            weighted_max_sum,
-        ) = candidate_scores.entry(score.candidate_id).or_insert((
-            score.candidate_number,
-            score.gender,
-            score.first_name.clone(),
-            score.middle_name.clone(),
-            score.last_name.clone(),
-            0.0,
-            0.0,
-        ));
+    Ok(calculate_final_scores(&scores))
+}
 
-        *weighted_scores_sum += score.weighted_score.round_to_two_decimals() as f32;
-        *weighted_max_sum += score.weighted_max.round_to_two_decimals() as f32;
-    }
+// The categories' standings over the candidates eligible when each opened, withdrawals included
+// Categories that never opened fall back to the current candidate list
+pub async fn fetch_round_final_scores(
+    pool: &PgPool,
+    category_ids: &[uuid::Uuid],
+) -> Result<Vec<CandidateFinalScore2>, AppError> {
+    let scores = sqlx::query_as::<_, CandidateScore>(&format!(
+        r#"
+        SELECT
+            c.id AS candidate_id,
+            c.candidate_number,
+            c.first_name,
+            c.middle_name,
+            c.last_name,
+            c.gender,
+            COALESCE(SUM(s.score), 0) AS total_score,
+            COALESCE(SUM(s.max), 0) AS total_max,
+            (COALESCE(SUM(cs.score), 0) - (
+                SELECT COALESCE(SUM(d.points), 0) FROM deductions d
+                WHERE d.candidate_id = c.id AND d.category_id = cat.id
+            )) * cat.weight AS weighted_score,
+            {weighted_max}
+        FROM
+            candidates c
+        JOIN
+            categories cat ON cat.id = ANY($1)
+        LEFT JOIN
+            scores s ON s.candidate_id = c.id AND s.category_id = cat.id
+            AND (s.group_id IS NULL OR NOT EXISTS (
+                SELECT 1 FROM performance_groups g WHERE g.id = s.group_id AND g.score_mode = 'group'
+            ))
+            AND (s.is_rehearsal = FALSE OR EXISTS (
+                SELECT 1 FROM events re WHERE re.id = cat.event_id AND re.rehearsal_mode = TRUE
+            ))
+            {excluded_judge_filter}
+        -- Raw scores unless the category grades on a curve
+        LEFT JOIN
+            curved_scores cs ON cs.id = s.id
+        WHERE EXISTS (SELECT 1 FROM round_eligibility e WHERE e.category_id = cat.id AND e.candidate_id = c.id)
+            OR (
+                NOT EXISTS (SELECT 1 FROM round_eligibility e WHERE e.category_id = cat.id)
+                AND EXISTS (
+                    SELECT 1 FROM categories home
+                    WHERE home.id = c.category_id AND home.event_id = cat.event_id
+                )
+                AND c.deleted_at IS NULL
+                AND NOT {eliminated_earlier}
+            )
+        GROUP BY
+            c.id, cat.id, cat.weight
+        "#,
+        weighted_max = WEIGHTED_MAX,
+        excluded_judge_filter = EXCLUDED_JUDGE_FILTER,
+        eliminated_earlier = advancement::ELIMINATED_EARLIER,
+    ))
+    .bind(category_ids)
+    .fetch_all(pool)
+    .await?;
 
-    // Very bad code (I think) xD
-    let mut final_scores: HashMap<uuid::Uuid, (i32, i32, String, String, String, f32)> =
-        HashMap::new();
+    let mut final_scores = calculate_final_scores(&scores);
 
-    for (
-        candidate_id,
-        (
-            candidate_number,
-            gender,
-            first_name,
-            middle_name,
-            last_name,
-            weighted_scores_sum,
-            weighted_max_sum,
-        ),
-    ) in candidate_scores.into_iter()
-    {
-        let final_score = (weighted_scores_sum / weighted_max_sum) * 100.0;
-        final_scores.insert(
-            candidate_id,
-            (
-                candidate_number,
-                gender,
-                first_name,
-                middle_name,
-                last_name,
-                final_score,
-            ),
-        );
+    // Nobody has scored the category yet
+    for final_score in final_scores.iter_mut() {
+        if !final_score.final_score.is_finite() {
+            final_score.final_score = 0.0;
+        }
     }
 
-    let mut sorted_final_scores: Vec<_> = final_scores.clone().into_iter().collect();
+    Ok(final_scores)
+}
 
-    // Sory by candidate number because it gets messed up
-    sorted_final_scores.sort_by(|(_, (a, _, _, _, _, _)), (_, (b, _, _, _, _, _))| a.cmp(b));
+fn calculate_final_scores(scores: &Vec<CandidateScore>) -> Vec<CandidateFinalScore2> {
+    // Candidate ID -> (final score so far, weighted scores sum, weighted max sum)
+    let mut candidate_scores: HashMap<uuid::Uuid, (CandidateFinalScore2, f32, f32)> =
+        HashMap::new();
 
-    sorted_final_scores
-}
+    for score in scores {
+        let (final_score, weighted_scores_sum, weighted_max_sum) = candidate_scores
+            .entry(score.candidate_id)
+            .or_insert((
+                CandidateFinalScore2 {
+                    candidate_id: score.candidate_id,
+                    candidate_number: score.candidate_number,
+                    first_name: score.first_name.clone(),
+                    middle_name: score.middle_name.clone(),
+                    last_name: score.last_name.clone(),
+                    gender: score.gender,
+                    final_score: 0.0,
+                    total_points: 0.0,
+                },
+                0.0,
+                0.0,
+            ));
 
-#[derive(Debug, Deserialize, FromRow)]
-pub struct CategoryWeight {
-    id: uuid::Uuid,
-    weight: f32,
-}
+        *weighted_scores_sum += score.weighted_score.round_to_two_decimals() as f32;
+        *weighted_max_sum += score.weighted_max.round_to_two_decimals() as f32;
+        final_score.total_points += score.total_score as f32;
+    }
 
-#[derive(Debug, Deserialize, FromRow)]
-pub struct ScoreMax {
-    total_score: i64,
-    total_max: i64,
-    weighted_score: f64,
-    weighted_max: f64,
-}
+    let mut sorted_final_scores: Vec<CandidateFinalScore2> = candidate_scores
+        .into_values()
+        .map(|(mut final_score, weighted_scores_sum, weighted_max_sum)| {
+            final_score.final_score = (weighted_scores_sum / weighted_max_sum) * 100.0;
+            final_score
+        })
+        .collect();
 
-#[derive(Debug, Deserialize, FromRow)]
-pub struct CriteriaIdName {
-    id: uuid::Uuid,
-    name: String,
-}
+    // Sory by candidate number because it gets messed up
+    sorted_final_scores.sort_by_key(|candidate| candidate.candidate_number);
 
-#[derive(Debug, Deserialize, FromRow)]
-pub struct CriteriaScore {
-    score: i32,
-    judge_name: String,
-    candidate_first_name: String,
-    candidate_middle_name: String,
-    candidate_last_name: String,
-    weight: f32,
-    max: i32,
-    event_name: String,
+    sorted_final_scores
 }
 
 #[derive(Debug, FromRow)]
@@ -426,16 +1412,58 @@ struct Candidate {
     pub candidate_number: i32,
 }
 
+#[derive(Debug, Default, Deserialize)]
+pub struct SpreadsheetParam {
+    // Embeds candidate photos next to their names, for the souvenir/archival copy
+    #[serde(default)]
+    pub photos: bool,
+    // Locks every sheet of the distributed copy, only the notes column stays editable
+    #[serde(default)]
+    pub protect: bool,
+}
+
+// Kept out of the query string, which ends up in the request log
+fn sheet_password() -> Option<String> {
+    std::env::var("EXPORT_SHEET_PASSWORD")
+        .ok()
+        .filter(|password| !password.is_empty())
+}
+
+// Locked cells are the default, so only cells written with an unlocked format stay editable
+pub fn protect_workbook(workbook: &mut Workbook, password: Option<&str>) {
+    // Resizing columns to read long names doesn't change anything
+    let options = ProtectionOptions {
+        format_columns: true,
+        ..ProtectionOptions::default()
+    };
+
+    for worksheet in workbook.worksheets_mut() {
+        if let Some(password) = password {
+            worksheet.protect_with_password(password);
+        }
+
+        worksheet.protect_with_options(&options);
+    }
+}
+
+// Thumbnails sit at the right end of the name cell, rows are made taller to fit them
+const PHOTO_SIZE: u32 = 48;
+const PHOTO_OFFSET_X: u32 = 160;
+const PHOTO_ROW_HEIGHT: f64 = 40.0;
+
 // The rest of the functions below are for writing the results in a spreadsheet file
 // It's a mess
 
 pub async fn generate_score_spreadsheet(
     State(pool): State<PgPool>,
+    Query(param): Query<ExportParam>,
+    Query(spreadsheet_param): Query<SpreadsheetParam>,
 ) -> Result<(http::StatusCode, Vec<u8>), AppError> {
     let categories = sqlx::query_as::<_, Category>(
         r#"
         SELECT *
         FROM categories
+        WHERE ($1)::UUID IS NULL OR event_id = ($1)
         ORDER BY 
             CASE 
                 WHEN name = 'Final Top 10 Candidates' THEN 1 
@@ -444,10 +1472,15 @@ pub async fn generate_score_spreadsheet(
             name
     "#,
     )
+    .bind(param.event_id)
     .fetch_all(&pool)
     .await?;
 
     let mut workbook = Workbook::new();
+
+    let cover_sheets = cover::fetch_cover_sheets(&pool, param.event_id.as_ref()).await?;
+    cover::write_cover_worksheet(&mut workbook, &cover_sheets)?;
+
     let worksheet = workbook.add_worksheet();
 
     let heading_format = Format::new().set_font_size(13.5).set_bold();
@@ -462,6 +1495,10 @@ pub async fn generate_score_spreadsheet(
     let candidates = sqlx::query_as::<_, Candidate>(
         r#"
         SELECT id, first_name, middle_name, last_name, gender, candidate_number FROM candidates 
+        WHERE deleted_at IS NULL
+            AND (($1)::UUID IS NULL OR EXISTS (
+                SELECT 1 FROM categories ec WHERE ec.id = category_id AND ec.event_id = ($1)
+            ))
         ORDER BY 
             CASE
                 WHEN gender = 1 THEN 1
@@ -470,15 +1507,41 @@ pub async fn generate_score_spreadsheet(
             candidate_number
         "#,
     )
+    .bind(param.event_id)
     .fetch_all(&pool)
     .await?;
 
+    let photos: HashMap<uuid::Uuid, Image> = if spreadsheet_param.photos {
+        let rows = sqlx::query_as::<_, (uuid::Uuid, Vec<u8>)>(
+            "SELECT candidate_id, data FROM candidate_photos",
+        )
+        .fetch_all(&pool)
+        .await?;
+
+        let mut photos = HashMap::new();
+
+        for (candidate_id, data) in rows {
+            match Image::new_from_buffer(&data) {
+                Ok(mut image) => {
+                    image.set_scale_to_size(PHOTO_SIZE, PHOTO_SIZE, true);
+                    photos.insert(candidate_id, image);
+                }
+                // A broken photo shouldn't stop the whole export
+                Err(err) => eprintln!("Skipping photo of candidate {}: {err:?}", candidate_id),
+            }
+        }
+
+        photos
+    } else {
+        HashMap::new()
+    };
+
     // Could use the Rayon crate for parallelization, but no need
     let (male_candidates, female_candidates): (Vec<&Candidate>, Vec<&Candidate>) = candidates
         .iter()
         .partition(|candidate| candidate.gender == 1);
 
-    for (category_idx, category) in categories.iter().enumerate() {
+    for category in categories.iter() {
         worksheet.merge_range(
             row_offset,
             0,
@@ -495,19 +1558,32 @@ pub async fn generate_score_spreadsheet(
         // event_id
         // Could use a Hashmap wherein the event_id is they key and the vector of judges
         // are the values after fetching every judge per event in one SQL query
-        let judges = sqlx::query_as::<_, (uuid::Uuid, String)>(
-            "SELECT id, name FROM judges WHERE event_id = ($1) AND score_exclusion = FALSE",
+        // Substitutes share the column of the judge whose seat they took
+        let mut judges = sqlx::query_as::<_, (uuid::Uuid, String)>(
+            r#"
+            SELECT id, name FROM judges
+            WHERE event_id = ($1) AND score_exclusion = FALSE AND substitute_for IS NULL
+            ORDER BY name
+            "#,
         )
         .bind(&category.event_id)
         .fetch_all(&pool)
         .await?;
 
+        if report::should_anonymize_judges(&pool, &category.event_id, &param).await? {
+            for (i, (_, judge_name)) in judges.iter_mut().enumerate() {
+                *judge_name = report::judge_number_label(i);
+            }
+        }
+
         // Please improve this
         if category.name.trim() == "Final Top 10 Candidates" {
+            let decimals = fetch_display_decimals(&pool, &category.event_id).await?;
+
             worksheet.write_with_format(row_offset + 1, 2, "Final Score", &bold_center_format)?;
 
             // Write final scores
-            write_top_ten(&pool, worksheet, row_offset + 2, 0).await?;
+            write_top_ten(&pool, &category.event_id, worksheet, row_offset + 2, 0, decimals).await?;
 
             row_offset += 15;
 
@@ -524,7 +1600,7 @@ pub async fn generate_score_spreadsheet(
             worksheet.write_with_format(1 + row_offset, 1, "Name", &bold_center_format)?;
             worksheet.write_with_format(1 + row_offset, 2, "Final Score", &bold_center_format)?;
 
-            write_by_rank(&pool, worksheet, row_offset + 2, 0).await?;
+            write_by_rank(&pool, &category.event_id, worksheet, row_offset + 2, 0, decimals).await?;
         } else {
             // Write judge names
             for (i, (_, judge_name)) in judges.iter().enumerate() {
@@ -555,6 +1631,23 @@ pub async fn generate_score_spreadsheet(
                 &bold_center_format,
             )?;
 
+            if spreadsheet_param.protect {
+                let notes_col = judges.len() as u16 + 4;
+                let unlocked_format = Format::new().set_unlocked();
+
+                worksheet.set_column_width(notes_col, 30)?;
+                worksheet.write_with_format(
+                    1 + row_offset,
+                    notes_col,
+                    "Notes",
+                    &bold_center_format,
+                )?;
+
+                for row in (row_offset + 2)..(row_offset + 4 + candidates.len() as u32) {
+                    worksheet.write_blank(row, notes_col, &unlocked_format)?;
+                }
+            }
+
             worksheet.write(row_offset + 2, 0, "MALE")?;
 
             // Write scores for male candidates
@@ -564,6 +1657,7 @@ pub async fn generate_score_spreadsheet(
                 &male_candidates,
                 category,
                 &judges,
+                &photos,
                 3 + row_offset,
                 0,
                 Some(&bold_format),
@@ -579,6 +1673,7 @@ pub async fn generate_score_spreadsheet(
                 &female_candidates,
                 category,
                 &judges,
+                &photos,
                 row_offset + 4 + male_candidates.len() as u32,
                 0,
                 Some(&bold_format),
@@ -589,17 +1684,79 @@ pub async fn generate_score_spreadsheet(
         }
     }
 
+    write_awards(&pool, param.event_id.as_ref(), &mut workbook, &bold_center_format).await?;
+
+    if spreadsheet_param.protect {
+        protect_workbook(&mut workbook, sheet_password().as_deref());
+    }
+
     let workbook_buffer = workbook.save_to_buffer()?;
 
     Ok((http::StatusCode::OK, workbook_buffer))
 }
 
+// Official titles of every finalized event (or just the one), on a sheet of their own
+async fn write_awards(
+    pool: &PgPool,
+    event_id: Option<&uuid::Uuid>,
+    workbook: &mut Workbook,
+    header_format: &Format,
+) -> Result<(), AppError> {
+    let events = sqlx::query_as::<_, (String, sqlx::types::Json<Vec<AwardWinner>>)>(
+        r#"
+        SELECT e.name, ss.awards
+        FROM events e
+        JOIN standings_snapshots ss ON ss.id = e.finalized_snapshot_id
+        WHERE ss.awards IS NOT NULL AND (($1)::UUID IS NULL OR e.id = ($1))
+        ORDER BY e.name
+        "#,
+    )
+    .bind(event_id)
+    .fetch_all(pool)
+    .await?;
+
+    if events.is_empty() {
+        return Ok(());
+    }
+
+    let worksheet = workbook.add_worksheet().set_name("Awards")?;
+
+    for (col, header) in ["Event", "Division", "Title", "Candidate #", "Name", "Final Score"]
+        .iter()
+        .enumerate()
+    {
+        worksheet.set_column_width(col as u16, 25)?;
+        worksheet.write_with_format(0, col as u16, *header, header_format)?;
+    }
+
+    let mut row: u32 = 1;
+
+    for (event_name, awards) in events {
+        for award in awards.0 {
+            let name = format!("{} {} {}", award.first_name, award.middle_name, award.last_name);
+
+            worksheet.write(row, 0, &event_name)?;
+            worksheet.write(row, 1, award.division.to_uppercase())?;
+            worksheet.write(row, 2, &award.award)?;
+            worksheet.write(row, 3, award.candidate_number)?;
+            worksheet.write(row, 4, name)?;
+            worksheet.write(row, 5, award.final_score)?;
+
+            row += 1;
+        }
+    }
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
 async fn write_scores(
     pool: &PgPool,
     worksheet: &mut Worksheet,
-    candidates: &Vec<&Candidate>,
+    candidates: &[&Candidate],
     category: &Category,
-    judges: &Vec<(uuid::Uuid, String)>,
+    judges: &[(uuid::Uuid, String)],
+    photos: &HashMap<uuid::Uuid, Image>,
     row: RowNum,
     col: ColNum,
     format: Option<&Format>,
@@ -615,7 +1772,7 @@ async fn write_scores(
         // Write candidate numbers
         worksheet.write(
             candidate_idx as u32 + row,
-            0 + col,
+            col,
             candidate.candidate_number,
         )?;
 
@@ -631,6 +1788,17 @@ async fn write_scores(
             ),
         )?;
 
+        if let Some(photo) = photos.get(&candidate.id) {
+            worksheet.set_row_height(candidate_idx as u32 + row, PHOTO_ROW_HEIGHT)?;
+            worksheet.insert_image_with_offset(
+                candidate_idx as u32 + row,
+                1 + col,
+                photo,
+                PHOTO_OFFSET_X,
+                2,
+            )?;
+        }
+
         let mut total_score: f32 = 0.0;
 
         // Write candidate scores
@@ -638,8 +1806,9 @@ async fn write_scores(
             let judge_total_score: i64 = sqlx::query_scalar(
                 r#"
                 SELECT COALESCE(SUM(score), 0) as judge_total_score 
-                FROM scores
-                WHERE candidate_id = ($1) AND category_id = ($2) AND judge_id = ($3)
+                FROM scores_history
+                WHERE candidate_id = ($1) AND category_id = ($2) AND is_rehearsal = FALSE
+                    AND judge_id IN (SELECT id FROM judges WHERE id = ($3) OR substitute_for = ($3))
                 "#,
             )
             .bind(candidate.id)
@@ -660,23 +1829,17 @@ async fn write_scores(
         let score_in_percentage: f32 = total_score * category.weight;
 
         match category.name.trim() {
-            "University Collegiate Costume" => {
-                if score_in_percentage > highest_collegiate {
-                    highest_collegiate = score_in_percentage;
-                    collegiate_row = row + candidate_idx as u32;
-                }
+            "University Collegiate Costume" if score_in_percentage > highest_collegiate => {
+                highest_collegiate = score_in_percentage;
+                collegiate_row = row + candidate_idx as u32;
             }
-            "Swimwear" => {
-                if score_in_percentage > highest_swimwear {
-                    highest_swimwear = score_in_percentage;
-                    swimwear_row = row + candidate_idx as u32;
-                }
+            "Swimwear" if score_in_percentage > highest_swimwear => {
+                highest_swimwear = score_in_percentage;
+                swimwear_row = row + candidate_idx as u32;
             }
-            "Formal Wear and Long Gown" => {
-                if score_in_percentage > highest_formal {
-                    highest_formal = score_in_percentage;
-                    formal_row = row + candidate_idx as u32;
-                }
+            "Formal Wear and Long Gown" if score_in_percentage > highest_formal => {
+                highest_formal = score_in_percentage;
+                formal_row = row + candidate_idx as u32;
             }
             _ => {}
         }
@@ -694,9 +1857,9 @@ async fn write_scores(
         )?;
     }
 
-    worksheet.set_row_format(collegiate_row, format.unwrap());
-    worksheet.set_row_format(swimwear_row, format.unwrap());
-    worksheet.set_row_format(formal_row, format.unwrap());
+    worksheet.set_row_format(collegiate_row, format.unwrap())?;
+    worksheet.set_row_format(swimwear_row, format.unwrap())?;
+    worksheet.set_row_format(formal_row, format.unwrap())?;
 
     Ok(())
 }
@@ -705,41 +1868,40 @@ async fn write_scores(
 // already on other functions here
 async fn write_top_ten(
     pool: &PgPool,
+    event_id: &uuid::Uuid,
     worksheet: &mut Worksheet,
     row: RowNum,
     col: ColNum,
+    decimals: usize,
 ) -> Result<(), AppError> {
-    let final_scores = fetch_final_scores(State(pool.to_owned())).await?;
-    let candidates = sqlx::query_as::<_, (String, i32, i32, f32)>(
-        r#"
-        (SELECT CONCAT(last_name, ', ', first_name, ' ', middle_name), candidate_number, gender, final_score
-        FROM candidates
-        WHERE gender = 1
-        ORDER BY final_score DESC
-        LIMIT 5)
-
-        UNION ALL
-
-        (SELECT CONCAT(last_name, ', ', first_name, ' ', middle_name), candidate_number, gender, final_score
-        FROM candidates
-        WHERE gender = 0
-        ORDER BY final_score DESC
-        LIMIT 5)
-        "#,
-    )
-    .fetch_all(pool)
-    .await?;
+    let mut final_scores = compute_event_final_scores(pool, event_id).await?;
 
-    let (male_candidates, female_candidates): (
-        Vec<(String, i32, i32, f32)>,
-        Vec<(String, i32, i32, f32)>,
-    ) = candidates
-        .into_iter()
-        .partition(|(_, _, gender, _)| gender.to_owned() == 1);
+    final_scores.sort_by(|a, b| b.final_score.total_cmp(&a.final_score));
+
+    let top_five = |gender: i32| -> Vec<(String, i32, i32, f32)> {
+        final_scores
+            .iter()
+            .filter(|candidate| candidate.gender == gender)
+            .take(5)
+            .map(|candidate| {
+                (
+                    format!(
+                        "{}, {} {}",
+                        candidate.last_name, candidate.first_name, candidate.middle_name
+                    ),
+                    candidate.candidate_number,
+                    candidate.gender,
+                    candidate.final_score,
+                )
+            })
+            .collect()
+    };
+
+    let (male_candidates, female_candidates) = (top_five(1), top_five(0));
 
     worksheet.write(row, 0, "MALE")?;
 
-    for (candidate_idx, (candidate_name, candidate_number, gender, final_score)) in
+    for (candidate_idx, (candidate_name, candidate_number, _, final_score)) in
         male_candidates.iter().enumerate()
     {
         worksheet.write(
@@ -751,13 +1913,13 @@ async fn write_top_ten(
         worksheet.write(
             row + 1 + candidate_idx as u32,
             col + 2,
-            format!("{:.3}", final_score),
+            format!("{:.*}", decimals, final_score),
         )?;
     }
 
     worksheet.write(row + 6, 0, "FEMALE")?;
 
-    for (candidate_idx, (candidate_name, candidate_number, gender, final_score)) in
+    for (candidate_idx, (candidate_name, candidate_number, _, final_score)) in
         female_candidates.iter().enumerate()
     {
         worksheet.write(
@@ -769,7 +1931,7 @@ async fn write_top_ten(
         worksheet.write(
             row + 7 + candidate_idx as u32,
             col + 2,
-            format!("{:.3}", final_score),
+            format!("{:.*}", decimals, final_score),
         )?;
     }
 
@@ -778,11 +1940,13 @@ async fn write_top_ten(
 
 async fn write_by_rank(
     pool: &PgPool,
+    event_id: &uuid::Uuid,
     worksheet: &mut Worksheet,
     row: RowNum,
     col: ColNum,
+    decimals: usize,
 ) -> Result<(), AppError> {
-    let res = sqlx::query_as::<_, CandidateScore>(
+    let res = sqlx::query_as::<_, CandidateScore>(&format!(
         r#"
         SELECT 
             c.id AS candidate_id,
@@ -793,20 +1957,32 @@ async fn write_by_rank(
             c.gender,
             COALESCE(SUM(s.score), 0) AS total_score, 
             COALESCE(SUM(s.max), 0) AS total_max,
-            COALESCE(SUM(s.score), 0) * cat.weight AS weighted_score,
-            COALESCE(SUM(s.max), 0) * cat.weight AS weighted_max
+            (COALESCE(SUM(cs.score), 0) - (
+                SELECT COALESCE(SUM(d.points), 0) FROM deductions d
+                WHERE d.candidate_id = c.id AND d.category_id = cat.id
+            )) * cat.weight AS weighted_score,
+            {weighted_max}
         FROM 
             candidates c
         LEFT JOIN 
-            scores s ON s.candidate_id = c.id
+            scores_history s ON s.candidate_id = c.id AND s.is_rehearsal = FALSE
+            {excluded_judge_filter}
         LEFT JOIN 
             categories cat ON s.category_id = cat.id
+        -- Raw scores unless the category grades on a curve
+        LEFT JOIN
+            curved_scores cs ON cs.id = s.id
+        WHERE c.deleted_at IS NULL
+            AND EXISTS (SELECT 1 FROM categories ec WHERE ec.id = c.category_id AND ec.event_id = ($1))
         GROUP BY
-            c.id, cat.weight
+            c.id, cat.id, cat.weight
         ORDER BY 
             c.candidate_number, c.gender
         "#,
-    )
+        weighted_max = WEIGHTED_MAX,
+        excluded_judge_filter = EXCLUDED_JUDGE_FILTER,
+    ))
+    .bind(event_id)
     .fetch_all(pool)
     .await;
 
@@ -820,46 +1996,38 @@ async fn write_by_rank(
             let male_final_scores = calculate_final_scores(&male_candidates);
             worksheet.write(row, 0, "MALE")?;
 
-            for (
-                candidate_idx,
-                (
-                    candidate_id,
-                    (candidate_number, _, first_name, middle_name, last_name, final_score),
-                ),
-            ) in male_final_scores.iter().enumerate()
-            {
-                let candidate_name = format!("{}, {} {}", first_name, middle_name, last_name);
+            for (candidate_idx, candidate) in male_final_scores.iter().enumerate() {
+                let candidate_name = format!(
+                    "{}, {} {}",
+                    candidate.first_name, candidate.middle_name, candidate.last_name
+                );
 
                 worksheet.write(
                     row + 1 + candidate_idx as u32,
                     col,
-                    candidate_number.to_owned(),
+                    candidate.candidate_number,
                 )?;
                 worksheet.write(row + 1 + candidate_idx as u32, col + 1, candidate_name)?;
                 worksheet.write(
                     row + 1 + candidate_idx as u32,
                     col + 2,
-                    format!("{:.2}", final_score),
+                    format!("{:.*}", decimals, candidate.final_score),
                 )?;
             }
 
             let female_final_scores = calculate_final_scores(&female_candidates);
             worksheet.write(row + 1 + male_final_scores.len() as u32, 0, "FEMALE")?;
 
-            for (
-                candidate_idx,
-                (
-                    candidate_id,
-                    (candidate_number, _, first_name, middle_name, last_name, final_score),
-                ),
-            ) in female_final_scores.iter().enumerate()
-            {
-                let candidate_name = format!("{}, {} {}", first_name, middle_name, last_name);
+            for (candidate_idx, candidate) in female_final_scores.iter().enumerate() {
+                let candidate_name = format!(
+                    "{}, {} {}",
+                    candidate.first_name, candidate.middle_name, candidate.last_name
+                );
 
                 worksheet.write(
                     row + 2 + candidate_idx as u32 + male_final_scores.len() as u32,
                     col,
-                    candidate_number.to_owned(),
+                    candidate.candidate_number,
                 )?;
                 worksheet.write(
                     row + 2 + candidate_idx as u32 + male_final_scores.len() as u32,
@@ -869,7 +2037,7 @@ async fn write_by_rank(
                 worksheet.write(
                     row + 2 + candidate_idx as u32 + male_final_scores.len() as u32,
                     col + 2,
-                    format!("{:.2}", final_score),
+                    format!("{:.*}", decimals, candidate.final_score),
                 )?;
             }
 
@@ -891,10 +2059,11 @@ async fn write_by_rank(
 // Generates a spreadsheet for the scoring system for the sake of transparency
 pub async fn generate_csv(
     State(pool): State<PgPool>,
+    Query(param): Query<ExportParam>,
+    Query(window): Query<TimeWindow>,
 ) -> Result<(http::StatusCode, Vec<u8>), AppError> {
-    let categories = sqlx::query_as::<_, Category>("SELECT id, name, weight FROM categories")
-        .fetch_all(&pool)
-        .await?;
+    let judge_labels = report::export_judge_labels(&pool, &param).await?;
+    let rows = report::fetch_score_report(&pool, &window, param.event_id.as_ref()).await?;
 
     let mut csv_writer = csv::Writer::from_writer(Vec::new());
 
@@ -907,8 +2076,10 @@ pub async fn generate_csv(
         "Candidate Last Name",
         "Judge",
         "Score",
+        "Curved Score",
         "Max",
         "Weight",
+        "Remark",
     ];
 
     csv_writer.write_record(&headers).map_err(|err| {
@@ -918,58 +2089,28 @@ pub async fn generate_csv(
         )
     })?;
 
-    for category in categories.iter() {
-        let criterias = sqlx::query_as::<_, CriteriaIdName>(
-            "SELECT id, name FROM criterias WHERE category_id = ($1)",
-        )
-        .bind(category.id)
-        .fetch_all(&pool)
-        .await?;
-
-        for criteria in criterias.iter() {
-            let scores = sqlx::query_as::<_, CriteriaScore>(
-                r#"
-                SELECT s.score, s.max, j.name as judge_name,
-                    can.first_name as candidate_first_name,
-                    can.middle_name as candidate_middle_name,
-                    can.last_name as candidate_last_name,
-                    cat.weight as weight,
-                    e.name as event_name
-                FROM scores s
-                JOIN judges j ON j.id = s.judge_id
-                JOIN candidates can ON can.id = s.candidate_id
-                JOIN categories cat ON cat.id = s.category_id
-                JOIN events e ON e.id = cat.event_id
-                WHERE s.category_id = ($1) AND s.criteria_id = ($2)
-                "#,
-            )
-            .bind(category.id)
-            .bind(criteria.id)
-            .fetch_all(&pool)
-            .await?;
-
-            for score in scores.iter() {
-                csv_writer
-                    .write_record(vec![
-                        &score.event_name,
-                        &category.name,
-                        &criteria.name,
-                        &score.candidate_first_name,
-                        &score.candidate_middle_name,
-                        &score.candidate_last_name,
-                        &score.judge_name,
-                        &score.score.to_string(),
-                        &score.max.to_string(),
-                        &score.weight.to_string(),
-                    ])
-                    .map_err(|err| {
-                        AppError::new(
-                            http::StatusCode::INTERNAL_SERVER_ERROR,
-                            format!("Failed to serialize record: {}", err),
-                        )
-                    })?;
-            }
-        }
+    for row in rows.iter() {
+        csv_writer
+            .write_record(vec![
+                &row.event_name,
+                &row.category_name,
+                &row.criteria_name,
+                &row.candidate_first_name,
+                &row.candidate_middle_name,
+                &row.candidate_last_name,
+                judge_labels.get(&row.judge_id).unwrap_or(&row.judge_name),
+                &row.score.to_string(),
+                &row.curved_score.to_string(),
+                &row.max.to_string(),
+                &row.category_weight.to_string(),
+                row.remark.as_ref().map(SealedText::as_str).unwrap_or_default(),
+            ])
+            .map_err(|err| {
+                AppError::new(
+                    http::StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Failed to serialize record: {}", err),
+                )
+            })?;
     }
 
     let csv_bytes = csv_writer.into_inner().map_err(|err| {
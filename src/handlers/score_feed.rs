@@ -0,0 +1,95 @@
+use std::convert::Infallible;
+use std::time::Duration;
+
+use axum::extract::{Path, State};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use futures::stream::{self, Stream};
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+use super::judge::Judge;
+use super::score::Score;
+
+// Scores submitted while a monitoring client is too slow to keep up are skipped for it
+const FEED_CAPACITY: usize = 256;
+
+const KEEP_ALIVE: Duration = Duration::from_secs(15);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SubmittedScore {
+    score_id: uuid::Uuid,
+    // `create`, `replace` or `update`
+    change: &'static str,
+    score: i32,
+    max: i32,
+    time_of_scoring: chrono::DateTime<chrono::Utc>,
+    judge_name: String,
+    // Relationships
+    event_id: uuid::Uuid,
+    judge_id: uuid::Uuid,
+    candidate_id: uuid::Uuid,
+    criteria_id: uuid::Uuid,
+    category_id: uuid::Uuid,
+}
+
+// Every stored score as it comes in, for the admins' monitoring view
+// Separate from the realtime channel, which only carries what judges and displays need
+#[derive(Clone)]
+pub struct ScoreFeed {
+    tx: broadcast::Sender<SubmittedScore>,
+}
+
+impl Default for ScoreFeed {
+    fn default() -> Self {
+        let (tx, _) = broadcast::channel(FEED_CAPACITY);
+
+        Self { tx }
+    }
+}
+
+impl ScoreFeed {
+    // Having no subscribers is not an error, nobody is just monitoring
+    pub fn publish(&self, judge: &Judge, score: &Score, change: &'static str) {
+        let _ = self.tx.send(SubmittedScore {
+            score_id: score.id,
+            change,
+            score: score.score,
+            max: score.max,
+            time_of_scoring: score.time_of_scoring,
+            judge_name: judge.name.clone(),
+            event_id: judge.event_id,
+            judge_id: score.judge_id,
+            candidate_id: score.candidate_id,
+            criteria_id: score.criteria_id,
+            category_id: score.category_id,
+        });
+    }
+}
+
+// Each score of the event is sent as a `score` event, skipped ones are reported as `lagged`
+pub async fn score_stream(
+    State(feed): State<ScoreFeed>,
+    Path(event_id): Path<uuid::Uuid>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let rx = feed.tx.subscribe();
+
+    let events = stream::unfold(rx, move |mut rx| async move {
+        loop {
+            let event = match rx.recv().await {
+                Ok(submitted) if submitted.event_id == event_id => Event::default()
+                    .event("score")
+                    .json_data(&submitted)
+                    .unwrap_or_else(|_| Event::default().event("error")),
+                Ok(_) => continue,
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    Event::default().event("lagged").data(skipped.to_string())
+                }
+                Err(broadcast::error::RecvError::Closed) => return None,
+            };
+
+            return Some((Ok(event), rx));
+        }
+    });
+
+    Sse::new(events).keep_alive(KeepAlive::new().interval(KEEP_ALIVE))
+}
@@ -0,0 +1,359 @@
+use std::collections::BTreeMap;
+
+use axum::extract::{Extension, Path, State};
+use axum::http;
+use axum::response::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use sqlx::PgPool;
+
+use crate::aggregation::Aggregator;
+use crate::error::AppError;
+
+use super::admin::StaffClaims;
+use super::audit;
+use super::candidate;
+use super::history;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TieBreakStrategy {
+    // Exact ties share a rank
+    SharedRank,
+    // Ties are broken by the event's tie-breaker rules
+    TieBreakers,
+}
+
+// What a candidate's points are divided by when averaging over the panel
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AveragingDenominator {
+    // Only the judges who actually scored the candidate
+    ScoredJudges,
+    // Every judge seat of the event, missing scores count as zero
+    AssignedJudges,
+    // Judges without `score_exclusion`, excluded judges' scores are left out entirely
+    NonExcludedJudges,
+}
+
+// Enforced by the `candidates_number_unique` trigger, which reads the stored setting
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CandidateNumbering {
+    // Male and female candidates may share a number
+    PerDivision,
+    // Every candidate of the event has their own number
+    PerEvent,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScoreInput {
+    Slider,
+    Buttons,
+}
+
+// What every judge tablet renders, so changing it doesn't take a new frontend build
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ClientConfig {
+    // Hex colors like `#7b1113`, the frontend's own colors when unset
+    pub primary_color: Option<String>,
+    pub accent_color: Option<String>,
+    pub logo_url: Option<String>,
+    // Label -> text shown instead, e.g. "candidate" -> "contestant"
+    pub labels: BTreeMap<String, String>,
+    pub remarks_enabled: bool,
+    pub score_input: ScoreInput,
+}
+
+impl Default for ClientConfig {
+    fn default() -> Self {
+        Self {
+            primary_color: None,
+            accent_color: None,
+            logo_url: None,
+            labels: BTreeMap::new(),
+            remarks_enabled: true,
+            score_input: ScoreInput::Buttons,
+        }
+    }
+}
+
+// Missing keys fall back to the defaults, so events never need every setting stored
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EventSettings {
+    pub tie_break_strategy: TieBreakStrategy,
+    // Decimals shown in rankings and displays, 2 or 4
+    pub display_decimals: u32,
+    pub anonymize_judges: bool,
+    pub averaging_denominator: AveragingDenominator,
+    // Judge seats allowed on the panel, 0 for no limit
+    pub max_judges: u32,
+    // Tablets the venue has, creating more seats than this only warns
+    pub tablet_count: u32,
+    // Division -> award titles by rank, given out when the event is finalized
+    pub award_titles: BTreeMap<String, Vec<String>>,
+    pub client_config: ClientConfig,
+    pub candidate_numbering: CandidateNumbering,
+}
+
+impl Default for EventSettings {
+    fn default() -> Self {
+        Self {
+            tie_break_strategy: TieBreakStrategy::SharedRank,
+            display_decimals: 2,
+            anonymize_judges: false,
+            averaging_denominator: AveragingDenominator::ScoredJudges,
+            max_judges: 0,
+            tablet_count: 0,
+            award_titles: ["male", "female"]
+                .into_iter()
+                .map(|division| {
+                    let titles = history::DEFAULT_AWARD_TITLES
+                        .iter()
+                        .map(|title| title.to_string())
+                        .collect();
+
+                    (division.to_string(), titles)
+                })
+                .collect(),
+            client_config: ClientConfig::default(),
+            candidate_numbering: CandidateNumbering::PerDivision,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateEventSettings {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tie_break_strategy: Option<TieBreakStrategy>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    display_decimals: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    anonymize_judges: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    averaging_denominator: Option<AveragingDenominator>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_judges: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tablet_count: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    award_titles: Option<BTreeMap<String, Vec<String>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    client_config: Option<ClientConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    candidate_numbering: Option<CandidateNumbering>,
+}
+
+// Stored key/value rows layered over the defaults
+// A row that no longer parses (e.g. a renamed variant) only resets its own setting
+pub fn settings_from_rows(rows: Vec<(String, serde_json::Value)>) -> EventSettings {
+    let mut settings = serde_json::to_value(EventSettings::default()).unwrap_or_default();
+
+    if let Some(map) = settings.as_object_mut() {
+        for (key, value) in rows {
+            let Some(default) = map.insert(key.clone(), value) else {
+                map.remove(&key);
+                continue;
+            };
+
+            let layered = serde_json::Value::Object(map.clone());
+
+            if let Err(err) = serde_json::from_value::<EventSettings>(layered) {
+                eprintln!("Ignoring the stored {} setting: {}", key, err);
+                map.insert(key, default);
+            }
+        }
+    }
+
+    serde_json::from_value(settings).unwrap_or_default()
+}
+
+pub async fn fetch_settings(
+    pool: &PgPool,
+    event_id: &uuid::Uuid,
+) -> Result<EventSettings, AppError> {
+    let rows = sqlx::query_as::<_, (String, Json<serde_json::Value>)>(
+        "SELECT key, value FROM event_settings WHERE event_id = ($1)",
+    )
+    .bind(event_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(settings_from_rows(
+        rows.into_iter()
+            .map(|(key, value)| (key, value.0))
+            .collect(),
+    ))
+}
+
+// Titles only exist for the male and female divisions and can't be blank
+pub fn validate_award_titles(award_titles: &BTreeMap<String, Vec<String>>) -> Result<(), String> {
+    for (division, titles) in award_titles {
+        if division != "male" && division != "female" {
+            return Err(format!("Unknown division: {}", division));
+        }
+
+        if titles.iter().any(|title| title.trim().is_empty()) {
+            return Err(format!(
+                "Award titles of the {} division can't be blank",
+                division
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+fn is_hex_color(color: &str) -> bool {
+    color.len() == 7 && color.starts_with('#') && color[1..].chars().all(|c| c.is_ascii_hexdigit())
+}
+
+pub fn validate_client_config(config: &ClientConfig) -> Result<(), String> {
+    for color in [&config.primary_color, &config.accent_color]
+        .into_iter()
+        .flatten()
+    {
+        if !is_hex_color(color) {
+            return Err(format!("'{}' is not a hex color like #7b1113", color));
+        }
+    }
+
+    if let Some(logo_url) = &config.logo_url {
+        if !logo_url.starts_with("https://") && !logo_url.starts_with("http://") {
+            return Err("The logo URL must start with http:// or https://".to_string());
+        }
+    }
+
+    if config
+        .labels
+        .iter()
+        .any(|(label, text)| label.trim().is_empty() || text.trim().is_empty())
+    {
+        return Err("Label overrides can't be blank".to_string());
+    }
+
+    Ok(())
+}
+
+async fn ensure_event_exists(pool: &PgPool, event_id: &uuid::Uuid) -> Result<(), AppError> {
+    let exists: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM events WHERE id = ($1))")
+        .bind(event_id)
+        .fetch_one(pool)
+        .await?;
+
+    if !exists {
+        return Err(AppError::new(
+            http::StatusCode::NOT_FOUND,
+            "Event not found",
+        ));
+    }
+
+    Ok(())
+}
+
+pub async fn get_event_settings(
+    State(pool): State<PgPool>,
+    Path(event_id): Path<uuid::Uuid>,
+) -> Result<axum::Json<EventSettings>, AppError> {
+    ensure_event_exists(&pool, &event_id).await?;
+
+    Ok(axum::Json(fetch_settings(&pool, &event_id).await?))
+}
+
+// Fetched by the judge tablets when they load, without the rest of the settings
+pub async fn get_client_config(
+    State(pool): State<PgPool>,
+    Path(event_id): Path<uuid::Uuid>,
+) -> Result<axum::Json<ClientConfig>, AppError> {
+    ensure_event_exists(&pool, &event_id).await?;
+
+    Ok(axum::Json(
+        fetch_settings(&pool, &event_id).await?.client_config,
+    ))
+}
+
+pub async fn update_event_settings(
+    State(pool): State<PgPool>,
+    State(aggregator): State<Aggregator>,
+    Extension(staff): Extension<StaffClaims>,
+    Path(event_id): Path<uuid::Uuid>,
+    axum::Json(payload): axum::Json<UpdateEventSettings>,
+) -> Result<axum::Json<EventSettings>, AppError> {
+    ensure_event_exists(&pool, &event_id).await?;
+
+    if let Some(decimals) = payload.display_decimals {
+        if decimals != 2 && decimals != 4 {
+            return Err(AppError::new(
+                http::StatusCode::UNPROCESSABLE_ENTITY,
+                "Display decimals must be 2 or 4",
+            ));
+        }
+    }
+
+    if let Some(award_titles) = &payload.award_titles {
+        validate_award_titles(award_titles)
+            .map_err(|err| AppError::new(http::StatusCode::UNPROCESSABLE_ENTITY, err))?;
+    }
+
+    if let Some(client_config) = &payload.client_config {
+        validate_client_config(client_config)
+            .map_err(|err| AppError::new(http::StatusCode::UNPROCESSABLE_ENTITY, err))?;
+    }
+
+    // Numbers already shared across divisions have to be sorted out before the stricter rule applies
+    if let Some(numbering) = payload.candidate_numbering {
+        let collisions = candidate::find_number_collisions(&pool, &event_id, numbering).await?;
+
+        if !collisions.is_empty() {
+            return Err(AppError::new(
+                http::StatusCode::CONFLICT,
+                format!(
+                    "Candidate numbers {:?} are used more than once, renumber the candidates first",
+                    collisions
+                ),
+            ));
+        }
+    }
+
+    let changes = serde_json::to_value(&payload).unwrap_or_default();
+
+    let mut txn = pool.begin().await?;
+
+    if let Some(map) = changes.as_object() {
+        for (key, value) in map {
+            sqlx::query(
+                r#"
+                INSERT INTO event_settings (event_id, key, value)
+                VALUES ($1, $2, $3)
+                ON CONFLICT (event_id, key) DO UPDATE SET value = EXCLUDED.value, updated_at = NOW()
+                "#,
+            )
+            .bind(&event_id)
+            .bind(key)
+            .bind(Json(value))
+            .execute(&mut *txn)
+            .await?;
+        }
+    }
+
+    audit::record(
+        &mut *txn,
+        staff.sub,
+        "event",
+        Some(&event_id),
+        "update_settings",
+        changes,
+    )
+    .await?;
+
+    txn.commit().await?;
+
+    // Rounding and the averaging policy both change what the display board shows
+    aggregator.request(event_id);
+
+    Ok(axum::Json(fetch_settings(&pool, &event_id).await?))
+}
@@ -0,0 +1,557 @@
+use std::collections::HashMap;
+
+use axum::extract::{Path, Query, State};
+use axum::http;
+use axum::response::Result;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::types::Json;
+use sqlx::{FromRow, PgPool};
+
+use crate::error::AppError;
+
+use super::category;
+use super::history::AwardWinner;
+use super::score::{
+    compute_event_final_scores, fetch_display_decimals, fetch_judge_final_scores,
+    fetch_round_final_scores, round_final_scores, CandidateFinalScore2,
+};
+use super::tie_break;
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct StandingsSnapshot {
+    pub id: uuid::Uuid,
+    pub version: i32,
+    pub standings: Json<Vec<CandidateFinalScore2>>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub scores_digest: Option<String>,
+    pub chain_hash: Option<String>,
+    // Only set on the snapshot an event was finalized with
+    pub awards: Option<Json<Vec<AwardWinner>>>,
+    // Relationships
+    pub event_id: uuid::Uuid,
+    pub category_id: Option<uuid::Uuid>,
+}
+
+#[derive(Debug, FromRow)]
+pub struct ScoreHashRow {
+    pub id: uuid::Uuid,
+    pub score: i32,
+    pub max: i32,
+    pub candidate_id: uuid::Uuid,
+    pub criteria_id: uuid::Uuid,
+    pub category_id: uuid::Uuid,
+    pub judge_id: uuid::Uuid,
+}
+
+// Hash of the event's score rows, in a fixed order
+pub fn scores_digest(rows: &[ScoreHashRow]) -> String {
+    let mut hasher = Sha256::new();
+
+    for row in rows {
+        hasher.update(format!(
+            "{}|{}|{}|{}|{}|{}|{}\n",
+            row.id,
+            row.score,
+            row.max,
+            row.candidate_id,
+            row.criteria_id,
+            row.category_id,
+            row.judge_id
+        ));
+    }
+
+    hex::encode(hasher.finalize())
+}
+
+// Links a snapshot to the one before it, changing any earlier snapshot breaks every later hash
+pub fn chain_hash(
+    previous_hash: Option<&str>,
+    scores_digest: &str,
+    standings: &[CandidateFinalScore2],
+) -> String {
+    let mut hasher = Sha256::new();
+
+    hasher.update(previous_hash.unwrap_or_default());
+    hasher.update(scores_digest);
+    hasher.update(serde_json::to_string(standings).unwrap_or_default());
+
+    hex::encode(hasher.finalize())
+}
+
+async fn fetch_score_hash_rows(
+    pool: &PgPool,
+    event_id: &uuid::Uuid,
+) -> Result<Vec<ScoreHashRow>, AppError> {
+    let rows = sqlx::query_as::<_, ScoreHashRow>(
+        r#"
+        SELECT s.id, s.score, s.max, s.candidate_id, s.criteria_id, s.category_id, s.judge_id
+        FROM scores_history s
+        JOIN categories cat ON cat.id = s.category_id
+        WHERE cat.event_id = ($1) AND s.is_rehearsal = FALSE
+        ORDER BY s.id
+        "#,
+    )
+    .bind(event_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows)
+}
+
+// Stores the current standings as the next version for the event
+// Snapshots are never updated, every close of a category adds a new one
+pub async fn store_snapshot(
+    pool: &PgPool,
+    event_id: &uuid::Uuid,
+    category_id: Option<&uuid::Uuid>,
+) -> Result<StandingsSnapshot, AppError> {
+    let standings = compute_event_final_scores(pool, event_id).await?;
+
+    let previous_hash = sqlx::query_scalar::<_, Option<String>>(
+        "SELECT chain_hash FROM standings_snapshots WHERE event_id = ($1) ORDER BY version DESC LIMIT 1",
+    )
+    .bind(event_id)
+    .fetch_optional(pool)
+    .await?
+    .flatten();
+
+    let digest = scores_digest(&fetch_score_hash_rows(pool, event_id).await?);
+    let hash = chain_hash(previous_hash.as_deref(), &digest, &standings);
+
+    let snapshot = sqlx::query_as::<_, StandingsSnapshot>(
+        r#"
+        INSERT INTO standings_snapshots (version, standings, event_id, category_id, scores_digest, chain_hash)
+        SELECT COALESCE(MAX(version), 0) + 1, ($1), ($2), ($3), ($4), ($5)
+        FROM standings_snapshots
+        WHERE event_id = ($2)
+        RETURNING *
+        "#,
+    )
+    .bind(Json(&standings))
+    .bind(event_id)
+    .bind(category_id)
+    .bind(&digest)
+    .bind(&hash)
+    .fetch_one(pool)
+    .await?;
+
+    println!(
+        "Stored standings snapshot v{} for event {}",
+        snapshot.version, event_id
+    );
+
+    Ok(snapshot)
+}
+
+pub async fn get_standings_history(
+    State(pool): State<PgPool>,
+    Path(event_id): Path<uuid::Uuid>,
+) -> Result<axum::Json<Vec<StandingsSnapshot>>, AppError> {
+    let snapshots = sqlx::query_as::<_, StandingsSnapshot>(
+        "SELECT * FROM standings_snapshots WHERE event_id = ($1) ORDER BY version",
+    )
+    .bind(&event_id)
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(axum::Json(snapshots))
+}
+
+#[derive(Debug, Serialize)]
+pub struct RankedCandidate {
+    pub rank: usize,
+    #[serde(flatten)]
+    pub candidate: CandidateFinalScore2,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DivisionStandings {
+    pub division: String,
+    pub standings: Vec<RankedCandidate>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DivisionedStandings {
+    pub divisions: Vec<DivisionStandings>,
+    pub overall: Vec<RankedCandidate>,
+}
+
+fn rank_candidates(mut candidates: Vec<CandidateFinalScore2>) -> Vec<RankedCandidate> {
+    candidates.sort_by(|a, b| {
+        b.final_score
+            .total_cmp(&a.final_score)
+            .then(a.candidate_number.cmp(&b.candidate_number))
+    });
+
+    candidates
+        .into_iter()
+        .enumerate()
+        .map(|(idx, candidate)| RankedCandidate {
+            rank: idx + 1,
+            candidate,
+        })
+        .collect()
+}
+
+// Each division is ranked on its own, male (gender = 1) first, so ranks never run across divisions
+pub fn divide_standings(standings: &[CandidateFinalScore2]) -> DivisionedStandings {
+    divide_standings_by(standings, rank_candidates)
+}
+
+pub fn divide_standings_by(
+    standings: &[CandidateFinalScore2],
+    rank: impl Fn(Vec<CandidateFinalScore2>) -> Vec<RankedCandidate>,
+) -> DivisionedStandings {
+    let (male, female): (Vec<CandidateFinalScore2>, Vec<CandidateFinalScore2>) = standings
+        .iter()
+        .cloned()
+        .partition(|candidate| candidate.gender == 1);
+
+    DivisionedStandings {
+        divisions: vec![
+            DivisionStandings {
+                division: "male".to_string(),
+                standings: rank(male),
+            },
+            DivisionStandings {
+                division: "female".to_string(),
+                standings: rank(female),
+            },
+        ],
+        overall: rank(standings.to_vec()),
+    }
+}
+
+pub async fn get_event_standings(
+    State(pool): State<PgPool>,
+    Path(event_id): Path<uuid::Uuid>,
+) -> Result<axum::Json<DivisionedStandings>, AppError> {
+    let mut standings = compute_event_final_scores(&pool, &event_id).await?;
+    let decimals = fetch_display_decimals(&pool, &event_id).await?;
+
+    round_final_scores(&mut standings, decimals);
+
+    if let Some(keys) = tie_break::fetch_tie_break_keys(&pool, &event_id).await? {
+        return Ok(axum::Json(divide_standings_by(&standings, |candidates| {
+            tie_break::rank_with_tie_breakers(candidates, &keys)
+        })));
+    }
+
+    Ok(axum::Json(divide_standings(&standings)))
+}
+
+// Standings of a single category over who was in it, see `fetch_round_final_scores`
+pub async fn get_round_standings(
+    State(pool): State<PgPool>,
+    Path((event_id, category_id)): Path<(uuid::Uuid, uuid::Uuid)>,
+) -> Result<axum::Json<DivisionedStandings>, AppError> {
+    category::ensure_event_category(&pool, &event_id, &category_id).await?;
+
+    let mut standings = fetch_round_final_scores(&pool, &[category_id]).await?;
+    let decimals = fetch_display_decimals(&pool, &event_id).await?;
+
+    round_final_scores(&mut standings, decimals);
+
+    Ok(axum::Json(divide_standings(&standings)))
+}
+
+pub async fn export_round_standings(
+    State(pool): State<PgPool>,
+    Path((event_id, category_id)): Path<(uuid::Uuid, uuid::Uuid)>,
+) -> Result<(http::StatusCode, Vec<u8>), AppError> {
+    category::ensure_event_category(&pool, &event_id, &category_id).await?;
+
+    let mut standings = fetch_round_final_scores(&pool, &[category_id]).await?;
+    let decimals = fetch_display_decimals(&pool, &event_id).await?;
+
+    round_final_scores(&mut standings, decimals);
+
+    let mut csv_writer = csv::Writer::from_writer(Vec::new());
+
+    let write_err = |err: csv::Error| {
+        AppError::new(
+            http::StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to write round standings: {}", err),
+        )
+    };
+
+    csv_writer
+        .write_record([
+            "Division",
+            "Rank",
+            "Candidate Number",
+            "First Name",
+            "Middle Name",
+            "Last Name",
+            "Final Score",
+        ])
+        .map_err(write_err)?;
+
+    for division in divide_standings(&standings).divisions {
+        for ranked in division.standings {
+            csv_writer
+                .write_record([
+                    &division.division,
+                    &ranked.rank.to_string(),
+                    &ranked.candidate.candidate_number.to_string(),
+                    &ranked.candidate.first_name,
+                    &ranked.candidate.middle_name,
+                    &ranked.candidate.last_name,
+                    &ranked.candidate.final_score.to_string(),
+                ])
+                .map_err(write_err)?;
+        }
+    }
+
+    let csv_bytes = csv_writer.into_inner().map_err(|err| {
+        AppError::new(
+            http::StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to generate CSV file: {}", err),
+        )
+    })?;
+
+    Ok((http::StatusCode::OK, csv_bytes))
+}
+
+// For post-event analysis and ordinal modes that need every judge's own rank list
+pub async fn get_judge_ranking(
+    State(pool): State<PgPool>,
+    Path((event_id, judge_id)): Path<(uuid::Uuid, uuid::Uuid)>,
+) -> Result<axum::Json<DivisionedStandings>, AppError> {
+    let exists: bool = sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM judges WHERE id = ($1) AND event_id = ($2))",
+    )
+    .bind(&judge_id)
+    .bind(&event_id)
+    .fetch_one(&pool)
+    .await?;
+
+    if !exists {
+        return Err(AppError::new(
+            http::StatusCode::NOT_FOUND,
+            "Judge not found in this event",
+        ));
+    }
+
+    let mut standings = fetch_judge_final_scores(&pool, &event_id, &judge_id).await?;
+    let decimals = fetch_display_decimals(&pool, &event_id).await?;
+
+    round_final_scores(&mut standings, decimals);
+
+    Ok(axum::Json(divide_standings(&standings)))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StandingChange {
+    pub candidate_id: uuid::Uuid,
+    pub candidate_number: i32,
+    pub first_name: String,
+    pub last_name: String,
+    pub from_rank: Option<usize>,
+    pub to_rank: Option<usize>,
+    pub from_score: Option<f32>,
+    pub to_score: Option<f32>,
+    pub score_delta: Option<f32>,
+}
+
+// Candidate ID -> rank within their division (male or female), starting at 1
+fn rank_by_division(standings: &[CandidateFinalScore2]) -> HashMap<uuid::Uuid, usize> {
+    let mut sorted: Vec<&CandidateFinalScore2> = standings.iter().collect();
+
+    sorted.sort_by(|a, b| {
+        (a.gender != 1)
+            .cmp(&(b.gender != 1))
+            .then(b.final_score.total_cmp(&a.final_score))
+    });
+
+    let mut ranks = HashMap::new();
+    let mut division_counts: HashMap<bool, usize> = HashMap::new();
+
+    for candidate in sorted {
+        let count = division_counts.entry(candidate.gender == 1).or_insert(0);
+        *count += 1;
+
+        ranks.insert(candidate.candidate_id, *count);
+    }
+
+    ranks
+}
+
+// Every candidate whose rank or score differs between the two standings
+pub fn diff_standings(
+    from: &[CandidateFinalScore2],
+    to: &[CandidateFinalScore2],
+) -> Vec<StandingChange> {
+    let from_ranks = rank_by_division(from);
+    let to_ranks = rank_by_division(to);
+
+    let from_by_id: HashMap<uuid::Uuid, &CandidateFinalScore2> = from
+        .iter()
+        .map(|candidate| (candidate.candidate_id, candidate))
+        .collect();
+    let to_by_id: HashMap<uuid::Uuid, &CandidateFinalScore2> = to
+        .iter()
+        .map(|candidate| (candidate.candidate_id, candidate))
+        .collect();
+
+    let mut candidate_ids: Vec<&uuid::Uuid> = from_by_id.keys().chain(to_by_id.keys()).collect();
+    candidate_ids.sort();
+    candidate_ids.dedup();
+
+    let mut changes = Vec::new();
+
+    for candidate_id in candidate_ids {
+        let before = from_by_id.get(candidate_id);
+        let after = to_by_id.get(candidate_id);
+        let candidate = after.or(before).unwrap();
+
+        let from_score = before.map(|candidate| candidate.final_score);
+        let to_score = after.map(|candidate| candidate.final_score);
+        let from_rank = from_ranks.get(candidate_id).copied();
+        let to_rank = to_ranks.get(candidate_id).copied();
+
+        if from_score == to_score && from_rank == to_rank {
+            continue;
+        }
+
+        changes.push(StandingChange {
+            candidate_id: *candidate_id,
+            candidate_number: candidate.candidate_number,
+            first_name: candidate.first_name.clone(),
+            last_name: candidate.last_name.clone(),
+            from_rank,
+            to_rank,
+            from_score,
+            to_score,
+            score_delta: from_score.zip(to_score).map(|(from, to)| to - from),
+        });
+    }
+
+    changes.sort_by_key(|change| change.candidate_number);
+
+    changes
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StandingsDiffParam {
+    from: i32,
+    // Compares against the live standings when not given
+    to: Option<i32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StandingsDiff {
+    from_version: i32,
+    to_version: Option<i32>,
+    changes: Vec<StandingChange>,
+}
+
+async fn fetch_snapshot_version(
+    pool: &PgPool,
+    event_id: &uuid::Uuid,
+    version: i32,
+) -> Result<StandingsSnapshot, AppError> {
+    sqlx::query_as::<_, StandingsSnapshot>(
+        "SELECT * FROM standings_snapshots WHERE event_id = ($1) AND version = ($2)",
+    )
+    .bind(event_id)
+    .bind(version)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| {
+        AppError::new(
+            http::StatusCode::NOT_FOUND,
+            format!("Standings snapshot v{} not found", version),
+        )
+    })
+}
+
+// Documents the impact of late corrections on the standings
+pub async fn get_standings_diff(
+    State(pool): State<PgPool>,
+    Path(event_id): Path<uuid::Uuid>,
+    Query(param): Query<StandingsDiffParam>,
+) -> Result<axum::Json<StandingsDiff>, AppError> {
+    let from = fetch_snapshot_version(&pool, &event_id, param.from).await?;
+
+    let to_standings = match param.to {
+        Some(version) => {
+            fetch_snapshot_version(&pool, &event_id, version)
+                .await?
+                .standings
+                .0
+        }
+        None => compute_event_final_scores(&pool, &event_id).await?,
+    };
+
+    Ok(axum::Json(StandingsDiff {
+        from_version: from.version,
+        to_version: param.to,
+        changes: diff_standings(&from.standings, &to_standings),
+    }))
+}
+
+#[derive(Debug, Serialize)]
+pub struct SnapshotVerification {
+    version: i32,
+    // The stored hash matches the one re-derived from the snapshot and its predecessor
+    chain_intact: bool,
+    // The scores in the database still hash to what the snapshot recorded
+    matches_current_scores: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChainVerification {
+    is_valid: bool,
+    snapshots: Vec<SnapshotVerification>,
+}
+
+// Re-derives the hash chain of the event's snapshots
+// Scores changing after the latest snapshot, even through an override, show up as a mismatch
+pub async fn verify_standings_chain(
+    State(pool): State<PgPool>,
+    Path(event_id): Path<uuid::Uuid>,
+) -> Result<axum::Json<ChainVerification>, AppError> {
+    let snapshots = sqlx::query_as::<_, StandingsSnapshot>(
+        "SELECT * FROM standings_snapshots WHERE event_id = ($1) ORDER BY version",
+    )
+    .bind(&event_id)
+    .fetch_all(&pool)
+    .await?;
+
+    let current_digest = scores_digest(&fetch_score_hash_rows(&pool, &event_id).await?);
+
+    let mut previous_hash: Option<String> = None;
+    let mut verifications = Vec::new();
+
+    for snapshot in snapshots.iter() {
+        let (Some(digest), Some(hash)) = (&snapshot.scores_digest, &snapshot.chain_hash) else {
+            previous_hash = None;
+            continue;
+        };
+
+        let derived = chain_hash(previous_hash.as_deref(), digest, &snapshot.standings);
+
+        verifications.push(SnapshotVerification {
+            version: snapshot.version,
+            chain_intact: derived == *hash,
+            matches_current_scores: *digest == current_digest,
+        });
+
+        previous_hash = Some(hash.to_owned());
+    }
+
+    // Only the latest snapshot has to match the scores, earlier ones are allowed to differ
+    let is_valid = verifications
+        .iter()
+        .all(|verification| verification.chain_intact)
+        && verifications
+            .last()
+            .is_none_or(|verification| verification.matches_current_scores);
+
+    Ok(axum::Json(ChainVerification {
+        is_valid,
+        snapshots: verifications,
+    }))
+}
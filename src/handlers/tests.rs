@@ -1,8 +1,2517 @@
-#[cfg(test)]
-
 use super::*;
 
 #[test]
 pub fn connection_test() {
 
-}
\ No newline at end of file
+}
+
+#[test]
+pub fn judge_letter_label_test() {
+    assert_eq!(report::judge_letter_label(0), "Judge A");
+    assert_eq!(report::judge_letter_label(2), "Judge C");
+    assert_eq!(report::judge_letter_label(27), "Judge B2");
+}
+
+#[test]
+pub fn bracket_pairings_test() {
+    let ids: Vec<uuid::Uuid> = (1..=3).map(uuid::Uuid::from_u128).collect();
+
+    assert_eq!(bracket::round_robin_pairings(&ids).len(), 3);
+    assert_eq!(
+        bracket::elimination_pairings(&ids),
+        vec![(ids[0], Some(ids[1])), (ids[2], None)]
+    );
+}
+
+#[test]
+pub fn majority_winner_test() {
+    let (a, b) = (uuid::Uuid::from_u128(1), uuid::Uuid::from_u128(2));
+
+    assert_eq!(bracket::majority_winner(&[a, b, a]), Some(a));
+    assert_eq!(bracket::majority_winner(&[a, b]), None);
+    assert_eq!(bracket::majority_winner(&[]), None);
+}
+
+#[test]
+pub fn requires_remark_test() {
+    assert!(score::requires_remark(5, 10, Some(0.6), false));
+    assert!(!score::requires_remark(6, 10, Some(0.6), false));
+    assert!(score::requires_remark(10, 10, None, true));
+    assert!(!score::requires_remark(10, 10, None, false));
+}
+
+#[test]
+pub fn render_qr_png_test() {
+    let png = candidate::render_qr_png(b"candidate").unwrap();
+
+    assert_eq!(&png[1..4], b"PNG");
+}
+
+#[test]
+pub fn weights_sum_to_one_test() {
+    assert!(readiness::weights_sum_to_one(&[0.25, 0.25, 0.5]));
+    assert!(!readiness::weights_sum_to_one(&[0.25, 0.25]));
+}
+
+#[test]
+pub fn scrub_sensitive_test() {
+    let mut body = serde_json::json!({
+        "username": "judge",
+        "password": "secret",
+        "judges": [{ "new_password": "secret" }],
+        "pinned": true,
+        "tokens_used": 3,
+    });
+
+    crate::request_log::scrub_sensitive(&mut body);
+
+    assert_eq!(body["username"], "judge");
+    assert_eq!(body["password"], "[REDACTED]");
+    assert_eq!(body["judges"][0]["new_password"], "[REDACTED]");
+    assert_eq!(body["pinned"], true);
+    assert_eq!(body["tokens_used"], 3);
+}
+
+#[tokio::test]
+pub async fn log_mutations_oversized_body_test() {
+    use axum::body::{Body, Bytes};
+    use tower::ServiceExt;
+
+    // Never connects, the log insert fails in the background
+    let pool = sqlx::PgPool::connect_lazy("postgres://localhost:1/none").unwrap();
+
+    let app = axum::Router::new()
+        .route(
+            "/upload",
+            axum::routing::post(|body: Bytes| async move { body.len().to_string() }),
+        )
+        .layer(axum::middleware::from_fn_with_state(
+            pool,
+            crate::request_log::log_mutations,
+        ));
+
+    // Chunked, so there's no content length to skip it by
+    let chunks = (0..3).map(|_| Ok::<_, std::io::Error>(Bytes::from(vec![b'x'; 512 * 1024])));
+    let request = axum::http::Request::post("/upload")
+        .body(Body::from_stream(futures::stream::iter(chunks)))
+        .unwrap();
+
+    let response = app.oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), axum::http::StatusCode::OK);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+
+    assert_eq!(body, (3 * 512 * 1024).to_string());
+}
+
+#[test]
+pub fn pick_award_winners_test() {
+    let standing = |id: u128, gender: i32, final_score: f32| score::CandidateFinalScore2 {
+        candidate_id: uuid::Uuid::from_u128(id),
+        candidate_number: id as i32,
+        first_name: String::new(),
+        middle_name: String::new(),
+        last_name: String::new(),
+        gender,
+        final_score,
+        total_points: 0.0,
+    };
+
+    let winners = history::pick_award_winners(
+        &[
+            standing(1, 1, 80.0),
+            standing(2, 0, 90.0),
+            standing(3, 1, 85.0),
+        ],
+        &settings::EventSettings::default().award_titles,
+        &tie_break::TieBreakKeys::new(),
+    );
+
+    assert_eq!(winners.len(), 3);
+    assert_eq!(winners[0].award, "Winner");
+    assert_eq!(winners[0].candidate_number, 3);
+    assert_eq!(winners[1].award, "1st Runner-up");
+    assert_eq!(winners[2].division, "female");
+    assert_eq!(winners[2].candidate_number, 2);
+
+    // A tie goes to the tie-breaker winner, not whoever comes first in the list
+    let tied = [standing(1, 0, 90.0), standing(2, 0, 90.0)];
+    let keys = tie_break::TieBreakKeys::from([
+        (uuid::Uuid::from_u128(1), vec![70.0]),
+        (uuid::Uuid::from_u128(2), vec![75.0]),
+    ]);
+
+    let winners = history::pick_award_winners(
+        &tied,
+        &settings::EventSettings::default().award_titles,
+        &keys,
+    );
+
+    assert_eq!(winners[0].candidate_number, 2);
+    assert_eq!(winners[1].candidate_number, 1);
+}
+
+#[test]
+pub fn award_titles_test() {
+    let standing = |id: u128, gender: i32, final_score: f32| score::CandidateFinalScore2 {
+        candidate_id: uuid::Uuid::from_u128(id),
+        candidate_number: id as i32,
+        first_name: String::new(),
+        middle_name: String::new(),
+        last_name: String::new(),
+        gender,
+        final_score,
+        total_points: 0.0,
+    };
+
+    let titles =
+        std::collections::BTreeMap::from([("female".to_string(), vec!["Miss MMU".to_string()])]);
+
+    let winners = history::pick_award_winners(
+        &[
+            standing(1, 1, 80.0),
+            standing(2, 0, 90.0),
+            standing(3, 0, 85.0),
+        ],
+        &titles,
+        &tie_break::TieBreakKeys::new(),
+    );
+
+    assert_eq!(winners.len(), 2);
+    assert_eq!(winners[0].award, "Winner");
+    assert_eq!(winners[1].award, "Miss MMU");
+    assert_eq!(winners[1].candidate_number, 2);
+
+    let mixed =
+        std::collections::BTreeMap::from([("mixed".to_string(), vec!["Winner".to_string()])]);
+
+    assert!(settings::validate_award_titles(&titles).is_ok());
+    assert!(settings::validate_award_titles(&mixed).is_err());
+}
+
+#[test]
+pub fn round_to_decimals_test() {
+    assert_eq!(89.456789_f64.round_to_decimals(2), 89.46);
+    assert_eq!(89.456789_f64.round_to_decimals(4), 89.4568);
+}
+
+#[test]
+pub fn diff_standings_test() {
+    let standing = |id: u128, final_score: f32| score::CandidateFinalScore2 {
+        candidate_id: uuid::Uuid::from_u128(id),
+        candidate_number: id as i32,
+        first_name: String::new(),
+        middle_name: String::new(),
+        last_name: String::new(),
+        gender: 1,
+        final_score,
+        total_points: 0.0,
+    };
+
+    let from = [standing(1, 90.0), standing(2, 85.0), standing(3, 80.0)];
+    let to = [standing(1, 84.0), standing(2, 85.0), standing(3, 80.0)];
+
+    let changes = standings::diff_standings(&from, &to);
+
+    assert_eq!(changes.len(), 2);
+    assert_eq!(changes[0].candidate_number, 1);
+    assert_eq!(changes[0].from_rank, Some(1));
+    assert_eq!(changes[0].to_rank, Some(2));
+    assert_eq!(changes[0].score_delta, Some(-6.0));
+    assert_eq!(changes[1].candidate_number, 2);
+    assert_eq!(changes[1].to_rank, Some(1));
+}
+
+#[test]
+pub fn divide_standings_test() {
+    let standing = |id: u128, gender: i32, final_score: f32| score::CandidateFinalScore2 {
+        candidate_id: uuid::Uuid::from_u128(id),
+        candidate_number: id as i32,
+        first_name: String::new(),
+        middle_name: String::new(),
+        last_name: String::new(),
+        gender,
+        final_score,
+        total_points: 0.0,
+    };
+
+    let divided = standings::divide_standings(&[
+        standing(1, 1, 80.0),
+        standing(2, 2, 95.0),
+        standing(3, 1, 90.0),
+        standing(4, 2, 70.0),
+    ]);
+
+    let male = &divided.divisions[0].standings;
+    assert_eq!(male[0].candidate.candidate_number, 3);
+    assert_eq!(male[1].rank, 2);
+
+    let female = &divided.divisions[1].standings;
+    assert_eq!(female[0].candidate.candidate_number, 2);
+    assert_eq!(female[0].rank, 1);
+
+    assert_eq!(divided.overall[0].candidate.candidate_number, 2);
+    assert_eq!(divided.overall[3].rank, 4);
+}
+
+#[test]
+pub fn score_points_test() {
+    let options = [criteria::SelectionOption {
+        label: "Gold".to_string(),
+        points: 10,
+    }];
+
+    assert_eq!(
+        criteria::score_points("numeric", 10, &[], &score::ScoreValue::Numeric(7)),
+        Ok(7)
+    );
+    assert_eq!(
+        criteria::score_points("boolean", 5, &[], &score::ScoreValue::Boolean(true)),
+        Ok(5)
+    );
+    assert_eq!(
+        criteria::score_points("boolean", 5, &[], &score::ScoreValue::Boolean(false)),
+        Ok(0)
+    );
+    assert_eq!(
+        criteria::score_points(
+            "selection",
+            10,
+            &options,
+            &score::ScoreValue::Selection("Gold".to_string())
+        ),
+        Ok(10)
+    );
+    assert!(criteria::score_points("boolean", 5, &[], &score::ScoreValue::Numeric(5)).is_err());
+}
+
+#[test]
+pub fn describe_quorum_gaps_test() {
+    let gap = quorum::QuorumGap {
+        category_id: uuid::Uuid::from_u128(1),
+        category_name: "Swimwear".to_string(),
+        quorum: 5,
+        candidate_id: uuid::Uuid::from_u128(2),
+        candidate_number: 7,
+        judges_scored: 4,
+    };
+
+    assert_eq!(
+        quorum::describe_quorum_gaps(&[gap]),
+        "Judge quorum not met for #7 in Swimwear (4 of 5 judges)"
+    );
+}
+
+#[test]
+pub fn overtime_deduction_test() {
+    assert_eq!(timing::overtime_deduction(180, 180, 1.0), None);
+    assert_eq!(timing::overtime_deduction(181, 180, 1.0), Some(1.0));
+    assert_eq!(timing::overtime_deduction(300, 180, 0.5), Some(1.0));
+}
+
+#[test]
+pub fn flag_lagging_judges_test() {
+    let latency = |id: u128, median_seconds: f64| analytics::JudgeLatency {
+        judge_id: uuid::Uuid::from_u128(id),
+        judge_name: String::new(),
+        candidates_scored: 10,
+        median_seconds,
+        is_lagging: false,
+    };
+
+    assert_eq!(analytics::median(&[3.0, 1.0, 2.0]), Some(2.0));
+    assert_eq!(analytics::median(&[1.0, 2.0]), Some(1.5));
+    assert_eq!(analytics::median(&[]), None);
+
+    let mut latencies = [latency(1, 30.0), latency(2, 32.0), latency(3, 60.0)];
+    analytics::flag_lagging_judges(&mut latencies);
+
+    assert!(!latencies[0].is_lagging);
+    assert!(!latencies[1].is_lagging);
+    assert!(latencies[2].is_lagging);
+}
+
+#[test]
+pub fn points_in_range_test() {
+    assert!(criteria::points_in_range(-2, true, -10, 0));
+    assert!(criteria::points_in_range(0, true, -10, 0));
+    assert!(!criteria::points_in_range(-12, true, -10, 0));
+    assert!(!criteria::points_in_range(2, true, -10, 0));
+    assert!(!criteria::points_in_range(-1, false, 0, 10));
+    assert!(criteria::points_in_range(10, false, 0, 10));
+    assert!(!criteria::points_in_range(500, false, 0, 10));
+}
+
+#[test]
+pub fn realtime_topics_test() {
+    let event_id = uuid::Uuid::from_u128(1);
+    let standings = format!(r#"{{"type":"standings","event_id":"{}","standings":[]}}"#, event_id);
+    let topic = realtime::message_topic(&standings);
+
+    assert_eq!(topic, format!("standings:{}", event_id));
+    assert_eq!(
+        realtime::message_topic(r#"{"type":"judge_lock","judge_id":"x","is_locked":true}"#),
+        "judges:presence"
+    );
+    assert_eq!(
+        realtime::message_topic(r#"{"type":"judge_presence","judge_id":"x","online":false}"#),
+        "judges:presence"
+    );
+    assert_eq!(
+        realtime::message_topic(r#"{"type":"judge_nudge","judge_id":"x","message":"Hi"}"#),
+        "judges:x"
+    );
+    assert_eq!(
+        realtime::message_topic(r#"{"table":"scores","data":{"category_id":"abc"}}"#),
+        "scores:abc"
+    );
+
+    assert!(realtime::is_subscribed(&[], &topic));
+    assert!(realtime::is_subscribed(&["standings".to_string()], &topic));
+    assert!(realtime::is_subscribed(std::slice::from_ref(&topic), &topic));
+    assert!(!realtime::is_subscribed(&["scores".to_string()], &topic));
+    assert!(!realtime::is_subscribed(&["stand".to_string()], &topic));
+}
+
+#[test]
+pub fn chain_hash_test() {
+    let row = |score: i32| standings::ScoreHashRow {
+        id: uuid::Uuid::from_u128(1),
+        score,
+        max: 10,
+        candidate_id: uuid::Uuid::from_u128(2),
+        criteria_id: uuid::Uuid::from_u128(3),
+        category_id: uuid::Uuid::from_u128(4),
+        judge_id: uuid::Uuid::from_u128(5),
+    };
+
+    let digest = standings::scores_digest(&[row(8)]);
+
+    assert_eq!(digest, standings::scores_digest(&[row(8)]));
+    assert_ne!(digest, standings::scores_digest(&[row(9)]));
+
+    let first = standings::chain_hash(None, &digest, &[]);
+
+    assert_eq!(first.len(), 64);
+    assert_ne!(
+        standings::chain_hash(Some(&first), &digest, &[]),
+        standings::chain_hash(Some("tampered"), &digest, &[])
+    );
+}
+
+#[test]
+pub fn receipt_signature_test() {
+    let signature = receipt::sign_receipt(b"secret", "score|judge|8|0");
+
+    assert!(receipt::verify_signature(b"secret", "score|judge|8|0", &signature));
+    assert!(!receipt::verify_signature(b"secret", "score|judge|9|0", &signature));
+    assert!(!receipt::verify_signature(b"other", "score|judge|8|0", &signature));
+    assert!(!receipt::verify_signature(b"secret", "score|judge|8|0", "not hex"));
+}
+
+#[test]
+pub fn settings_from_rows_test() {
+    let settings = settings::settings_from_rows(vec![
+        ("display_decimals".to_string(), serde_json::json!(4)),
+        ("anonymize_judges".to_string(), serde_json::json!(true)),
+        ("unknown_key".to_string(), serde_json::json!("ignored")),
+        ("tie_break_strategy".to_string(), serde_json::json!("coin_flip")),
+        ("max_judges".to_string(), serde_json::json!(-1)),
+        ("tablet_count".to_string(), serde_json::json!(6)),
+    ]);
+
+    assert_eq!(settings.display_decimals, 4);
+    assert!(settings.anonymize_judges);
+    // Bad rows fall back to their own default without resetting the rest
+    assert_eq!(
+        settings.tie_break_strategy,
+        settings::TieBreakStrategy::SharedRank
+    );
+    assert_eq!(settings.max_judges, 0);
+    assert_eq!(settings.tablet_count, 6);
+}
+
+#[test]
+pub fn client_config_test() {
+    let mut config = settings::ClientConfig {
+        primary_color: Some("#7b1113".to_string()),
+        logo_url: Some("https://example.com/logo.png".to_string()),
+        ..Default::default()
+    };
+
+    assert!(config.remarks_enabled);
+    assert_eq!(config.score_input, settings::ScoreInput::Buttons);
+    assert!(settings::validate_client_config(&config).is_ok());
+
+    config.accent_color = Some("gold".to_string());
+    assert!(settings::validate_client_config(&config).is_err());
+
+    config.accent_color = None;
+    config.logo_url = Some("logo.png".to_string());
+    assert!(settings::validate_client_config(&config).is_err());
+}
+
+#[test]
+pub fn spearman_test() {
+    assert_eq!(analytics::average_ranks(&[9.0, 7.0, 9.0, 5.0]), vec![1.5, 3.0, 1.5, 4.0]);
+
+    let a = [90.0, 80.0, 70.0, 60.0];
+
+    assert_eq!(analytics::spearman(&a, &[50.0, 40.0, 30.0, 20.0]), Some(1.0));
+    assert_eq!(analytics::spearman(&a, &[20.0, 30.0, 40.0, 50.0]), Some(-1.0));
+    assert_eq!(analytics::spearman(&a, &[5.0, 5.0, 5.0, 5.0]), None);
+}
+
+#[test]
+pub fn pdf_document_test() {
+    assert_eq!(crate::pdf::text_width("10", 10.0), 11.12);
+
+    let mut document = crate::pdf::Document::new(419.53, 297.64);
+    document
+        .add_page()
+        .text(10.0, 10.0, 12.0, crate::pdf::Font::Helvetica, "Peña (Jr.)");
+
+    let bytes = document.to_bytes();
+    let text = String::from_utf8_lossy(&bytes);
+
+    assert!(bytes.starts_with(b"%PDF-1.4"));
+    assert!(text.contains("/Count 1"));
+    assert!(text.contains("\\(Jr.\\)"));
+    assert!(text.ends_with("%%EOF\n"));
+}
+
+#[test]
+pub fn update_already_applied_test() {
+    let stored = score::Score {
+        id: uuid::Uuid::from_u128(1),
+        score: 8,
+        max: 10,
+        time_of_scoring: chrono::Utc::now(),
+        candidate_id: uuid::Uuid::from_u128(2),
+        criteria_id: uuid::Uuid::from_u128(3),
+        category_id: uuid::Uuid::from_u128(4),
+        judge_id: uuid::Uuid::from_u128(5),
+        remark: Some(crate::field_crypto::SealedText("Strong finish".to_string())),
+        group_id: None,
+        updated_at: chrono::Utc::now(),
+    };
+
+    assert!(score::is_update_applied(&stored, 8, None));
+    assert!(score::is_update_applied(&stored, 8, Some(&"Strong finish".to_string())));
+    assert!(!score::is_update_applied(&stored, 9, None));
+    assert!(!score::is_update_applied(&stored, 8, Some(&"Weak finish".to_string())));
+}
+
+#[test]
+pub fn panel_warnings_test() {
+    let mut settings = settings::EventSettings::default();
+
+    assert_eq!(judge::panel_warnings(12, &settings), Ok(Vec::new()));
+
+    settings.max_judges = 5;
+    settings.tablet_count = 4;
+
+    assert_eq!(judge::panel_warnings(4, &settings), Ok(Vec::new()));
+    assert_eq!(judge::panel_warnings(5, &settings).map(|warnings| warnings.len()), Ok(1));
+    assert!(judge::panel_warnings(6, &settings).is_err());
+}
+
+#[test]
+pub fn suggest_username_test() {
+    let taken = vec!["judge".to_string(), "judge2".to_string()];
+
+    assert_eq!(judge::suggest_username("judge", &taken), "judge3");
+    assert_eq!(judge::suggest_username("panel", &taken), "panel2");
+}
+
+#[test]
+pub fn check_criteria_set_test() {
+    let criteria = |id: u128, name: &str, is_deduction: bool| criteria::Criteria {
+        id: uuid::Uuid::from_u128(id),
+        name: name.to_string(),
+        max_score: 10,
+        criteria_type: "numeric".to_string(),
+        options: None,
+        is_deduction,
+        min_score: -5,
+        category_id: uuid::Uuid::from_u128(100),
+    };
+    let criterias = vec![
+        criteria(1, "Poise", false),
+        criteria(2, "Stage Presence", false),
+        criteria(3, "Time Violation", true),
+    ];
+    let ids = |ids: &[u128]| -> Vec<uuid::Uuid> {
+        ids.iter().map(|id| uuid::Uuid::from_u128(*id)).collect()
+    };
+
+    assert!(score::check_criteria_set(&criterias, &ids(&[1, 2])).is_ok());
+    assert!(score::check_criteria_set(&criterias, &ids(&[1, 2, 3])).is_ok());
+    assert_eq!(
+        score::check_criteria_set(&criterias, &ids(&[1])),
+        Err("Missing scores for Stage Presence".to_string())
+    );
+    assert!(score::check_criteria_set(&criterias, &ids(&[1, 2, 4])).is_err());
+}
+
+#[test]
+pub fn legacy_import_test() {
+    let template: legacy::LegacyTemplate = serde_json::from_value(serde_json::json!({
+        "event_name": "Mr. and Ms. MMU 2022",
+        "held_on": "2022-09-30",
+        "header_row": 2,
+        "columns": {
+            "candidate_number": "No.",
+            "first_name": "First Name",
+            "last_name": "Last Name",
+            "gender": "Sex",
+            "category": "Segment",
+            "judge": "Judge"
+        },
+        "criteria": [
+            { "column": "Poise", "max_score": 50 },
+            { "column": "Wit", "max_score": 50 }
+        ]
+    }))
+    .unwrap();
+
+    let sheet: Vec<Vec<String>> = [
+        vec!["2022 Tabulation"],
+        vec!["No.", "First Name", "Last Name", "Sex", "Segment", "Judge", "Poise", "Wit"],
+        vec!["1", "Juan", "Cruz", "M", "Talent", "Judge 1", "40", "45.4"],
+        vec!["1", "Maria", "Santos", "F", "Talent", "Judge 1", "50", "50"],
+        vec!["", "", "", "", "", "TOTAL", "90", "95"],
+    ]
+    .iter()
+    .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+    .collect();
+
+    let rows = legacy::parse_rows(&sheet, &template).unwrap();
+
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0].gender, 1);
+    assert_eq!(rows[0].scores, vec![(0, 40), (1, 45)]);
+
+    let standings = legacy::legacy_standings(&rows, &template, &std::collections::HashMap::new());
+
+    assert_eq!(standings.len(), 2);
+    assert_eq!(standings[0].first_name, "Maria");
+    assert_eq!(standings[0].final_score, 100.0);
+    assert_eq!(standings[1].final_score, 85.0);
+
+    let mut out_of_range = sheet.clone();
+    out_of_range[2][6] = "60".to_string();
+
+    assert!(legacy::parse_rows(&out_of_range, &template).is_err());
+}
+
+#[test]
+pub fn field_encryption_test() {
+    let old_key = format!("2025:{}", "11".repeat(32));
+    let new_key = format!("2026:{}", "22".repeat(32));
+
+    let old = crate::field_crypto::Keyring::new(Some(&old_key), None).unwrap();
+    let rotated = crate::field_crypto::Keyring::new(Some(&new_key), Some(&old_key)).unwrap();
+    let disabled = crate::field_crypto::Keyring::new(None, None).unwrap();
+
+    let sealed = old.seal("Strong finish");
+
+    assert!(sealed.starts_with("enc:v1:2025:"));
+    assert_ne!(sealed, old.seal("Strong finish"));
+    assert_eq!(rotated.open(&sealed).unwrap(), "Strong finish");
+    assert!(rotated.needs_rotation(&sealed));
+    assert!(rotated.needs_rotation("written before encryption"));
+    assert!(!rotated.needs_rotation(&rotated.seal("Strong finish")));
+
+    assert_eq!(disabled.seal("Strong finish"), "Strong finish");
+    assert!(disabled.open(&sealed).is_err());
+    assert!(crate::field_crypto::Keyring::new(Some("2026:abcd"), None).is_err());
+}
+
+#[test]
+pub fn judge_login_token_test() {
+    let hash = auth::hash_password("secret").unwrap();
+
+    assert!(hash.starts_with("$argon2"));
+    assert!(auth::verify_password("secret", &hash));
+    assert!(!auth::verify_password("wrong", &hash));
+    assert!(!auth::verify_password("secret", "secret"));
+
+    let claims = auth::JudgeClaims {
+        sub: uuid::Uuid::from_u128(1),
+        event_id: uuid::Uuid::from_u128(2),
+        exp: (chrono::Utc::now() + chrono::Duration::hours(1)).timestamp(),
+        jti: "session".to_string(),
+    };
+    let token = auth::encode_token(b"tabulation", &claims).unwrap();
+
+    let decoded: auth::JudgeClaims = auth::decode_token(b"tabulation", &token).unwrap();
+
+    assert_eq!(decoded.sub, claims.sub);
+    assert_eq!(decoded.event_id, claims.event_id);
+    assert!(auth::decode_token::<auth::JudgeClaims>(b"another secret", &token).is_none());
+
+    // Login tokens are checked against their session, PIN sessions are their own key
+    assert_eq!(auth::session_key(b"tabulation", &token), "session");
+    assert_eq!(auth::session_key(b"another secret", &token), token);
+    assert_eq!(auth::session_key(b"tabulation", "pin-session"), "pin-session");
+
+    let expired = auth::JudgeClaims {
+        exp: (chrono::Utc::now() - chrono::Duration::hours(1)).timestamp(),
+        ..claims
+    };
+    let token = auth::encode_token(b"tabulation", &expired).unwrap();
+
+    assert!(auth::decode_token::<auth::JudgeClaims>(b"tabulation", &token).is_none());
+}
+
+#[test]
+pub fn organization_schema_test() {
+    use crate::tenancy;
+
+    assert_eq!(tenancy::schema_name("mmu").unwrap(), "org_mmu");
+    assert_eq!(tenancy::schema_name("mmu-davao").unwrap(), "org_mmu_davao");
+    assert!(tenancy::schema_name("").is_err());
+    assert!(tenancy::schema_name("2026").is_err());
+    assert!(tenancy::schema_name("mmu; DROP SCHEMA public").is_err());
+
+    let request = axum::http::Request::builder()
+        .uri("/ws?topics=scores&organization=mmu")
+        .body(())
+        .unwrap();
+
+    assert_eq!(tenancy::request_organization(&request).unwrap(), "mmu");
+
+    let request = axum::http::Request::builder()
+        .uri("/events")
+        .header(tenancy::ORGANIZATION_HEADER, "mmu-davao")
+        .body(())
+        .unwrap();
+
+    assert_eq!(tenancy::request_organization(&request).unwrap(), "mmu-davao");
+
+    let allowed = tenancy::parse_organizations(" mmu, mmu-davao,,");
+
+    assert_eq!(allowed, vec!["mmu", "mmu-davao"]);
+    assert!(tenancy::check_allowed("mmu-davao", &allowed).is_ok());
+    assert!(tenancy::check_allowed("mmu-cebu", &allowed).is_err());
+    assert!(tenancy::check_allowed("mmu", &[]).is_err());
+}
+
+#[test]
+pub fn staff_routes_test() {
+    use admin::Role;
+    use axum::http::Method;
+
+    assert_eq!(
+        admin::allowed_roles(&Method::POST, "/judges"),
+        Some(&[Role::Admin][..])
+    );
+    assert_eq!(
+        admin::allowed_roles(&Method::GET, "/scores/download"),
+        Some(&[Role::Admin, Role::Tabulator][..])
+    );
+    assert_eq!(
+        admin::allowed_roles(&Method::GET, "/events/:event_id/score-stream"),
+        Some(&[Role::Admin, Role::Tabulator][..])
+    );
+    assert_eq!(admin::allowed_roles(&Method::GET, "/judges"), None);
+    assert_eq!(admin::allowed_roles(&Method::POST, "/scores"), None);
+
+    // Standings and dashboards need staff or an observer, the audience's views stay open
+    assert_eq!(
+        admin::allowed_roles(&Method::GET, "/events/:event_id/standings"),
+        Some(&[Role::Admin, Role::Tabulator][..])
+    );
+    assert_eq!(
+        admin::allowed_roles(&Method::GET, "/events/:event_id/standings/compact"),
+        None
+    );
+    assert!(admin::observers_allowed(&Method::GET, "/events/:event_id/standings"));
+    assert!(admin::observers_allowed(&Method::GET, "/exports/:job_id/download"));
+    assert!(!admin::observers_allowed(&Method::POST, "/exports"));
+    assert!(!admin::observers_allowed(&Method::GET, "/audit"));
+
+    // Writes nobody listed fall back to admins
+    assert_eq!(
+        admin::allowed_roles(&Method::POST, "/events/:event_id/finalize"),
+        Some(&[Role::Admin][..])
+    );
+    assert_eq!(
+        admin::allowed_roles(&Method::PUT, "/candidates/:candidate_id/photo"),
+        Some(&[Role::Admin][..])
+    );
+    // Takes the admin from the staff claims, so it can't be opened up
+    assert_eq!(
+        admin::allowed_roles(&Method::POST, "/judges/:judge_id/impersonate"),
+        Some(&[Role::Admin][..])
+    );
+    assert_eq!(
+        admin::allowed_roles(&Method::POST, "/judges/:judge_id/check-in"),
+        Some(&[Role::Admin, Role::Tabulator][..])
+    );
+
+    // Open writes still need somebody's token unless the credentials are in the body
+    assert!(admin::needs_token(&Method::POST, "/notes"));
+    assert!(admin::needs_token(&Method::POST, "/scores"));
+    assert!(admin::needs_token(&Method::POST, "/logout"));
+    assert!(!admin::needs_token(&Method::POST, "/observers/login"));
+    assert!(!admin::needs_token(&Method::POST, "/events/:event_id/approvals"));
+    assert!(!admin::needs_token(&Method::GET, "/judges"));
+
+    let claims = admin::StaffClaims {
+        sub: uuid::Uuid::from_u128(1),
+        role: "tabulator".to_string(),
+        exp: (chrono::Utc::now() + chrono::Duration::hours(1)).timestamp(),
+        org: None,
+    };
+    let token = auth::encode_token(b"tabulation", &claims).unwrap();
+
+    // Staff tokens can't pass as a judge's and the other way around
+    assert!(auth::decode_token::<auth::JudgeClaims>(b"tabulation", &token).is_none());
+    assert!(auth::decode_token::<admin::StaffClaims>(b"tabulation", &token).is_some());
+}
+
+#[test]
+pub fn category_curve_test() {
+    assert!(category::validate_curve(None, None).is_ok());
+    assert!(category::validate_curve(Some(75.0), Some(10.0)).is_ok());
+    assert!(category::validate_curve(Some(75.0), None).is_err());
+    assert!(category::validate_curve(Some(120.0), Some(10.0)).is_err());
+    assert!(category::validate_curve(Some(75.0), Some(0.0)).is_err());
+}
+
+#[test]
+pub fn compact_standings_test() {
+    assert_eq!(compact::short_name("Maria", "Santos"), "Maria S.");
+    assert_eq!(compact::short_name(" Juan ", " dela Cruz"), "Juan d.");
+    assert_eq!(compact::short_name("Cher", ""), "Cher");
+}
+
+#[test]
+pub fn submission_latency_test() {
+    let received_at = chrono::Utc::now();
+    let stored_at = received_at + chrono::Duration::milliseconds(40);
+
+    assert_eq!(
+        analytics::submission_latency_ms(
+            Some(received_at - chrono::Duration::milliseconds(250)),
+            received_at,
+            stored_at
+        ),
+        (Some(250.0), 40.0)
+    );
+    // A device clock ahead of the server's
+    assert_eq!(
+        analytics::submission_latency_ms(
+            Some(received_at + chrono::Duration::seconds(3)),
+            received_at,
+            stored_at
+        ),
+        (Some(0.0), 40.0)
+    );
+    assert_eq!(
+        analytics::submission_latency_ms(None, received_at, stored_at),
+        (None, 40.0)
+    );
+}
+
+#[test]
+pub fn candidate_highlights_test() {
+    let criteria = |name: &str, percent: f64, field_percent: f64| report::CriteriaHighlight {
+        criteria_id: uuid::Uuid::nil(),
+        criteria_name: name.to_string(),
+        category_name: "Talent".to_string(),
+        percent,
+        field_percent,
+        difference: 0.0,
+    };
+
+    let names = |highlights: &[report::CriteriaHighlight]| {
+        highlights
+            .iter()
+            .map(|highlight| highlight.criteria_name.clone())
+            .collect::<Vec<_>>()
+    };
+
+    let (strongest, weakest) = report::pick_highlights(
+        vec![
+            criteria("Poise", 90.0, 80.0),
+            criteria("Stage presence", 70.0, 85.0),
+            criteria("Diction", 88.0, 70.0),
+            criteria("Wit", 75.0, 76.0),
+        ],
+        2,
+    );
+
+    assert_eq!(names(&strongest), ["Diction", "Poise"]);
+    assert_eq!(names(&weakest), ["Stage presence", "Wit"]);
+    assert_eq!(strongest[0].difference, 18.0);
+
+    // Too few criteria to fill both sides
+    let (strongest, weakest) = report::pick_highlights(vec![criteria("Poise", 90.0, 80.0)], 3);
+
+    assert_eq!(strongest.len(), 1);
+    assert!(weakest.is_empty());
+}
+
+#[test]
+pub fn competition_ranks_test() {
+    assert_eq!(
+        score::competition_ranks(&[92.5, 88.0, 88.0, 75.25]),
+        [1, 2, 2, 4]
+    );
+    assert_eq!(score::competition_ranks(&[90.0, 90.0, 90.0]), [1, 1, 1]);
+    assert_eq!(score::competition_ranks(&[]), Vec::<usize>::new());
+}
+
+#[test]
+pub fn standings_recheck_test() {
+    let change = |from_rank: usize, to_rank: usize| standings::StandingChange {
+        candidate_id: uuid::Uuid::nil(),
+        candidate_number: 1,
+        first_name: "Maria".to_string(),
+        last_name: "Santos".to_string(),
+        from_rank: Some(from_rank),
+        to_rank: Some(to_rank),
+        from_score: Some(90.0),
+        to_score: Some(91.0),
+        score_delta: Some(1.0),
+    };
+
+    // A higher score in the same place doesn't need the committee
+    assert!(!recheck::changes_ranks(&[change(1, 1)]));
+    assert!(recheck::changes_ranks(&[change(1, 1), change(3, 2)]));
+    assert!(!recheck::changes_ranks(&[]));
+}
+
+#[test]
+pub fn candidate_numbering_test() {
+    use settings::CandidateNumbering::{PerDivision, PerEvent};
+
+    // (gender, number), male #1 and female #1 only collide across the event
+    let numbers = [(1, 1), (1, 2), (0, 1), (0, 3), (0, 3)];
+
+    assert_eq!(candidate::number_collisions(&numbers, PerDivision), vec![3]);
+    assert_eq!(candidate::number_collisions(&numbers, PerEvent), vec![1, 3]);
+
+    assert_eq!(candidate::assign_numbers(&[1, 1, 0, 0, 0], PerDivision), vec![1, 2, 1, 2, 3]);
+    assert_eq!(candidate::assign_numbers(&[1, 1, 0, 0, 0], PerEvent), vec![1, 2, 3, 4, 5]);
+}
+
+#[test]
+pub fn score_preview_test() {
+    let criteria = |points: i32, max: i32| category::CriteriaPreview {
+        criteria_id: uuid::Uuid::nil(),
+        name: String::new(),
+        points,
+        max,
+    };
+
+    // A 40% category scored 45 out of 50, in an event of 100 weighted points
+    let preview = category::ScorePreview::new(vec![criteria(20, 20), criteria(25, 30)], 0.4, 100.0);
+
+    assert_eq!(preview.points, 45);
+    assert_eq!(preview.max, 50);
+    assert_eq!(preview.percent, 90.0);
+    assert_eq!(preview.weighted_score, 18.0);
+    assert_eq!(preview.final_score_impact, 18.0);
+    assert_eq!(preview.final_score_share, 20.0);
+
+    // Deductions lower the points without raising the max
+    let preview = category::ScorePreview::new(vec![criteria(10, 10), criteria(-2, 0)], 1.0, 0.0);
+
+    assert_eq!(preview.percent, 80.0);
+    assert_eq!(preview.final_score_impact, 0.0);
+}
+
+#[test]
+pub fn round_advancement_test() {
+    let standing = |id: u128, gender: i32, final_score: f32| score::CandidateFinalScore2 {
+        candidate_id: uuid::Uuid::from_u128(id),
+        candidate_number: id as i32,
+        first_name: String::new(),
+        middle_name: String::new(),
+        last_name: String::new(),
+        gender,
+        final_score,
+        total_points: 0.0,
+    };
+
+    let standings = vec![
+        standing(1, 1, 90.0),
+        standing(2, 1, 85.0),
+        standing(3, 0, 85.0),
+        standing(4, 0, 70.0),
+    ];
+
+    let advanced = |count: usize, per_gender: bool| -> Vec<u128> {
+        advancement::pick_advancing(&standings, count, per_gender)
+            .into_iter()
+            .filter(|(_, advanced)| *advanced)
+            .map(|(ranked, _)| ranked.candidate.candidate_id.as_u128())
+            .collect()
+    };
+
+    // Tied at the cutoff, both proceed
+    assert_eq!(advanced(2, false), vec![1, 2, 3]);
+    assert_eq!(advanced(1, true), vec![1, 3]);
+    assert_eq!(advanced(4, false).len(), 4);
+
+    // Male (1) division first, then female (0)
+    assert_eq!(advanced(2, true), vec![1, 2, 3, 4]);
+}
+
+#[test]
+pub fn tie_breakers_test() {
+    use std::cmp::Ordering;
+
+    let first = vec![48.0, -1700000000.0];
+    let second = vec![48.0, -1700000300.0];
+
+    // Same category score, whoever was scored first wins
+    assert_eq!(tie_break::compare_keys(Some(&first), Some(&second)), Ordering::Less);
+    assert_eq!(tie_break::compare_keys(Some(&first), Some(&first)), Ordering::Equal);
+    assert_eq!(tie_break::compare_keys(None, Some(&second)), Ordering::Greater);
+    assert_eq!(tie_break::compare_keys(None, None), Ordering::Equal);
+
+    let tie_breaker = |value: serde_json::Value| {
+        tie_break::validate_tie_breaker(&serde_json::from_value(value).unwrap())
+    };
+
+    assert!(tie_breaker(serde_json::json!({ "kind": "earliest_submission" })).is_ok());
+    assert!(tie_breaker(serde_json::json!({ "kind": "category", "category_id": uuid::Uuid::nil() })).is_ok());
+    assert!(tie_breaker(serde_json::json!({ "kind": "category", "judge_id": uuid::Uuid::nil() })).is_err());
+    assert!(tie_breaker(serde_json::json!({ "kind": "coin_flip" })).is_err());
+}
+
+#[test]
+pub fn candidate_import_test() {
+    use settings::CandidateNumbering::{PerDivision, PerEvent};
+
+    let rows: Vec<Vec<String>> = [
+        vec!["candidate #", "First Name", "Last Name", "Gender"],
+        vec!["1", "Juan", "Cruz", "Male"],
+        vec!["1", "Maria", "Santos", "F"],
+        vec!["", "", "", ""],
+        vec!["2.5", "Pedro", "Reyes", "M"],
+        vec!["3", "", "Lim", "M"],
+        vec!["4", "Ana", "Tan", "Other"],
+    ]
+    .iter()
+    .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+    .collect();
+
+    // Blank rows are skipped, invalid ones reported by their spreadsheet row
+    let (candidates, errors) = registration::parse_candidate_rows(&rows, &[], PerDivision).unwrap();
+
+    assert_eq!(candidates.len(), 2);
+    assert_eq!(candidates[1].gender, 0);
+    assert_eq!(errors.iter().map(|error| error.row).collect::<Vec<_>>(), vec![5, 6, 7]);
+
+    // Male and female #1 only collide across the event, or with an existing candidate
+    let (_, errors) = registration::parse_candidate_rows(&rows[..3], &[], PerEvent).unwrap();
+
+    assert_eq!(errors.iter().map(|error| error.row).collect::<Vec<_>>(), vec![2, 3]);
+
+    let (_, errors) = registration::parse_candidate_rows(&rows[..3], &[(0, 1)], PerDivision).unwrap();
+
+    assert_eq!(errors.iter().map(|error| error.row).collect::<Vec<_>>(), vec![3]);
+
+    assert!(registration::parse_candidate_rows(&rows[..1], &[], PerEvent).unwrap().0.is_empty());
+    assert!(registration::parse_candidate_rows(&[vec!["Name".to_string()]], &[], PerEvent).is_err());
+}
+
+#[test]
+pub fn announcement_order_test() {
+    let winner = |award: &str, division: &str, candidate_number: i32| history::AwardWinner {
+        award: award.to_string(),
+        division: division.to_string(),
+        candidate_id: uuid::Uuid::nil(),
+        candidate_number,
+        first_name: "Juan".to_string(),
+        middle_name: " ".to_string(),
+        last_name: "Dela Cruz".to_string(),
+        final_score: 92.5,
+    };
+
+    // As `pick_award_winners` lists them, male placements first
+    let winners = vec![
+        winner("Winner", "male", 1),
+        winner("1st Runner-up", "male", 2),
+        winner("Winner", "female", 3),
+        winner("1st Runner-up", "female", 4),
+    ];
+
+    let order: Vec<i32> = announcement::order_placements(winners)
+        .iter()
+        .map(|winner| winner.candidate_number)
+        .collect();
+
+    assert_eq!(order, vec![2, 4, 1, 3]);
+    assert_eq!(
+        announcement::announcement_line(&winner("Winner", "male", 7), 2),
+        "Candidate #7, Juan Dela Cruz, with a score of 92.50"
+    );
+}
+
+#[test]
+pub fn criteria_validation_test() {
+    let options = vec![criteria::SelectionOption {
+        label: "Excellent".to_string(),
+        points: 10,
+    }];
+
+    assert!(criteria::validate_criteria("numeric", 10, None, false, 0).is_ok());
+    assert!(criteria::validate_criteria("numeric", 0, None, false, 0).is_err());
+    assert!(criteria::validate_criteria("rating", 10, None, false, 0).is_err());
+    assert!(criteria::validate_criteria("selection", 10, None, false, 0).is_err());
+    assert!(criteria::validate_criteria("selection", 10, Some(&options), false, 0).is_ok());
+
+    // Deductions have no max score, only a negative minimum
+    assert!(criteria::validate_criteria("numeric", 0, None, true, -5).is_ok());
+    assert!(criteria::validate_criteria("numeric", 0, None, true, 0).is_err());
+}
+
+#[test]
+pub fn score_review_test() {
+    let row = |candidate: u128, score: i32, max: i32| confirmation::ReviewRow {
+        candidate_id: uuid::Uuid::from_u128(candidate),
+        candidate_number: candidate as i32,
+        first_name: String::new(),
+        last_name: String::new(),
+        gender: 1,
+        criteria_id: uuid::Uuid::nil(),
+        criteria_name: String::new(),
+        score,
+        max,
+        remark: None,
+        time_of_scoring: chrono::Utc::now(),
+    };
+
+    let candidates = confirmation::group_review_rows(vec![row(1, 8, 10), row(1, 15, 20), row(2, 5, 10)]);
+
+    assert_eq!(candidates.len(), 2);
+    assert_eq!(candidates[0].scores.len(), 2);
+    assert_eq!((candidates[0].total, candidates[0].max), (23, 30));
+    assert_eq!((candidates[1].total, candidates[1].max), (5, 10));
+    assert!(confirmation::group_review_rows(Vec::new()).is_empty());
+}
+
+#[test]
+pub fn category_edit_test() {
+    assert!(category::validate_category_edit(Some("Talent"), Some(0.3)).is_ok());
+    assert!(category::validate_category_edit(None, None).is_ok());
+    assert!(category::validate_category_edit(Some("  "), None).is_err());
+    assert!(category::validate_category_edit(None, Some(0.0)).is_err());
+    assert!(category::validate_category_edit(None, Some(f32::NAN)).is_err());
+}
+
+// Needs a migrated database, e.g. `TEST_DATABASE_URL=postgres://... cargo test`, and is skipped without one
+#[tokio::test]
+pub async fn judge_score_policies_test() {
+    let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+        return;
+    };
+
+    let pool = sqlx::PgPool::connect(&url).await.unwrap();
+    let mut tx = pool.begin().await.unwrap();
+
+    let event_id: uuid::Uuid =
+        sqlx::query_scalar("INSERT INTO events (name) VALUES ('RLS test') RETURNING id")
+            .fetch_one(&mut *tx)
+            .await
+            .unwrap();
+    let category_id: uuid::Uuid = sqlx::query_scalar(
+        "INSERT INTO categories (name, weight, event_id) VALUES ('Talent', 1, ($1)) RETURNING id",
+    )
+    .bind(&event_id)
+    .fetch_one(&mut *tx)
+    .await
+    .unwrap();
+    let criteria_id: uuid::Uuid = sqlx::query_scalar(
+        "INSERT INTO criterias (name, max_score, category_id) VALUES ('Poise', 10, ($1)) RETURNING id",
+    )
+    .bind(&category_id)
+    .fetch_one(&mut *tx)
+    .await
+    .unwrap();
+    let candidate_id: uuid::Uuid = sqlx::query_scalar(
+        r#"
+        INSERT INTO candidates
+            (first_name, middle_name, last_name, gender, college_id, candidate_number, category_id)
+        VALUES ('Ana', '', 'Cruz', 0, 'CAS', 1, ($1)) RETURNING id
+        "#,
+    )
+    .bind(&category_id)
+    .fetch_one(&mut *tx)
+    .await
+    .unwrap();
+
+    let mut judge_ids = Vec::new();
+    for username in ["rls_judge_a", "rls_judge_b"] {
+        let judge_id: uuid::Uuid = sqlx::query_scalar(
+            "INSERT INTO judges (name, username, password, event_id) VALUES (($1), ($1), '', ($2)) RETURNING id",
+        )
+        .bind(username)
+        .bind(&event_id)
+        .fetch_one(&mut *tx)
+        .await
+        .unwrap();
+        judge_ids.push(judge_id);
+    }
+
+    sqlx::query("CREATE ROLE rls_test_tablet IN ROLE tabulation_judge")
+        .execute(&mut *tx)
+        .await
+        .unwrap();
+    sqlx::query("INSERT INTO judge_db_logins (rolname, judge_id) VALUES ('rls_test_tablet', ($1))")
+        .bind(&judge_ids[0])
+        .execute(&mut *tx)
+        .await
+        .unwrap();
+    sqlx::query("SET LOCAL ROLE rls_test_tablet")
+        .execute(&mut *tx)
+        .await
+        .unwrap();
+
+    let insert_score = |judge_id: uuid::Uuid| {
+        sqlx::query(
+            r#"
+            INSERT INTO scores (score, max, candidate_id, criteria_id, category_id, judge_id)
+            VALUES (8, 10, ($1), ($2), ($3), ($4))
+            "#,
+        )
+        .bind(candidate_id)
+        .bind(criteria_id)
+        .bind(category_id)
+        .bind(judge_id)
+    };
+
+    // Setting the old session variable doesn't get around the mapping
+    sqlx::query(&format!("SET LOCAL app.judge_id = '{}'", judge_ids[1]))
+        .execute(&mut *tx)
+        .await
+        .unwrap();
+    sqlx::query("SAVEPOINT other_judge").execute(&mut *tx).await.unwrap();
+    assert!(insert_score(judge_ids[1]).execute(&mut *tx).await.is_err());
+    sqlx::query("ROLLBACK TO SAVEPOINT other_judge")
+        .execute(&mut *tx)
+        .await
+        .unwrap();
+
+    insert_score(judge_ids[0]).execute(&mut *tx).await.unwrap();
+    assert!(sqlx::query("SELECT password FROM judges")
+        .fetch_all(&mut *tx)
+        .await
+        .is_err());
+
+    tx.rollback().await.unwrap();
+}
+
+// Needs a migrated database like `judge_score_policies_test`
+#[tokio::test]
+pub async fn void_scores_test() {
+    let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+        return;
+    };
+
+    let pool = sqlx::PgPool::connect(&url).await.unwrap();
+    let mut tx = pool.begin().await.unwrap();
+
+    let event_id: uuid::Uuid =
+        sqlx::query_scalar("INSERT INTO events (name) VALUES ('Void test') RETURNING id")
+            .fetch_one(&mut *tx)
+            .await
+            .unwrap();
+    let category_id: uuid::Uuid = sqlx::query_scalar(
+        "INSERT INTO categories (name, weight, event_id) VALUES ('Talent', 1, ($1)) RETURNING id",
+    )
+    .bind(&event_id)
+    .fetch_one(&mut *tx)
+    .await
+    .unwrap();
+    let criteria_id: uuid::Uuid = sqlx::query_scalar(
+        "INSERT INTO criterias (name, max_score, category_id) VALUES ('Poise', 10, ($1)) RETURNING id",
+    )
+    .bind(&category_id)
+    .fetch_one(&mut *tx)
+    .await
+    .unwrap();
+    let judge_id: uuid::Uuid = sqlx::query_scalar(
+        "INSERT INTO judges (name, username, password, event_id) VALUES ('void_judge', 'void_judge', '', ($1)) RETURNING id",
+    )
+    .bind(&event_id)
+    .fetch_one(&mut *tx)
+    .await
+    .unwrap();
+
+    let mut candidate_ids = Vec::new();
+    let mut score_ids = Vec::new();
+    for number in 1..=2 {
+        let candidate_id: uuid::Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO candidates
+                (first_name, middle_name, last_name, gender, college_id, candidate_number, category_id)
+            VALUES ('Ana', '', 'Cruz', 0, 'CAS', ($1), ($2)) RETURNING id
+            "#,
+        )
+        .bind(number)
+        .bind(&category_id)
+        .fetch_one(&mut *tx)
+        .await
+        .unwrap();
+        let score_id: uuid::Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO scores (score, max, candidate_id, criteria_id, category_id, judge_id)
+            VALUES (8, 10, ($1), ($2), ($3), ($4)) RETURNING id
+            "#,
+        )
+        .bind(&candidate_id)
+        .bind(&criteria_id)
+        .bind(&category_id)
+        .bind(&judge_id)
+        .fetch_one(&mut *tx)
+        .await
+        .unwrap();
+        candidate_ids.push(candidate_id);
+        score_ids.push(score_id);
+    }
+
+    sqlx::query(
+        "INSERT INTO score_correction_requests (requested_score, reason, score_id, judge_id) VALUES (9, 'Typo', ($1), ($2))",
+    )
+    .bind(&score_ids[0])
+    .bind(&judge_id)
+    .execute(&mut *tx)
+    .await
+    .unwrap();
+
+    let voided = score::void_scores(
+        &mut *tx,
+        score::VoidedScores::Candidate(candidate_ids[0]),
+        "Candidate deleted",
+    )
+    .await
+    .unwrap();
+    assert_eq!(voided, 1);
+
+    let status: String =
+        sqlx::query_scalar("SELECT status FROM score_correction_requests WHERE score_id = ($1)")
+            .bind(&score_ids[0])
+            .fetch_one(&mut *tx)
+            .await
+            .unwrap();
+    assert_eq!(status, "rejected");
+
+    let reason: String =
+        sqlx::query_scalar("SELECT void_reason FROM voided_scores WHERE id = ($1)")
+            .bind(&score_ids[0])
+            .fetch_one(&mut *tx)
+            .await
+            .unwrap();
+    assert_eq!(reason, "Candidate deleted");
+
+    let changes: Vec<(String, Option<String>)> = sqlx::query_as(
+        "SELECT change, void_reason FROM score_changes WHERE score_id = ($1) ORDER BY seq",
+    )
+    .bind(&score_ids[0])
+    .fetch_all(&mut *tx)
+    .await
+    .unwrap();
+    assert_eq!(
+        changes,
+        vec![
+            ("upsert".to_string(), None),
+            ("void".to_string(), Some("Candidate deleted".to_string()))
+        ]
+    );
+
+    // The other candidate's score is left alone until it's voided on its own
+    let remaining: Vec<uuid::Uuid> =
+        sqlx::query_scalar("SELECT id FROM scores WHERE category_id = ($1)")
+            .bind(&category_id)
+            .fetch_all(&mut *tx)
+            .await
+            .unwrap();
+    assert_eq!(remaining, vec![score_ids[1]]);
+
+    let voided = score::void_scores(
+        &mut *tx,
+        score::VoidedScores::JudgeCandidate(judge_id, candidate_ids[1]),
+        "Judge blacked out from the candidate",
+    )
+    .await
+    .unwrap();
+    assert_eq!(voided, 1);
+
+    tx.rollback().await.unwrap();
+}
+
+// The claims `restrict_staff_routes` hands to the admin-only handlers
+fn staff_claims() -> axum::Extension<admin::StaffClaims> {
+    axum::Extension(admin::StaffClaims {
+        // The audit log keeps the ID as text, it doesn't have to be an admin's
+        sub: uuid::Uuid::from_u128(1),
+        role: "admin".to_string(),
+        exp: (chrono::Utc::now() + chrono::Duration::hours(1)).timestamp(),
+        org: None,
+    })
+}
+
+// Needs a migrated database like `judge_score_policies_test`, the handlers commit so the events are deleted after
+#[tokio::test]
+pub async fn rounds_test() {
+    use axum::extract::{Path, State};
+    use axum::response::IntoResponse;
+
+    let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+        return;
+    };
+
+    let pool = sqlx::PgPool::connect(&url).await.unwrap();
+
+    let mut event_ids = Vec::new();
+    let mut category_ids = Vec::new();
+    for name in ["Rounds test", "Other rounds test"] {
+        let event_id: uuid::Uuid =
+            sqlx::query_scalar("INSERT INTO events (name) VALUES ($1) RETURNING id")
+                .bind(name)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        event_ids.push(event_id);
+    }
+    for event_id in [event_ids[0], event_ids[0], event_ids[1]] {
+        let category_id: uuid::Uuid = sqlx::query_scalar(
+            "INSERT INTO categories (name, weight, event_id) VALUES ('Talent', 1, ($1)) RETURNING id",
+        )
+        .bind(&event_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        category_ids.push(category_id);
+    }
+
+    let event_id = event_ids[0];
+    let create = |name: &str| {
+        round::create_round(
+            State(pool.clone()),
+            staff_claims(),
+            Path(event_id),
+            axum::Json(serde_json::from_value(serde_json::json!({ "name": name })).unwrap()),
+        )
+    };
+    let update = |round_id: uuid::Uuid, value: serde_json::Value| {
+        round::update_round(
+            State(pool.clone()),
+            staff_claims(),
+            Path((event_id, round_id)),
+            axum::Json(serde_json::from_value(value).unwrap()),
+        )
+    };
+    let set_categories = |round_id: uuid::Uuid, ids: Vec<uuid::Uuid>| {
+        round::set_round_categories(
+            State(pool.clone()),
+            staff_claims(),
+            Path((event_id, round_id)),
+            axum::Json(serde_json::from_value(serde_json::json!({ "category_ids": ids })).unwrap()),
+        )
+    };
+    let round_of = |category_id: uuid::Uuid| {
+        sqlx::query_scalar::<_, Option<uuid::Uuid>>(
+            "SELECT round_id FROM categories WHERE id = ($1)",
+        )
+        .bind(category_id)
+        .fetch_one(&pool)
+    };
+
+    assert!(create("  ").await.is_err());
+
+    // Appended after the event's last round
+    let (_, axum::Json(prelims)) = create("Prelims").await.unwrap();
+    let (_, axum::Json(finals)) = create("Finals").await.unwrap();
+    assert_eq!((prelims.position, finals.position), (1, 2));
+
+    let _ = update(finals.id, serde_json::json!({ "is_active": true }))
+        .await
+        .unwrap();
+    let axum::Json(prelims) = update(prelims.id, serde_json::json!({ "is_active": true }))
+        .await
+        .unwrap();
+    assert!(prelims.is_active);
+
+    let axum::Json(rounds) = round::get_rounds(State(pool.clone()), Path(event_id))
+        .await
+        .unwrap();
+    assert_eq!(
+        rounds
+            .iter()
+            .map(|round| (round.id, round.is_active))
+            .collect::<Vec<_>>(),
+        vec![(prelims.id, true), (finals.id, false)]
+    );
+
+    let axum::Json(set) = set_categories(prelims.id, vec![category_ids[0], category_ids[0]])
+        .await
+        .unwrap();
+    assert_eq!(set, vec![category_ids[0]]);
+
+    // Another event's category is refused and nothing changes
+    let err = set_categories(prelims.id, vec![category_ids[1], category_ids[2]])
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.into_response().status(),
+        axum::http::StatusCode::UNPROCESSABLE_ENTITY
+    );
+    assert_eq!(round_of(category_ids[0]).await.unwrap(), Some(prelims.id));
+    assert_eq!(round_of(category_ids[1]).await.unwrap(), None);
+
+    // Moving a category takes it out of its previous round
+    let _ = set_categories(finals.id, vec![category_ids[0]])
+        .await
+        .unwrap();
+    assert_eq!(round_of(category_ids[0]).await.unwrap(), Some(finals.id));
+
+    // An elimination in the prelims shuts the candidate out of the finals and categories outside of any round
+    let candidate_id: uuid::Uuid = sqlx::query_scalar(
+        r#"
+        INSERT INTO candidates
+            (first_name, middle_name, last_name, gender, college_id, candidate_number, category_id)
+        VALUES ('Ana', '', 'Cruz', 0, 'CAS', 1, ($1)) RETURNING id
+        "#,
+    )
+    .bind(&category_ids[1])
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    sqlx::query("INSERT INTO round_advancements (rank, advanced, round_id, candidate_id) VALUES (2, FALSE, ($1), ($2))")
+        .bind(&prelims.id)
+        .bind(&candidate_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    assert!(
+        advancement::ensure_not_eliminated(&pool, &category_ids[0], &[candidate_id])
+            .await
+            .is_err()
+    );
+    assert!(
+        advancement::ensure_not_eliminated(&pool, &category_ids[1], &[candidate_id])
+            .await
+            .is_err()
+    );
+
+    // The round's categories are kept, outside of any round
+    round::delete_round(
+        State(pool.clone()),
+        staff_claims(),
+        Path((event_id, finals.id)),
+    )
+    .await
+    .unwrap();
+    assert_eq!(round_of(category_ids[0]).await.unwrap(), None);
+    assert!(
+        round::get_round(State(pool.clone()), Path((event_id, finals.id)))
+            .await
+            .is_err()
+    );
+
+    sqlx::query("DELETE FROM candidates WHERE id = ($1)")
+        .bind(&candidate_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    sqlx::query("DELETE FROM events WHERE id = ANY($1)")
+        .bind(&event_ids)
+        .execute(&pool)
+        .await
+        .unwrap();
+}
+
+// Needs a migrated database like `judge_score_policies_test`, the scores have to be committed to sync them
+#[tokio::test]
+pub async fn score_changes_test() {
+    use axum::extract::{Path, Query, State};
+
+    let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+        return;
+    };
+
+    let pool = sqlx::PgPool::connect(&url).await.unwrap();
+
+    let event_id: uuid::Uuid =
+        sqlx::query_scalar("INSERT INTO events (name) VALUES ('Changes test') RETURNING id")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    let category_id: uuid::Uuid = sqlx::query_scalar(
+        "INSERT INTO categories (name, weight, event_id) VALUES ('Talent', 1, ($1)) RETURNING id",
+    )
+    .bind(&event_id)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    let criteria_id: uuid::Uuid = sqlx::query_scalar(
+        "INSERT INTO criterias (name, max_score, category_id) VALUES ('Poise', 10, ($1)) RETURNING id",
+    )
+    .bind(&category_id)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    let judge_id: uuid::Uuid = sqlx::query_scalar(
+        "INSERT INTO judges (name, username, password, event_id) VALUES ('changes_judge', 'changes_judge', '', ($1)) RETURNING id",
+    )
+    .bind(&event_id)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+
+    let mut candidate_ids = Vec::new();
+    for number in 1..=2 {
+        let candidate_id: uuid::Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO candidates
+                (first_name, middle_name, last_name, gender, college_id, candidate_number, category_id)
+            VALUES ('Ana', '', 'Cruz', 0, 'CAS', ($1), ($2)) RETURNING id
+            "#,
+        )
+        .bind(number)
+        .bind(&category_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        candidate_ids.push(candidate_id);
+    }
+
+    let insert_score = |candidate_id: uuid::Uuid| {
+        sqlx::query_scalar::<_, uuid::Uuid>(
+            r#"
+            INSERT INTO scores (score, max, candidate_id, criteria_id, category_id, judge_id)
+            VALUES (8, 10, ($1), ($2), ($3), ($4)) RETURNING id
+            "#,
+        )
+        .bind(candidate_id)
+        .bind(criteria_id)
+        .bind(category_id)
+        .bind(judge_id)
+    };
+    let sync = |since: Option<i64>| {
+        let pool = pool.clone();
+
+        async move {
+            let param = serde_json::from_value(serde_json::json!({ "since": since })).unwrap();
+            let axum::Json(changes) =
+                report::get_score_changes(State(pool), Path(event_id), Query(param))
+                    .await
+                    .unwrap();
+            let changes = serde_json::to_value(changes).unwrap();
+            let kinds = changes["changes"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|change| (change["change"].clone(), change["score_id"].clone()))
+                .collect::<Vec<_>>();
+
+            (kinds, changes["cursor"].as_i64().unwrap())
+        }
+    };
+    // Other tests' open transactions hold the changes back too, until they finish
+    let sync_some = |since: i64| async move {
+        for _ in 0..100 {
+            let (changes, cursor) = sync(Some(since)).await;
+            if !changes.is_empty() {
+                return (changes, cursor);
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+
+        panic!("No changes after {}", since);
+    };
+    let change =
+        |kind: &str, score_id: uuid::Uuid| (serde_json::json!(kind), serde_json::json!(score_id));
+
+    let (changes, cursor) = sync(None).await;
+    assert!(changes.is_empty());
+
+    // Started first but committed last, its score must not be skipped
+    let mut slow = pool.begin().await.unwrap();
+    let slow_score = insert_score(candidate_ids[0])
+        .fetch_one(&mut *slow)
+        .await
+        .unwrap();
+    let fast_score = insert_score(candidate_ids[1])
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+    let (changes, cursor) = sync(Some(cursor)).await;
+    assert!(changes.is_empty());
+
+    slow.commit().await.unwrap();
+
+    let (changes, cursor) = sync_some(cursor).await;
+    assert_eq!(
+        changes,
+        vec![change("upsert", slow_score), change("upsert", fast_score)]
+    );
+
+    // Removed scores come through as tombstones
+    sqlx::query("DELETE FROM scores WHERE id = ($1)")
+        .bind(&fast_score)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let (changes, _) = sync_some(cursor).await;
+    assert_eq!(changes, vec![change("delete", fast_score)]);
+
+    sqlx::query("DELETE FROM scores WHERE category_id = ($1)")
+        .bind(&category_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    sqlx::query("DELETE FROM candidates WHERE id = ANY($1)")
+        .bind(&candidate_ids)
+        .execute(&pool)
+        .await
+        .unwrap();
+    sqlx::query("DELETE FROM events WHERE id = ($1)")
+        .bind(&event_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+}
+
+// Needs a migrated database like `judge_score_policies_test`
+#[tokio::test]
+pub async fn scheduler_claim_test() {
+    let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+        return;
+    };
+
+    let pool = sqlx::PgPool::connect(&url).await.unwrap();
+
+    let event_id: uuid::Uuid =
+        sqlx::query_scalar("INSERT INTO events (name) VALUES ('Scheduler test') RETURNING id")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    let category_id: uuid::Uuid = sqlx::query_scalar(
+        r#"
+        INSERT INTO categories (name, weight, event_id, scheduled_open_at)
+        VALUES ('Talent', 1, ($1), NOW() - INTERVAL '1 minute') RETURNING id
+        "#,
+    )
+    .bind(&event_id)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    let scheduled_open_at = || {
+        sqlx::query_scalar::<_, Option<chrono::DateTime<chrono::Utc>>>(
+            "SELECT scheduled_open_at FROM categories WHERE id = ($1)",
+        )
+        .bind(category_id)
+        .fetch_one(&pool)
+    };
+
+    let original = scheduled_open_at().await.unwrap();
+
+    let claim = || async {
+        crate::scheduler::claim_due(&pool, "scheduled_open_at")
+            .await
+            .unwrap()
+            .into_iter()
+            .find(|category| category.id == category_id)
+    };
+
+    // A failed open puts the schedule back for the next tick
+    let claimed = claim().await.unwrap();
+    assert_eq!(scheduled_open_at().await.unwrap(), None);
+    crate::scheduler::release_claim(&pool, "scheduled_open_at", &claimed).await;
+    assert_eq!(scheduled_open_at().await.unwrap(), original);
+
+    // Unless the tabulator rescheduled it in the meantime
+    let claimed = claim().await.unwrap();
+    let rescheduled = chrono::Utc::now() + chrono::Duration::hours(1);
+    sqlx::query("UPDATE categories SET scheduled_open_at = ($2) WHERE id = ($1)")
+        .bind(&category_id)
+        .bind(&rescheduled)
+        .execute(&pool)
+        .await
+        .unwrap();
+    crate::scheduler::release_claim(&pool, "scheduled_open_at", &claimed).await;
+    assert_eq!(
+        scheduled_open_at().await.unwrap().map(|at| at.timestamp()),
+        Some(rescheduled.timestamp())
+    );
+    assert!(claim().await.is_none());
+
+    sqlx::query("DELETE FROM events WHERE id = ($1)")
+        .bind(&event_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+}
+
+// Needs a migrated database like `judge_score_policies_test`
+#[tokio::test]
+pub async fn judge_username_race_test() {
+    use axum::response::IntoResponse;
+
+    let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+        return;
+    };
+
+    let pool = sqlx::PgPool::connect(&url).await.unwrap();
+
+    let event_id: uuid::Uuid =
+        sqlx::query_scalar("INSERT INTO events (name) VALUES ('Username test') RETURNING id")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+
+    let create = || {
+        let pool = pool.clone();
+        async move {
+            judge::create_judge(
+                axum::extract::State(pool),
+                axum::Json(
+                    serde_json::from_value(serde_json::json!({
+                        "name": "Race judge",
+                        "username": "race_judge",
+                        "password": "secret",
+                        "is_active": true,
+                        "event_id": event_id,
+                    }))
+                    .unwrap(),
+                ),
+            )
+            .await
+        }
+    };
+
+    // Both pass any check made before inserting, only one insert wins
+    let (first, second) = tokio::join!(create(), create());
+    let mut statuses = [first, second].map(|res| match res {
+        Ok((status, _)) => status,
+        Err(err) => err.into_response().status(),
+    });
+    statuses.sort();
+    assert_eq!(
+        statuses,
+        [
+            axum::http::StatusCode::CREATED,
+            axum::http::StatusCode::CONFLICT
+        ]
+    );
+
+    let judges: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM judges WHERE event_id = ($1)")
+        .bind(&event_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(judges, 1);
+
+    sqlx::query("DELETE FROM events WHERE id = ($1)")
+        .bind(&event_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+}
+
+// Needs a migrated database like `judge_score_policies_test`
+#[tokio::test]
+pub async fn export_cancelled_test() {
+    let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+        return;
+    };
+
+    let pool = sqlx::PgPool::connect(&url).await.unwrap();
+
+    let job_id: uuid::Uuid = sqlx::query_scalar(
+        "INSERT INTO export_jobs (kind, status, started_at) VALUES ('csv', 'running', NOW()) RETURNING id",
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+
+    let watch = crate::exports::cancelled(&pool, &job_id);
+    tokio::pin!(watch);
+
+    // Keeps waiting while the job runs
+    let running = tokio::time::timeout(std::time::Duration::from_millis(1500), &mut watch).await;
+    assert!(running.is_err());
+
+    sqlx::query("UPDATE export_jobs SET status = 'cancelled' WHERE id = ($1)")
+        .bind(&job_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let cancelled = tokio::time::timeout(std::time::Duration::from_secs(3), &mut watch).await;
+    assert!(cancelled.is_ok());
+
+    sqlx::query("DELETE FROM export_jobs WHERE id = ($1)")
+        .bind(&job_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+}
+
+// Needs a migrated database like `judge_score_policies_test`
+#[tokio::test]
+pub async fn staff_token_test() {
+    use crate::tenancy::Organization;
+    use axum::http::{Request, StatusCode};
+    use tower::ServiceExt;
+
+    let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+        return;
+    };
+
+    let pool = sqlx::PgPool::connect(&url).await.unwrap();
+    auth::init(&pool).await.unwrap();
+
+    let admin_id: uuid::Uuid = sqlx::query_scalar(
+        "INSERT INTO admins (name, username, password, role) VALUES ('Staff', 'staff_token_admin', '', 'admin') RETURNING id",
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+
+    let token = |org: Option<&str>| {
+        auth::encode_token(
+            auth::token_secret().unwrap(),
+            &admin::StaffClaims {
+                sub: admin_id,
+                role: "admin".to_string(),
+                exp: (chrono::Utc::now() + chrono::Duration::hours(1)).timestamp(),
+                org: org.map(str::to_string),
+            },
+        )
+        .unwrap()
+    };
+
+    let app = axum::Router::new()
+        .route("/judges", axum::routing::post(|| async { "created" }))
+        .layer(axum::middleware::from_fn_with_state(
+            pool.clone(),
+            admin::restrict_staff_routes,
+        ));
+
+    let send = |token: String, org: Option<&str>| {
+        let mut request = Request::post("/judges")
+            .header("authorization", format!("Bearer {}", token))
+            .body(axum::body::Body::empty())
+            .unwrap();
+        if let Some(org) = org {
+            request
+                .extensions_mut()
+                .insert(Organization(org.to_string()));
+        }
+
+        let app = app.clone();
+        async move { app.oneshot(request).await.unwrap().status() }
+    };
+
+    assert_eq!(send(token(None), None).await, StatusCode::OK);
+    assert_eq!(send(token(Some("a")), Some("a")).await, StatusCode::OK);
+
+    // Another organization's token, or one from before schema isolation
+    assert_eq!(
+        send(token(Some("a")), Some("b")).await,
+        StatusCode::UNAUTHORIZED
+    );
+    assert_eq!(send(token(None), Some("a")).await, StatusCode::UNAUTHORIZED);
+
+    // Demoted admins lose admin routes and deleted ones lose everything, tokens or not
+    sqlx::query("UPDATE admins SET role = 'tabulator' WHERE id = ($1)")
+        .bind(&admin_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    assert_eq!(send(token(None), None).await, StatusCode::FORBIDDEN);
+
+    sqlx::query("DELETE FROM admins WHERE id = ($1)")
+        .bind(&admin_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    assert_eq!(send(token(None), None).await, StatusCode::UNAUTHORIZED);
+}
+
+// Needs a migrated database like `judge_score_policies_test`
+#[tokio::test]
+pub async fn committee_password_test() {
+    let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+        return;
+    };
+
+    let pool = sqlx::PgPool::connect(&url).await.unwrap();
+
+    let event_id: uuid::Uuid =
+        sqlx::query_scalar("INSERT INTO events (name) VALUES ('Committee test') RETURNING id")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+
+    let (_, axum::Json(member)) = approval::create_committee_member(
+        axum::extract::State(pool.clone()),
+        axum::extract::Path(event_id),
+        axum::Json(
+            serde_json::from_value(serde_json::json!({
+                "name": "Chair",
+                "username": "committee_chair",
+                "password": "secret",
+            }))
+            .unwrap(),
+        ),
+    )
+    .await
+    .unwrap();
+    assert!(member.password.starts_with("$argon2"));
+
+    // Members added before passwords were hashed
+    sqlx::query(
+        "INSERT INTO committee_members (name, username, password, event_id) VALUES ('Member', 'committee_member', 'plain', ($1))",
+    )
+    .bind(&event_id)
+    .execute(&pool)
+    .await
+    .unwrap();
+    approval::init(&pool).await.unwrap();
+
+    let login = |username: &str, password: &str| {
+        serde_json::from_value::<approval::CommitteeLogin>(serde_json::json!({
+            "username": username,
+            "password": password,
+        }))
+        .unwrap()
+    };
+
+    assert!(
+        approval::authenticate_member(&pool, &login("committee_chair", "secret"))
+            .await
+            .is_ok()
+    );
+    assert!(
+        approval::authenticate_member(&pool, &login("committee_chair", "wrong"))
+            .await
+            .is_err()
+    );
+    assert!(
+        approval::authenticate_member(&pool, &login("committee_member", "plain"))
+            .await
+            .is_ok()
+    );
+
+    sqlx::query("DELETE FROM committee_members WHERE event_id = ($1)")
+        .bind(&event_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    sqlx::query("DELETE FROM events WHERE id = ($1)")
+        .bind(&event_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+}
+
+// An event with one category and a male candidate #1, committed for handlers that take the pool
+async fn seed_event(pool: &sqlx::PgPool, name: &str) -> (uuid::Uuid, uuid::Uuid, uuid::Uuid) {
+    let event_id: uuid::Uuid =
+        sqlx::query_scalar("INSERT INTO events (name) VALUES ($1) RETURNING id")
+            .bind(name)
+            .fetch_one(pool)
+            .await
+            .unwrap();
+    let category_id: uuid::Uuid = sqlx::query_scalar(
+        "INSERT INTO categories (name, weight, event_id) VALUES ('Talent', 1, ($1)) RETURNING id",
+    )
+    .bind(&event_id)
+    .fetch_one(pool)
+    .await
+    .unwrap();
+    let candidate_id: uuid::Uuid = sqlx::query_scalar(
+        r#"
+        INSERT INTO candidates
+            (first_name, middle_name, last_name, gender, college_id, candidate_number, category_id)
+        VALUES ('Juan', '', 'Cruz', 1, 'CAS', 1, ($1)) RETURNING id
+        "#,
+    )
+    .bind(&category_id)
+    .fetch_one(pool)
+    .await
+    .unwrap();
+
+    (event_id, category_id, candidate_id)
+}
+
+// Candidates don't go away with their category's event
+async fn delete_seeded_event(pool: &sqlx::PgPool, event_id: &uuid::Uuid) {
+    let candidate_ids: Vec<uuid::Uuid> = sqlx::query_scalar(
+        "SELECT c.id FROM candidates c JOIN categories cat ON cat.id = c.category_id WHERE cat.event_id = ($1)",
+    )
+    .bind(event_id)
+    .fetch_all(pool)
+    .await
+    .unwrap();
+
+    sqlx::query("DELETE FROM events WHERE id = ($1)")
+        .bind(event_id)
+        .execute(pool)
+        .await
+        .unwrap();
+    sqlx::query("DELETE FROM candidates WHERE id = ANY($1)")
+        .bind(&candidate_ids)
+        .execute(pool)
+        .await
+        .unwrap();
+}
+
+// Needs a migrated database like `judge_score_policies_test`
+#[tokio::test]
+pub async fn round_eligibility_event_test() {
+    use axum::response::IntoResponse;
+
+    let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+        return;
+    };
+
+    let pool = sqlx::PgPool::connect(&url).await.unwrap();
+
+    let (event_id, category_id, candidate_id) = seed_event(&pool, "Eligibility test").await;
+    let (other_event_id, _, _) = seed_event(&pool, "Other eligibility test").await;
+
+    // Before the category opens, the current candidate list of its own event
+    let standings = score::fetch_round_final_scores(&pool, &[category_id])
+        .await
+        .unwrap();
+    let candidate_ids: Vec<uuid::Uuid> = standings.iter().map(|c| c.candidate_id).collect();
+    assert_eq!(candidate_ids, vec![candidate_id]);
+
+    category::snapshot_eligibility(&pool, &category_id)
+        .await
+        .unwrap();
+    let eligible: Vec<uuid::Uuid> =
+        sqlx::query_scalar("SELECT candidate_id FROM round_eligibility WHERE category_id = ($1)")
+            .bind(&category_id)
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+    assert_eq!(eligible, vec![candidate_id]);
+
+    let err = standings::get_round_standings(
+        axum::extract::State(pool.clone()),
+        axum::extract::Path((other_event_id, category_id)),
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        err.into_response().status(),
+        axum::http::StatusCode::NOT_FOUND
+    );
+
+    delete_seeded_event(&pool, &event_id).await;
+    delete_seeded_event(&pool, &other_event_id).await;
+}
+
+// Needs a migrated database like `judge_score_policies_test`
+#[tokio::test]
+pub async fn lineup_event_test() {
+    use axum::extract::{Path, Query, State};
+    use axum::response::IntoResponse;
+
+    let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+        return;
+    };
+
+    let pool = sqlx::PgPool::connect(&url).await.unwrap();
+
+    let (event_id, category_id, candidate_id) = seed_event(&pool, "Lineup test").await;
+    let (other_event_id, _, other_candidate_id) = seed_event(&pool, "Other lineup test").await;
+
+    let staff = staff_claims();
+    let axum::Json(lineup) = lineup::draw_lineup(
+        State(pool.clone()),
+        staff.clone(),
+        Path((event_id, category_id)),
+    )
+    .await
+    .unwrap();
+    let drawn: Vec<serde_json::Value> = lineup
+        .iter()
+        .map(|entry| serde_json::to_value(entry).unwrap()["id"].clone())
+        .collect();
+    assert_eq!(drawn, vec![serde_json::json!(candidate_id)]);
+
+    // The draw is on the record under the admin who made it
+    let actor: String = sqlx::query_scalar(
+        "SELECT actor FROM audit_log WHERE entity_id = ($1) AND action = 'draw_lineup'",
+    )
+    .bind(&category_id)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(actor, staff.sub.to_string());
+
+    let err = lineup::get_lineup(
+        State(pool.clone()),
+        Path((other_event_id, category_id)),
+        Query(serde_json::from_value(serde_json::json!({})).unwrap()),
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        err.into_response().status(),
+        axum::http::StatusCode::NOT_FOUND
+    );
+
+    let err = lineup::update_lineup(
+        State(pool.clone()),
+        staff_claims(),
+        Path((event_id, category_id)),
+        axum::Json(
+            serde_json::from_value(serde_json::json!({
+                "candidate_ids": [candidate_id, other_candidate_id],
+            }))
+            .unwrap(),
+        ),
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        err.into_response().status(),
+        axum::http::StatusCode::UNPROCESSABLE_ENTITY
+    );
+
+    delete_seeded_event(&pool, &event_id).await;
+    delete_seeded_event(&pool, &other_event_id).await;
+}
+
+// Needs a migrated database like `judge_score_policies_test`
+#[tokio::test]
+pub async fn lock_blockers_event_test() {
+    let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+        return;
+    };
+
+    let pool = sqlx::PgPool::connect(&url).await.unwrap();
+
+    let (event_id, category_id, _) = seed_event(&pool, "Blockers test").await;
+    let (other_event_id, _, _) = seed_event(&pool, "Other blockers test").await;
+
+    sqlx::query("INSERT INTO criterias (name, max_score, category_id) VALUES ('Poise', 10, ($1))")
+        .bind(&category_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    sqlx::query(
+        "INSERT INTO judges (name, username, password, event_id) VALUES ('Judge', 'blockers_judge', '', ($1))",
+    )
+    .bind(&event_id)
+    .execute(&pool)
+    .await
+    .unwrap();
+    sqlx::query("UPDATE categories SET quorum = 1 WHERE id = ($1)")
+        .bind(&category_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    // Only the event's own candidate #1 owes a score, the other event's #1 doesn't count
+    let blockers = category::find_lock_blockers(&pool, &event_id, &category_id)
+        .await
+        .unwrap();
+    assert_eq!(blockers[0], "Candidate #1 is missing 1 score(s) from Judge");
+
+    let gaps = quorum::find_quorum_gaps(&pool, &event_id, Some(&category_id))
+        .await
+        .unwrap();
+    assert_eq!(gaps.len(), 1);
+
+    delete_seeded_event(&pool, &event_id).await;
+    delete_seeded_event(&pool, &other_event_id).await;
+}
+
+// Needs a migrated database like `judge_score_policies_test`
+#[tokio::test]
+pub async fn readiness_numbers_event_test() {
+    let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+        return;
+    };
+
+    let pool = sqlx::PgPool::connect(&url).await.unwrap();
+
+    // Both events have a male #1, which is fine since they're separate events
+    let (event_id, _, _) = seed_event(&pool, "Readiness test").await;
+    let (other_event_id, _, _) = seed_event(&pool, "Other readiness test").await;
+
+    let axum::Json(readiness) = readiness::get_event_readiness(
+        axum::extract::State(pool.clone()),
+        axum::extract::Path(event_id),
+    )
+    .await
+    .unwrap();
+    let numbers = serde_json::to_value(&readiness).unwrap()["checks"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|check| check["name"] == "Candidates have numbers")
+        .cloned()
+        .unwrap();
+    assert_eq!(numbers["passed"], true);
+
+    delete_seeded_event(&pool, &event_id).await;
+    delete_seeded_event(&pool, &other_event_id).await;
+}
+
+// Needs a migrated database like `judge_score_policies_test`
+#[tokio::test]
+pub async fn observer_routes_test() {
+    use axum::http::{Request, StatusCode};
+    use tower::ServiceExt;
+
+    let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+        return;
+    };
+
+    let pool = sqlx::PgPool::connect(&url).await.unwrap();
+    auth::init(&pool).await.unwrap();
+
+    let event_id: uuid::Uuid =
+        sqlx::query_scalar("INSERT INTO events (name) VALUES ('Observer test') RETURNING id")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    let token: String = sqlx::query_scalar(
+        r#"
+        WITH observer AS (
+            INSERT INTO observers (name, username, password, event_id)
+            VALUES ('Adviser', 'observer_routes_adviser', '', ($1)) RETURNING id
+        )
+        INSERT INTO observer_sessions (observer_id) SELECT id FROM observer RETURNING token
+        "#,
+    )
+    .bind(&event_id)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+
+    let ok = || async { "ok" };
+    let app = axum::Router::new()
+        .route("/events/:event_id/standings", axum::routing::get(ok))
+        .route("/scores/download", axum::routing::get(ok))
+        .route("/audit", axum::routing::get(ok))
+        .layer(axum::middleware::from_fn_with_state(
+            pool.clone(),
+            admin::restrict_staff_routes,
+        ));
+
+    let send = |path: &str, token: Option<&str>| {
+        let mut request = Request::get(path);
+        if let Some(token) = token {
+            request = request.header("authorization", format!("Bearer {}", token));
+        }
+        let request = request.body(axum::body::Body::empty()).unwrap();
+
+        let app = app.clone();
+        async move { app.oneshot(request).await.unwrap().status() }
+    };
+
+    let standings = format!("/events/{}/standings", event_id);
+
+    assert_eq!(send(&standings, None).await, StatusCode::UNAUTHORIZED);
+    assert_eq!(send(&standings, Some(&token)).await, StatusCode::OK);
+    assert_eq!(send("/scores/download", Some(&token)).await, StatusCode::OK);
+    // Not an export, observer sessions aren't staff tokens
+    assert_eq!(send("/audit", Some(&token)).await, StatusCode::UNAUTHORIZED);
+
+    sqlx::query("DELETE FROM events WHERE id = ($1)")
+        .bind(&event_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+}
+
+// Needs a migrated database like `judge_score_policies_test`
+#[tokio::test]
+pub async fn archived_final_scores_test() {
+    let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+        return;
+    };
+
+    let pool = sqlx::PgPool::connect(&url).await.unwrap();
+    let mut tx = pool.begin().await.unwrap();
+
+    let event_id: uuid::Uuid =
+        sqlx::query_scalar("INSERT INTO events (name) VALUES ('Archive test') RETURNING id")
+            .fetch_one(&mut *tx)
+            .await
+            .unwrap();
+    let category_id: uuid::Uuid = sqlx::query_scalar(
+        "INSERT INTO categories (name, weight, event_id) VALUES ('Talent', 1, ($1)) RETURNING id",
+    )
+    .bind(&event_id)
+    .fetch_one(&mut *tx)
+    .await
+    .unwrap();
+    let criteria_id: uuid::Uuid = sqlx::query_scalar(
+        "INSERT INTO criterias (name, max_score, category_id) VALUES ('Poise', 10, ($1)) RETURNING id",
+    )
+    .bind(&category_id)
+    .fetch_one(&mut *tx)
+    .await
+    .unwrap();
+    let candidate_id: uuid::Uuid = sqlx::query_scalar(
+        r#"
+        INSERT INTO candidates
+            (first_name, middle_name, last_name, gender, college_id, candidate_number, category_id)
+        VALUES ('Ana', '', 'Cruz', 0, 'CAS', 1, ($1)) RETURNING id
+        "#,
+    )
+    .bind(&category_id)
+    .fetch_one(&mut *tx)
+    .await
+    .unwrap();
+    let judge_id: uuid::Uuid = sqlx::query_scalar(
+        "INSERT INTO judges (name, username, password, event_id) VALUES ('Judge', 'archive_test_judge', '', ($1)) RETURNING id",
+    )
+    .bind(&event_id)
+    .fetch_one(&mut *tx)
+    .await
+    .unwrap();
+
+    // The same rows `archive_event` leaves behind
+    sqlx::query(
+        r#"
+        INSERT INTO scores_archive (score, max, candidate_id, criteria_id, category_id, judge_id)
+        VALUES (8, 10, ($1), ($2), ($3), ($4))
+        "#,
+    )
+    .bind(&candidate_id)
+    .bind(&criteria_id)
+    .bind(&category_id)
+    .bind(&judge_id)
+    .execute(&mut *tx)
+    .await
+    .unwrap();
+    sqlx::query("UPDATE events SET is_archived = TRUE WHERE id = ($1)")
+        .bind(&event_id)
+        .execute(&mut *tx)
+        .await
+        .unwrap();
+
+    let standings = score::compute_event_final_scores(&mut *tx, &event_id)
+        .await
+        .unwrap();
+
+    assert_eq!(standings.len(), 1);
+    assert_eq!(standings[0].candidate_id, candidate_id);
+    assert!(standings[0].final_score > 0.0);
+
+    tx.rollback().await.unwrap();
+}
+
+// Needs a migrated database like `judge_score_policies_test`
+#[tokio::test]
+pub async fn bracket_candidates_test() {
+    use axum::extract::State;
+    use axum::response::IntoResponse;
+
+    let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+        return;
+    };
+
+    let pool = sqlx::PgPool::connect(&url).await.unwrap();
+
+    let (event_id, category_id, candidate_id) = seed_event(&pool, "Bracket test").await;
+    let (other_event_id, _, other_candidate_id) = seed_event(&pool, "Other bracket test").await;
+    let second_candidate_id: uuid::Uuid = sqlx::query_scalar(
+        r#"
+        INSERT INTO candidates
+            (first_name, middle_name, last_name, gender, college_id, candidate_number, category_id)
+        VALUES ('Pedro', '', 'Santos', 1, 'CAS', 2, ($1)) RETURNING id
+        "#,
+    )
+    .bind(&category_id)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+
+    let create = |candidate_ids: Vec<uuid::Uuid>| {
+        let payload = serde_json::from_value(serde_json::json!({
+            "name": "Debate",
+            "format": "elimination",
+            "category_id": category_id,
+            "candidate_ids": candidate_ids,
+        }))
+        .unwrap();
+
+        bracket::create_bracket(State(pool.clone()), axum::Json(payload))
+    };
+    let status = |res: Result<_, crate::error::AppError>| match res {
+        Ok(_) => axum::http::StatusCode::CREATED,
+        Err(err) => err.into_response().status(),
+    };
+
+    assert_eq!(
+        status(create(vec![candidate_id, candidate_id]).await),
+        axum::http::StatusCode::UNPROCESSABLE_ENTITY
+    );
+    assert_eq!(
+        status(create(vec![candidate_id, other_candidate_id]).await),
+        axum::http::StatusCode::UNPROCESSABLE_ENTITY
+    );
+    assert_eq!(
+        status(create(vec![candidate_id, second_candidate_id]).await),
+        axum::http::StatusCode::CREATED
+    );
+
+    sqlx::query("DELETE FROM brackets WHERE category_id = ($1)")
+        .bind(&category_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    delete_seeded_event(&pool, &event_id).await;
+    delete_seeded_event(&pool, &other_event_id).await;
+}
+
+// Needs a migrated database like `judge_score_policies_test`
+#[tokio::test]
+pub async fn feedback_report_event_test() {
+    use axum::extract::{Path, Query, State};
+    use axum::response::IntoResponse;
+
+    let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+        return;
+    };
+
+    let pool = sqlx::PgPool::connect(&url).await.unwrap();
+
+    let (event_id, _, candidate_id) = seed_event(&pool, "Feedback test").await;
+    let (other_event_id, _, _) = seed_event(&pool, "Other feedback test").await;
+
+    let report = |event_id: uuid::Uuid| {
+        let param = serde_json::from_value(serde_json::json!({ "event_id": event_id })).unwrap();
+
+        report::generate_feedback_report(State(pool.clone()), Path(candidate_id), Query(param))
+    };
+
+    let (status, _) = report(event_id).await.unwrap();
+    assert_eq!(status, axum::http::StatusCode::OK);
+
+    let err = report(other_event_id).await.unwrap_err();
+    assert_eq!(
+        err.into_response().status(),
+        axum::http::StatusCode::NOT_FOUND
+    );
+
+    delete_seeded_event(&pool, &event_id).await;
+    delete_seeded_event(&pool, &other_event_id).await;
+}
@@ -0,0 +1,275 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use axum::extract::{Extension, Path, State};
+use axum::http;
+use axum::response::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+
+use crate::error::AppError;
+
+use super::admin::StaffClaims;
+use super::audit;
+use super::score::{self, CandidateFinalScore2};
+use super::settings::{self, TieBreakStrategy};
+use super::standings::RankedCandidate;
+
+const TIE_BREAKER_KINDS: [&str; 3] = ["category", "head_judge", "earliest_submission"];
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct TieBreaker {
+    id: uuid::Uuid,
+    // 1 is applied first
+    position: i32,
+    // `category`: higher score in the category wins
+    // `head_judge`: higher total from the head judge wins
+    // `earliest_submission`: whoever was fully scored first wins
+    kind: String,
+    // Relationships
+    event_id: uuid::Uuid,
+    category_id: Option<uuid::Uuid>,
+    judge_id: Option<uuid::Uuid>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NewTieBreaker {
+    kind: String,
+    category_id: Option<uuid::Uuid>,
+    judge_id: Option<uuid::Uuid>,
+}
+
+// Candidate ID -> one value per tie-breaker in order, higher wins
+pub type TieBreakKeys = HashMap<uuid::Uuid, Vec<f64>>;
+
+pub fn validate_tie_breaker(tie_breaker: &NewTieBreaker) -> Result<(), String> {
+    match (
+        tie_breaker.kind.as_str(),
+        tie_breaker.category_id,
+        tie_breaker.judge_id,
+    ) {
+        ("category", Some(_), None) | ("head_judge", None, Some(_)) => Ok(()),
+        ("earliest_submission", None, None) => Ok(()),
+        ("category", _, _) => Err("A category tie-breaker needs only a category_id".to_string()),
+        ("head_judge", _, _) => Err("A head judge tie-breaker needs only a judge_id".to_string()),
+        ("earliest_submission", _, _) => {
+            Err("An earliest submission tie-breaker takes no category_id or judge_id".to_string())
+        }
+        (kind, _, _) => Err(format!(
+            "'{}' is not a tie-breaker, expected one of {}",
+            kind,
+            TIE_BREAKER_KINDS.join(", ")
+        )),
+    }
+}
+
+// Higher values first, a candidate without a value loses to one with
+pub fn compare_keys(a: Option<&Vec<f64>>, b: Option<&Vec<f64>>) -> Ordering {
+    let empty = Vec::new();
+    let (a, b) = (a.unwrap_or(&empty), b.unwrap_or(&empty));
+
+    for idx in 0..a.len().max(b.len()) {
+        let a = a.get(idx).copied().unwrap_or(f64::NEG_INFINITY);
+        let b = b.get(idx).copied().unwrap_or(f64::NEG_INFINITY);
+
+        match b.total_cmp(&a) {
+            Ordering::Equal => continue,
+            ordering => return ordering,
+        }
+    }
+
+    Ordering::Equal
+}
+
+// Exact ties are ordered by the tie-breakers, candidates tied on every one of them still share a rank
+pub fn rank_with_tie_breakers(
+    mut candidates: Vec<CandidateFinalScore2>,
+    keys: &TieBreakKeys,
+) -> Vec<RankedCandidate> {
+    candidates.sort_by(|a, b| {
+        b.final_score
+            .total_cmp(&a.final_score)
+            .then_with(|| compare_keys(keys.get(&a.candidate_id), keys.get(&b.candidate_id)))
+            .then(a.candidate_number.cmp(&b.candidate_number))
+    });
+
+    score::competition_ranks_by(&candidates, |a, b| {
+        a.final_score == b.final_score
+            && compare_keys(keys.get(&a.candidate_id), keys.get(&b.candidate_id)) == Ordering::Equal
+    })
+    .into_iter()
+    .zip(candidates)
+    .map(|(rank, candidate)| RankedCandidate { rank, candidate })
+    .collect()
+}
+
+async fn fetch_tie_breakers(
+    pool: &PgPool,
+    event_id: &uuid::Uuid,
+) -> Result<Vec<TieBreaker>, AppError> {
+    let tie_breakers = sqlx::query_as::<_, TieBreaker>(
+        "SELECT * FROM tie_breakers WHERE event_id = ($1) ORDER BY position",
+    )
+    .bind(event_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(tie_breakers)
+}
+
+// None unless the event breaks ties with its tie-breakers and has any
+pub async fn fetch_tie_break_keys(
+    pool: &PgPool,
+    event_id: &uuid::Uuid,
+) -> Result<Option<TieBreakKeys>, AppError> {
+    let settings = settings::fetch_settings(pool, event_id).await?;
+
+    if settings.tie_break_strategy != TieBreakStrategy::TieBreakers {
+        return Ok(None);
+    }
+
+    let tie_breakers = fetch_tie_breakers(pool, event_id).await?;
+
+    if tie_breakers.is_empty() {
+        return Ok(None);
+    }
+
+    let mut keys = TieBreakKeys::new();
+
+    for (idx, tie_breaker) in tie_breakers.iter().enumerate() {
+        let values = match tie_breaker.kind.as_str() {
+            "category" => {
+                sqlx::query_as::<_, (uuid::Uuid, f64)>(
+                    r#"
+                    SELECT s.candidate_id, SUM(cs.score)
+                    FROM scores s
+                    JOIN curved_scores cs ON cs.id = s.id
+                    JOIN judges j ON j.id = s.judge_id
+                    WHERE s.category_id = ($1) AND s.is_rehearsal = FALSE AND j.score_exclusion = FALSE
+                    GROUP BY s.candidate_id
+                    "#,
+                )
+                .bind(&tie_breaker.category_id)
+                .fetch_all(pool)
+                .await?
+            }
+            "head_judge" => {
+                sqlx::query_as::<_, (uuid::Uuid, f64)>(
+                    r#"
+                    SELECT candidate_id, SUM(score)::FLOAT8
+                    FROM scores
+                    WHERE judge_id = ($1) AND is_rehearsal = FALSE
+                    GROUP BY candidate_id
+                    "#,
+                )
+                .bind(&tie_breaker.judge_id)
+                .fetch_all(pool)
+                .await?
+            }
+            // Negated so that earlier is higher, like the other tie-breakers
+            _ => {
+                sqlx::query_as::<_, (uuid::Uuid, f64)>(
+                    r#"
+                    SELECT s.candidate_id, -EXTRACT(EPOCH FROM MAX(s.time_of_scoring))::FLOAT8
+                    FROM scores s
+                    JOIN categories cat ON cat.id = s.category_id
+                    WHERE cat.event_id = ($1) AND s.is_rehearsal = FALSE
+                    GROUP BY s.candidate_id
+                    "#,
+                )
+                .bind(event_id)
+                .fetch_all(pool)
+                .await?
+            }
+        };
+
+        for (candidate_id, value) in values {
+            let candidate_keys = keys.entry(candidate_id).or_default();
+
+            candidate_keys.resize(idx, f64::NEG_INFINITY);
+            candidate_keys.push(value);
+        }
+    }
+
+    Ok(Some(keys))
+}
+
+pub async fn get_tie_breakers(
+    State(pool): State<PgPool>,
+    Path(event_id): Path<uuid::Uuid>,
+) -> Result<axum::Json<Vec<TieBreaker>>, AppError> {
+    Ok(axum::Json(fetch_tie_breakers(&pool, &event_id).await?))
+}
+
+// Replaces the event's tie-breakers, applied in the order given
+pub async fn set_tie_breakers(
+    State(pool): State<PgPool>,
+    Extension(staff): Extension<StaffClaims>,
+    Path(event_id): Path<uuid::Uuid>,
+    axum::Json(payload): axum::Json<Vec<NewTieBreaker>>,
+) -> Result<axum::Json<Vec<TieBreaker>>, AppError> {
+    for tie_breaker in payload.iter() {
+        validate_tie_breaker(tie_breaker)
+            .map_err(|message| AppError::new(http::StatusCode::UNPROCESSABLE_ENTITY, message))?;
+    }
+
+    let mut txn = pool.begin().await?;
+
+    sqlx::query("DELETE FROM tie_breakers WHERE event_id = ($1)")
+        .bind(&event_id)
+        .execute(&mut *txn)
+        .await?;
+
+    let mut tie_breakers = Vec::with_capacity(payload.len());
+
+    for (idx, tie_breaker) in payload.iter().enumerate() {
+        // The category or head judge has to be part of the same event
+        let tie_breaker = sqlx::query_as::<_, TieBreaker>(
+            r#"
+            INSERT INTO tie_breakers (position, kind, event_id, category_id, judge_id)
+            SELECT ($1), ($2), ($3), ($4), ($5)
+            WHERE (($4)::UUID IS NULL OR EXISTS (SELECT 1 FROM categories WHERE id = ($4) AND event_id = ($3)))
+            AND (($5)::UUID IS NULL OR EXISTS (SELECT 1 FROM judges WHERE id = ($5) AND event_id = ($3)))
+            RETURNING *
+            "#,
+        )
+        .bind(idx as i32 + 1)
+        .bind(&tie_breaker.kind)
+        .bind(&event_id)
+        .bind(&tie_breaker.category_id)
+        .bind(&tie_breaker.judge_id)
+        .fetch_optional(&mut *txn)
+        .await?
+        .ok_or_else(|| {
+            AppError::new(
+                http::StatusCode::UNPROCESSABLE_ENTITY,
+                format!("Tie-breaker #{} refers to another event", idx + 1),
+            )
+        })?;
+
+        tie_breakers.push(tie_breaker);
+    }
+
+    audit::record(
+        &mut *txn,
+        staff.sub,
+        "event",
+        Some(&event_id),
+        "set_tie_breakers",
+        serde_json::json!({
+            "tie_breakers": tie_breakers
+                .iter()
+                .map(|tie_breaker| serde_json::json!({
+                    "kind": tie_breaker.kind,
+                    "category_id": tie_breaker.category_id,
+                    "judge_id": tie_breaker.judge_id,
+                }))
+                .collect::<Vec<_>>(),
+        }),
+    )
+    .await?;
+
+    txn.commit().await?;
+
+    Ok(axum::Json(tie_breakers))
+}
@@ -0,0 +1,204 @@
+use axum::extract::{Extension, Path, State};
+use axum::http;
+use axum::response::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+
+use crate::aggregation::Aggregator;
+use crate::error::AppError;
+
+use super::admin::StaffClaims;
+use super::audit;
+use super::score;
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct PerformanceTiming {
+    started_at: chrono::DateTime<chrono::Utc>,
+    stopped_at: Option<chrono::DateTime<chrono::Utc>>,
+    duration_seconds: Option<i32>,
+    // Relationships
+    candidate_id: uuid::Uuid,
+    category_id: uuid::Uuid,
+}
+
+// Points to deduct for a performance, every started minute over the limit counts
+pub fn overtime_deduction(
+    duration_seconds: i32,
+    time_limit_seconds: i32,
+    deduction_per_minute: f32,
+) -> Option<f32> {
+    let overtime = duration_seconds - time_limit_seconds;
+
+    if overtime <= 0 {
+        return None;
+    }
+
+    let started_minutes = (overtime + 59) / 60;
+
+    Some(started_minutes as f32 * deduction_per_minute)
+}
+
+// Starting again restarts the timer, for false starts
+pub async fn start_timer(
+    State(pool): State<PgPool>,
+    Path((_event_id, category_id, candidate_id)): Path<(uuid::Uuid, uuid::Uuid, uuid::Uuid)>,
+) -> Result<axum::Json<PerformanceTiming>, AppError> {
+    let timing = sqlx::query_as::<_, PerformanceTiming>(
+        r#"
+        INSERT INTO performance_timings (candidate_id, category_id)
+        VALUES ($1, $2)
+        ON CONFLICT (candidate_id, category_id)
+        DO UPDATE SET started_at = NOW(), stopped_at = NULL, duration_seconds = NULL
+        RETURNING *
+        "#,
+    )
+    .bind(&candidate_id)
+    .bind(&category_id)
+    .fetch_one(&pool)
+    .await?;
+
+    Ok(axum::Json(timing))
+}
+
+pub async fn stop_timer(
+    State(pool): State<PgPool>,
+    State(aggregator): State<Aggregator>,
+    Extension(staff): Extension<StaffClaims>,
+    Path((_event_id, category_id, candidate_id)): Path<(uuid::Uuid, uuid::Uuid, uuid::Uuid)>,
+) -> Result<axum::Json<PerformanceTiming>, AppError> {
+    let mut txn = pool.begin().await?;
+
+    let timing = sqlx::query_as::<_, PerformanceTiming>(
+        r#"
+        UPDATE performance_timings
+        SET stopped_at = NOW(), duration_seconds = EXTRACT(EPOCH FROM NOW() - started_at)::INT4
+        WHERE candidate_id = ($1) AND category_id = ($2) AND stopped_at IS NULL
+        RETURNING *
+        "#,
+    )
+    .bind(&candidate_id)
+    .bind(&category_id)
+    .fetch_optional(&mut *txn)
+    .await?
+    .ok_or_else(|| AppError::new(http::StatusCode::CONFLICT, "Timer is not running"))?;
+
+    let (time_limit_seconds, deduction_per_minute) =
+        sqlx::query_as::<_, (Option<i32>, Option<f32>)>(
+            "SELECT time_limit_seconds, overtime_deduction FROM categories WHERE id = ($1)",
+        )
+        .bind(&category_id)
+        .fetch_one(&mut *txn)
+        .await?;
+
+    // A restarted performance replaces the overtime deduction of the previous attempt
+    sqlx::query(
+        "DELETE FROM deductions WHERE candidate_id = ($1) AND category_id = ($2) AND source = 'overtime'",
+    )
+    .bind(&candidate_id)
+    .bind(&category_id)
+    .execute(&mut *txn)
+    .await?;
+
+    if let (Some(duration_seconds), Some(time_limit_seconds), Some(deduction_per_minute)) = (
+        timing.duration_seconds,
+        time_limit_seconds,
+        deduction_per_minute,
+    ) {
+        if let Some(points) =
+            overtime_deduction(duration_seconds, time_limit_seconds, deduction_per_minute)
+        {
+            sqlx::query(
+                r#"
+                INSERT INTO deductions (points, reason, source, candidate_id, category_id)
+                VALUES ($1, $2, 'overtime', $3, $4)
+                "#,
+            )
+            .bind(points)
+            .bind(format!(
+                "Overtime: {}s over the {}s limit",
+                duration_seconds - time_limit_seconds,
+                time_limit_seconds
+            ))
+            .bind(&candidate_id)
+            .bind(&category_id)
+            .execute(&mut *txn)
+            .await?;
+
+            audit::record(
+                &mut *txn,
+                staff.sub,
+                "deduction",
+                None,
+                "overtime",
+                serde_json::json!({
+                    "candidate_id": candidate_id,
+                    "category_id": category_id,
+                    "duration_seconds": duration_seconds,
+                    "points": points,
+                }),
+            )
+            .await?;
+        }
+    }
+
+    txn.commit().await?;
+
+    score::request_recompute(&pool, &aggregator, &category_id).await?;
+
+    Ok(axum::Json(timing))
+}
+
+pub async fn get_timings(
+    State(pool): State<PgPool>,
+    Path(event_id): Path<uuid::Uuid>,
+) -> Result<axum::Json<Vec<PerformanceTiming>>, AppError> {
+    let timings = sqlx::query_as::<_, PerformanceTiming>(
+        r#"
+        SELECT t.* FROM performance_timings t
+        JOIN categories cat ON cat.id = t.category_id
+        WHERE cat.event_id = ($1)
+        ORDER BY t.started_at
+        "#,
+    )
+    .bind(&event_id)
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(axum::Json(timings))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateTimeLimit {
+    // None removes the limit
+    time_limit_seconds: Option<i32>,
+    // Points per started minute over the limit, None only records the time
+    overtime_deduction: Option<f32>,
+}
+
+pub async fn set_time_limit(
+    State(pool): State<PgPool>,
+    Path((event_id, category_id)): Path<(uuid::Uuid, uuid::Uuid)>,
+    axum::Json(payload): axum::Json<UpdateTimeLimit>,
+) -> Result<http::StatusCode, AppError> {
+    let res = sqlx::query(
+        r#"
+        UPDATE categories SET time_limit_seconds = ($1), overtime_deduction = ($2)
+        WHERE id = ($3) AND event_id = ($4)
+        "#,
+    )
+    .bind(&payload.time_limit_seconds)
+    .bind(&payload.overtime_deduction)
+    .bind(&category_id)
+    .bind(&event_id)
+    .execute(&pool)
+    .await?;
+
+    if res.rows_affected() == 0 {
+        return Err(AppError::new(
+            http::StatusCode::NOT_FOUND,
+            "Category not found",
+        ));
+    }
+
+    Ok(http::StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,19 @@
+// Shared by the HTTP server and the `tabulate` CLI
+// Query parameters are bound by reference throughout, e.g. `.bind(&event_id)`
+#![allow(clippy::needless_borrows_for_generic_args)]
+
+pub mod aggregation;
+pub mod error;
+pub mod event_lock;
+pub mod exports;
+pub mod field_crypto;
+pub mod handlers;
+pub mod pdf;
+pub mod reporting;
+pub mod request_log;
+pub mod scheduler;
+pub mod score_batcher;
+pub mod session_reaper;
+pub mod tabulation;
+pub mod tenancy;
+pub mod watchdog;
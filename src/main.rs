@@ -6,24 +6,99 @@ use anyhow::Context;
 use axum::{
     extract::{
         ws::{Message, WebSocket, WebSocketUpgrade},
-        State,
+        DefaultBodyLimit, FromRef, Query, Request, State,
     },
     http,
-    response::Response,
-    routing::{get, post},
+    response::{IntoResponse, Response},
+    routing::{delete, get, post, put},
     Router,
 };
 use dotenv::dotenv;
 use futures::{sink::SinkExt, stream::StreamExt};
 use sqlx::postgres::PgListener;
+use sqlx::PgPool;
+use std::collections::HashMap;
 use std::env;
-use tokio::{net::TcpListener, sync::broadcast};
+use std::sync::Arc;
+use tokio::{
+    net::TcpListener,
+    sync::{broadcast, Mutex, OnceCell},
+};
+use tower::ServiceExt;
 use tower_http::cors::CorsLayer;
 
-mod error;
-mod handlers;
+use mmu_tabulation_backend::{
+    aggregation, error, exports, field_crypto, handlers, reporting, request_log, scheduler,
+    score_batcher, session_reaper, tenancy, watchdog,
+};
+
+use handlers::{
+    admin, advancement, analytics, announcement, approval, archive, audit, auth, blackout, bracket,
+    candidate, category, college, compact, confirmation, correction, cover, criteria, deduction,
+    device, event, export, group, history, impersonation, integrity, judge, legacy, lineup, note,
+    observer, public, quorum, readiness, realtime, receipt, recheck, registration, report, round,
+    score, score_feed, settings, standings, tie_break, timing,
+};
+
+#[derive(Clone)]
+pub struct AppState {
+    pub pool: PgPool,
+    pub tx: broadcast::Sender<String>,
+    pub aggregator: aggregation::Aggregator,
+    pub exports: exports::ExportQueue,
+    pub realtime_clients: realtime::ClientRegistry,
+    pub score_batcher: score_batcher::ScoreBatcher,
+    pub compact_standings: compact::CompactStandingsCache,
+    pub score_feed: score_feed::ScoreFeed,
+}
+
+impl FromRef<AppState> for PgPool {
+    fn from_ref(state: &AppState) -> Self {
+        state.pool.clone()
+    }
+}
 
-use handlers::{auth, candidate, category, college, criteria, event, judge, note, score};
+impl FromRef<AppState> for broadcast::Sender<String> {
+    fn from_ref(state: &AppState) -> Self {
+        state.tx.clone()
+    }
+}
+
+impl FromRef<AppState> for aggregation::Aggregator {
+    fn from_ref(state: &AppState) -> Self {
+        state.aggregator.clone()
+    }
+}
+
+impl FromRef<AppState> for exports::ExportQueue {
+    fn from_ref(state: &AppState) -> Self {
+        state.exports.clone()
+    }
+}
+
+impl FromRef<AppState> for realtime::ClientRegistry {
+    fn from_ref(state: &AppState) -> Self {
+        state.realtime_clients.clone()
+    }
+}
+
+impl FromRef<AppState> for score_batcher::ScoreBatcher {
+    fn from_ref(state: &AppState) -> Self {
+        state.score_batcher.clone()
+    }
+}
+
+impl FromRef<AppState> for compact::CompactStandingsCache {
+    fn from_ref(state: &AppState) -> Self {
+        state.compact_standings.clone()
+    }
+}
+
+impl FromRef<AppState> for score_feed::ScoreFeed {
+    fn from_ref(state: &AppState) -> Self {
+        state.score_feed.clone()
+    }
+}
 
 #[tokio::main]
 async fn main() -> anyhow::Result<(), anyhow::Error> {
@@ -39,6 +114,19 @@ async fn main() -> anyhow::Result<(), anyhow::Error> {
         .connect(&db_url)
         .await?;
 
+    sqlx::migrate!().run(&pool).await?;
+
+    receipt::init(&pool).await?;
+    auth::init(&pool).await?;
+    admin::init(&pool).await?;
+    observer::init(&pool).await?;
+    approval::init(&pool).await?;
+    field_crypto::init().map_err(anyhow::Error::msg)?;
+
+    if env::var("ERROR_REPORTING").is_ok_and(|value| value == "true") {
+        reporting::init(pool.clone());
+    }
+
     let mut pg_listener = PgListener::connect_with(&pool).await?;
 
     pg_listener.listen_all(vec!["updates"]).await?;
@@ -47,17 +135,292 @@ async fn main() -> anyhow::Result<(), anyhow::Error> {
 
     db_ws_listen(pg_listener, tx.clone());
 
-    let app = Router::new()
+    let app = if tenancy::schema_isolation_enabled() {
+        println!("Routing organizations to their own schemas\n");
+
+        Router::new()
+            .fallback(route_organization)
+            .with_state(OrganizationRouters {
+                db_url: db_url.clone(),
+                allowed: Arc::new(tenancy::allowed_organizations()),
+                routers: Default::default(),
+            })
+    } else {
+        let (app_state, watchdog) = start_services(pool, tx).await?;
+
+        app(app_state, watchdog)
+    };
+
+    // For local development (NOT EXPOSURE TO THE NETWORK) it must be [127.0.0.1]
+    let listener = TcpListener::bind(format!("{}:8000", ip_addr)).await?;
+
+    println!(
+        "Server has started, listening on: {:?}\n",
+        listener.local_addr()?
+    );
+
+    axum::serve(listener, app.into_make_service()).await?;
+
+    Ok(())
+}
+
+// Background workers of one database (or one organization's schema)
+async fn start_services(
+    pool: PgPool,
+    tx: broadcast::Sender<String>,
+) -> anyhow::Result<(AppState, watchdog::Watchdog)> {
+    let aggregator = aggregation::spawn_worker(pool.clone(), tx.clone());
+    let watchdog = watchdog::spawn_watchdog(pool.clone(), tx.clone());
+    scheduler::spawn_scheduler(pool.clone(), tx.clone());
+    session_reaper::spawn_session_reaper(pool.clone(), tx.clone());
+
+    exports::requeue_interrupted(&pool).await?;
+    let exports = exports::spawn_workers(pool.clone());
+    let score_batcher = score_batcher::spawn_batcher(pool.clone());
+
+    let app_state = AppState {
+        pool,
+        tx,
+        aggregator,
+        exports,
+        realtime_clients: realtime::ClientRegistry::default(),
+        score_batcher,
+        compact_standings: compact::CompactStandingsCache::default(),
+        score_feed: score_feed::ScoreFeed::default(),
+    };
+
+    Ok((app_state, watchdog))
+}
+
+#[derive(Clone)]
+struct OrganizationRouters {
+    db_url: String,
+    allowed: Arc<Vec<String>>,
+    // Only held to find an organization's cell, the cell itself makes sure its migrations run once
+    routers: Arc<Mutex<HashMap<String, Arc<OnceCell<Router>>>>>,
+}
+
+// Each organization gets its own pool, workers and realtime channel, built on its first request
+async fn route_organization(
+    State(organizations): State<OrganizationRouters>,
+    mut request: Request,
+) -> Result<Response, error::AppError> {
+    let organization = tenancy::request_organization(&request)?;
+    let schema = tenancy::schema_name(&organization)?;
+    tenancy::check_allowed(&organization, &organizations.allowed)?;
+
+    // Staff tokens are tied to the organization they were issued in
+    request
+        .extensions_mut()
+        .insert(tenancy::Organization(organization.clone()));
+
+    let cell = organizations
+        .routers
+        .lock()
+        .await
+        .entry(schema.clone())
+        .or_default()
+        .clone();
+
+    // Other organizations keep being served while this one is set up, a failed setup is retried next request
+    let router = cell
+        .get_or_try_init(|| async {
+            let pool = tenancy::connect(&organizations.db_url, &schema).await?;
+            auth::init(&pool).await?;
+            admin::init(&pool).await?;
+            observer::init(&pool).await?;
+            approval::init(&pool).await?;
+
+            let (tx, _rx) = broadcast::channel(50);
+            let (app_state, watchdog) = start_services(pool, tx).await?;
+
+            println!("Organization {} is ready\n", organization);
+
+            Ok::<_, error::AppError>(app(app_state, watchdog))
+        })
+        .await?
+        .clone();
+
+    Ok(router.oneshot(request).await.into_response())
+}
+
+fn app(app_state: AppState, watchdog: watchdog::Watchdog) -> Router {
+    Router::new()
         // WebSocket
         .route("/ws", get(ws_handler))
-        .with_state(tx)
+        .route("/ws/leaderboard/:event_id", get(realtime::leaderboard_ws))
         .route("/", get(health))
+        .route("/admin/realtime/status", get(realtime::get_realtime_status))
         // Auth
         .route("/login", post(auth::login))
+        .route("/admins", post(admin::create_admin))
+        .route("/admins/login", post(admin::admin_login))
         .route("/logout", post(auth::logout))
+        .route("/login/pin", post(auth::pin_login))
+        .route("/observers/login", post(observer::observer_login))
+        .route(
+            "/events/:event_id/observers",
+            post(observer::create_observer).get(observer::get_observers),
+        )
+        .route(
+            "/events/:event_id/scoring-devices",
+            post(device::create_scoring_device).get(device::get_scoring_devices),
+        )
+        .route(
+            "/events/:event_id/scoring-devices/:id",
+            delete(device::delete_scoring_device),
+        )
+        .route(
+            "/sessions/current",
+            get(auth::get_session).delete(auth::end_session),
+        )
+        .route("/sessions/current/heartbeat", post(auth::heartbeat))
+        .route(
+            "/events/:event_id/judges/presence",
+            get(auth::get_judge_presence),
+        )
+        .route(
+            "/events/:event_id/judges/:judge_id/ranking",
+            get(standings::get_judge_ranking),
+        )
+        .route(
+            "/events/:event_id/judges/pins",
+            post(auth::generate_judge_pins),
+        )
         // Events
         .route("/events", post(event::create_event).get(event::get_events))
-        .route("/events/:event_id", get(event::get_event))
+        .route(
+            "/events/:event_id",
+            get(event::get_event).patch(event::update_event),
+        )
+        .route(
+            "/events/:event_id/settings",
+            get(settings::get_event_settings).patch(settings::update_event_settings),
+        )
+        .route(
+            "/events/:event_id/client-config",
+            get(settings::get_client_config),
+        )
+        .route(
+            "/events/:event_id/number-cards",
+            get(candidate::get_number_cards),
+        )
+        .route(
+            "/events/:event_id/scores/changes",
+            get(report::get_score_changes),
+        )
+        .route("/events/:event_id/archive", post(archive::archive_event))
+        .route(
+            "/imports/legacy",
+            // Old tabulation files with every judge's sheet can get big
+            post(legacy::import_legacy_results).layer(DefaultBodyLimit::max(20 * 1024 * 1024)),
+        )
+        .route(
+            "/events/:event_id/rehearsal-scores",
+            delete(event::purge_rehearsal_scores),
+        )
+        .route(
+            "/events/:event_id/announcements",
+            post(announcement::create_announcement).get(announcement::get_announcements),
+        )
+        .route(
+            "/events/:event_id/announcement-script",
+            get(announcement::get_announcement_script),
+        )
+        // Performance groups
+        .route(
+            "/events/:event_id/groups",
+            post(group::create_group).get(group::get_groups),
+        )
+        .route(
+            "/events/:event_id/groups/standings",
+            get(group::get_group_standings),
+        )
+        .route("/groups/:group_id/scores", post(group::submit_group_score))
+        // Score corrections
+        .route("/scores/:score_id/override", post(score::override_score))
+        .route(
+            "/scores/:score_id/correction-request",
+            post(correction::create_correction_request),
+        )
+        .route(
+            "/correction-requests",
+            get(correction::get_correction_requests),
+        )
+        .route(
+            "/correction-requests/:request_id/approve",
+            post(correction::approve_correction_request),
+        )
+        .route(
+            "/correction-requests/:request_id/reject",
+            post(correction::reject_correction_request),
+        )
+        // Certified results of past events
+        .route("/results/history", get(history::get_results_history))
+        .route("/results/history/years", get(history::get_results_years))
+        // Committee approvals
+        .route(
+            "/events/:event_id/committee",
+            post(approval::create_committee_member).get(approval::get_committee_members),
+        )
+        .route(
+            "/events/:event_id/committee/roster",
+            get(approval::get_committee_roster),
+        )
+        .route(
+            "/events/:event_id/report/cover.pdf",
+            get(cover::generate_cover_pdf),
+        )
+        .route("/committee/login", post(approval::committee_login))
+        .route("/events/:event_id/finalize", post(approval::finalize_event))
+        .route("/events/:event_id/approvals", post(approval::approve_results))
+        .route("/events/:event_id/rechecks", get(recheck::get_rechecks))
+        .route(
+            "/events/:event_id/rechecks/:recheck_id/acknowledge",
+            post(recheck::acknowledge_recheck),
+        )
+        .route("/events/:event_id/results", get(approval::get_event_results))
+        .route(
+            "/events/:event_id/public-token",
+            get(public::get_public_token).post(public::rotate_public_token),
+        )
+        .route(
+            "/public/events/:token/results.json",
+            get(public::get_public_results),
+        )
+        .route(
+            "/events/:event_id/integrity",
+            get(integrity::get_event_integrity),
+        )
+        .route(
+            "/events/:event_id/readiness",
+            get(readiness::get_event_readiness),
+        )
+        .route(
+            "/events/:event_id/standings",
+            get(standings::get_event_standings),
+        )
+        .route("/events/:event_id/rankings", get(score::get_rankings))
+        .route(
+            "/events/:event_id/tie-breakers",
+            get(tie_break::get_tie_breakers).put(tie_break::set_tie_breakers),
+        )
+        .route(
+            "/events/:event_id/standings/compact",
+            get(compact::get_compact_standings),
+        )
+        .route(
+            "/events/:event_id/standings/history",
+            get(standings::get_standings_history),
+        )
+        .route(
+            "/events/:event_id/standings/diff",
+            get(standings::get_standings_diff),
+        )
+        .route(
+            "/events/:event_id/standings/verify",
+            get(standings::verify_standings_chain),
+        )
         // Categories
         .route(
             "/events/:event_id/categories",
@@ -65,7 +428,101 @@ async fn main() -> anyhow::Result<(), anyhow::Error> {
         )
         .route(
             "/events/:event_id/categories/:category_id",
-            get(category::get_category),
+            get(category::get_category)
+                .patch(category::edit_category)
+                .delete(category::delete_category),
+        )
+        .route(
+            "/events/:event_id/categories/:category_id/lock",
+            post(category::lock_category),
+        )
+        .route(
+            "/events/:event_id/categories/:category_id/standings",
+            get(standings::get_round_standings),
+        )
+        .route(
+            "/events/:event_id/rounds",
+            post(round::create_round).get(round::get_rounds),
+        )
+        .route(
+            "/events/:event_id/rounds/:round_id",
+            get(round::get_round)
+                .patch(round::update_round)
+                .delete(round::delete_round),
+        )
+        .route(
+            "/events/:event_id/rounds/:round_id/categories",
+            put(round::set_round_categories),
+        )
+        .route(
+            "/events/:event_id/rounds/:round_id/advancements",
+            get(advancement::get_round_advancements),
+        )
+        .route(
+            "/events/:event_id/rounds/:round_id/advance",
+            post(advancement::advance_round),
+        )
+        .route(
+            "/events/:event_id/categories/:category_id/standings.csv",
+            get(standings::export_round_standings),
+        )
+        .route(
+            "/events/:event_id/categories/:category_id/schedule",
+            put(category::set_category_schedule),
+        )
+        .route(
+            "/events/:event_id/categories/:category_id/curve",
+            put(category::set_category_curve),
+        )
+        .route(
+            "/events/:event_id/categories/:category_id/quorum",
+            put(quorum::set_category_quorum),
+        )
+        .route("/events/:event_id/quorum", get(quorum::get_quorum_gaps))
+        // Performance timing and deductions
+        .route(
+            "/events/:event_id/categories/:category_id/time-limit",
+            put(timing::set_time_limit),
+        )
+        .route(
+            "/events/:event_id/categories/:category_id/candidates/:candidate_id/timer/start",
+            post(timing::start_timer),
+        )
+        .route(
+            "/events/:event_id/categories/:category_id/candidates/:candidate_id/timer/stop",
+            post(timing::stop_timer),
+        )
+        .route("/events/:event_id/timings", get(timing::get_timings))
+        // Analytics
+        .route(
+            "/events/:event_id/analytics/scoring-latency",
+            get(analytics::get_scoring_latency),
+        )
+        .route(
+            "/events/:event_id/score-stream",
+            get(score_feed::score_stream),
+        )
+        .route(
+            "/events/:event_id/analytics/submission-latency",
+            get(analytics::get_submission_latency),
+        )
+        .route(
+            "/events/:event_id/analytics/judge-agreement",
+            get(analytics::get_judge_agreement),
+        )
+        .route("/events/:event_id/deductions", get(deduction::get_deductions))
+        .route("/deductions", post(deduction::create_deduction))
+        .route(
+            "/deductions/:deduction_id",
+            delete(deduction::delete_deduction),
+        )
+        .route(
+            "/events/:event_id/categories/:category_id/lineup",
+            get(lineup::get_lineup).put(lineup::update_lineup),
+        )
+        .route(
+            "/events/:event_id/categories/:category_id/lineup/draw",
+            post(lineup::draw_lineup),
         )
         // Criterias
         .route(
@@ -74,40 +531,156 @@ async fn main() -> anyhow::Result<(), anyhow::Error> {
         )
         .route(
             "/events/:event_id/categories/:category_id/criterias/:criteria_id",
-            get(criteria::get_criteria),
+            get(criteria::get_criteria)
+                .patch(criteria::update_criteria)
+                .delete(criteria::delete_criteria),
         )
         // Candidates
         .route(
             "/candidates",
             post(candidate::create_candidate).get(candidate::get_candidates),
         )
+        .route("/candidates/merge", post(candidate::merge_candidates))
         .route("/candidates/score", get(score::get_candidate_score))
-        .route("/candidates/:candidate_id", get(candidate::get_candidate))
+        .route(
+            "/candidates/:candidate_id",
+            get(candidate::get_candidate)
+                .patch(candidate::update_candidate)
+                .delete(candidate::delete_candidate),
+        )
+        .route(
+            "/candidates/:candidate_id/check-in",
+            post(registration::check_in_candidate),
+        )
+        .route(
+            "/events/:event_id/candidates/import",
+            post(registration::import_candidates),
+        )
+        .route(
+            "/events/:event_id/candidates/renumber",
+            post(candidate::renumber_candidates),
+        )
+        .route(
+            "/events/:event_id/candidates/export.csv",
+            get(registration::export_candidates_csv),
+        )
+        .route(
+            "/events/:event_id/judges/export.csv",
+            get(registration::export_judges_csv),
+        )
+        .route(
+            "/candidates/:candidate_id/photo",
+            put(candidate::upload_candidate_photo).get(candidate::get_candidate_photo),
+        )
+        .route(
+            "/candidates/:candidate_id/qr.png",
+            get(candidate::get_candidate_qr),
+        )
+        .route(
+            "/candidates/:candidate_id/feedback-report",
+            get(report::generate_feedback_report),
+        )
+        .route(
+            "/candidates/:candidate_id/highlights",
+            get(report::get_candidate_highlights),
+        )
         .route("/judges", post(judge::create_judge).get(judge::get_judges))
         .route("/judges/:judge_id", get(judge::get_judge))
+        .route("/judges/:judge_id/lock", post(judge::set_judge_lock))
+        .route("/judges/:judge_id/nudge", post(judge::nudge_judge))
+        .route(
+            "/judges/:judge_id/review",
+            get(confirmation::get_score_review),
+        )
+        .route(
+            "/judges/:judge_id/impersonate",
+            post(impersonation::start_impersonation).delete(impersonation::end_impersonation),
+        )
+        .route(
+            "/judges/:judge_id/check-in",
+            post(registration::check_in_judge),
+        )
+        .route("/judges/:judge_id/substitute", post(judge::substitute_judge))
+        .route("/judges/:judge_id/blackouts", get(blackout::get_blackouts))
+        .route(
+            "/judges/:judge_id/blackouts/:candidate_id",
+            put(blackout::create_blackout).delete(blackout::delete_blackout),
+        )
+        .route(
+            "/events/:event_id/judges/lock",
+            post(judge::set_event_judges_lock),
+        )
         .route(
             "/scores",
             post(score::submit_score).get(score::get_candidate_scores),
         )
         .route("/scores/update", post(score::update_score))
+        .route("/scores/batch", post(score::submit_criteria_scores))
+        .route("/devices/scores", post(device::submit_device_score))
+        .route(
+            "/categories/:category_id/confirmations",
+            post(confirmation::confirm_scores).get(confirmation::get_confirmations),
+        )
+        .route(
+            "/categories/:category_id/void-scores",
+            post(category::void_scores),
+        )
+        .route(
+            "/categories/:category_id/preview-score",
+            post(category::preview_score),
+        )
         .route("/scores/final", get(score::get_candidate_final_scores))
         .route("/scores/download", get(score::generate_score_spreadsheet))
+        .route("/scores/audit", get(score::generate_csv))
+        .route("/scores/export.jsonl", get(report::generate_jsonl))
+        .route(
+            "/exports",
+            post(export::create_export_job).get(export::get_export_jobs),
+        )
+        .route("/exports/:job_id", get(export::get_export_job))
+        .route("/exports/:job_id/download", get(export::download_export))
+        .route("/exports/:job_id/cancel", post(export::cancel_export_job))
+        .route("/scores/receipts/verify", get(receipt::verify_receipt))
+        // Brackets
+        .route("/brackets", post(bracket::create_bracket))
+        .route("/brackets/:bracket_id", get(bracket::get_bracket))
+        .route(
+            "/brackets/:bracket_id/matches/:match_id/ballots",
+            post(bracket::submit_ballot),
+        )
+        .route(
+            "/brackets/:bracket_id/standings",
+            get(bracket::get_bracket_standings),
+        )
+        .route("/brackets/:bracket_id/advance", post(bracket::advance_bracket))
         .route("/notes", post(note::create_note).get(note::get_note))
         .route("/college", get(college::get_colleges))
+        // Audit
+        .route("/audit", get(audit::get_audit_log))
+        .route("/audit/export.csv", get(audit::export_audit_log))
+        .layer(axum::middleware::from_fn_with_state(
+            app_state.pool.clone(),
+            admin::restrict_staff_routes,
+        ))
+        .layer(axum::middleware::from_fn_with_state(
+            app_state.pool.clone(),
+            request_log::log_mutations,
+        ))
+        .layer(axum::middleware::from_fn_with_state(
+            app_state.pool.clone(),
+            impersonation::mark_impersonation,
+        ))
+        .layer(axum::middleware::from_fn_with_state(
+            app_state.pool.clone(),
+            observer::reject_observer_writes,
+        ))
+        .layer(axum::middleware::from_fn_with_state(
+            watchdog,
+            watchdog::reject_writes_when_degraded,
+        ))
+        .layer(axum::middleware::from_fn(reporting::capture_context))
         .layer(CorsLayer::permissive())
-        .with_state(pool);
-
-    // For local development (NOT EXPOSURE TO THE NETWORK) it must be [127.0.0.1]
-    let listener = TcpListener::bind(format!("{}:8000", ip_addr)).await?;
-
-    println!(
-        "Server has started, listening on: {:?}\n",
-        listener.local_addr()?
-    );
-
-    axum::serve(listener, app.into_make_service()).await?;
-
-    Ok(())
+        .with_state(app_state)
 }
 
 async fn health() -> (http::StatusCode, String) {
@@ -141,21 +714,50 @@ fn db_ws_listen(mut pg_listener: PgListener, tx: broadcast::Sender<String>) {
 async fn ws_handler(
     ws: WebSocketUpgrade,
     State(state): State<broadcast::Sender<String>>,
+    State(registry): State<realtime::ClientRegistry>,
+    Query(param): Query<realtime::TopicParam>,
 ) -> Response {
-    ws.on_upgrade(|socket| handle_socket(socket, state))
+    ws.on_upgrade(|socket| handle_socket(socket, state, registry, param))
 }
 
-async fn handle_socket(socket: WebSocket, tx: broadcast::Sender<String>) {
+async fn handle_socket(
+    socket: WebSocket,
+    tx: broadcast::Sender<String>,
+    registry: realtime::ClientRegistry,
+    param: realtime::TopicParam,
+) {
     let (mut sender, mut receiver) = socket.split();
 
     let mut rx = tx.subscribe();
+    let topics = param.topics();
+    let client_id = registry.register(&param);
+    let send_registry = registry.clone();
 
     // Spawn the first task that will receive broadcast messages and send text messages over the websocket to our client.
     let mut send_task = tokio::spawn(async move {
-        while let Ok(msg) = rx.recv().await {
+        loop {
+            let msg = match rx.recv().await {
+                Ok(msg) => msg,
+                // Slow clients skip what they missed instead of disconnecting
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    send_registry.update(client_id, |status| status.lagged_messages += skipped);
+                    continue;
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            };
+
+            if !realtime::is_subscribed(&topics, &realtime::message_topic(&msg)) {
+                continue;
+            }
+
             if sender.send(Message::Text(msg)).await.is_err() {
                 break;
             }
+
+            send_registry.update(client_id, |status| {
+                status.messages_sent += 1;
+                status.backlog = rx.len();
+            });
         }
     });
 
@@ -172,4 +774,6 @@ async fn handle_socket(socket: WebSocket, tx: broadcast::Sender<String>) {
         _ = (&mut send_task) => recv_task.abort(),
         _ = (&mut recv_task) => send_task.abort(),
     };
+
+    registry.unregister(client_id);
 }
@@ -0,0 +1,180 @@
+// Just enough of PDF to lay out text with the built-in Helvetica fonts, no embedding needed
+
+#[derive(Debug, Clone, Copy)]
+pub enum Font {
+    Helvetica,
+    HelveticaBold,
+}
+
+impl Font {
+    fn resource_name(self) -> &'static str {
+        match self {
+            Font::Helvetica => "F1",
+            Font::HelveticaBold => "F2",
+        }
+    }
+}
+
+// Glyph widths of Helvetica-Bold for ' '..='~', in thousandths of the font size
+const HELVETICA_BOLD_WIDTHS: [u16; 95] = [
+    278, 333, 474, 556, 556, 889, 722, 238, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556,
+    556, 556, 556, 556, 556, 556, 556, 333, 333, 584, 584, 584, 611, 975, 722, 722, 722, 722, 667,
+    611, 778, 722, 278, 556, 722, 611, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667,
+    667, 611, 333, 278, 333, 584, 556, 333, 556, 611, 556, 611, 556, 333, 611, 611, 278, 278, 556,
+    278, 889, 611, 611, 611, 611, 389, 556, 333, 611, 556, 778, 556, 556, 500, 389, 280, 389, 584,
+];
+
+// Width of the text in points, regular Helvetica is close enough to use the bold widths
+pub fn text_width(text: &str, size: f32) -> f32 {
+    let units: u32 = text
+        .chars()
+        .map(|c| match c {
+            ' '..='~' => HELVETICA_BOLD_WIDTHS[c as usize - 32] as u32,
+            _ => 611,
+        })
+        .sum();
+
+    units as f32 * size / 1000.0
+}
+
+// Strings are written as WinAnsi bytes, anything outside Latin-1 becomes '?'
+fn encode_text(text: &str) -> Vec<u8> {
+    let mut bytes = Vec::new();
+
+    for c in text.chars() {
+        match c {
+            '(' | ')' | '\\' => {
+                bytes.push(b'\\');
+                bytes.push(c as u8);
+            }
+            c if (c as u32) < 256 => bytes.push(c as u32 as u8),
+            _ => bytes.push(b'?'),
+        }
+    }
+
+    bytes
+}
+
+pub struct Page {
+    content: Vec<u8>,
+}
+
+impl Page {
+    pub fn text(&mut self, x: f32, y: f32, size: f32, font: Font, text: &str) {
+        self.content.extend_from_slice(
+            format!(
+                "BT /{} {:.2} Tf {:.2} {:.2} Td (",
+                font.resource_name(),
+                size,
+                x,
+                y
+            )
+            .as_bytes(),
+        );
+        self.content.extend_from_slice(&encode_text(text));
+        self.content.extend_from_slice(b") Tj ET\n");
+    }
+
+    pub fn centered_text(&mut self, center_x: f32, y: f32, size: f32, font: Font, text: &str) {
+        self.text(center_x - text_width(text, size) / 2.0, y, size, font, text);
+    }
+}
+
+pub struct Document {
+    width: f32,
+    height: f32,
+    pages: Vec<Page>,
+}
+
+impl Document {
+    // Size in points, 1/72 of an inch
+    pub fn new(width: f32, height: f32) -> Self {
+        Self {
+            width,
+            height,
+            pages: Vec::new(),
+        }
+    }
+
+    pub fn add_page(&mut self) -> &mut Page {
+        self.pages.push(Page {
+            content: Vec::new(),
+        });
+
+        self.pages.last_mut().unwrap()
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        // 1: catalog, 2: page tree, 3-4: fonts, then a page and its content stream per page
+        let mut objects: Vec<Vec<u8>> = Vec::new();
+
+        let page_ids: Vec<usize> = (0..self.pages.len()).map(|idx| 5 + idx * 2).collect();
+        let kids: Vec<String> = page_ids.iter().map(|id| format!("{} 0 R", id)).collect();
+
+        objects.push(b"<< /Type /Catalog /Pages 2 0 R >>".to_vec());
+        objects.push(
+            format!(
+                "<< /Type /Pages /Kids [{}] /Count {} >>",
+                kids.join(" "),
+                self.pages.len()
+            )
+            .into_bytes(),
+        );
+        objects.push(
+            b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>"
+                .to_vec(),
+        );
+        objects.push(
+            b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold /Encoding /WinAnsiEncoding >>"
+                .to_vec(),
+        );
+
+        for (page, page_id) in self.pages.iter().zip(page_ids.iter()) {
+            objects.push(
+                format!(
+                    "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {:.2} {:.2}] /Resources << /Font << /F1 3 0 R /F2 4 0 R >> >> /Contents {} 0 R >>",
+                    self.width,
+                    self.height,
+                    page_id + 1
+                )
+                .into_bytes(),
+            );
+
+            let mut stream = format!("<< /Length {} >>\nstream\n", page.content.len()).into_bytes();
+            stream.extend_from_slice(&page.content);
+            stream.extend_from_slice(b"endstream");
+            objects.push(stream);
+        }
+
+        let mut bytes = b"%PDF-1.4\n".to_vec();
+        let mut offsets = Vec::new();
+
+        for (idx, object) in objects.iter().enumerate() {
+            offsets.push(bytes.len());
+            bytes.extend_from_slice(format!("{} 0 obj\n", idx + 1).as_bytes());
+            bytes.extend_from_slice(object);
+            bytes.extend_from_slice(b"\nendobj\n");
+        }
+
+        let xref_offset = bytes.len();
+
+        bytes.extend_from_slice(
+            format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).as_bytes(),
+        );
+
+        for offset in offsets {
+            bytes.extend_from_slice(format!("{:010} 00000 n \n", offset).as_bytes());
+        }
+
+        bytes.extend_from_slice(
+            format!(
+                "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+                objects.len() + 1,
+                xref_offset
+            )
+            .as_bytes(),
+        );
+
+        bytes
+    }
+}
@@ -0,0 +1,115 @@
+// Optional error sink, enabled with `ERROR_REPORTING=true`
+// Server errors and panics are stored in `error_reports` along with the request they came from
+
+use std::sync::OnceLock;
+
+use axum::extract::Request;
+use axum::http;
+use axum::middleware::Next;
+use axum::response::Response;
+use sqlx::PgPool;
+use tokio::sync::mpsc;
+
+#[derive(Debug, Clone)]
+pub struct RequestContext {
+    method: String,
+    uri: String,
+    // Sent by the judge clients so errors can be traced back to a tablet
+    judge_id: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct ErrorReport {
+    kind: &'static str,
+    status: Option<u16>,
+    message: String,
+    context: Option<RequestContext>,
+}
+
+static ERROR_SINK: OnceLock<mpsc::UnboundedSender<ErrorReport>> = OnceLock::new();
+
+tokio::task_local! {
+    static REQUEST_CONTEXT: RequestContext;
+}
+
+pub fn init(pool: PgPool) {
+    let (tx, mut rx) = mpsc::unbounded_channel::<ErrorReport>();
+
+    if ERROR_SINK.set(tx).is_err() {
+        return;
+    }
+
+    tokio::spawn(async move {
+        while let Some(report) = rx.recv().await {
+            let context = report.context.as_ref();
+
+            let res = sqlx::query(
+                r#"
+                INSERT INTO error_reports (kind, status, message, method, uri, judge_id)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                "#,
+            )
+            .bind(report.kind)
+            .bind(report.status.map(|status| status as i32))
+            .bind(&report.message)
+            .bind(context.map(|context| context.method.clone()))
+            .bind(context.map(|context| context.uri.clone()))
+            .bind(context.and_then(|context| context.judge_id.clone()))
+            .execute(&pool)
+            .await;
+
+            if let Err(err) = res {
+                eprintln!("Failed to store error report: {err:?}");
+            }
+        }
+    });
+
+    let default_hook = std::panic::take_hook();
+
+    std::panic::set_hook(Box::new(move |info| {
+        send(ErrorReport {
+            kind: "panic",
+            status: None,
+            message: info.to_string(),
+            context: current_context(),
+        });
+
+        default_hook(info);
+    }));
+
+    println!("Error reporting is enabled.");
+}
+
+fn current_context() -> Option<RequestContext> {
+    REQUEST_CONTEXT.try_with(|context| context.clone()).ok()
+}
+
+fn send(report: ErrorReport) {
+    if let Some(sink) = ERROR_SINK.get() {
+        let _ = sink.send(report);
+    }
+}
+
+pub fn report_error(status: http::StatusCode, message: &str) {
+    send(ErrorReport {
+        kind: "error",
+        status: Some(status.as_u16()),
+        message: message.to_string(),
+        context: current_context(),
+    });
+}
+
+// Keeps the request details around so errors raised while handling it can include them
+pub async fn capture_context(request: Request, next: Next) -> Response {
+    let context = RequestContext {
+        method: request.method().to_string(),
+        uri: request.uri().to_string(),
+        judge_id: request
+            .headers()
+            .get("x-judge-id")
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_string()),
+    };
+
+    REQUEST_CONTEXT.scope(context, next.run(request)).await
+}
@@ -0,0 +1,124 @@
+use axum::body::Body;
+use axum::extract::{Request, State};
+use axum::http;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use futures::{stream, StreamExt};
+use serde_json::Value;
+use sqlx::types::Json;
+use sqlx::PgPool;
+
+// Bodies bigger than this (file uploads) are passed through without being logged
+const MAX_LOGGED_BODY: usize = 1024 * 1024;
+
+// Matched against whole keys, so `pinned` or `tokens_used` are still logged
+const SENSITIVE_FIELDS: [&str; 5] = ["password", "new_password", "pin", "token", "secret"];
+
+// Replaces the values of sensitive fields anywhere in the body
+pub fn scrub_sensitive(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                let key = key.to_lowercase();
+
+                if SENSITIVE_FIELDS.contains(&key.as_str()) {
+                    *value = Value::String("[REDACTED]".to_string());
+                } else {
+                    scrub_sensitive(value);
+                }
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(scrub_sensitive),
+        _ => {}
+    }
+}
+
+// Stores the body and response status of every mutating request
+pub async fn log_mutations(State(pool): State<PgPool>, request: Request, next: Next) -> Response {
+    let method = request.method().clone();
+
+    if !matches!(
+        method,
+        http::Method::POST | http::Method::PUT | http::Method::PATCH | http::Method::DELETE
+    ) {
+        return next.run(request).await;
+    }
+
+    let uri = request.uri().to_string();
+
+    let content_length = request
+        .headers()
+        .get(http::header::CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<usize>().ok())
+        .unwrap_or(0);
+
+    let (response, logged_body) = if content_length > MAX_LOGGED_BODY {
+        (next.run(request).await, None)
+    } else {
+        let (parts, request_body) = request.into_parts();
+
+        // Chunked bodies don't say how big they are, read up to the limit and stop logging past it
+        let mut data = request_body.into_data_stream();
+        let mut chunks = Vec::new();
+        let mut size = 0;
+
+        while size <= MAX_LOGGED_BODY {
+            match data.next().await {
+                Some(Ok(chunk)) => {
+                    size += chunk.len();
+                    chunks.push(chunk);
+                }
+                Some(Err(err)) => {
+                    return (
+                        http::StatusCode::BAD_REQUEST,
+                        format!("Failed to read request body: {}", err),
+                    )
+                        .into_response()
+                }
+                None => break,
+            }
+        }
+
+        if size > MAX_LOGGED_BODY {
+            let body = Body::from_stream(stream::iter(chunks.into_iter().map(Ok)).chain(data));
+
+            (next.run(Request::from_parts(parts, body)).await, None)
+        } else {
+            let bytes = chunks.concat();
+
+            let mut logged_body: Option<Value> = serde_json::from_slice(&bytes).ok();
+
+            if let Some(logged_body) = logged_body.as_mut() {
+                scrub_sensitive(logged_body);
+            }
+
+            let response = next
+                .run(Request::from_parts(parts, Body::from(bytes)))
+                .await;
+
+            (response, logged_body)
+        }
+    };
+
+    let status = response.status().as_u16() as i32;
+
+    // Logging must never slow down or fail the request itself
+    tokio::spawn(async move {
+        let res = sqlx::query(
+            "INSERT INTO request_log (method, uri, body, status) VALUES ($1, $2, $3, $4)",
+        )
+        .bind(method.as_str())
+        .bind(&uri)
+        .bind(logged_body.map(Json))
+        .bind(status)
+        .execute(&pool)
+        .await;
+
+        if let Err(err) = res {
+            eprintln!("Failed to store request log: {err:?}");
+        }
+    });
+
+    response
+}
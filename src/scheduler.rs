@@ -0,0 +1,148 @@
+use std::time::Duration;
+
+use sqlx::PgPool;
+use tokio::sync::broadcast;
+
+use crate::error::AppError;
+use crate::handlers::{audit, category};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(sqlx::FromRow)]
+pub struct DueCategory {
+    pub id: uuid::Uuid,
+    event_id: uuid::Uuid,
+    // The claimed schedule, put back when acting on it fails
+    scheduled_at: chrono::DateTime<chrono::Utc>,
+}
+
+// Opens and closes categories at their scheduled times so the tabulator doesn't have to
+pub fn spawn_scheduler(pool: PgPool, realtime_tx: broadcast::Sender<String>) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(POLL_INTERVAL);
+
+        loop {
+            ticker.tick().await;
+
+            if let Err(err) = open_due(&pool, &realtime_tx).await {
+                eprintln!("Scheduled open failed: {err:?}");
+            }
+
+            if let Err(err) = close_due(&pool).await {
+                eprintln!("Scheduled close failed: {err:?}");
+            }
+        }
+    });
+}
+
+// Claims the due schedules by clearing them, so a manual override in between is left alone
+pub async fn claim_due(pool: &PgPool, column: &str) -> Result<Vec<DueCategory>, AppError> {
+    let due = sqlx::query_as::<_, DueCategory>(&format!(
+        r#"
+        WITH due AS (
+            SELECT id, {column} AS scheduled_at FROM categories
+            WHERE {column} <= NOW() AND is_locked = FALSE
+            FOR UPDATE SKIP LOCKED
+        )
+        UPDATE categories cat SET {column} = NULL
+        FROM due
+        WHERE cat.id = due.id
+        RETURNING cat.id, cat.event_id, due.scheduled_at
+        "#
+    ))
+    .fetch_all(pool)
+    .await?;
+
+    Ok(due)
+}
+
+// Puts the schedule back for the next tick, unless it was set again in the meantime
+pub async fn release_claim(pool: &PgPool, column: &str, category: &DueCategory) {
+    let res = sqlx::query(&format!(
+        "UPDATE categories SET {column} = ($2) WHERE id = ($1) AND {column} IS NULL"
+    ))
+    .bind(&category.id)
+    .bind(&category.scheduled_at)
+    .execute(pool)
+    .await;
+
+    if let Err(err) = res {
+        eprintln!(
+            "Failed to release the {column} of category {}: {err:?}",
+            category.id
+        );
+    }
+}
+
+// One category failing doesn't hold up the others, it's retried on the next tick
+async fn open_due(pool: &PgPool, tx: &broadcast::Sender<String>) -> Result<(), AppError> {
+    for category in claim_due(pool, "scheduled_open_at").await? {
+        if let Err(err) = category::open_category(pool, tx, &category.event_id, &category.id).await
+        {
+            eprintln!("Scheduled open of category {} failed: {err:?}", category.id);
+            release_claim(pool, "scheduled_open_at", &category).await;
+            continue;
+        }
+
+        // Already opened, a missing audit entry isn't worth opening it again
+        if let Err(err) = audit::record(
+            pool,
+            audit::SCHEDULER,
+            "category",
+            Some(&category.id),
+            "scheduled_open",
+            serde_json::json!({}),
+        )
+        .await
+        {
+            eprintln!(
+                "Failed to audit the scheduled open of category {}: {err:?}",
+                category.id
+            );
+        }
+    }
+
+    Ok(())
+}
+
+// Categories that still have blockers are left open for the tabulator to sort out
+async fn close_due(pool: &PgPool) -> Result<(), AppError> {
+    for category in claim_due(pool, "scheduled_close_at").await? {
+        if let Err(err) = close_scheduled(pool, &category).await {
+            eprintln!(
+                "Scheduled close of category {} failed: {err:?}",
+                category.id
+            );
+            release_claim(pool, "scheduled_close_at", &category).await;
+        }
+    }
+
+    Ok(())
+}
+
+async fn close_scheduled(pool: &PgPool, category: &DueCategory) -> Result<(), AppError> {
+    let blockers = category::find_lock_blockers(pool, &category.event_id, &category.id).await?;
+
+    if blockers.is_empty() {
+        category::close_category(
+            pool,
+            &category.event_id,
+            &category.id,
+            audit::SCHEDULER,
+            &[],
+        )
+        .await?;
+
+        return Ok(());
+    }
+
+    audit::record(
+        pool,
+        audit::SCHEDULER,
+        "category",
+        Some(&category.id),
+        "scheduled_close_blocked",
+        serde_json::json!({ "blockers": blockers }),
+    )
+    .await
+}
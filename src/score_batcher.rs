@@ -0,0 +1,170 @@
+use std::time::Duration;
+
+use axum::http;
+use sqlx::{PgPool, Postgres, QueryBuilder};
+use tokio::sync::{mpsc, oneshot};
+
+use crate::error::AppError;
+use crate::field_crypto::SealedText;
+use crate::handlers::score::{self, Score, UpsertedScore};
+
+// Submissions arriving within this window share one INSERT
+const BATCH_WINDOW: Duration = Duration::from_millis(5);
+
+const MAX_BATCH_SIZE: usize = 64;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewScore {
+    pub score: i32,
+    pub max: i32,
+    pub candidate_id: uuid::Uuid,
+    pub criteria_id: uuid::Uuid,
+    pub category_id: uuid::Uuid,
+    pub judge_id: uuid::Uuid,
+    pub remark: Option<String>,
+}
+
+impl NewScore {
+    // Identical submissions get identical rows, so any of them can answer either request
+    fn is_row(&self, score: &Score) -> bool {
+        self.score == score.score
+            && self.max == score.max
+            && self.candidate_id == score.candidate_id
+            && self.criteria_id == score.criteria_id
+            && self.category_id == score.category_id
+            && self.judge_id == score.judge_id
+            && self.remark.as_deref() == score.remark.as_ref().map(SealedText::as_str)
+    }
+}
+
+type PendingScore = (NewScore, oneshot::Sender<Result<UpsertedScore, AppError>>);
+
+// Handle used by `submit_score` to queue its INSERT
+#[derive(Clone)]
+pub struct ScoreBatcher {
+    tx: mpsc::UnboundedSender<PendingScore>,
+}
+
+impl ScoreBatcher {
+    pub async fn insert(&self, score: NewScore) -> Result<UpsertedScore, AppError> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+
+        self.tx.send((score, reply_tx)).map_err(|_| {
+            AppError::new(
+                http::StatusCode::INTERNAL_SERVER_ERROR,
+                "Score writer is not running",
+            )
+        })?;
+
+        reply_rx.await.map_err(|_| {
+            AppError::new(
+                http::StatusCode::INTERNAL_SERVER_ERROR,
+                "Score writer dropped the submission",
+            )
+        })?
+    }
+}
+
+// Groups concurrent submissions into multi-row INSERTs, each caller still gets its own row back
+pub fn spawn_batcher(pool: PgPool) -> ScoreBatcher {
+    let (tx, mut rx) = mpsc::unbounded_channel::<PendingScore>();
+
+    tokio::spawn(async move {
+        while let Some(first) = rx.recv().await {
+            let mut batch = vec![first];
+            let deadline = tokio::time::sleep(BATCH_WINDOW);
+            tokio::pin!(deadline);
+
+            while batch.len() < MAX_BATCH_SIZE {
+                tokio::select! {
+                    pending = rx.recv() => match pending {
+                        Some(pending) => batch.push(pending),
+                        None => break,
+                    },
+                    _ = &mut deadline => break,
+                }
+            }
+
+            write_batch(&pool, batch).await;
+        }
+    });
+
+    ScoreBatcher { tx }
+}
+
+async fn insert_scores(
+    pool: &PgPool,
+    scores: &[&NewScore],
+) -> Result<Vec<UpsertedScore>, sqlx::Error> {
+    let mut query = QueryBuilder::<Postgres>::new(
+        "INSERT INTO scores (score, max, candidate_id, criteria_id, category_id, judge_id, remark) ",
+    );
+
+    query.push_values(scores, |mut row, score| {
+        row.push_bind(score.score)
+            .push_bind(score.max)
+            .push_bind(score.candidate_id)
+            .push_bind(score.criteria_id)
+            .push_bind(score.category_id)
+            .push_bind(score.judge_id)
+            .push_bind(score.remark.clone().map(SealedText));
+    });
+
+    query.push(score::UPSERT_SCORE);
+
+    query
+        .build_query_as::<UpsertedScore>()
+        .fetch_all(pool)
+        .await
+}
+
+async fn write_batch(pool: &PgPool, batch: Vec<PendingScore>) {
+    let scores: Vec<&NewScore> = batch.iter().map(|(score, _)| score).collect();
+
+    let mut rows = match insert_scores(pool, &scores).await {
+        Ok(rows) => rows,
+        Err(err) => {
+            // One bad submission fails the whole INSERT, so the rest are written one at a time
+            // Same for two submissions of the same score, an upsert can't touch a row twice
+            if batch.len() > 1 {
+                eprintln!("Batched score insert failed, retrying one by one: {err:?}");
+            }
+
+            for (score, reply) in batch {
+                let res = insert_scores(pool, &[&score])
+                    .await
+                    .map_err(|err| {
+                        eprintln!("Failed to submit score: {err:?}");
+                        AppError::new(
+                            http::StatusCode::INTERNAL_SERVER_ERROR,
+                            format!("Failed to submit score: {}", err),
+                        )
+                    })
+                    .and_then(|mut rows| {
+                        rows.pop().ok_or_else(|| {
+                            AppError::new(
+                                http::StatusCode::INTERNAL_SERVER_ERROR,
+                                "Score was not written",
+                            )
+                        })
+                    });
+
+                let _ = reply.send(res);
+            }
+
+            return;
+        }
+    };
+
+    for (score, reply) in batch {
+        let res = match rows.iter().position(|row| score.is_row(&row.score)) {
+            Some(idx) => Ok(rows.swap_remove(idx)),
+            None => Err(AppError::new(
+                http::StatusCode::INTERNAL_SERVER_ERROR,
+                "Score was not written",
+            )),
+        };
+
+        let _ = reply.send(res);
+    }
+}
@@ -0,0 +1,102 @@
+use std::env;
+use std::time::Duration;
+
+use sqlx::PgPool;
+use tokio::sync::broadcast;
+
+use crate::error::AppError;
+use crate::handlers::realtime::{self, RealtimeMessage};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+// Minutes without a heartbeat before a session is dropped, tablets ping every few seconds
+const DEFAULT_TIMEOUT_MINUTES: i32 = 5;
+
+fn timeout_minutes() -> i32 {
+    env::var("SESSION_HEARTBEAT_TIMEOUT_MINUTES")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .filter(|minutes: &i32| *minutes > 0)
+        .unwrap_or(DEFAULT_TIMEOUT_MINUTES)
+}
+
+// Ends sessions whose device went quiet, so the online judges count doesn't include dead tablets
+pub fn spawn_session_reaper(pool: PgPool, realtime_tx: broadcast::Sender<String>) {
+    let timeout = timeout_minutes();
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(POLL_INTERVAL);
+
+        loop {
+            ticker.tick().await;
+
+            if let Err(err) = reap_stale_sessions(&pool, &realtime_tx, timeout).await {
+                eprintln!("Session cleanup failed: {err:?}");
+            }
+        }
+    });
+}
+
+async fn reap_stale_sessions(
+    pool: &PgPool,
+    tx: &broadcast::Sender<String>,
+    timeout: i32,
+) -> Result<(), AppError> {
+    let mut txn = pool.begin().await?;
+
+    let reaped = sqlx::query_scalar::<_, uuid::Uuid>(
+        r#"
+        DELETE FROM judge_sessions
+        WHERE impersonated_by IS NULL
+            AND (last_heartbeat_at < NOW() - make_interval(mins => ($1)) OR expires_at <= NOW())
+        RETURNING judge_id
+        "#,
+    )
+    .bind(timeout)
+    .fetch_all(&mut *txn)
+    .await?;
+
+    if reaped.is_empty() {
+        return Ok(());
+    }
+
+    // A judge who logged in again on another device is still online
+    let offline = sqlx::query_as::<_, (uuid::Uuid, uuid::Uuid)>(
+        r#"
+        UPDATE judges j SET is_active = FALSE
+        WHERE j.id = ANY($1) AND j.is_active = TRUE AND NOT EXISTS (
+            SELECT 1 FROM judge_sessions s
+            WHERE s.judge_id = j.id AND s.expires_at > NOW() AND s.impersonated_by IS NULL
+        )
+        RETURNING j.id, j.event_id
+        "#,
+    )
+    .bind(&reaped)
+    .fetch_all(&mut *txn)
+    .await?;
+
+    txn.commit().await?;
+
+    for (judge_id, event_id) in offline {
+        let online_judges: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM judge_presence WHERE event_id = ($1) AND online",
+        )
+        .bind(&event_id)
+        .fetch_one(pool)
+        .await?;
+
+        println!("Judge {} went offline", judge_id);
+
+        realtime::publish(
+            tx,
+            &RealtimeMessage::JudgePresence {
+                event_id,
+                judge_id,
+                online: false,
+                online_judges,
+            },
+        );
+    }
+
+    Ok(())
+}
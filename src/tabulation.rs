@@ -0,0 +1,67 @@
+use std::str::FromStr;
+
+use axum::extract::{Query, State};
+use sqlx::PgPool;
+
+use crate::error::AppError;
+use crate::handlers::report::{self, ExportParam, TimeWindow};
+use crate::handlers::score::{self, SpreadsheetParam};
+
+// Formats results can be exported in, shared by the export queue and the CLI
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    Xlsx,
+    Csv,
+    Jsonl,
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            // Export jobs call it a spreadsheet
+            "xlsx" | "spreadsheet" => Ok(ExportFormat::Xlsx),
+            "csv" => Ok(ExportFormat::Csv),
+            "jsonl" => Ok(ExportFormat::Jsonl),
+            _ => Err(format!("Unknown export format: {}", value)),
+        }
+    }
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Xlsx => "xlsx",
+            ExportFormat::Csv => "csv",
+            ExportFormat::Jsonl => "jsonl",
+        }
+    }
+}
+
+// Runs the same generators as the download endpoints, without going through HTTP
+// Every event's results unless `event_id` is given
+pub async fn generate_export(
+    pool: &PgPool,
+    format: ExportFormat,
+    certified: bool,
+    event_id: Option<uuid::Uuid>,
+) -> Result<Vec<u8>, AppError> {
+    let state = State(pool.clone());
+    let param = Query(ExportParam {
+        certified,
+        event_id,
+    });
+    let window = Query(TimeWindow::default());
+
+    let (_, bytes) = match format {
+        ExportFormat::Xlsx => {
+            score::generate_score_spreadsheet(state, param, Query(SpreadsheetParam::default()))
+                .await?
+        }
+        ExportFormat::Csv => score::generate_csv(state, param, window).await?,
+        ExportFormat::Jsonl => report::generate_jsonl(state, param, window).await?,
+    };
+
+    Ok(bytes)
+}
@@ -0,0 +1,117 @@
+use std::env;
+use std::str::FromStr;
+
+use axum::http;
+use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+use sqlx::{Connection, PgConnection, PgPool};
+
+use crate::error::AppError;
+
+// Hosted deployments can keep each organization in its own Postgres schema instead of sharing tables
+//
+// TENANT_ISOLATION=schema turns it on, every request then names its organization in the
+// `x-organization` header (or `?organization=` for websockets, browsers can't set headers there)
+//
+// Only organizations listed in TENANT_ORGANIZATIONS (comma separated) get a schema, anything else is refused
+// before touching the database
+
+pub const ORGANIZATION_HEADER: &str = "x-organization";
+
+// Put in the request's extensions by the organization router, missing without schema isolation
+#[derive(Debug, Clone)]
+pub struct Organization(pub String);
+
+// Connections are per organization, so each pool stays small
+const TENANT_MAX_CONNECTIONS: u32 = 10;
+
+pub fn schema_isolation_enabled() -> bool {
+    env::var("TENANT_ISOLATION").is_ok_and(|value| value == "schema")
+}
+
+pub fn allowed_organizations() -> Vec<String> {
+    parse_organizations(&env::var("TENANT_ORGANIZATIONS").unwrap_or_default())
+}
+
+pub fn parse_organizations(list: &str) -> Vec<String> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|organization| !organization.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+// Organizations become part of SQL identifiers, so only plain slugs are accepted
+pub fn schema_name(organization: &str) -> Result<String, AppError> {
+    let is_slug = organization
+        .chars()
+        .next()
+        .is_some_and(|first| first.is_ascii_lowercase())
+        && organization.len() <= 48
+        && organization
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+
+    if !is_slug {
+        return Err(AppError::new(
+            http::StatusCode::BAD_REQUEST,
+            "Organizations are lowercase slugs of at most 48 characters",
+        ));
+    }
+
+    Ok(format!("org_{}", organization.replace('-', "_")))
+}
+
+pub fn check_allowed(organization: &str, allowed: &[String]) -> Result<(), AppError> {
+    if !allowed.iter().any(|allowed| allowed == organization) {
+        return Err(AppError::new(
+            http::StatusCode::NOT_FOUND,
+            format!("Unknown organization {}", organization),
+        ));
+    }
+
+    Ok(())
+}
+
+pub fn request_organization<B>(request: &http::Request<B>) -> Result<String, AppError> {
+    let header = request
+        .headers()
+        .get(ORGANIZATION_HEADER)
+        .and_then(|value| value.to_str().ok());
+
+    let query = request.uri().query().and_then(|query| {
+        query
+            .split('&')
+            .find_map(|pair| pair.strip_prefix("organization="))
+    });
+
+    header.or(query).map(str::to_string).ok_or_else(|| {
+        AppError::new(
+            http::StatusCode::BAD_REQUEST,
+            format!("Missing {} header", ORGANIZATION_HEADER),
+        )
+    })
+}
+
+// Creates the schema on first use and brings it up to date with the same migrations as the default one
+pub async fn connect(db_url: &str, schema: &str) -> anyhow::Result<PgPool> {
+    let mut conn = PgConnection::connect(db_url).await?;
+
+    // `schema` is a validated slug, see `schema_name`
+    sqlx::query(&format!("CREATE SCHEMA IF NOT EXISTS {}", schema))
+        .execute(&mut conn)
+        .await?;
+
+    conn.close().await?;
+
+    // Unqualified table names, `_sqlx_migrations` included, resolve to the organization's schema
+    let options = PgConnectOptions::from_str(db_url)?.options([("search_path", schema)]);
+
+    let pool = PgPoolOptions::new()
+        .max_connections(TENANT_MAX_CONNECTIONS)
+        .connect_with(options)
+        .await?;
+
+    sqlx::migrate!().run(&pool).await?;
+
+    Ok(pool)
+}
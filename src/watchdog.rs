@@ -0,0 +1,102 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::extract::{Request, State};
+use axum::http;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use sqlx::PgPool;
+use tokio::sync::broadcast;
+
+use crate::handlers::realtime::{self, RealtimeMessage};
+
+const PING_INTERVAL: Duration = Duration::from_secs(5);
+const PING_TIMEOUT: Duration = Duration::from_secs(2);
+
+// Consecutive failed pings before the database is considered degraded
+const FAILURE_THRESHOLD: u32 = 2;
+
+// Seconds clients are told to wait before retrying a rejected write
+const RETRY_AFTER: u64 = 10;
+
+#[derive(Clone)]
+pub struct Watchdog {
+    degraded: Arc<AtomicBool>,
+}
+
+impl Watchdog {
+    pub fn is_degraded(&self) -> bool {
+        self.degraded.load(Ordering::Relaxed)
+    }
+}
+
+// Pings the database and flips the degraded flag, alerting the dashboard on every change
+pub fn spawn_watchdog(pool: PgPool, realtime_tx: broadcast::Sender<String>) -> Watchdog {
+    let watchdog = Watchdog {
+        degraded: Arc::new(AtomicBool::new(false)),
+    };
+
+    let degraded = watchdog.degraded.clone();
+
+    tokio::spawn(async move {
+        let mut failures: u32 = 0;
+        let mut ticker = tokio::time::interval(PING_INTERVAL);
+
+        loop {
+            ticker.tick().await;
+
+            let ping = tokio::time::timeout(PING_TIMEOUT, sqlx::query("SELECT 1").execute(&pool));
+
+            match ping.await {
+                Ok(Ok(_)) => failures = 0,
+                Ok(Err(err)) => {
+                    eprintln!("Database ping failed: {err:?}");
+                    failures += 1;
+                }
+                Err(_) => {
+                    eprintln!("Database ping timed out");
+                    failures += 1;
+                }
+            }
+
+            let is_degraded = failures >= FAILURE_THRESHOLD;
+
+            if degraded.swap(is_degraded, Ordering::Relaxed) != is_degraded {
+                println!("Database degraded: {}", is_degraded);
+
+                realtime::publish(
+                    &realtime_tx,
+                    &RealtimeMessage::DatabaseStatus {
+                        degraded: is_degraded,
+                    },
+                );
+            }
+        }
+    });
+
+    watchdog
+}
+
+// Writes fail fast while the database is degraded instead of hanging on the pool
+pub async fn reject_writes_when_degraded(
+    State(watchdog): State<Watchdog>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let is_write = matches!(
+        *request.method(),
+        http::Method::POST | http::Method::PUT | http::Method::PATCH | http::Method::DELETE
+    );
+
+    if is_write && watchdog.is_degraded() {
+        return (
+            http::StatusCode::SERVICE_UNAVAILABLE,
+            [(http::header::RETRY_AFTER, RETRY_AFTER.to_string())],
+            "Database is unavailable, try again shortly",
+        )
+            .into_response();
+    }
+
+    next.run(request).await
+}